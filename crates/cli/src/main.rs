@@ -1,32 +1,71 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use indicatif::{ProgressBar, ProgressStyle};
-use jay_rag_core::config::{Language, ProcessingConfig, Quality};
+use is_terminal::IsTerminal;
+use jay_rag_core::config::{parse_page_selection, Language, ProcessingConfig, Quality};
 use jay_rag_core::progress::ProgressReporter;
 use jay_rag_core::provider;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
+use tracing::Instrument;
+use tracing_subscriber::prelude::*;
+
+#[cfg(feature = "otel")]
+mod otel;
 
 /// JAY-RAG-TOOLS — Thai-first PDF Vision Processor for RAG pipelines
 #[derive(Parser)]
 #[command(name = "jay-rag", version, about, long_about = None)]
 struct Cli {
+    /// Disable coloured output (also honors the NO_COLOR env var)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Log format: human-readable, or one JSON object per line with span fields
+    #[arg(long, global = true, default_value = "pretty", value_parser = ["pretty", "json"])]
+    log_format: String,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+/// How CLI output should be rendered, detected once at startup.
+#[derive(Debug, Clone, Copy)]
+struct OutputMode {
+    /// stdout is a terminal: show an animated progress bar.
+    interactive: bool,
+    /// ANSI colours allowed (no `--no-color` and no `NO_COLOR`).
+    color: bool,
+}
+
+impl OutputMode {
+    fn detect(no_color: bool) -> Self {
+        let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let interactive = std::io::stdout().is_terminal();
+        Self {
+            interactive,
+            color: interactive && !no_color && !no_color_env,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Process PDF files with Vision LLM
     Process(ProcessArgs),
     /// Start the web dashboard API server
     Serve(ServeArgs),
+    /// Check that markdown image tags and image files match up
+    VerifyOutput(VerifyOutputArgs),
+    /// Benchmark providers/settings on the same PDF
+    Bench(BenchArgs),
 }
 
 #[derive(Parser)]
 struct ProcessArgs {
-    /// Path to PDF file or folder containing PDFs
+    /// Path to a PDF or page image (png/jpg/webp), or a folder of them
     #[arg(short, long)]
     input: PathBuf,
 
@@ -34,8 +73,12 @@ struct ProcessArgs {
     #[arg(short, long, default_value = "./output")]
     output: PathBuf,
 
+    /// TOML file of processing settings (`ProcessingConfig` keys); flags given here override it
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
     /// Vision LLM provider
-    #[arg(short, long, default_value = "ollama", value_parser = ["ollama", "openai", "claude", "gemini", "xai", "groq"])]
+    #[arg(short, long, default_value = "ollama", value_parser = ["ollama", "openai", "claude", "gemini", "xai", "groq", "openai-compat"])]
     provider: String,
 
     /// Model name (default: provider-specific)
@@ -43,7 +86,7 @@ struct ProcessArgs {
     model: Option<String>,
 
     /// Document language for prompts
-    #[arg(short, long, default_value = "th", value_parser = ["th", "en"])]
+    #[arg(short, long, default_value = "th", value_parser = ["th", "en", "lo", "km", "vi"])]
     lang: String,
 
     /// Start page number (0-indexed)
@@ -54,6 +97,26 @@ struct ProcessArgs {
     #[arg(long)]
     end_page: Option<u32>,
 
+    /// Skip the first N pages of every PDF (relative to each file)
+    #[arg(long, default_value = "0")]
+    skip_first: u32,
+
+    /// Skip the last N pages of every PDF (relative to each file)
+    #[arg(long, default_value = "0")]
+    skip_last: u32,
+
+    /// Leave out these 1-indexed pages entirely, e.g. "3,7,10-12" (ad inserts, blank sheets)
+    #[arg(long)]
+    skip_pages: Option<String>,
+
+    /// Render only this PDF layer (repeatable); other layers are hidden
+    #[arg(long = "layer", value_name = "NAME")]
+    include_layers: Vec<String>,
+
+    /// Hide this PDF layer when rendering (repeatable), e.g. CAD dimensions
+    #[arg(long = "hide-layer", value_name = "NAME")]
+    exclude_layers: Vec<String>,
+
     /// Skip provider availability check
     #[arg(long)]
     skip_check: bool,
@@ -66,14 +129,38 @@ struct ProcessArgs {
     #[arg(long)]
     text_only: bool,
 
-    /// Max pages processed concurrently (default: 4)
-    #[arg(long, default_value = "4")]
-    concurrency: usize,
+    /// Max pages processed concurrently (default: recommended for the provider)
+    #[arg(long)]
+    concurrency: Option<usize>,
+
+    /// Max image files written concurrently (lower for spinning disks or NFS output)
+    #[arg(long, default_value = "64")]
+    io_concurrency: usize,
+
+    /// Max Vision LLM requests in flight across all pages and images
+    #[arg(long)]
+    max_requests: Option<usize>,
+
+    /// Max Vision LLM requests per minute (stay under the provider's rate limit)
+    #[arg(long)]
+    rpm: Option<u32>,
+
+    /// Fail a page whose vision request has not answered after this many seconds
+    #[arg(long, value_name = "SECS")]
+    page_timeout: Option<u64>,
 
     /// Disable trash detection
     #[arg(long)]
     no_detect_trash: bool,
 
+    /// Keep running headers/footers in the markdown text (still reported as trash)
+    #[arg(long)]
+    keep_headers_footers: bool,
+
+    /// Trash detection sensitivity: "conservative", "balanced" or "aggressive"
+    #[arg(long, default_value = "balanced", value_parser = ["conservative", "balanced", "aggressive"])]
+    trash_preset: String,
+
     /// Processing quality: "standard" (pdfium text + LLM for images) or "high" (every page → Vision LLM OCR)
     #[arg(long, default_value = "standard", value_parser = ["standard", "high"])]
     quality: String,
@@ -82,10 +169,146 @@ struct ProcessArgs {
     #[arg(long)]
     dpi: Option<u32>,
 
+    /// Longest side of a page render in pixels; larger pages render at a lower DPI
+    #[arg(long, default_value = "4096")]
+    max_render_pixels: u32,
+
+    /// Encoding of images sent to the LLM: png, jpeg or jpeg:<quality> (see --saved-image-format)
+    #[arg(long, default_value = "png")]
+    image_encoding: String,
+
+    /// Format of images saved to disk: png, jpeg or webp (lossless, smaller than PNG)
+    #[arg(long, default_value = "png")]
+    saved_image_format: String,
+
     /// Auto-strip detected trash pages from output (creates _cleaned.md).
-    /// Optionally filter by type: toc,boilerplate,blank
+    /// Optionally filter by type (toc,boilerplate,blank) or reason code (short_page,dot_leaders,...)
     #[arg(long, value_name = "TYPES")]
     strip_trash: Option<Option<String>>,
+
+    /// Figure numbering: "per_page" (restart on every page) or "continuous" (Figure 1..N)
+    #[arg(long, default_value = "per_page", value_parser = ["per_page", "continuous"])]
+    figure_numbering: String,
+
+    /// Write a `<image>.json` metadata sidecar next to each saved image
+    #[arg(long)]
+    image_sidecars: bool,
+
+    /// Give the page text to single-image prompts so figures are described in context
+    #[arg(long)]
+    image_context: bool,
+
+    /// TOML or JSON file of custom prompts (`full_page`, `single_image`, `table`)
+    #[arg(long, value_name = "PATH")]
+    prompt_file: Option<PathBuf>,
+
+    /// Handlebars template for the markdown layout (pages with text, images, tables)
+    #[arg(long, value_name = "FILE")]
+    template: Option<PathBuf>,
+
+    /// Split the output markdown into `_part{n}` files at page boundaries above this size
+    #[arg(long, value_name = "BYTES")]
+    max_markdown_bytes: Option<usize>,
+
+    /// Premium provider for full-page, table and high-quality pages (images keep --provider)
+    #[arg(long, value_parser = ["ollama", "openai", "claude", "gemini", "xai", "groq", "openai-compat"])]
+    premium_provider: Option<String>,
+
+    /// Premium model name (default: premium provider's default)
+    #[arg(long)]
+    premium_model: Option<String>,
+
+    /// Append filled-in PDF form (AcroForm) field values to each page
+    #[arg(long)]
+    form_fields: bool,
+
+    /// Start the markdown with a table of contents built from the PDF bookmarks
+    #[arg(long)]
+    toc: bool,
+
+    /// Write images here instead of <output>/images (e.g. a CDN-synced folder)
+    #[arg(long, value_name = "DIR")]
+    images_output: Option<PathBuf>,
+
+    /// Drop matching lines from RAG chunk text (repeatable; `/regex/` or substring)
+    #[arg(long = "chunk-noise", value_name = "PATTERN")]
+    chunk_noise_patterns: Vec<String>,
+
+    /// High quality: send up to N consecutive pages per Vision LLM request
+    #[arg(long, default_value = "1")]
+    pages_per_request: usize,
+
+    /// Render pages with an empty text layer but visible content as full-page images
+    #[arg(long)]
+    escalate_empty_text: bool,
+
+    /// Switch to --quality high when sampled pages have almost no text layer (scanned PDFs)
+    #[arg(long)]
+    auto_high_quality_on_scanned: bool,
+
+    /// Describe repeated images (logos, icons) once and reuse the description
+    #[arg(long)]
+    dedup_images: bool,
+
+    /// Describe identical full pages (blank versos, divider pages) once and reuse the description
+    #[arg(long)]
+    dedup_pages: bool,
+
+    /// Turn rendered pages whose text reads sideways or upside down upright before OCR
+    #[arg(long)]
+    auto_rotate: bool,
+
+    /// Also write <doc>_rag.jsonl: one record per page with image descriptions inlined
+    #[arg(long)]
+    rag_jsonl: bool,
+
+    /// Output: <doc>_enriched.md, <doc>_pages.jsonl (one record per page), or both
+    #[arg(long, default_value = "markdown", value_parser = ["markdown", "jsonl", "both"])]
+    format: String,
+
+    /// Skip files whose contents match the hash recorded in <doc>_source.json
+    #[arg(long)]
+    skip_unchanged: bool,
+
+    /// Rewrite LLM tables wider than --wide-table-max-columns
+    #[arg(long, default_value = "keep", value_parser = ["keep", "transpose", "split", "records"])]
+    wide_tables: String,
+
+    /// Column count above which a table is considered wide
+    #[arg(long, default_value = "14")]
+    wide_table_max_columns: usize,
+
+    /// Share of text lines with multi-space column gaps that makes a page a table candidate
+    #[arg(long, default_value = "0.4")]
+    table_multispace_ratio: f64,
+
+    /// Consecutive lines of similar token counts that make a page a table candidate
+    #[arg(long, default_value = "6")]
+    table_min_consistent_run: usize,
+
+    /// Line endings of the written markdown ("crlf" for Windows-based tools)
+    #[arg(long, default_value = "lf", value_parser = ["lf", "crlf"])]
+    line_ending: String,
+
+    /// Few-shot example as IMAGE=EXPECTED_OUTPUT_FILE, sent before each image request (repeatable)
+    #[arg(long)]
+    few_shot: Vec<String>,
+
+    /// Retry a failed full-page/table/high-quality page once: "quadrants", "high_quality" or "auto"
+    #[arg(long, default_value = "none", value_parser = ["none", "quadrants", "high_quality", "auto"])]
+    page_fallback: String,
+
+    /// Image description placement: "after" the [IMAGE:] tag, "before" it, or "as_alt_text" (![desc](path))
+    #[arg(long, default_value = "after", value_parser = ["after", "before", "as_alt_text"])]
+    caption_placement: String,
+
+    /// Stop once the estimated provider cost (USD) for this run would exceed this budget
+    #[arg(long, value_name = "USD")]
+    max_cost: Option<f64>,
+
+    /// Show each page's strategy and image count without calling the LLM (writes <doc>_plan.json)
+    #[arg(long)]
+    dry_run: bool,
 }
 
 #[derive(Parser)]
@@ -97,33 +320,134 @@ struct ServeArgs {
     /// Output directory for processed files
     #[arg(short, long, default_value = "./output")]
     output: PathBuf,
+
+    /// Start even if the pdfium library cannot be loaded (jobs will fail)
+    #[arg(long)]
+    skip_pdfium_check: bool,
+
+    /// Fake jobs: synthetic progress and canned results, no pdfium or API keys (for UI development)
+    #[arg(long)]
+    simulate: bool,
+
+    /// Delete jobs and their files this many days after they finish (checked hourly)
+    #[arg(long)]
+    retention_days: Option<u32>,
+
+    /// Directory that jobs' local/NFS storage_path is resolved under (default: --output)
+    #[arg(long)]
+    storage_root: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+struct VerifyOutputArgs {
+    /// Path to the enriched markdown file
+    #[arg(short, long)]
+    markdown: PathBuf,
+
+    /// Document images directory (default: <markdown dir>/images/<doc stem>)
+    #[arg(long)]
+    images_dir: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+struct BenchArgs {
+    /// Path to the sample PDF
+    #[arg(short, long)]
+    input: PathBuf,
+
+    /// Comma-separated providers, optionally with model: "ollama,openai:gpt-4o,mock"
+    #[arg(long, default_value = "mock")]
+    providers: String,
+
+    /// Runs per provider
+    #[arg(long, default_value = "1")]
+    runs: u32,
+
+    /// Scratch output directory for benchmark runs
+    #[arg(short, long, default_value = "./bench-output")]
+    output: PathBuf,
+
+    /// Document language for prompts
+    #[arg(short, long, default_value = "th", value_parser = ["th", "en", "lo", "km", "vi"])]
+    lang: String,
+
+    /// Processing quality
+    #[arg(long, default_value = "standard", value_parser = ["standard", "high"])]
+    quality: String,
+
+    /// Start page number (0-indexed)
+    #[arg(long)]
+    start_page: Option<u32>,
+
+    /// End page number (exclusive)
+    #[arg(long)]
+    end_page: Option<u32>,
+}
+
+/// Reporter for `bench`: records the page count, prints nothing.
+#[derive(Default)]
+struct BenchReporter {
+    total_pages: AtomicU32,
+}
+
+impl ProgressReporter for BenchReporter {
+    fn on_pdf_start(&self, _filename: &str, total_pages: u32) {
+        self.total_pages.store(total_pages, Ordering::Relaxed);
+    }
+    fn on_page_start(&self, _page_num: u32, _total_pages: u32) {}
+    fn on_page_complete(&self, _page_num: u32, _total_pages: u32) {}
+    fn on_image_processed(&self, _page_num: u32, _image_index: u32, _desc: &str) {}
+    fn on_pdf_complete(&self, _filename: &str, _total_images: u32) {}
+    fn on_error(&self, _page_num: u32, _error: &str) {}
 }
 
 /// CLI progress reporter using indicatif progress bars.
+///
+/// When stdout is not a terminal the bar is hidden and progress is logged as
+/// plain lines roughly every 10% of pages instead. Pages finish out of order
+/// under concurrency, so progress counts completed pages.
 struct CliProgressReporter {
     bar: ProgressBar,
     images: AtomicU32,
+    interactive: bool,
+    total_pages: AtomicU32,
+    completed_pages: AtomicU32,
 }
 
 impl CliProgressReporter {
-    fn new() -> Self {
-        let bar = ProgressBar::new(0);
+    fn new(mode: OutputMode) -> Self {
+        let bar = if mode.interactive {
+            ProgressBar::new(0)
+        } else {
+            ProgressBar::hidden()
+        };
+        let template = if mode.color {
+            "{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} pages ({msg})"
+        } else {
+            "{spinner} [{bar:40}] {pos}/{len} pages ({msg})"
+        };
         bar.set_style(
-            ProgressStyle::with_template(
-                "{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} pages ({msg})",
-            )
-            .unwrap()
-            .progress_chars("█▉▊▋▌▍▎▏ "),
+            ProgressStyle::with_template(template)
+                .unwrap()
+                .progress_chars("█▉▊▋▌▍▎▏ "),
         );
         Self {
             bar,
             images: AtomicU32::new(0),
+            interactive: mode.interactive,
+            total_pages: AtomicU32::new(0),
+            completed_pages: AtomicU32::new(0),
         }
     }
 }
 
 impl ProgressReporter for CliProgressReporter {
     fn on_pdf_start(&self, filename: &str, total_pages: u32) {
+        if !self.interactive {
+            println!("Processing {filename}: {total_pages} page(s)");
+        }
+        self.total_pages.store(total_pages, Ordering::Relaxed);
+        self.completed_pages.store(0, Ordering::Relaxed);
         self.bar.set_length(total_pages as u64);
         self.bar.set_position(0);
         self.bar.set_message(filename.to_string());
@@ -132,8 +456,16 @@ impl ProgressReporter for CliProgressReporter {
 
     fn on_page_start(&self, _page_num: u32, _total_pages: u32) {}
 
-    fn on_page_complete(&self, page_num: u32, _total_pages: u32) {
-        self.bar.set_position(page_num as u64);
+    fn on_page_complete(&self, _page_num: u32, _total_pages: u32) {
+        let done = self.completed_pages.fetch_add(1, Ordering::Relaxed) + 1;
+        self.bar.inc(1);
+        if !self.interactive {
+            let total = self.total_pages.load(Ordering::Relaxed);
+            let step = (total / 10).max(1);
+            if done % step == 0 || done == total {
+                println!("  {done}/{total} pages");
+            }
+        }
     }
 
     fn on_image_processed(&self, _page_num: u32, _image_index: u32, _desc: &str) {
@@ -141,61 +473,404 @@ impl ProgressReporter for CliProgressReporter {
     }
 
     fn on_pdf_complete(&self, filename: &str, total_images: u32) {
+        if !self.interactive {
+            println!("Finished {filename}: {total_images} images");
+        }
         self.bar.finish_with_message(format!(
             "{filename} — {total_images} images"
         ));
     }
 
     fn on_error(&self, page_num: u32, error: &str) {
-        self.bar.println(format!("  Error on page {page_num}: {error}"));
+        if self.interactive {
+            self.bar.println(format!("  Error on page {page_num}: {error}"));
+        } else {
+            eprintln!("  Error on page {page_num}: {error}");
+        }
+    }
+
+    fn on_warning(&self, page_num: u32, message: &str) {
+        if self.interactive {
+            self.bar
+                .println(format!("  Warning on page {page_num}: {message}"));
+        } else {
+            eprintln!("  Warning on page {page_num}: {message}");
+        }
     }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "info".into()),
-        )
-        .init();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let mode = OutputMode::detect(cli.no_color);
 
-    let cli = Cli::parse();
+    let filter =
+        tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into());
+    let fmt_layer = if cli.log_format == "json" {
+        // Span fields (job_id, doc, page, provider) land in "span"/"spans"
+        tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .boxed()
+    } else {
+        tracing_subscriber::fmt::layer()
+            .with_ansi(mode.color)
+            .boxed()
+    };
+    let subscriber = tracing_subscriber::registry().with(filter).with(fmt_layer);
+
+    // Held until the end of main so buffered spans are flushed on exit
+    #[cfg(feature = "otel")]
+    let (subscriber, _otel_guard) = match otel::layer()? {
+        Some((layer, guard)) => (subscriber.with(Some(layer)), Some(guard)),
+        None => (subscriber.with(None), None),
+    };
+    subscriber.init();
 
     match cli.command {
-        Commands::Process(args) => run_process(args).await?,
+        Commands::Process(args) => {
+            let explicit = matches
+                .subcommand_matches("process")
+                .expect("process subcommand");
+            run_process(args, explicit, mode).await?
+        }
         Commands::Serve(args) => run_serve(args).await?,
+        Commands::VerifyOutput(args) => run_verify_output(args).await?,
+        Commands::Bench(args) => run_bench(args).await?,
     }
 
     Ok(())
 }
 
-async fn run_process(args: ProcessArgs) -> Result<()> {
+/// Print a dry-run plan as a per-page table.
+fn print_plan(plan: &jay_rag_core::processor::DryRunReport, path: Option<&std::path::Path>) {
+    println!("\nPlan for {}:", plan.doc_stem);
+    println!(
+        "  {:<6} {:<13} {:>8} {:>7}  Table",
+        "Page", "Strategy", "Coverage", "Images"
+    );
+    for page in &plan.pages {
+        let coverage = page
+            .coverage
+            .map(|c| format!("{:.0}%", c * 100.0))
+            .unwrap_or_else(|| "-".to_string());
+        let table = if page.table_candidate { "yes" } else { "" };
+        println!(
+            "  {:<6} {:<13} {:>8} {:>7}  {table}",
+            page.page, page.strategy, coverage, page.image_count
+        );
+        if let Some(error) = &page.error {
+            println!("         {error}");
+        }
+    }
+    println!("  Vision images: {}", plan.vision_images);
+    if let Some(path) = path {
+        println!("  Plan: {}", path.display());
+    }
+}
+
+/// Parse `--few-shot IMAGE=EXPECTED_OUTPUT_FILE` values, reading each expected output.
+fn parse_few_shot(specs: &[String]) -> Result<Vec<(PathBuf, String)>> {
+    specs
+        .iter()
+        .map(|spec| {
+            let Some((image, expected_file)) = spec.split_once('=') else {
+                anyhow::bail!("Invalid --few-shot '{spec}' (expected IMAGE=EXPECTED_OUTPUT_FILE)");
+            };
+            let expected = std::fs::read_to_string(expected_file).map_err(|e| {
+                anyhow::anyhow!("Cannot read few-shot output '{expected_file}': {e}")
+            })?;
+            Ok((PathBuf::from(image), expected))
+        })
+        .collect()
+}
+
+/// Custom prompts read from `--prompt-file`; unset keys keep the built-in prompts.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PromptFile {
+    full_page: Option<String>,
+    single_image: Option<String>,
+    table: Option<String>,
+}
+
+/// Load a prompt file, as JSON for a `.json` extension and TOML otherwise.
+fn load_prompt_file(path: &std::path::Path) -> Result<PromptFile> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Cannot read prompt file '{}': {e}", path.display()))?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let prompts = if is_json {
+        serde_json::from_str(&content)?
+    } else {
+        toml::from_str(&content)?
+    };
+    Ok(prompts)
+}
+
+/// Override the settings of a `--config` file with the flags given on the command line.
+///
+/// `flags` is the config built from every flag (defaults included); only
+/// those the user typed are copied over.
+fn apply_explicit_flags(
+    config: &mut ProcessingConfig,
+    flags: ProcessingConfig,
+    explicit: &ArgMatches,
+) {
+    let given = |id: &str| explicit.value_source(id) == Some(ValueSource::CommandLine);
+
+    if given("lang") {
+        config.language = flags.language;
+    }
+    if given("no_tables") || given("text_only") {
+        config.table_extraction = flags.table_extraction;
+    }
+    if given("text_only") {
+        config.text_only = flags.text_only;
+    }
+    if given("concurrency") {
+        config.max_concurrent_pages = flags.max_concurrent_pages;
+    }
+    if given("io_concurrency") {
+        config.max_concurrent_io = flags.max_concurrent_io;
+    }
+    if given("max_requests") {
+        config.max_concurrent_requests = flags.max_concurrent_requests;
+    }
+    if given("rpm") {
+        config.requests_per_minute = flags.requests_per_minute;
+    }
+    if given("page_timeout") {
+        config.page_timeout_secs = flags.page_timeout_secs;
+    }
+    if given("no_detect_trash") {
+        config.detect_trash = flags.detect_trash;
+    }
+    if given("keep_headers_footers") {
+        config.strip_repeated_lines = flags.strip_repeated_lines;
+    }
+    if given("trash_preset") {
+        config.trash_preset = flags.trash_preset;
+    }
+    if given("quality") {
+        config.quality = flags.quality;
+    }
+    if given("dpi") {
+        config.image_dpi = flags.image_dpi;
+    }
+    if given("max_render_pixels") {
+        config.max_render_pixels = flags.max_render_pixels;
+    }
+    if given("image_encoding") {
+        config.image_encoding = flags.image_encoding;
+    }
+    if given("saved_image_format") {
+        config.saved_image_format = flags.saved_image_format;
+    }
+    if given("figure_numbering") {
+        config.figure_numbering = flags.figure_numbering;
+    }
+    if given("image_sidecars") {
+        config.emit_image_sidecars = flags.emit_image_sidecars;
+    }
+    if given("image_context") {
+        config.image_context = flags.image_context;
+    }
+    if given("prompt_file") {
+        config.custom_full_page = flags.custom_full_page;
+        config.custom_single_image = flags.custom_single_image;
+        config.custom_table = flags.custom_table;
+    }
+    if given("template") {
+        config.output_template = flags.output_template;
+    }
+    if given("max_markdown_bytes") {
+        config.max_markdown_bytes = flags.max_markdown_bytes;
+    }
+    if given("premium_provider") {
+        config.premium_provider = flags.premium_provider;
+    }
+    if given("form_fields") {
+        config.extract_form_fields = flags.extract_form_fields;
+    }
+    if given("toc") {
+        config.emit_toc = flags.emit_toc;
+    }
+    if given("images_output") {
+        config.images_output_dir = flags.images_output_dir;
+    }
+    if given("chunk_noise_patterns") {
+        config.chunk_noise_patterns = flags.chunk_noise_patterns;
+    }
+    if given("pages_per_request") {
+        config.pages_per_request = flags.pages_per_request;
+    }
+    if given("escalate_empty_text") {
+        config.escalate_empty_text = flags.escalate_empty_text;
+    }
+    if given("auto_high_quality_on_scanned") {
+        config.auto_high_quality_on_scanned = flags.auto_high_quality_on_scanned;
+    }
+    if given("dedup_images") {
+        config.dedup_images = flags.dedup_images;
+    }
+    if given("dedup_pages") {
+        config.dedup_pages = flags.dedup_pages;
+    }
+    if given("auto_rotate") {
+        config.auto_rotate = flags.auto_rotate;
+    }
+    if given("rag_jsonl") {
+        config.rag_jsonl = flags.rag_jsonl;
+    }
+    if given("format") {
+        config.output_format = flags.output_format;
+    }
+    if given("wide_tables") {
+        config.wide_table_strategy = flags.wide_table_strategy;
+    }
+    if given("wide_table_max_columns") {
+        config.wide_table_max_columns = flags.wide_table_max_columns;
+    }
+    if given("table_multispace_ratio") {
+        config.table_multispace_ratio = flags.table_multispace_ratio;
+    }
+    if given("table_min_consistent_run") {
+        config.table_min_consistent_run = flags.table_min_consistent_run;
+    }
+    if given("line_ending") {
+        config.line_ending = flags.line_ending;
+    }
+    if given("few_shot") {
+        config.few_shot = flags.few_shot;
+    }
+    if given("page_fallback") {
+        config.page_fallback_strategy = flags.page_fallback_strategy;
+    }
+    if given("caption_placement") {
+        config.caption_placement = flags.caption_placement;
+    }
+    if given("dry_run") {
+        config.dry_run = flags.dry_run;
+    }
+    if given("skip_pages") {
+        config.skip_pages = flags.skip_pages;
+    }
+    if given("include_layers") {
+        config.include_layers = flags.include_layers;
+    }
+    if given("exclude_layers") {
+        config.exclude_layers = flags.exclude_layers;
+    }
+}
+
+async fn run_process(args: ProcessArgs, explicit: &ArgMatches, mode: OutputMode) -> Result<()> {
     let lang: Language = args.lang.parse().unwrap_or_default();
     let quality: Quality = args.quality.parse().unwrap_or_default();
+    let few_shot = parse_few_shot(&args.few_shot)?;
+    let skip_pages = match &args.skip_pages {
+        Some(spec) => parse_page_selection(spec)
+            .map_err(|e| anyhow::anyhow!("Invalid --skip-pages '{spec}': {e}"))?,
+        None => Default::default(),
+    };
+    let custom_prompts = match &args.prompt_file {
+        Some(path) => load_prompt_file(path)?,
+        None => PromptFile::default(),
+    };
+    let output_template = match &args.template {
+        Some(path) => Some(
+            std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("Cannot read template '{}': {e}", path.display()))?,
+        ),
+        None => None,
+    };
 
     let image_dpi = match args.dpi {
         Some(d) => d,
-        None if lang == Language::Th => {
-            println!("  Thai language — auto DPI: 200");
+        None if lang.needs_high_dpi() => {
+            println!("  {} language — auto DPI: 200", lang.name());
             200
         }
         None => 150,
     };
 
-    let config = ProcessingConfig {
+    let mut config = ProcessingConfig {
         language: lang,
         table_extraction: !args.no_tables && !args.text_only,
         text_only: args.text_only,
-        max_concurrent_pages: args.concurrency,
+        max_concurrent_pages: args.concurrency.unwrap_or_else(|| {
+            if args.text_only {
+                4
+            } else {
+                provider::recommended_concurrency(&args.provider)
+            }
+        }),
+        max_concurrent_io: args.io_concurrency,
+        max_concurrent_requests: args.max_requests,
+        requests_per_minute: args.rpm,
+        page_timeout_secs: args.page_timeout,
         detect_trash: !args.no_detect_trash,
+        strip_repeated_lines: !args.keep_headers_footers,
+        trash_preset: args.trash_preset.parse().unwrap_or_default(),
         quality,
         image_dpi,
+        max_render_pixels: args.max_render_pixels,
+        image_encoding: args.image_encoding.parse().unwrap_or_default(),
+        saved_image_format: args.saved_image_format.parse().unwrap_or_default(),
+        figure_numbering: args.figure_numbering.parse().unwrap_or_default(),
+        emit_image_sidecars: args.image_sidecars,
+        image_context: args.image_context,
+        custom_full_page: custom_prompts.full_page,
+        custom_single_image: custom_prompts.single_image,
+        custom_table: custom_prompts.table,
+        output_template,
+        max_markdown_bytes: args.max_markdown_bytes,
+        premium_provider: args.premium_provider.as_ref().map(|p| {
+            let model = args
+                .premium_model
+                .clone()
+                .unwrap_or_else(|| provider::default_model(p).to_string());
+            (p.clone(), model)
+        }),
+        extract_form_fields: args.form_fields,
+        emit_toc: args.toc,
+        images_output_dir: args.images_output.clone(),
+        chunk_noise_patterns: args.chunk_noise_patterns.clone(),
+        pages_per_request: args.pages_per_request.max(1),
+        escalate_empty_text: args.escalate_empty_text,
+        auto_high_quality_on_scanned: args.auto_high_quality_on_scanned,
+        dedup_images: args.dedup_images,
+        dedup_pages: args.dedup_pages,
+        auto_rotate: args.auto_rotate,
+        rag_jsonl: args.rag_jsonl,
+        output_format: args.format.parse().unwrap_or_default(),
+        wide_table_strategy: args.wide_tables.parse().unwrap_or_default(),
+        wide_table_max_columns: args.wide_table_max_columns.max(1),
+        table_multispace_ratio: args.table_multispace_ratio.clamp(0.0, 1.0),
+        table_min_consistent_run: args.table_min_consistent_run.max(2),
+        line_ending: args.line_ending.parse().unwrap_or_default(),
+        few_shot: (!few_shot.is_empty()).then_some(few_shot),
+        page_fallback_strategy: args.page_fallback.parse().unwrap_or_default(),
+        caption_placement: args.caption_placement.parse().unwrap_or_default(),
+        dry_run: args.dry_run,
+        skip_pages,
+        include_layers: args.include_layers.clone(),
+        exclude_layers: args.exclude_layers.clone(),
         ..Default::default()
     };
 
+    // Settings file first, then the flags typed on the command line
+    if let Some(path) = &args.config {
+        let mut file_config = ProcessingConfig::from_toml_path(path)?;
+        apply_explicit_flags(&mut file_config, config, explicit);
+        config = file_config;
+    }
+
     // Print cost warning for high quality mode
-    if quality == Quality::High && !args.text_only {
+    if config.quality == Quality::High && !config.text_only {
         println!();
         println!("=== HIGH QUALITY MODE ===");
         println!("  Every page → Vision LLM as 300 DPI image.");
@@ -204,7 +879,7 @@ async fn run_process(args: ProcessArgs) -> Result<()> {
     }
 
     // Create provider (skip when text_only)
-    let vision_provider: Option<Arc<dyn jay_rag_core::VisionProvider>> = if args.text_only {
+    let vision_provider: Option<Arc<dyn jay_rag_core::VisionProvider>> = if config.text_only {
         println!("\nText-only mode: skipping Vision LLM (no images, no API calls)");
         None
     } else {
@@ -212,9 +887,13 @@ async fn run_process(args: ProcessArgs) -> Result<()> {
             .model
             .unwrap_or_else(|| provider::default_model(&args.provider).to_string());
 
-        let p = provider::create_provider(&args.provider, &model)?;
+        let p = provider::create_provider_with_examples(
+            &args.provider,
+            &model,
+            config.few_shot.as_deref().unwrap_or_default(),
+        )?;
 
-        if !args.skip_check {
+        if !args.skip_check && !config.dry_run {
             println!("\nChecking provider: {} / {}", args.provider, model);
             p.check().await?;
         }
@@ -233,39 +912,98 @@ async fn run_process(args: ProcessArgs) -> Result<()> {
         let mut files = Vec::new();
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
-            if path.extension().is_some_and(|e| e == "pdf") {
+            let is_pdf = path.extension().is_some_and(|e| e == "pdf");
+            if is_pdf || jay_rag_core::is_image_input(&path) {
                 files.push(path);
             }
         }
         files.sort();
-        println!("Found {} PDF(s) in {}", files.len(), args.input.display());
+        println!("Found {} PDF/image file(s) in {}", files.len(), args.input.display());
         files
     } else {
         anyhow::bail!("Input not found: {}", args.input.display());
     };
 
     if pdfs.is_empty() {
-        anyhow::bail!("No PDF files found.");
+        anyhow::bail!("No PDF or image files found.");
     }
 
-    let reporter: Arc<dyn ProgressReporter> = Arc::new(CliProgressReporter::new());
+    let reporter: Arc<dyn ProgressReporter> = Arc::new(CliProgressReporter::new(mode));
     let mut results = Vec::new();
+    let mut total_cost = 0.0;
+    let max_cost = args.max_cost.or(config.max_cost_usd);
 
+    let mut skipped_unchanged = 0;
     for pdf_path in &pdfs {
+        let doc_stem = pdf_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("document");
+        if args.skip_unchanged
+            && jay_rag_core::checksum::is_unchanged(pdf_path, &args.output, doc_stem).await?
+        {
+            println!("Skipping {}: unchanged since last run", pdf_path.display());
+            skipped_unchanged += 1;
+            continue;
+        }
+
+        let relative = args.skip_first > 0 || args.skip_last > 0;
+        let (start_page, end_page) = if relative && !jay_rag_core::is_image_input(pdf_path) {
+            let total = jay_rag_core::pdf::PdfEngine::document_page_count(pdf_path).await?;
+            match relative_page_range(total, &args) {
+                Some(range) => range,
+                None => {
+                    println!(
+                        "Skipping {}: no pages left after --skip-first/--skip-last ({total} total)",
+                        pdf_path.display()
+                    );
+                    continue;
+                }
+            }
+        } else {
+            (
+                if args.start_page > 0 {
+                    Some(args.start_page)
+                } else {
+                    None
+                },
+                args.end_page,
+            )
+        };
+
+        // --max-cost covers the whole run: each document gets what is left
+        config.max_cost_usd = max_cost.map(|max| (max - total_cost).max(0.0));
+
+        let span = tracing::info_span!("document", doc = doc_stem, provider = %args.provider);
         let result = jay_rag_core::process_pdf(
             pdf_path,
             &args.output,
             vision_provider.clone(),
             &config,
             reporter.clone(),
-            if args.start_page > 0 {
-                Some(args.start_page)
-            } else {
-                None
-            },
-            args.end_page,
+            start_page,
+            end_page,
         )
+        .instrument(span)
         .await?;
+        match &result.plan {
+            // A dry run must not mark the PDF as processed for --skip-unchanged
+            Some(plan) => print_plan(plan, result.plan_path.as_deref()),
+            None => {
+                jay_rag_core::checksum::write_source_record(pdf_path, &args.output, doc_stem)
+                    .await?
+            }
+        }
+        println!("Estimated cost: ${:.2}", result.estimated_cost_usd);
+        if let Some(slowest) = &result.slowest_page {
+            println!(
+                "Time: {:.1}s, slowest page: {} ({:.1}s)",
+                result.elapsed_secs,
+                slowest.page,
+                slowest.duration_ms as f64 / 1000.0
+            );
+        }
+        total_cost += result.estimated_cost_usd;
         results.push(result);
     }
 
@@ -290,46 +1028,73 @@ async fn run_process(args: ProcessArgs) -> Result<()> {
                 }
 
                 // Auto-strip if --strip-trash provided
-                if args.strip_trash.is_some() {
-                    let type_filter = args.strip_trash.as_ref().unwrap();
-                    let pages_to_remove: Vec<u32> = trash_items
-                        .iter()
-                        .filter(|t| {
-                            t.page > 0 && match_trash_filter(t, type_filter.as_deref())
-                        })
-                        .map(|t| t.page)
-                        .collect();
-
-                    if pages_to_remove.is_empty() {
-                        println!("  No removable pages match the filter.");
-                    } else {
-                        let (cleaned_path, _) = jay_rag_core::clean_markdown(
-                            &result.markdown_path,
-                            &pages_to_remove,
-                        )
-                        .await?;
-                        println!(
-                            "  Stripped {} page(s) -> {}",
-                            pages_to_remove.len(),
-                            cleaned_path.display()
+                match (&args.strip_trash, &result.markdown_path) {
+                    (Some(_), None) => {
+                        println!("  --strip-trash needs markdown output (--format markdown or both)");
+                    }
+                    (Some(type_filter), Some(markdown_path)) => {
+                        let pages_to_remove = jay_rag_core::trash::pages_to_strip(
+                            &trash_items,
+                            type_filter.as_deref(),
                         );
+
+                        if pages_to_remove.is_empty() {
+                            println!("  No removable pages match the filter.");
+                        } else {
+                            let (cleaned_path, _) =
+                                jay_rag_core::clean_markdown(markdown_path, &pages_to_remove)
+                                    .await?;
+                            println!(
+                                "  Stripped {} page(s) -> {}",
+                                pages_to_remove.len(),
+                                cleaned_path.display()
+                            );
+                        }
                     }
-                } else {
-                    println!("  Tip: Use --strip-trash to auto-remove");
+                    (None, _) => println!("  Tip: Use --strip-trash to auto-remove"),
                 }
             }
         }
     }
 
+    for result in &results {
+        if !result.markdown_parts.is_empty() {
+            println!(
+                "\nMarkdown split into {} part(s):",
+                result.markdown_parts.len()
+            );
+            for part in &result.markdown_parts {
+                println!("  {}", part.display());
+            }
+        }
+        if let Some(rag_path) = &result.rag_jsonl_path {
+            println!("RAG JSONL: {}", rag_path.display());
+        }
+        if let Some(pages_path) = &result.pages_jsonl_path {
+            println!("Pages JSONL: {}", pages_path.display());
+        }
+    }
+
     println!("\n{}", "=".repeat(60));
     println!("Done! {} file(s) processed.", results.len());
+    if skipped_unchanged > 0 {
+        println!("Skipped {skipped_unchanged} unchanged file(s).");
+    }
+    let input_tokens: u64 = results.iter().map(|r| r.input_tokens).sum();
+    let output_tokens: u64 = results.iter().map(|r| r.output_tokens).sum();
+    if input_tokens + output_tokens > 0 {
+        println!("Tokens used: {input_tokens} input / {output_tokens} output");
+    }
     println!("Output: {}", args.output.canonicalize()?.display());
 
-    if !args.text_only {
+    if !config.text_only && !config.dry_run {
         println!();
         println!("Flowise Next Steps:");
         println!("  1. Load .md files using Text File Loader in Document Store");
-        println!("  2. Serve output/images/ as static HTTP");
+        match &config.images_output_dir {
+            Some(dir) => println!("  2. Serve {} as static HTTP", dir.display()),
+            None => println!("  2. Serve output/images/ as static HTTP"),
+        }
         println!("     e.g. jay-rag serve --output {}", args.output.display());
         println!("  3. Add to System Prompt:");
         println!(
@@ -342,25 +1107,146 @@ async fn run_process(args: ProcessArgs) -> Result<()> {
     Ok(())
 }
 
-/// Check if a trash item matches the optional type filter string.
-/// Filter is comma-separated: "toc,boilerplate,blank,header_footer".
-/// If no filter, all types match.
-fn match_trash_filter(
-    item: &jay_rag_core::TrashDetection,
-    filter: Option<&str>,
-) -> bool {
-    let Some(filter) = filter else {
-        return true;
+/// Resolve a per-file page range from `--skip-first`/`--skip-last`, combined
+/// with any absolute `--start-page`/`--end-page`. Returns `None` when no pages
+/// remain.
+fn relative_page_range(total: u32, args: &ProcessArgs) -> Option<(Option<u32>, Option<u32>)> {
+    let start = args.start_page.max(args.skip_first);
+    let end = args
+        .end_page
+        .unwrap_or(total)
+        .min(total.saturating_sub(args.skip_last));
+    (start < end).then_some((Some(start), Some(end)))
+}
+
+async fn run_bench(args: BenchArgs) -> Result<()> {
+    if !args.input.is_file() {
+        anyhow::bail!("Input not found: {}", args.input.display());
+    }
+    let runs = args.runs.max(1);
+    let lang: Language = args.lang.parse().unwrap_or_default();
+    let quality: Quality = args.quality.parse().unwrap_or_default();
+    let config = ProcessingConfig {
+        language: lang,
+        quality,
+        image_dpi: if lang.needs_high_dpi() { 200 } else { 150 },
+        ..Default::default()
+    };
+
+    println!("Benchmark: {} ({} run(s) per provider)", args.input.display(), runs);
+
+    let mut rows = Vec::new();
+    for spec in args.providers.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let (name, model) = match spec.split_once(':') {
+            Some((name, model)) => (name, model.to_string()),
+            None => (spec, provider::default_model(spec).to_string()),
+        };
+
+        let vision: Arc<dyn jay_rag_core::VisionProvider> = if name == "mock" {
+            Arc::new(provider::MockProvider::default())
+        } else {
+            let p = provider::create_provider(name, &model)?;
+            p.check().await?;
+            Arc::from(p)
+        };
+        let label = format!("{} / {}", vision.provider_name(), vision.model_name());
+        let cost_per_image = provider::find_provider(name)
+            .map(|m| m.cost_per_image_usd)
+            .unwrap_or(0.0);
+
+        let mut total_secs = 0.0;
+        let mut pages = 0;
+        let mut images = 0;
+        for run in 1..=runs {
+            let out_dir = args.output.join(format!("{name}_run{run}"));
+            tokio::fs::create_dir_all(&out_dir).await?;
+            let reporter = Arc::new(BenchReporter::default());
+
+            let started = std::time::Instant::now();
+            let result = jay_rag_core::process_pdf(
+                &args.input,
+                &out_dir,
+                Some(vision.clone()),
+                &config,
+                reporter.clone(),
+                args.start_page,
+                args.end_page,
+            )
+            .await?;
+            let secs = started.elapsed().as_secs_f64();
+
+            println!("  {label} run {run}/{runs}: {secs:.1}s");
+            total_secs += secs;
+            pages = reporter.total_pages.load(Ordering::Relaxed);
+            images = result.image_count;
+        }
+
+        let avg_secs = total_secs / runs as f64;
+        rows.push((
+            label,
+            avg_secs,
+            if avg_secs > 0.0 { pages as f64 / avg_secs } else { 0.0 },
+            pages,
+            images,
+            images as f64 * cost_per_image,
+        ));
+    }
+
+    println!();
+    println!(
+        "{:<36} {:>9} {:>9} {:>6} {:>7} {:>10}",
+        "Provider / Model", "Avg time", "Pages/s", "Pages", "Images", "Est. cost"
+    );
+    for (label, avg_secs, pages_per_sec, pages, images, cost) in &rows {
+        println!(
+            "{label:<36} {:>8.1}s {pages_per_sec:>9.2} {pages:>6} {images:>7} {:>10}",
+            avg_secs,
+            format!("${cost:.4}")
+        );
+    }
+
+    Ok(())
+}
+
+async fn run_verify_output(args: VerifyOutputArgs) -> Result<()> {
+    let images_dir = match args.images_dir {
+        Some(dir) => dir,
+        None => {
+            let stem = args
+                .markdown
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default();
+            let doc_stem = stem.strip_suffix("_enriched").unwrap_or(stem);
+            args.markdown
+                .parent()
+                .unwrap_or_else(|| std::path::Path::new("."))
+                .join("images")
+                .join(doc_stem)
+        }
     };
 
-    let types: Vec<&str> = filter.split(',').map(|s| s.trim()).collect();
-    types.iter().any(|t| match *t {
-        "toc" => item.trash_type == jay_rag_core::TrashType::TableOfContents,
-        "boilerplate" => item.trash_type == jay_rag_core::TrashType::Boilerplate,
-        "blank" => item.trash_type == jay_rag_core::TrashType::BlankPage,
-        "header_footer" => item.trash_type == jay_rag_core::TrashType::HeaderFooter,
-        _ => false,
-    })
+    let report = jay_rag_core::verify_output(&args.markdown, &images_dir).await?;
+
+    println!("Markdown: {}", args.markdown.display());
+    println!("Images:   {}", images_dir.display());
+    println!("  {} tag(s), {} image file(s)", report.referenced, report.files);
+    for tag in &report.dangling_tags {
+        println!("  dangling tag:  [IMAGE:{tag}]");
+    }
+    for file in &report.orphan_files {
+        println!("  orphan file:   {file}");
+    }
+
+    if !report.is_ok() {
+        anyhow::bail!(
+            "{} dangling tag(s), {} orphan file(s)",
+            report.dangling_tags.len(),
+            report.orphan_files.len()
+        );
+    }
+    println!("  OK");
+    Ok(())
 }
 
 async fn run_serve(args: ServeArgs) -> Result<()> {
@@ -369,7 +1255,29 @@ async fn run_serve(args: ServeArgs) -> Result<()> {
     let upload_dir = args.output.join(".uploads");
     tokio::fs::create_dir_all(&upload_dir).await?;
 
-    let state = jay_rag_server::AppState::new(upload_dir, args.output.clone());
+    let storage_root = args.storage_root.clone().unwrap_or(args.output.clone());
+    let state = if args.simulate {
+        jay_rag_server::AppState::new_simulated(
+            upload_dir,
+            args.output.clone(),
+            storage_root,
+            args.retention_days,
+        )
+    } else {
+        jay_rag_server::AppState::new(
+            upload_dir,
+            args.output.clone(),
+            storage_root,
+            args.retention_days,
+        )
+    };
+    if let Some(e) = state.pdfium_error.as_ref().filter(|_| !args.simulate) {
+        if !args.skip_pdfium_check {
+            anyhow::bail!("{e}\n(Use --skip-pdfium-check to start the server anyway)");
+        }
+        println!("  Warning: pdfium not available — jobs will fail until it is installed.");
+    }
+    jay_rag_server::jobs::retention::spawn_retention_task(state.clone());
     let app = jay_rag_server::create_app(state);
 
     let listener = tokio::net::TcpListener::bind(&args.bind).await?;
@@ -378,6 +1286,12 @@ async fn run_serve(args: ServeArgs) -> Result<()> {
     println!("  API:       http://{}", args.bind);
     println!("  Dashboard: http://{}", args.bind);
     println!("  Output:    {}", args.output.display());
+    if args.simulate {
+        println!("  Mode:      SIMULATED — jobs return canned results");
+    }
+    if let Some(days) = args.retention_days {
+        println!("  Retention: jobs deleted {days} day(s) after finishing");
+    }
     println!("{}\n", "=".repeat(60));
 
     axum::serve(listener, app).await?;
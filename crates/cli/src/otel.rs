@@ -0,0 +1,49 @@
+//! OTLP trace export, compiled in with `--features otel`.
+//!
+//! Export is switched on at runtime by `OTEL_EXPORTER_OTLP_ENDPOINT`; the
+//! standard `OTEL_*` exporter variables (headers, timeout) are honored.
+
+use anyhow::Result;
+use opentelemetry::KeyValue;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Flushes buffered spans when dropped at the end of `main`.
+pub struct OtelGuard(TracerProvider);
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.0.shutdown() {
+            eprintln!("OpenTelemetry shutdown failed: {e}");
+        }
+    }
+}
+
+/// Build the tracing layer that exports spans over OTLP (gRPC).
+///
+/// Returns `None` when `OTEL_EXPORTER_OTLP_ENDPOINT` is unset. The service
+/// name is `OTEL_SERVICE_NAME`, defaulting to `jay-rag`.
+pub fn layer<S>() -> Result<Option<(OpenTelemetryLayer<S, Tracer>, OtelGuard)>>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").unwrap_or_default();
+    if endpoint.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let service = std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "jay-rag".to_string());
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .build()?;
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new("service.name", service)]))
+        .build();
+
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer("jay-rag"));
+    Ok(Some((layer, OtelGuard(provider))))
+}
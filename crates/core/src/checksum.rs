@@ -0,0 +1,89 @@
+use crate::error::CoreResult;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Sidecar recording which input produced a document's outputs:
+/// `{output_dir}/{doc_stem}_source.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceRecord {
+    /// Input file name (e.g. `manual.pdf`).
+    pub source_file: String,
+    /// Hex SHA-256 of the input file contents.
+    pub sha256: String,
+}
+
+/// Path of the source sidecar for a document.
+pub fn source_record_path(output_dir: &Path, doc_stem: &str) -> PathBuf {
+    output_dir.join(format!("{doc_stem}_source.json"))
+}
+
+/// Hex SHA-256 of a file's contents.
+pub async fn sha256_file(path: &Path) -> CoreResult<String> {
+    let bytes = tokio::fs::read(path).await?;
+    let digest = Sha256::digest(&bytes);
+    Ok(digest.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// Record the hash of `input` next to its outputs.
+pub async fn write_source_record(
+    input: &Path,
+    output_dir: &Path,
+    doc_stem: &str,
+) -> CoreResult<()> {
+    let record = SourceRecord {
+        source_file: input
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        sha256: sha256_file(input).await?,
+    };
+    let json = serde_json::to_string_pretty(&record)?;
+    tokio::fs::write(source_record_path(output_dir, doc_stem), json).await?;
+    Ok(())
+}
+
+/// True when `input` has the same hash as recorded for `doc_stem` and the
+/// enriched markdown still exists, i.e. re-processing would change nothing.
+///
+/// Only the file contents are compared; a changed config does not count.
+pub async fn is_unchanged(input: &Path, output_dir: &Path, doc_stem: &str) -> CoreResult<bool> {
+    let markdown = output_dir.join(format!("{doc_stem}_enriched.md"));
+    if !markdown.is_file() {
+        return Ok(false);
+    }
+    let Ok(json) = tokio::fs::read_to_string(source_record_path(output_dir, doc_stem)).await else {
+        return Ok(false);
+    };
+    let Ok(record) = serde_json::from_str::<SourceRecord>(&json) else {
+        return Ok(false);
+    };
+    Ok(record.sha256 == sha256_file(input).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unchanged_detection() {
+        let dir = std::env::temp_dir().join(format!("jay-rag-checksum-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let input = dir.join("doc.pdf");
+        tokio::fs::write(&input, b"%PDF-1.7 v1").await.unwrap();
+
+        // No outputs yet
+        assert!(!is_unchanged(&input, &dir, "doc").await.unwrap());
+
+        tokio::fs::write(dir.join("doc_enriched.md"), "# doc")
+            .await
+            .unwrap();
+        write_source_record(&input, &dir, "doc").await.unwrap();
+        assert!(is_unchanged(&input, &dir, "doc").await.unwrap());
+
+        tokio::fs::write(&input, b"%PDF-1.7 v2").await.unwrap();
+        assert!(!is_unchanged(&input, &dir, "doc").await.unwrap());
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
@@ -1,4 +1,10 @@
+use crate::error::{CoreError, CoreResult};
+use crate::trash::TrashPreset;
+use jay_rag_storage::StorageBackend;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
 /// Processing quality level.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -45,6 +51,31 @@ pub enum Language {
     Th,
     /// English
     En,
+    /// Lao
+    Lo,
+    /// Khmer
+    Km,
+    /// Vietnamese
+    Vi,
+}
+
+impl Language {
+    /// English name of the language.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Th => "Thai",
+            Self::En => "English",
+            Self::Lo => "Lao",
+            Self::Km => "Khmer",
+            Self::Vi => "Vietnamese",
+        }
+    }
+
+    /// Whether the script stacks vowels and tone marks above and below the
+    /// line (Thai, Lao, Khmer), which needs a higher render DPI to stay legible.
+    pub fn needs_high_dpi(self) -> bool {
+        matches!(self, Self::Th | Self::Lo | Self::Km)
+    }
 }
 
 impl Default for Language {
@@ -58,6 +89,9 @@ impl std::fmt::Display for Language {
         match self {
             Self::Th => write!(f, "th"),
             Self::En => write!(f, "en"),
+            Self::Lo => write!(f, "lo"),
+            Self::Km => write!(f, "km"),
+            Self::Vi => write!(f, "vi"),
         }
     }
 }
@@ -69,7 +103,443 @@ impl std::str::FromStr for Language {
         match s.to_lowercase().as_str() {
             "th" => Ok(Self::Th),
             "en" => Ok(Self::En),
-            other => Err(format!("Unknown language: {other}. Use: th | en")),
+            "lo" => Ok(Self::Lo),
+            "km" => Ok(Self::Km),
+            "vi" => Ok(Self::Vi),
+            other => Err(format!(
+                "Unknown language: {other}. Use: th | en | lo | km | vi"
+            )),
+        }
+    }
+}
+
+/// How extracted images are numbered in the output markdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FigureNumbering {
+    /// Numbering restarts on every page (default).
+    PerPage,
+    /// A single document-wide sequence: Figure 1..N.
+    Continuous,
+}
+
+impl Default for FigureNumbering {
+    fn default() -> Self {
+        Self::PerPage
+    }
+}
+
+impl std::fmt::Display for FigureNumbering {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PerPage => write!(f, "per_page"),
+            Self::Continuous => write!(f, "continuous"),
+        }
+    }
+}
+
+impl std::str::FromStr for FigureNumbering {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "per_page" | "per-page" => Ok(Self::PerPage),
+            "continuous" => Ok(Self::Continuous),
+            other => Err(format!(
+                "Unknown figure numbering: {other}. Use: per_page | continuous"
+            )),
+        }
+    }
+}
+
+/// How markdown tables wider than `wide_table_max_columns` are rewritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WideTableStrategy {
+    /// Leave tables as produced by the LLM (default).
+    Keep,
+    /// Swap rows and columns (good for few rows, many attributes).
+    Transpose,
+    /// Split into several narrower tables, repeating the first column.
+    Split,
+    /// Convert each row into a block of `header: value` lines.
+    Records,
+}
+
+impl Default for WideTableStrategy {
+    fn default() -> Self {
+        Self::Keep
+    }
+}
+
+impl std::fmt::Display for WideTableStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Keep => write!(f, "keep"),
+            Self::Transpose => write!(f, "transpose"),
+            Self::Split => write!(f, "split"),
+            Self::Records => write!(f, "records"),
+        }
+    }
+}
+
+impl std::str::FromStr for WideTableStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "keep" => Ok(Self::Keep),
+            "transpose" => Ok(Self::Transpose),
+            "split" => Ok(Self::Split),
+            "records" => Ok(Self::Records),
+            other => Err(format!(
+                "Unknown wide table strategy: {other}. Use: keep | transpose | split | records"
+            )),
+        }
+    }
+}
+
+/// Line endings used when writing markdown outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEnding {
+    /// `\n` (default).
+    Lf,
+    /// `\r\n`, for Windows-based downstream tools.
+    CrLf,
+}
+
+impl Default for LineEnding {
+    fn default() -> Self {
+        Self::Lf
+    }
+}
+
+impl LineEnding {
+    /// Convert `text` (written with `\n`) to this line ending.
+    pub fn apply<'a>(&self, text: &'a str) -> std::borrow::Cow<'a, str> {
+        match self {
+            Self::Lf => text.into(),
+            Self::CrLf => text.replace("\r\n", "\n").replace('\n', "\r\n").into(),
+        }
+    }
+}
+
+impl std::fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lf => write!(f, "lf"),
+            Self::CrLf => write!(f, "crlf"),
+        }
+    }
+}
+
+impl std::str::FromStr for LineEnding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "lf" => Ok(Self::Lf),
+            "crlf" => Ok(Self::CrLf),
+            other => Err(format!("Unknown line ending: {other}. Use: lf | crlf")),
+        }
+    }
+}
+
+/// Which document outputs are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// `{doc}_enriched.md` (default).
+    Markdown,
+    /// `{doc}_pages.jsonl` only: one structured record per page.
+    JsonLines,
+    /// Both the markdown and the JSON Lines.
+    Both,
+}
+
+impl Default for OutputFormat {
+    fn default() -> Self {
+        Self::Markdown
+    }
+}
+
+impl OutputFormat {
+    /// Whether the enriched markdown is written.
+    pub fn markdown(self) -> bool {
+        matches!(self, Self::Markdown | Self::Both)
+    }
+
+    /// Whether `{doc}_pages.jsonl` is written.
+    pub fn json_lines(self) -> bool {
+        matches!(self, Self::JsonLines | Self::Both)
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Markdown => write!(f, "markdown"),
+            Self::JsonLines => write!(f, "jsonl"),
+            Self::Both => write!(f, "both"),
+        }
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "jsonl" | "json_lines" => Ok(Self::JsonLines),
+            "both" => Ok(Self::Both),
+            other => Err(format!(
+                "Unknown output format: {other}. Use: markdown | jsonl | both"
+            )),
+        }
+    }
+}
+
+/// Where an image's description goes relative to the image in the markdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionPlacement {
+    /// `[IMAGE:...]` tag, then the description (default; what the export
+    /// converter expects).
+    After,
+    /// Description first, then the `[IMAGE:...]` tag.
+    Before,
+    /// Standard markdown `![description](path)` instead of `[IMAGE:...]` tags.
+    /// Page and table transcriptions still follow the image as text.
+    AsAltText,
+}
+
+impl Default for CaptionPlacement {
+    fn default() -> Self {
+        Self::After
+    }
+}
+
+impl std::fmt::Display for CaptionPlacement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::After => write!(f, "after"),
+            Self::Before => write!(f, "before"),
+            Self::AsAltText => write!(f, "as_alt_text"),
+        }
+    }
+}
+
+impl std::str::FromStr for CaptionPlacement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "after" => Ok(Self::After),
+            "before" => Ok(Self::Before),
+            "as_alt_text" => Ok(Self::AsAltText),
+            other => Err(format!(
+                "Unknown caption placement: {other}. Use: after | before | as_alt_text"
+            )),
+        }
+    }
+}
+
+/// Second attempt for a page whose full-page / table / high-quality request
+/// failed after all retries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageFallbackStrategy {
+    /// Keep the failure placeholder (default).
+    None,
+    /// Split the page render into four quadrants and describe each.
+    Quadrants,
+    /// Re-render the page at the high-quality DPI (300+) and send it with the
+    /// high-quality OCR prompt.
+    HighQuality,
+    /// High-quality re-render for standard pages, quadrants for high-quality pages.
+    Auto,
+}
+
+impl Default for PageFallbackStrategy {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl PageFallbackStrategy {
+    /// The fallback to run for a failed page, or `None` when there is nothing
+    /// different left to try.
+    pub fn resolve(self, high_quality_page: bool) -> Option<Self> {
+        match self {
+            Self::None => None,
+            Self::Quadrants => Some(Self::Quadrants),
+            Self::HighQuality if high_quality_page => None,
+            Self::HighQuality => Some(Self::HighQuality),
+            Self::Auto if high_quality_page => Some(Self::Quadrants),
+            Self::Auto => Some(Self::HighQuality),
+        }
+    }
+}
+
+impl std::fmt::Display for PageFallbackStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::Quadrants => write!(f, "quadrants"),
+            Self::HighQuality => write!(f, "high_quality"),
+            Self::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl std::str::FromStr for PageFallbackStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" | "off" => Ok(Self::None),
+            "quadrants" => Ok(Self::Quadrants),
+            "high_quality" | "high-quality" => Ok(Self::HighQuality),
+            "auto" => Ok(Self::Auto),
+            other => Err(format!(
+                "Unknown page fallback strategy: {other}. Use: none | quadrants | high_quality | auto"
+            )),
+        }
+    }
+}
+
+/// Encoding of the page renders and images sent to the vision LLM. Images
+/// saved to disk follow [`SavedImageFormat`] instead.
+///
+/// Serialized as `png` or `jpeg:<quality>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ImageEncoding {
+    /// Lossless PNG (default).
+    Png,
+    /// JPEG at the given quality (1–100): several times smaller payloads.
+    Jpeg(u8),
+}
+
+/// JPEG quality used when none is given (`jpeg`).
+pub(crate) const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+impl ImageEncoding {
+    /// MIME type of payloads in this encoding.
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg(_) => "image/jpeg",
+        }
+    }
+}
+
+impl Default for ImageEncoding {
+    fn default() -> Self {
+        Self::Png
+    }
+}
+
+impl std::fmt::Display for ImageEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Png => write!(f, "png"),
+            Self::Jpeg(quality) => write!(f, "jpeg:{quality}"),
+        }
+    }
+}
+
+impl std::str::FromStr for ImageEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        let (format, quality) = match s.split_once(':') {
+            Some((format, quality)) => (format, Some(quality)),
+            None => (s.as_str(), None),
+        };
+        match (format, quality) {
+            ("png", None) => Ok(Self::Png),
+            ("jpeg" | "jpg", None) => Ok(Self::Jpeg(DEFAULT_JPEG_QUALITY)),
+            ("jpeg" | "jpg", Some(quality)) => match quality.parse::<u8>() {
+                Ok(q @ 1..=100) => Ok(Self::Jpeg(q)),
+                _ => Err(format!("Invalid JPEG quality: {quality}. Use 1-100")),
+            },
+            _ => Err(format!(
+                "Unknown image encoding: {s}. Use: png | jpeg | jpeg:<quality>"
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for ImageEncoding {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<ImageEncoding> for String {
+    fn from(encoding: ImageEncoding) -> Self {
+        encoding.to_string()
+    }
+}
+
+/// File format of the images saved to disk: page renders, table renders and
+/// extracted images. The vision LLM payload follows [`ImageEncoding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SavedImageFormat {
+    /// Lossless PNG (default).
+    Png,
+    /// JPEG at quality 85: smallest files, but lossy and without transparency.
+    #[serde(alias = "jpg")]
+    Jpeg,
+    /// Lossless WebP: typically a quarter to a third smaller than PNG.
+    WebP,
+}
+
+impl SavedImageFormat {
+    /// File extension of saved images, without the dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::WebP => "webp",
+        }
+    }
+}
+
+impl Default for SavedImageFormat {
+    fn default() -> Self {
+        Self::Png
+    }
+}
+
+impl std::fmt::Display for SavedImageFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Png => write!(f, "png"),
+            Self::Jpeg => write!(f, "jpeg"),
+            Self::WebP => write!(f, "webp"),
+        }
+    }
+}
+
+impl std::str::FromStr for SavedImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "png" => Ok(Self::Png),
+            "jpeg" | "jpg" => Ok(Self::Jpeg),
+            "webp" => Ok(Self::WebP),
+            other => Err(format!(
+                "Unknown saved image format: {other}. Use: png | jpeg | webp"
+            )),
         }
     }
 }
@@ -80,6 +550,22 @@ pub struct ProcessingConfig {
     /// DPI for rendering pages as images (default: 150).
     pub image_dpi: u32,
 
+    /// Longest side in pixels of a page render (default: 4096). Oversized
+    /// pages (A3, posters) render at a lower DPI to fit; smaller pages keep
+    /// `image_dpi`.
+    #[serde(default = "default_max_render_pixels")]
+    pub max_render_pixels: u32,
+
+    /// Encoding of images sent to the vision LLM (default: png). JPEG cuts
+    /// request size; saved images follow `saved_image_format`.
+    #[serde(default)]
+    pub image_encoding: ImageEncoding,
+
+    /// Format of the images saved to disk (default: png). `ImageMetadata`
+    /// file names and `[IMAGE:...]` tags carry the matching extension.
+    #[serde(default)]
+    pub saved_image_format: SavedImageFormat,
+
     /// Skip images smaller than this dimension in pixels (default: 100).
     pub min_image_size: u32,
 
@@ -95,6 +581,14 @@ pub struct ProcessingConfig {
     /// Delay between retries in milliseconds (default: 2000).
     pub retry_delay_ms: u64,
 
+    /// Give up on a vision request that has not answered after this many
+    /// seconds, retries included, and record the page as failed (default: no
+    /// limit). Time queued behind `max_concurrent_requests` or
+    /// `requests_per_minute` does not count. While streaming, the limit
+    /// applies to the wait for each chunk.
+    #[serde(default)]
+    pub page_timeout_secs: Option<u64>,
+
     /// Enable table extraction (default: true).
     pub table_extraction: bool,
 
@@ -110,17 +604,241 @@ pub struct ProcessingConfig {
     #[serde(default = "default_concurrent_images")]
     pub max_concurrent_images: usize,
 
+    /// Max image files written concurrently across all pages (default: 64).
+    /// Lower it for spinning disks or NFS-backed output directories.
+    #[serde(default = "default_concurrent_io")]
+    pub max_concurrent_io: usize,
+
+    /// Max vision requests in flight across all pages and images of a run
+    /// (default: unlimited; pages × images otherwise).
+    #[serde(default)]
+    pub max_concurrent_requests: Option<usize>,
+
+    /// Max vision requests sent per minute across a run, to stay under the
+    /// provider's rate limit (default: unlimited).
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+
     /// Enable trash detection (default: true).
     #[serde(default = "default_true")]
     pub detect_trash: bool,
 
+    /// Trash detection sensitivity (default: balanced).
+    #[serde(default)]
+    pub trash_preset: TrashPreset,
+
     /// Processing quality level (default: standard).
     #[serde(default)]
     pub quality: Quality,
 
+    /// Switch to high quality when sampled pages have almost no text layer,
+    /// as in scanned PDFs; otherwise such documents only get a warning
+    /// (default: false).
+    #[serde(default)]
+    pub auto_high_quality_on_scanned: bool,
+
     /// Sharpen + contrast enhancement for better Thai OCR (default: false).
     #[serde(default)]
     pub enhance: bool,
+
+    /// Turn page renders whose text reads sideways or upside down upright
+    /// before OCR (default: false). Upright pages render unchanged.
+    #[serde(default)]
+    pub auto_rotate: bool,
+
+    /// Figure numbering scheme for extracted images (default: per page).
+    #[serde(default)]
+    pub figure_numbering: FigureNumbering,
+
+    /// Write a `<image>.json` sidecar with its metadata next to each image (default: false).
+    #[serde(default)]
+    pub emit_image_sidecars: bool,
+
+    /// Include the page's surrounding text in single-image prompts so figures
+    /// are described in context (default: false).
+    #[serde(default)]
+    pub image_context: bool,
+
+    /// Stream high-quality page transcriptions and report the text as it
+    /// arrives via `on_partial_text` (default: false).
+    #[serde(default)]
+    pub stream_text: bool,
+
+    /// Replaces the built-in full-page and high-quality prompts; may contain
+    /// `{hint_text}` for the PDF text layer (default: none).
+    #[serde(default)]
+    pub custom_full_page: Option<String>,
+
+    /// Replaces the built-in single-image prompt; may contain `{context_text}`
+    /// for the page text when `image_context` is on (default: none).
+    #[serde(default)]
+    pub custom_single_image: Option<String>,
+
+    /// Replaces the built-in table extraction prompt (default: none).
+    #[serde(default)]
+    pub custom_table: Option<String>,
+
+    /// Handlebars template for the markdown layout, rendered from structured
+    /// page data (see [`crate::template::DocumentContext`]); not applied in
+    /// text-only mode (default: built-in layout).
+    #[serde(default)]
+    pub output_template: Option<String>,
+
+    /// Strip detected running headers/footers from the text layer written to
+    /// the markdown, in text-only and vision modes (default: true).
+    #[serde(default = "default_true")]
+    pub strip_repeated_lines: bool,
+
+    /// Detect whole paragraphs repeated across pages, anywhere on the page (default: true).
+    #[serde(default = "default_true")]
+    pub detect_repeated_paragraphs: bool,
+
+    /// Fraction of pages a paragraph must appear on to count as repeated (default: 0.6).
+    #[serde(default = "default_repeated_paragraph_ratio")]
+    pub repeated_paragraph_ratio: f64,
+
+    /// Split the markdown into `_part{n}` files at page boundaries above this size (default: none).
+    #[serde(default)]
+    pub max_markdown_bytes: Option<usize>,
+
+    /// Optional `(provider, model)` for full-page, table-region and high-quality
+    /// requests; individual images keep using the base provider (default: none).
+    #[serde(default)]
+    pub premium_provider: Option<(String, String)>,
+
+    /// Append filled-in AcroForm field values as a "Form Fields" section per page (default: false).
+    #[serde(default)]
+    pub extract_form_fields: bool,
+
+    /// Start the markdown with a "Contents" section built from the PDF
+    /// outline (bookmarks), linking to the `## Page N` headings (default: false).
+    /// Documents without an outline get no section.
+    #[serde(default)]
+    pub emit_toc: bool,
+
+    /// Root directory for extracted images (default: `{output_dir}/images`).
+    /// Markdown and metadata stay in `output_dir`.
+    #[serde(default)]
+    pub images_output_dir: Option<PathBuf>,
+
+    /// Fail the run instead of writing output when at least this fraction of
+    /// pages failed (default: 1.0, i.e. only when every page failed).
+    #[serde(default = "default_max_failed_page_ratio")]
+    pub max_failed_page_ratio: f64,
+
+    /// Lines matching any of these are dropped from RAG chunk text (not from the
+    /// markdown). Plain substrings, or regexes wrapped in slashes: `/^หน้า \d+$/`.
+    #[serde(default)]
+    pub chunk_noise_patterns: Vec<String>,
+
+    /// High-quality mode: send up to this many consecutive page images in one
+    /// multi-image request (default: 1, one page per request).
+    #[serde(default = "default_pages_per_request")]
+    pub pages_per_request: usize,
+
+    /// Standard mode: render a Mixed page as a full-page image when pdfium
+    /// returns no text but the page is visibly not blank (default: false).
+    #[serde(default)]
+    pub escalate_empty_text: bool,
+
+    /// Describe each distinct extracted image once per document: near-identical
+    /// copies (logos, icons) reuse the first description (default: false).
+    #[serde(default)]
+    pub dedup_images: bool,
+
+    /// Describe each distinct full-page render once per document: pages whose
+    /// render and text layer match an earlier page (blank versos, repeated
+    /// dividers) reuse its description (default: false). Not applied to
+    /// batched high-quality pages (`pages_per_request > 1`).
+    #[serde(default)]
+    pub dedup_pages: bool,
+
+    /// Also write `{doc_stem}_rag.jsonl`: one record per page with image
+    /// descriptions inlined into the text (default: false).
+    #[serde(default)]
+    pub rag_jsonl: bool,
+
+    /// Rewrite LLM markdown tables wider than `wide_table_max_columns` (default: keep).
+    #[serde(default)]
+    pub wide_table_strategy: WideTableStrategy,
+
+    /// Column count above which a table counts as wide (default: 14).
+    #[serde(default = "default_wide_table_max_columns")]
+    pub wide_table_max_columns: usize,
+
+    /// Fraction of a page's text lines with 2+ multi-space column gaps above
+    /// which the page is treated as a table (default: 0.4).
+    #[serde(default = "default_table_multispace_ratio")]
+    pub table_multispace_ratio: f64,
+
+    /// Consecutive lines of similar token counts (≥3 each) that mark a page
+    /// as a table when pdfium collapses column gaps (default: 6).
+    #[serde(default = "default_table_min_consistent_run")]
+    pub table_min_consistent_run: usize,
+
+    /// Outputs to write: markdown, per-page JSON Lines, or both (default: markdown).
+    #[serde(default)]
+    pub output_format: OutputFormat,
+
+    /// Line endings of the written markdown files (default: lf).
+    #[serde(default)]
+    pub line_ending: LineEnding,
+
+    /// Few-shot examples `(image path, expected output)` sent before each
+    /// single-image request (default: none).
+    #[serde(default)]
+    pub few_shot: Option<Vec<(PathBuf, String)>>,
+
+    /// Retry a failed full-page / table / high-quality page once with another
+    /// strategy before giving up (default: none).
+    #[serde(default)]
+    pub page_fallback_strategy: PageFallbackStrategy,
+
+    /// Stop the run once the estimated cloud cost (USD) would exceed this
+    /// budget (default: no limit).
+    #[serde(default)]
+    pub max_cost_usd: Option<f64>,
+
+    /// Extract and classify pages, then write `{doc_stem}_plan.json` instead
+    /// of calling the LLM (default: false).
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// Image description placement: after / before the `[IMAGE:...]` tag, or
+    /// as markdown alt text (default: after).
+    #[serde(default)]
+    pub caption_placement: CaptionPlacement,
+
+    /// 1-indexed pages left out entirely: not rendered, not sent to the LLM
+    /// and absent from every output (default: none). Serialized as a spec
+    /// like `"3,7,10-12"`.
+    #[serde(default)]
+    pub skip_pages: PageSelection,
+
+    /// PDF layers (optional content groups) to render; every other layer is
+    /// hidden. Empty shows the document's default layers.
+    #[serde(default)]
+    pub include_layers: Vec<String>,
+
+    /// PDF layers to hide when rendering (e.g. CAD dimension layers), applied
+    /// after `include_layers`. Ignored for PDFs without layers.
+    #[serde(default)]
+    pub exclude_layers: Vec<String>,
+
+    /// Stops the run between pages when cancelled (not serialized).
+    #[serde(skip)]
+    pub cancel: Option<CancellationToken>,
+
+    /// Where outputs are written (not serialized; default: local files under
+    /// `output_dir`). Keys are relative to the backend root: markdown and
+    /// metadata at the top, images under `images/`; `images_output_dir` is
+    /// ignored.
+    #[serde(skip)]
+    pub storage: Option<Arc<dyn StorageBackend>>,
+}
+
+fn default_max_render_pixels() -> u32 {
+    4096
 }
 
 fn default_concurrent_pages() -> usize {
@@ -131,26 +849,326 @@ fn default_concurrent_images() -> usize {
     5
 }
 
+fn default_concurrent_io() -> usize {
+    64
+}
+
 fn default_true() -> bool {
     true
 }
 
+fn default_repeated_paragraph_ratio() -> f64 {
+    0.6
+}
+
+fn default_max_failed_page_ratio() -> f64 {
+    1.0
+}
+
+fn default_pages_per_request() -> usize {
+    1
+}
+
+fn default_wide_table_max_columns() -> usize {
+    14
+}
+
+fn default_table_multispace_ratio() -> f64 {
+    0.4
+}
+
+fn default_table_min_consistent_run() -> usize {
+    6
+}
+
 impl Default for ProcessingConfig {
     fn default() -> Self {
         Self {
             image_dpi: 150,
+            max_render_pixels: default_max_render_pixels(),
+            image_encoding: ImageEncoding::default(),
+            saved_image_format: SavedImageFormat::default(),
             min_image_size: 100,
             page_as_image_threshold: 0.5,
             language: Language::default(),
             max_retries: 3,
             retry_delay_ms: 2000,
+            page_timeout_secs: None,
             table_extraction: true,
             text_only: false,
             max_concurrent_pages: default_concurrent_pages(),
             max_concurrent_images: default_concurrent_images(),
+            max_concurrent_io: default_concurrent_io(),
+            max_concurrent_requests: None,
+            requests_per_minute: None,
             detect_trash: true,
+            trash_preset: TrashPreset::default(),
             quality: Quality::default(),
+            auto_high_quality_on_scanned: false,
             enhance: false,
+            auto_rotate: false,
+            figure_numbering: FigureNumbering::default(),
+            emit_image_sidecars: false,
+            image_context: false,
+            stream_text: false,
+            custom_full_page: None,
+            custom_single_image: None,
+            custom_table: None,
+            output_template: None,
+            strip_repeated_lines: true,
+            detect_repeated_paragraphs: true,
+            repeated_paragraph_ratio: default_repeated_paragraph_ratio(),
+            max_markdown_bytes: None,
+            premium_provider: None,
+            extract_form_fields: false,
+            emit_toc: false,
+            images_output_dir: None,
+            max_failed_page_ratio: default_max_failed_page_ratio(),
+            chunk_noise_patterns: Vec::new(),
+            pages_per_request: default_pages_per_request(),
+            escalate_empty_text: false,
+            dedup_images: false,
+            dedup_pages: false,
+            rag_jsonl: false,
+            wide_table_strategy: WideTableStrategy::default(),
+            wide_table_max_columns: default_wide_table_max_columns(),
+            table_multispace_ratio: default_table_multispace_ratio(),
+            table_min_consistent_run: default_table_min_consistent_run(),
+            output_format: OutputFormat::default(),
+            line_ending: LineEnding::default(),
+            few_shot: None,
+            page_fallback_strategy: PageFallbackStrategy::default(),
+            max_cost_usd: None,
+            dry_run: false,
+            caption_placement: CaptionPlacement::default(),
+            skip_pages: PageSelection::default(),
+            include_layers: Vec::new(),
+            exclude_layers: Vec::new(),
+            cancel: None,
+            storage: None,
+        }
+    }
+}
+
+impl ProcessingConfig {
+    /// Whether the run's cancellation token has been triggered.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|c| c.is_cancelled())
+    }
+
+    /// Load a config from a TOML file whose keys are the field names.
+    ///
+    /// Keys left out keep their defaults. Unknown keys are logged as warnings
+    /// and ignored, so a file written for a newer version still loads.
+    pub fn from_toml_path(path: &Path) -> CoreResult<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            CoreError::Config(format!("Cannot read config file '{}': {e}", path.display()))
+        })?;
+        let (config, unknown) = Self::from_toml_str(&content).map_err(|e| {
+            CoreError::Config(format!("Invalid config file '{}': {e}", path.display()))
+        })?;
+        for key in unknown {
+            tracing::warn!(
+                "Ignoring unknown key '{key}' in config file '{}'",
+                path.display()
+            );
+        }
+        Ok(config)
+    }
+
+    /// Parse TOML over the defaults, returning the config and the unknown keys.
+    fn from_toml_str(content: &str) -> Result<(Self, Vec<String>), String> {
+        let table: toml::Table = toml::from_str(content).map_err(|e| e.to_string())?;
+        let serde_json::Value::Object(mut fields) =
+            serde_json::to_value(Self::default()).map_err(|e| e.to_string())?
+        else {
+            unreachable!("ProcessingConfig serializes to an object");
+        };
+
+        let mut unknown = Vec::new();
+        for (key, value) in table {
+            if fields.contains_key(&key) {
+                let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+                fields.insert(key, value);
+            } else {
+                unknown.push(key);
+            }
+        }
+        let config =
+            serde_json::from_value(serde_json::Value::Object(fields)).map_err(|e| e.to_string())?;
+        Ok((config, unknown))
+    }
+}
+
+/// A set of 1-indexed pages, kept as sorted, disjoint ranges so a selection
+/// like `1-4000000000` costs no more than `1-4`.
+///
+/// Serialized as its spec, e.g. `"3,7,10-12"` (empty: no pages).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PageSelection {
+    ranges: Vec<(u32, u32)>,
+}
+
+impl PageSelection {
+    /// Whether `page` (1-indexed) is selected.
+    pub fn contains(&self, page: u32) -> bool {
+        let after = self.ranges.partition_point(|&(start, _)| start <= page);
+        after > 0 && page <= self.ranges[after - 1].1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+impl std::fmt::Display for PageSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, &(start, end)) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            if start == end {
+                write!(f, "{start}")?;
+            } else {
+                write!(f, "{start}-{end}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for PageSelection {
+    type Err = String;
+
+    /// Like [`parse_page_selection`], but an empty spec selects no pages.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        parse_page_selection(s)
+    }
+}
+
+impl TryFrom<String> for PageSelection {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<PageSelection> for String {
+    fn from(selection: PageSelection) -> Self {
+        selection.to_string()
+    }
+}
+
+/// Parse a page selection like `3,7,10` or `2-5,9` (1-indexed pages).
+pub fn parse_page_selection(spec: &str) -> Result<PageSelection, String> {
+    let mut ranges = Vec::new();
+    for part in spec.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        let parse = |s: &str| {
+            s.trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| format!("Invalid page number '{s}' in pages selection"))
+        };
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (parse(start)?, parse(end)?);
+                if start > end {
+                    return Err(format!("Invalid page range '{part}'"));
+                }
+                ranges.push((start, end));
+            }
+            None => {
+                let page = parse(part)?;
+                ranges.push((page, page));
+            }
         }
     }
+    if ranges.is_empty() {
+        return Err("pages selection must not be empty".to_string());
+    }
+
+    // Merge overlapping and adjacent ranges
+    ranges.sort_unstable();
+    let mut merged: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    Ok(PageSelection { ranges: merged })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_encoding_from_str() {
+        assert_eq!("png".parse(), Ok(ImageEncoding::Png));
+        assert_eq!("JPEG".parse(), Ok(ImageEncoding::Jpeg(85)));
+        assert_eq!("jpg:70".parse(), Ok(ImageEncoding::Jpeg(70)));
+        assert!("jpeg:0".parse::<ImageEncoding>().is_err());
+        assert!("webp".parse::<ImageEncoding>().is_err());
+        assert_eq!(
+            serde_json::to_value(ImageEncoding::Jpeg(70)).unwrap(),
+            serde_json::json!("jpeg:70")
+        );
+    }
+
+    #[test]
+    fn test_page_selection() {
+        let selection = parse_page_selection("10-12, 3,7,11-14,8").unwrap();
+        assert_eq!(selection.to_string(), "3,7-8,10-14");
+        assert!(selection.contains(7) && selection.contains(14));
+        assert!(!selection.contains(9) && !selection.contains(15) && !selection.contains(1));
+
+        // Huge ranges are not expanded
+        let selection = parse_page_selection("1-4000000000").unwrap();
+        assert!(selection.contains(3_999_999_999));
+        assert_eq!(selection.to_string(), "1-4000000000");
+
+        assert_eq!(
+            "".parse::<PageSelection>().unwrap(),
+            PageSelection::default()
+        );
+        assert_eq!(
+            serde_json::from_value::<PageSelection>(serde_json::json!("2-3")).unwrap(),
+            parse_page_selection("2,3").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_saved_image_format() {
+        assert_eq!("WebP".parse(), Ok(SavedImageFormat::WebP));
+        assert_eq!("jpg".parse(), Ok(SavedImageFormat::Jpeg));
+        assert!("gif".parse::<SavedImageFormat>().is_err());
+        assert_eq!(SavedImageFormat::Jpeg.extension(), "jpg");
+        assert_eq!(
+            serde_json::to_value(SavedImageFormat::WebP).unwrap(),
+            serde_json::json!("webp")
+        );
+    }
+
+    #[test]
+    fn test_from_toml_str_overrides_defaults() {
+        let (config, unknown) = ProcessingConfig::from_toml_str(
+            "image_dpi = 300\nquality = \"high\"\nmax_concurrent_requests = 8\nshiny_new_knob = true\n",
+        )
+        .unwrap();
+        assert_eq!(config.image_dpi, 300);
+        assert_eq!(config.quality, Quality::High);
+        assert_eq!(config.max_concurrent_requests, Some(8));
+        assert_eq!(config.min_image_size, 100);
+        assert!(config.detect_trash);
+        assert_eq!(unknown, ["shiny_new_knob"]);
+
+        assert!(ProcessingConfig::from_toml_str("image_dpi = \"high\"").is_err());
+    }
 }
@@ -0,0 +1,90 @@
+use crate::metadata::{ImageMetadata, ImageType};
+use crate::provider::{find_provider, VisionProvider};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Approximate cost of one image for a provider (`0.0` for local or unknown providers).
+pub fn cost_per_image(provider: &dyn VisionProvider) -> f64 {
+    find_provider(provider.provider_name()).map_or(0.0, |m| m.cost_per_image_usd)
+}
+
+/// Per-image rates of the two providers a run can use.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImageRates {
+    /// Extracted images (the base provider).
+    pub base: f64,
+    /// Full-page, table and high-quality page renders (the premium provider).
+    pub premium: f64,
+}
+
+impl ImageRates {
+    /// Estimated cost of the described images in `metadata`.
+    pub fn cost_of(&self, metadata: &[ImageMetadata]) -> f64 {
+        metadata
+            .iter()
+            .map(|m| match m.image_type {
+                ImageType::ExtractedImage => self.base,
+                ImageType::FullPage | ImageType::TableRegion => self.premium,
+            })
+            .sum()
+    }
+}
+
+/// Running cost estimate for one document, shared by the page tasks.
+///
+/// Amounts are kept in micro-dollars so tasks can update them atomically.
+/// Pages in flight hold a reservation until their actual cost is known.
+#[derive(Debug, Default)]
+pub struct CostMeter {
+    spent: AtomicU64,
+    reserved: AtomicU64,
+}
+
+impl CostMeter {
+    /// Hold `usd` for a page that has been started.
+    pub fn reserve(&self, usd: f64) {
+        self.reserved.fetch_add(to_micros(usd), Ordering::Relaxed);
+    }
+
+    /// Replace a page's reservation with what it actually cost.
+    pub fn settle(&self, reserved: f64, actual: f64) {
+        self.reserved
+            .fetch_sub(to_micros(reserved), Ordering::Relaxed);
+        self.spent.fetch_add(to_micros(actual), Ordering::Relaxed);
+    }
+
+    /// Cost of the pages finished so far.
+    pub fn spent(&self) -> f64 {
+        from_micros(self.spent.load(Ordering::Relaxed))
+    }
+
+    /// Finished plus in-flight cost, plus `next` for a page about to start.
+    pub fn projected(&self, next: f64) -> f64 {
+        let held = self.spent.load(Ordering::Relaxed) + self.reserved.load(Ordering::Relaxed);
+        from_micros(held) + next
+    }
+}
+
+fn to_micros(usd: f64) -> u64 {
+    (usd * 1_000_000.0).round() as u64
+}
+
+fn from_micros(micros: u64) -> f64 {
+    micros as f64 / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settle_replaces_reservation() {
+        let meter = CostMeter::default();
+        meter.reserve(0.02);
+        meter.reserve(0.01);
+        assert!((meter.projected(0.01) - 0.04).abs() < 1e-9);
+
+        meter.settle(0.02, 0.03);
+        assert!((meter.spent() - 0.03).abs() < 1e-9);
+        assert!((meter.projected(0.0) - 0.04).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,265 @@
+use image::imageops::FilterType;
+use std::sync::Mutex;
+
+/// Largest Hamming distance between two hashes still treated as the same image.
+const MAX_HASH_DISTANCE: u32 = 4;
+
+/// Largest relative difference in aspect ratio still treated as the same image.
+const MAX_ASPECT_DIFF: f32 = 0.1;
+
+/// Side of the grayscale thumbnail compared between page renders.
+const PAGE_THUMB_SIZE: u32 = 32;
+
+/// Largest mean per-pixel difference (0–255) between two page thumbnails
+/// still treated as the same page: scanner noise, not a changed line of text.
+const MAX_PAGE_PIXEL_DIFF: f32 = 3.0;
+
+/// Luma below which a pixel of a page render counts as ink.
+const INK_LUMA: u8 = 128;
+
+/// Largest share of ink pixels for a render to count as (nearly) blank.
+const MAX_BLANK_INK_RATIO: f32 = 0.002;
+
+/// Perceptual fingerprint of an image: a 64-bit difference hash (dHash) plus
+/// its aspect ratio, so re-encoded or slightly rescaled copies still match.
+#[derive(Debug, Clone, Copy)]
+pub struct Fingerprint {
+    hash: u64,
+    aspect: f32,
+}
+
+impl Fingerprint {
+    /// Fingerprint of an encoded image (`None` if it cannot be decoded).
+    pub fn of_image(bytes: &[u8]) -> Option<Self> {
+        let img = image::load_from_memory(bytes).ok()?;
+        if img.width() == 0 || img.height() == 0 {
+            return None;
+        }
+        let aspect = img.width() as f32 / img.height() as f32;
+
+        // Each bit: is a pixel brighter than its right neighbour on a 9x8 thumbnail
+        let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+        let mut hash = 0u64;
+        for y in 0..8 {
+            for x in 0..8 {
+                let bright = small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0];
+                hash = (hash << 1) | u64::from(bright);
+            }
+        }
+        Some(Self { hash, aspect })
+    }
+
+    fn matches(&self, other: &Self) -> bool {
+        (self.hash ^ other.hash).count_ones() <= MAX_HASH_DISTANCE
+            && (self.aspect / other.aspect - 1.0).abs() <= MAX_ASPECT_DIFF
+    }
+}
+
+/// Descriptions of the images already described in a document, shared by
+/// the page tasks (`dedup_images`).
+///
+/// Pages run concurrently, so copies of an image on pages in flight at the
+/// same time may still each be described once.
+#[derive(Debug, Default)]
+pub struct ImageDedup {
+    seen: Mutex<Vec<(Fingerprint, String)>>,
+}
+
+impl ImageDedup {
+    /// Description of an already-described image that looks like `fingerprint`.
+    pub fn find(&self, fingerprint: &Fingerprint) -> Option<String> {
+        let seen = self.seen.lock().unwrap();
+        seen.iter()
+            .find(|(seen, _)| seen.matches(fingerprint))
+            .map(|(_, description)| description.clone())
+    }
+
+    /// Remember the description of a newly described image.
+    pub fn insert(&self, fingerprint: Fingerprint, description: String) {
+        self.seen.lock().unwrap().push((fingerprint, description));
+    }
+}
+
+/// Fingerprint of a full-page render: a downsampled grayscale copy plus its
+/// text layer with whitespace removed.
+///
+/// Two renders only match when their text layers are also identical, so
+/// pages that merely share a layout (same template, different text) are
+/// never mistaken for each other. Without a text layer that safeguard is
+/// gone, so only (nearly) blank renders get a fingerprint: two scans of
+/// different text look alike once downsampled.
+#[derive(Debug, Clone)]
+pub struct PageFingerprint {
+    thumb: Vec<u8>,
+    aspect: f32,
+    text: String,
+}
+
+impl PageFingerprint {
+    /// Fingerprint of an encoded page render and its text layer (`None` if
+    /// the render cannot be decoded, or has no text layer and is not blank).
+    pub fn of_page(bytes: &[u8], text: &str) -> Option<Self> {
+        let img = image::load_from_memory(bytes).ok()?;
+        if img.width() == 0 || img.height() == 0 {
+            return None;
+        }
+        let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+        if text.is_empty() && !is_near_blank(&img) {
+            return None;
+        }
+        let thumb = img
+            .resize_exact(PAGE_THUMB_SIZE, PAGE_THUMB_SIZE, FilterType::Triangle)
+            .to_luma8()
+            .into_raw();
+        Some(Self {
+            thumb,
+            aspect: img.width() as f32 / img.height() as f32,
+            text,
+        })
+    }
+
+    fn matches(&self, other: &Self) -> bool {
+        if self.text != other.text || (self.aspect / other.aspect - 1.0).abs() > MAX_ASPECT_DIFF {
+            return false;
+        }
+        let diff: u32 = self
+            .thumb
+            .iter()
+            .zip(&other.thumb)
+            .map(|(a, b)| u32::from(a.abs_diff(*b)))
+            .sum();
+        diff as f32 / self.thumb.len() as f32 <= MAX_PAGE_PIXEL_DIFF
+    }
+}
+
+/// Whether at most `MAX_BLANK_INK_RATIO` of a render's pixels are ink,
+/// counted at full resolution so thin strokes are not averaged away.
+fn is_near_blank(img: &image::DynamicImage) -> bool {
+    let luma = img.to_luma8();
+    let ink = luma.pixels().filter(|p| p[0] < INK_LUMA).count();
+    ink as f32 <= MAX_BLANK_INK_RATIO * (luma.width() * luma.height()) as f32
+}
+
+/// Descriptions of the full-page renders already described in a document,
+/// shared by the page tasks (`dedup_pages`): blank versos and repeated
+/// divider pages in duplex scans are described once.
+///
+/// As with [`ImageDedup`], matching pages in flight at the same time may
+/// still each be described.
+#[derive(Debug, Default)]
+pub struct PageDedup {
+    seen: Mutex<Vec<(PageFingerprint, u32, String)>>,
+}
+
+impl PageDedup {
+    /// 1-indexed page and description of an already-described page that
+    /// looks like `fingerprint`.
+    pub fn find(&self, fingerprint: &PageFingerprint) -> Option<(u32, String)> {
+        let seen = self.seen.lock().unwrap();
+        seen.iter()
+            .find(|(seen, _, _)| seen.matches(fingerprint))
+            .map(|(_, page, description)| (*page, description.clone()))
+    }
+
+    /// Remember the description of newly described page `page` (1-indexed).
+    pub fn insert(&self, fingerprint: PageFingerprint, page: u32, description: String) {
+        self.seen
+            .lock()
+            .unwrap()
+            .push((fingerprint, page, description));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, ImageFormat, Rgb, RgbImage};
+    use std::io::Cursor;
+
+    fn png(width: u32, height: u32, pixel: impl Fn(u32, u32) -> Rgb<u8>) -> Vec<u8> {
+        let img = RgbImage::from_fn(width, height, pixel);
+        let mut bytes = Vec::new();
+        DynamicImage::ImageRgb8(img)
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_rescaled_copy_reuses_description() {
+        let logo = |scale: u32| {
+            png(40 * scale, 20 * scale, move |x, _| {
+                Rgb([((x / scale) * 6) as u8, 0, 0])
+            })
+        };
+        let dedup = ImageDedup::default();
+        dedup.insert(Fingerprint::of_image(&logo(1)).unwrap(), "Logo".to_string());
+
+        let copy = Fingerprint::of_image(&logo(2)).unwrap();
+        assert_eq!(dedup.find(&copy).as_deref(), Some("Logo"));
+
+        // Same gradient, mirrored: a different image
+        let mirrored = png(40, 20, |x, _| Rgb([((39 - x) * 6) as u8, 0, 0]));
+        let mirrored = Fingerprint::of_image(&mirrored).unwrap();
+        assert!(dedup.find(&mirrored).is_none());
+
+        // Same pattern, different shape
+        let banner = png(160, 20, |x, _| Rgb([((x / 4) * 6) as u8, 0, 0]));
+        let banner = Fingerprint::of_image(&banner).unwrap();
+        assert!(dedup.find(&banner).is_none());
+    }
+
+    #[test]
+    fn test_identical_page_reuses_description() {
+        let blank = png(120, 170, |_, _| Rgb([255, 255, 255]));
+        let noisy = png(120, 170, |x, y| {
+            let v = if (x * 7 + y * 13) % 97 == 0 { 235 } else { 255 };
+            Rgb([v, v, v])
+        });
+        let divider = png(120, 170, |_, y| {
+            let v = if (80..90).contains(&y) { 0 } else { 255 };
+            Rgb([v, v, v])
+        });
+
+        let dedup = PageDedup::default();
+        dedup.insert(
+            PageFingerprint::of_page(&blank, "").unwrap(),
+            2,
+            "หน้าว่าง".to_string(),
+        );
+        let verso = PageFingerprint::of_page(&noisy, " \n").unwrap();
+        assert_eq!(dedup.find(&verso), Some((2, "หน้าว่าง".to_string())));
+
+        // Same render, different text layer
+        let captioned = PageFingerprint::of_page(&blank, "หมายเหตุ").unwrap();
+        assert!(dedup.find(&captioned).is_none());
+        // Same text layer, visibly different page
+        dedup.insert(captioned, 3, "หมายเหตุ".to_string());
+        let divider = PageFingerprint::of_page(&divider, "หมายเหตุ").unwrap();
+        assert!(dedup.find(&divider).is_none());
+    }
+
+    #[test]
+    fn test_scans_without_text_layer_never_match() {
+        // Two scans of different text in the same layout: lines of "glyphs"
+        // that blur into the same gray bands on a small thumbnail
+        let scan = |shift: u32| {
+            png(400, 560, move |x, y| {
+                let glyph = (40..360).contains(&x) && y % 24 < 10 && (x + shift * (y / 24)) % 7 < 3;
+                let v = if glyph { 0 } else { 255 };
+                Rgb([v, v, v])
+            })
+        };
+        let (first, second) = (scan(1), scan(4));
+        assert_ne!(first, second);
+        assert!(PageFingerprint::of_page(&first, "").is_none());
+        assert!(PageFingerprint::of_page(&second, " \n").is_none());
+
+        // With their text layers they are told apart by the text
+        let first = PageFingerprint::of_page(&first, "บทที่ 1").unwrap();
+        let second = PageFingerprint::of_page(&second, "บทที่ 2").unwrap();
+        let dedup = PageDedup::default();
+        dedup.insert(first, 1, "บทที่ 1".to_string());
+        assert!(dedup.find(&second).is_none());
+    }
+}
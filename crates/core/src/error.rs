@@ -1,3 +1,4 @@
+use std::time::Duration;
 use thiserror::Error;
 
 /// Result type alias using [`CoreError`].
@@ -15,15 +16,41 @@ pub enum CoreError {
     #[error("Provider error: {0}")]
     Provider(String),
 
+    /// The provider answered HTTP 429, with how long it asked us to wait.
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
     #[error("JSON error: {0}")]
     Serde(#[from] serde_json::Error),
 
+    #[error("Storage error: {0}")]
+    Storage(#[from] jay_rag_storage::StorageError),
+
     #[error("Config error: {0}")]
     Config(String),
 
     #[error("Pdfium error: {0}")]
     Pdfium(String),
+
+    #[error("Cost limit exceeded: {0}")]
+    CostLimit(String),
+
+    #[error("Cancelled")]
+    Cancelled,
+}
+
+impl CoreError {
+    /// Wait requested by the provider before retrying (`Retry-After`).
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
 }
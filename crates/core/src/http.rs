@@ -0,0 +1,98 @@
+use crate::error::{CoreError, CoreResult};
+use std::time::Duration;
+
+/// Timeouts and retry policy for outbound HTTP calls (Ollama probe, RAG
+/// platform uploads, notification webhooks).
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    /// TCP connect timeout in seconds (default: 10).
+    pub connect_timeout_secs: u64,
+    /// Read timeout in seconds, applied between received chunks (default: 30).
+    pub read_timeout_secs: u64,
+    /// Extra attempts after the first failure (default: 2).
+    pub max_retries: u32,
+    /// Base delay between retries in milliseconds, doubled per attempt (default: 1000).
+    pub retry_delay_ms: u64,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 10,
+            read_timeout_secs: 30,
+            max_retries: 2,
+            retry_delay_ms: 1000,
+        }
+    }
+}
+
+impl HttpConfig {
+    /// Build from environment variables, falling back to defaults:
+    /// `JAY_RAG_HTTP_CONNECT_TIMEOUT`, `JAY_RAG_HTTP_READ_TIMEOUT` (seconds),
+    /// `JAY_RAG_HTTP_RETRIES` and `JAY_RAG_HTTP_RETRY_DELAY_MS`.
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(default)
+        }
+
+        let defaults = Self::default();
+        Self {
+            connect_timeout_secs: env_or("JAY_RAG_HTTP_CONNECT_TIMEOUT", defaults.connect_timeout_secs),
+            read_timeout_secs: env_or("JAY_RAG_HTTP_READ_TIMEOUT", defaults.read_timeout_secs),
+            max_retries: env_or("JAY_RAG_HTTP_RETRIES", defaults.max_retries),
+            retry_delay_ms: env_or("JAY_RAG_HTTP_RETRY_DELAY_MS", defaults.retry_delay_ms),
+        }
+    }
+
+    /// Build a `reqwest::Client` with the configured timeouts.
+    pub fn client(&self) -> CoreResult<reqwest::Client> {
+        reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(self.connect_timeout_secs))
+            .read_timeout(Duration::from_secs(self.read_timeout_secs))
+            .build()
+            .map_err(|e| CoreError::Config(format!("Failed to create HTTP client: {e}")))
+    }
+}
+
+/// Send a request, retrying with exponential backoff on connection errors,
+/// timeouts, HTTP 429 and 5xx responses.
+///
+/// `make_request` is called once per attempt since a `RequestBuilder` can
+/// only be sent once.
+pub async fn send_with_retry<F>(
+    config: &HttpConfig,
+    make_request: F,
+) -> Result<reqwest::Response, reqwest::Error>
+where
+    F: Fn() -> reqwest::RequestBuilder,
+{
+    let mut attempt: u32 = 0;
+    loop {
+        let reason = match make_request().send().await {
+            Ok(resp) if is_retryable_status(resp.status()) && attempt < config.max_retries => {
+                format!("HTTP {}", resp.status())
+            }
+            Ok(resp) => return Ok(resp),
+            Err(e) if (e.is_connect() || e.is_timeout()) && attempt < config.max_retries => {
+                crate::redact::redact_secrets(&e.to_string())
+            }
+            Err(e) => return Err(e),
+        };
+
+        let delay = config.retry_delay_ms.saturating_mul(2u64.saturating_pow(attempt));
+        tracing::warn!(
+            "HTTP request failed (attempt {}/{}): {reason} — retrying in {delay}ms",
+            attempt + 1,
+            config.max_retries + 1
+        );
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        attempt += 1;
+    }
+}
+
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
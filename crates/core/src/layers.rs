@@ -0,0 +1,263 @@
+use crate::error::{CoreError, CoreResult};
+use lopdf::{Document, Object, ObjectId};
+
+/// Names of the document's optional content groups (PDF layers), in the
+/// order the catalog lists them. Empty when the PDF has no layers.
+pub fn list_layers(pdf: &[u8]) -> CoreResult<Vec<String>> {
+    let doc = load(pdf)?;
+    Ok(layer_groups(&doc)
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect())
+}
+
+/// Rewrite the default layer configuration so only the selected layers are
+/// visible, returning the patched PDF.
+///
+/// With `include` non-empty every layer starts hidden and only the named ones
+/// are shown; `exclude` then hides layers on top of that. Names are matched
+/// exactly (every group carrying a name is toggled). Returns `None` when there
+/// is nothing to change: no selection, or a PDF without layers.
+pub fn select_layers(
+    pdf: &[u8],
+    include: &[String],
+    exclude: &[String],
+) -> CoreResult<Option<Vec<u8>>> {
+    if include.is_empty() && exclude.is_empty() {
+        return Ok(None);
+    }
+    let mut doc = load(pdf)?;
+    let groups = layer_groups(&doc);
+    if groups.is_empty() {
+        tracing::warn!("PDF has no layers; rendering every page as is");
+        return Ok(None);
+    }
+    for name in include.iter().chain(exclude) {
+        if !groups.iter().any(|(_, layer)| layer == name) {
+            let available: Vec<&str> = groups.iter().map(|(_, layer)| layer.as_str()).collect();
+            tracing::warn!(
+                "Unknown PDF layer '{name}' (available: {})",
+                available.join(", ")
+            );
+        }
+    }
+
+    let shown =
+        |name: &String| (include.is_empty() || include.contains(name)) && !exclude.contains(name);
+    let (on, off): (Vec<_>, Vec<_>) = groups.iter().partition(|(_, name)| shown(name));
+    let refs = |groups: Vec<&(ObjectId, String)>| -> Vec<Object> {
+        groups
+            .into_iter()
+            .map(|(id, _)| Object::Reference(*id))
+            .collect()
+    };
+
+    let config = default_config_mut(&mut doc)?;
+    config.set("BaseState", Object::Name(b"ON".to_vec()));
+    config.set("ON", Object::Array(refs(on)));
+    config.set("OFF", Object::Array(refs(off)));
+    // Usage-based auto states could re-enable a hidden layer at render time.
+    config.remove(b"AS");
+
+    let mut out = Vec::with_capacity(pdf.len());
+    doc.save_to(&mut out)
+        .map_err(|e| CoreError::Pdf(format!("Failed to write PDF layers: {e}")))?;
+    Ok(Some(out))
+}
+
+fn load(pdf: &[u8]) -> CoreResult<Document> {
+    Document::load_mem(pdf).map_err(|e| CoreError::Pdf(format!("Failed to read PDF layers: {e}")))
+}
+
+/// `(object id, name)` of every group in `/Root /OCProperties /OCGs`.
+fn layer_groups(doc: &Document) -> Vec<(ObjectId, String)> {
+    let Some(ocgs) = doc
+        .catalog()
+        .and_then(|c| c.get(b"OCProperties"))
+        .and_then(|p| doc.dereference(p))
+        .and_then(|(_, p)| p.as_dict())
+        .and_then(|p| p.get(b"OCGs"))
+        .and_then(|o| doc.dereference(o))
+        .and_then(|(_, o)| o.as_array())
+        .ok()
+    else {
+        return Vec::new();
+    };
+
+    ocgs.iter()
+        .filter_map(|group| {
+            let id = group.as_reference().ok()?;
+            let name = doc
+                .get_dictionary(id)
+                .and_then(|g| g.get(b"Name"))
+                .and_then(|n| doc.dereference(n))
+                .ok()
+                .and_then(|(_, n)| lopdf::decode_text_string(n).ok())?;
+            Some((id, name))
+        })
+        .collect()
+}
+
+/// The `/D` (default) configuration dictionary of the optional content
+/// properties, created when missing.
+fn default_config_mut(doc: &mut Document) -> CoreResult<&mut lopdf::Dictionary> {
+    let missing = |what: &str| CoreError::Pdf(format!("PDF layers: missing {what}"));
+
+    // Either entry may be inline or an indirect object.
+    let props = doc
+        .catalog()
+        .and_then(|c| c.get(b"OCProperties"))
+        .map_err(|_| missing("/OCProperties"))?;
+    let props_id = props.as_reference().ok();
+    let config_id = match props_id {
+        Some(id) => doc.get_dictionary(id),
+        None => props.as_dict(),
+    }
+    .and_then(|p| p.get(b"D"))
+    .ok()
+    .and_then(|d| d.as_reference().ok());
+
+    if let Some(id) = config_id {
+        return doc.get_dictionary_mut(id).map_err(|_| missing("/D"));
+    }
+    let props = match props_id {
+        Some(id) => doc.get_dictionary_mut(id),
+        None => doc
+            .catalog_mut()
+            .and_then(|c| c.get_mut(b"OCProperties"))
+            .and_then(|p| p.as_dict_mut()),
+    }
+    .map_err(|_| missing("/OCProperties"))?;
+    if props.get(b"D").and_then(|d| d.as_dict()).is_err() {
+        props.set("D", lopdf::Dictionary::new());
+    }
+    props
+        .get_mut(b"D")
+        .and_then(|d| d.as_dict_mut())
+        .map_err(|_| missing("/D"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::{Dictionary, dictionary};
+
+    /// A one-page PDF with `names` as layers, the first listed as hidden.
+    fn layered_pdf(names: &[&str]) -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let ocgs: Vec<Object> = names
+            .iter()
+            .map(|name| {
+                let id = doc.add_object(dictionary! {
+                    "Type" => "OCG",
+                    "Name" => Object::string_literal(*name),
+                });
+                Object::Reference(id)
+            })
+            .collect();
+        let pages_id = doc.new_object_id();
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 100.into(), 100.into()],
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+            }),
+        );
+        let mut catalog = dictionary! { "Type" => "Catalog", "Pages" => pages_id };
+        if !names.is_empty() {
+            catalog.set(
+                "OCProperties",
+                dictionary! {
+                    "OCGs" => ocgs.clone(),
+                    "D" => dictionary! { "OFF" => vec![ocgs[0].clone()] },
+                },
+            );
+        }
+        let catalog_id = doc.add_object(catalog);
+        doc.trailer.set("Root", catalog_id);
+
+        let mut out = Vec::new();
+        doc.save_to(&mut out).unwrap();
+        out
+    }
+
+    fn names(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// Layer names listed under `/D /<key>` of a patched PDF.
+    fn state(pdf: &[u8], key: &[u8]) -> Vec<String> {
+        let doc = Document::load_mem(pdf).unwrap();
+        let layers = layer_groups(&doc);
+        let props = doc.catalog().unwrap().get(b"OCProperties").unwrap();
+        let config: &Dictionary = props
+            .as_dict()
+            .unwrap()
+            .get(b"D")
+            .unwrap()
+            .as_dict()
+            .unwrap();
+        config
+            .get(key)
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                let id = r.as_reference().unwrap();
+                layers.iter().find(|(l, _)| *l == id).unwrap().1.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_list_layers() {
+        let pdf = layered_pdf(&["Walls", "Dimensions", "Notes"]);
+        assert_eq!(
+            list_layers(&pdf).unwrap(),
+            names(&["Walls", "Dimensions", "Notes"])
+        );
+        assert!(list_layers(&layered_pdf(&[])).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_select_layers_include_and_exclude() {
+        let pdf = layered_pdf(&["Walls", "Dimensions", "Notes"]);
+
+        let included = select_layers(&pdf, &names(&["Walls", "Notes"]), &[])
+            .unwrap()
+            .unwrap();
+        assert_eq!(state(&included, b"ON"), names(&["Walls", "Notes"]));
+        assert_eq!(state(&included, b"OFF"), names(&["Dimensions"]));
+
+        // Excluding only hides the named layer; the one hidden by default is shown
+        let excluded = select_layers(&pdf, &[], &names(&["Dimensions"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(state(&excluded, b"ON"), names(&["Walls", "Notes"]));
+        assert_eq!(state(&excluded, b"OFF"), names(&["Dimensions"]));
+
+        let both = select_layers(&pdf, &names(&["Walls", "Notes"]), &names(&["Notes"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(state(&both, b"ON"), names(&["Walls"]));
+    }
+
+    #[test]
+    fn test_select_layers_without_layers_renders_everything() {
+        let pdf = layered_pdf(&[]);
+        assert!(
+            select_layers(&pdf, &names(&["Walls"]), &[])
+                .unwrap()
+                .is_none()
+        );
+        let layered = layered_pdf(&["Walls"]);
+        assert!(select_layers(&layered, &[], &[]).unwrap().is_none());
+    }
+}
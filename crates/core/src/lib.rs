@@ -1,18 +1,38 @@
+pub mod checksum;
 pub mod config;
+pub mod cost;
+pub mod dedup;
 pub mod error;
+pub mod http;
+pub mod layers;
 pub mod metadata;
+pub mod noise;
+pub mod pages;
 pub mod pdf;
 pub mod processor;
 pub mod progress;
 pub mod prompts;
 pub mod provider;
+pub mod rag;
+pub mod redact;
 pub mod table;
+pub mod template;
 pub mod trash;
+pub mod verify;
 
-pub use config::{ProcessingConfig, Quality};
+pub use config::{
+    CaptionPlacement, FigureNumbering, ImageEncoding, LineEnding, OutputFormat,
+    PageFallbackStrategy, PageSelection, ProcessingConfig, Quality, SavedImageFormat,
+    WideTableStrategy,
+};
 pub use error::{CoreError, CoreResult};
 pub use metadata::ImageMetadata;
-pub use processor::{clean_markdown, process_pdf};
+pub use processor::{
+    clean_markdown, is_image_input, merge_markdown_parts, process_pdf, select_markdown_pages,
+    PageTiming, ProcessingManifest,
+};
 pub use progress::ProgressReporter;
-pub use provider::VisionProvider;
-pub use trash::{TrashDetection, TrashType};
+pub use provider::{AskResult, VisionProvider};
+pub use redact::redact_secrets;
+pub use trash::{TrashConfig, TrashDetection, TrashPreset, TrashReasonCode, TrashType};
+pub use verify::{verify_output, VerifyReport};
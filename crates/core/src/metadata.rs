@@ -1,3 +1,4 @@
+use crate::table::Table;
 use serde::{Deserialize, Serialize};
 
 /// Type of image extracted from PDF.
@@ -15,9 +16,19 @@ pub enum ImageType {
 /// Metadata for a single extracted/rendered image.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageMetadata {
+    /// Stable id, unique within the document (see [`image_id`]). The file
+    /// name and the markdown `[IMAGE:...]` tag are both built from it.
+    #[serde(default)]
+    pub image_id: String,
+
     /// Filename of the saved image.
     pub image_file: String,
 
+    /// Public URL of the image, when written to a storage backend
+    /// (`ProcessingConfig::storage`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+
     /// 1-indexed page number.
     pub page: u32,
 
@@ -37,6 +48,12 @@ pub struct ImageMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub height: Option<u32>,
 
+    /// Position on the page of an extracted image, in PDF points:
+    /// `[left, top, right, bottom]` from the page's bottom-left origin
+    /// (so `top > bottom`; 72 points per inch).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bbox: Option<[f32; 4]>,
+
     /// Vision LLM description of the image.
     pub description: String,
 
@@ -48,4 +65,95 @@ pub struct ImageMetadata {
 
     /// Model name used for description.
     pub model: String,
+
+    /// Fallback that produced the description after the page request failed
+    /// (`quadrants` / `high_quality`), see `page_fallback_strategy`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<String>,
+    /// Prompt tokens reported by the provider for this image's request(s).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u32>,
+    /// Completion tokens reported by the provider for this image's request(s).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<u32>,
+    /// Wall time of the provider request(s), retries and fallback included.
+    /// Pages described in one batch request share the batch's time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Estimated cost of the description, from the provider's per-image rate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    /// The description was reused from an earlier near-identical image
+    /// (`dedup_images`) or page (`dedup_pages`) instead of requested from
+    /// the provider.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deduped: bool,
+    /// Page (1-indexed) whose full-page description was reused (`dedup_pages`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deduped_from: Option<u32>,
+    /// The transcription of a page or table render was suspiciously short,
+    /// usually a silent vision failure; worth re-running the page.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub low_confidence: bool,
+    /// Tables parsed from the description of a table region, for export
+    /// without re-parsing markdown.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tables: Vec<Table>,
+}
+
+impl ImageMetadata {
+    /// Id of an image file name or `{doc_stem}/{file}` markdown reference:
+    /// the file name without directory and extension.
+    pub fn id_for_file(image_file: &str) -> String {
+        let file_name = image_file.rsplit('/').next().unwrap_or(image_file);
+        file_name
+            .rsplit_once('.')
+            .map_or(file_name, |(stem, _)| stem)
+            .to_string()
+    }
+
+    /// The stored id, or the one derived from `image_file` for metadata
+    /// written before ids existed.
+    pub fn resolved_id(&self) -> String {
+        if self.image_id.is_empty() {
+            Self::id_for_file(&self.image_file)
+        } else {
+            self.image_id.clone()
+        }
+    }
+}
+
+/// Stable image id: `{doc_stem}_page_{page:03}_{kind}`.
+///
+/// `page` is 1-indexed; `kind` is `full`, `table`, `hq` or `img{index}` for
+/// extracted images, so full-page, table and extracted images on the same
+/// page never collide. Images are saved as `{image_id}.png`.
+pub fn image_id(doc_stem: &str, page: u32, kind: &str) -> String {
+    format!("{doc_stem}_page_{page:03}_{kind}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_ids_unique_per_kind() {
+        let ids = ["full", "table", "hq", "img1", "img2"].map(|kind| image_id("manual", 4, kind));
+        assert_eq!(ids[0], "manual_page_004_full");
+        assert_eq!(ids[3], "manual_page_004_img1");
+        let unique: std::collections::HashSet<_> = ids.iter().collect();
+        assert_eq!(unique.len(), ids.len());
+        assert_eq!(
+            ImageMetadata::id_for_file("manual/manual_page_004_img1.png"),
+            ids[3]
+        );
+    }
+
+    #[test]
+    fn test_legacy_metadata_resolves_id_from_file() {
+        let json = r#"{"image_file":"doc/doc_page_002_table.png","page":2,"type":"table_region","description":"","source_doc":"doc","provider":"ollama","model":"m"}"#;
+        let meta: ImageMetadata = serde_json::from_str(json).unwrap();
+        assert!(meta.image_id.is_empty());
+        assert_eq!(meta.resolved_id(), "doc_page_002_table");
+    }
 }
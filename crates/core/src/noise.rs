@@ -0,0 +1,84 @@
+use crate::error::{CoreError, CoreResult};
+use regex::Regex;
+
+/// A single noise pattern: plain substring or regex.
+#[derive(Debug, Clone)]
+enum NoisePattern {
+    Substring(String),
+    Regex(Regex),
+}
+
+/// Drops noise lines (navigation hints, leftover footers) from RAG chunk text.
+///
+/// Built from `ProcessingConfig::chunk_noise_patterns`. A pattern wrapped in
+/// slashes (`/^หน้า \d+$/`) is a regex; anything else is matched as a plain
+/// substring. Only chunk output is filtered — the full markdown is untouched.
+#[derive(Debug, Clone, Default)]
+pub struct NoiseFilter {
+    patterns: Vec<NoisePattern>,
+}
+
+impl NoiseFilter {
+    /// Compile the configured patterns, failing on an invalid regex.
+    pub fn new(patterns: &[String]) -> CoreResult<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .map(|p| match p.strip_prefix('/').and_then(|r| r.strip_suffix('/')) {
+                Some(re) if !re.is_empty() => Regex::new(re)
+                    .map(NoisePattern::Regex)
+                    .map_err(|e| CoreError::Config(format!("Invalid noise pattern '{p}': {e}"))),
+                _ => Ok(NoisePattern::Substring(p.to_string())),
+            })
+            .collect::<CoreResult<Vec<_>>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// True when no patterns are configured.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Whether a single line matches any noise pattern.
+    pub fn is_noise(&self, line: &str) -> bool {
+        let line = line.trim();
+        !line.is_empty()
+            && self.patterns.iter().any(|p| match p {
+                NoisePattern::Substring(s) => line.contains(s.as_str()),
+                NoisePattern::Regex(re) => re.is_match(line),
+            })
+    }
+
+    /// Return `text` with all matching lines removed.
+    pub fn filter(&self, text: &str) -> String {
+        if self.is_empty() {
+            return text.to_string();
+        }
+        text.lines()
+            .filter(|line| !self.is_noise(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters_substring_and_regex_lines() {
+        let filter =
+            NoiseFilter::new(&["กดปุ่ม".to_string(), r"/^หน้า \d+$/".to_string()]).unwrap();
+        let text = "ขั้นตอนที่ 1 เปิดเครื่อง\nกดปุ่ม ถัดไป เพื่อดำเนินการต่อ\nหน้า 12\nหน้า 12 ของคู่มือ";
+        assert_eq!(
+            filter.filter(text),
+            "ขั้นตอนที่ 1 เปิดเครื่อง\nหน้า 12 ของคู่มือ"
+        );
+    }
+
+    #[test]
+    fn test_invalid_regex_is_config_error() {
+        assert!(NoiseFilter::new(&["/[unclosed/".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::error::CoreResult;
+use crate::metadata::{ImageMetadata, ImageType};
+use serde::Serialize;
+
+/// One line of the `{doc_stem}_pages.jsonl` output: a page as structured data.
+#[derive(Debug, Clone, Serialize)]
+pub struct PageRecord {
+    /// 1-indexed page number.
+    pub page: u32,
+    /// Page text: the pdfium text layer (plus any form fields), or the
+    /// transcription of a high-quality page.
+    pub text: String,
+    /// Individual images and image-heavy page renders, with their descriptions.
+    pub images: Vec<PageRecordImage>,
+    /// Tables on the page, as markdown.
+    pub tables: Vec<String>,
+}
+
+/// An image in a [`PageRecord`].
+#[derive(Debug, Clone, Serialize)]
+pub struct PageRecordImage {
+    /// `{doc_stem}/{file}` path relative to the images root.
+    pub file: String,
+    #[serde(rename = "type")]
+    pub image_type: ImageType,
+    pub description: String,
+}
+
+impl PageRecord {
+    /// Build the record of a page from its text and image metadata.
+    ///
+    /// With `text_from_render`, the text is the transcription of the page's
+    /// full-page render, which is then not repeated under `images`.
+    pub fn new(
+        page: u32,
+        text: String,
+        metadata: &[ImageMetadata],
+        text_from_render: bool,
+    ) -> Self {
+        let mut images = Vec::new();
+        let mut tables = Vec::new();
+        for meta in metadata {
+            match meta.image_type {
+                ImageType::TableRegion => tables.push(meta.description.clone()),
+                ImageType::FullPage if text_from_render => {}
+                _ => images.push(PageRecordImage {
+                    file: meta.image_file.clone(),
+                    image_type: meta.image_type.clone(),
+                    description: meta.description.clone(),
+                }),
+            }
+        }
+        Self {
+            page,
+            text,
+            images,
+            tables,
+        }
+    }
+}
+
+/// Serialize records as JSON Lines.
+pub fn pages_jsonl(records: &[PageRecord]) -> CoreResult<String> {
+    let mut out = String::new();
+    for record in records {
+        out.push_str(&serde_json::to_string(record)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(file: &str, image_type: &str, description: &str) -> ImageMetadata {
+        serde_json::from_value(serde_json::json!({
+            "image_file": file,
+            "page": 2,
+            "type": image_type,
+            "description": description,
+            "source_doc": "doc",
+            "provider": "ollama",
+            "model": "m",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_page_record_splits_images_and_tables() {
+        let metadata = [
+            meta("doc/doc_page_002_table.png", "table_region", "| a | b |"),
+            meta(
+                "doc/doc_page_002_img1.png",
+                "extracted_image",
+                "Login screen",
+            ),
+        ];
+        let record = PageRecord::new(2, "Intro".to_string(), &metadata, false);
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["tables"], serde_json::json!(["| a | b |"]));
+        assert_eq!(json["images"][0]["type"], "extracted_image");
+        assert_eq!(json["images"][0]["description"], "Login screen");
+
+        // A high-quality page's transcription is the text, not an image
+        let metadata = [meta("doc/doc_page_002_hq.png", "full_page", "# Title")];
+        let record = PageRecord::new(2, "# Title".to_string(), &metadata, true);
+        assert!(record.images.is_empty());
+    }
+}
@@ -1,21 +1,50 @@
+use crate::config::{ImageEncoding, SavedImageFormat, DEFAULT_JPEG_QUALITY};
 use crate::error::{CoreError, CoreResult};
 use base64::Engine;
 use image::DynamicImage;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
 use pdfium_render::prelude::*;
 use std::path::Path;
+use std::sync::{mpsc, OnceLock};
+use std::time::Instant;
+
+/// Fewest text-layer characters needed to judge a page's orientation.
+const MIN_ORIENTATION_CHARS: usize = 20;
+
+/// Most outline nodes visited in one document, titled or not.
+const MAX_BOOKMARKS: usize = 5000;
+
+/// Deepest outline level read (0 = top level).
+const MAX_BOOKMARK_DEPTH: u32 = 32;
+
+/// DPI at which a `width_pt` × `height_pt` page fits within `max_pixels` on
+/// its longer side: `dpi` itself unless the page is oversized.
+fn fit_render_dpi(width_pt: f32, height_pt: f32, dpi: u32, max_pixels: u32) -> f32 {
+    let longest_pt = width_pt.max(height_pt);
+    if longest_pt <= 0.0 {
+        return dpi as f32;
+    }
+    (dpi as f32).min(max_pixels.max(1) as f32 * 72.0 / longest_pt)
+}
 
 /// An extracted image from a PDF page.
 pub struct ExtractedImage {
     /// Raw PNG bytes.
     pub bytes: Vec<u8>,
-    /// Base64-encoded PNG string.
+    /// Base64-encoded payload for the vision LLM (PNG or JPEG, see [`ImageEncoding`]).
     pub base64: String,
+    /// Encoding of `base64`: the one asked for, or PNG when JPEG encoding failed.
+    pub encoding: ImageEncoding,
     /// Width in pixels.
     pub width: u32,
     /// Height in pixels.
     pub height: u32,
     /// Index of this image on the page.
     pub index: u32,
+    /// Where the image sits on the page, in PDF points: `[left, top, right,
+    /// bottom]` from the page's bottom-left origin (so `top > bottom`).
+    pub bbox: Option<[f32; 4]>,
 }
 
 /// Apply sharpening and contrast enhancement to improve Thai OCR accuracy.
@@ -26,6 +55,150 @@ fn enhance_image(img: DynamicImage) -> DynamicImage {
     img.adjust_contrast(20.0).unsharpen(1.5, 3)
 }
 
+/// Base64 payload of `img` for the vision LLM: its PNG encoding `png_bytes`
+/// as-is, or `img` re-encoded as JPEG.
+fn encode_payload(
+    img: &DynamicImage,
+    png_bytes: &[u8],
+    encoding: ImageEncoding,
+) -> CoreResult<String> {
+    match encoding {
+        ImageEncoding::Png => Ok(base64::engine::general_purpose::STANDARD.encode(png_bytes)),
+        ImageEncoding::Jpeg(quality) => {
+            let mut jpeg = Vec::new();
+            let encoder = JpegEncoder::new_with_quality(&mut jpeg, quality);
+            // JPEG has no alpha channel
+            img.to_rgb8()
+                .write_with_encoder(encoder)
+                .map_err(|e| CoreError::Image(format!("Failed to encode JPEG: {e}")))?;
+            Ok(base64::engine::general_purpose::STANDARD.encode(&jpeg))
+        }
+    }
+}
+
+/// Re-encode a PNG in the [`SavedImageFormat`] images are saved in.
+pub fn encode_saved_image(png_bytes: &[u8], format: SavedImageFormat) -> CoreResult<Vec<u8>> {
+    if format == SavedImageFormat::Png {
+        return Ok(png_bytes.to_vec());
+    }
+    let img = image::load_from_memory_with_format(png_bytes, image::ImageFormat::Png)
+        .map_err(|e| CoreError::Image(format!("Failed to decode PNG: {e}")))?;
+    let mut out = Vec::new();
+    let result = match format {
+        // JPEG has no alpha channel
+        SavedImageFormat::Jpeg => img
+            .to_rgb8()
+            .write_with_encoder(JpegEncoder::new_with_quality(
+                &mut out,
+                DEFAULT_JPEG_QUALITY,
+            )),
+        // The WebP encoder takes 8-bit RGB(A) only
+        _ => img
+            .to_rgba8()
+            .write_with_encoder(WebPEncoder::new_lossless(&mut out)),
+    };
+    result.map_err(|e| CoreError::Image(format!("Failed to encode {format}: {e}")))?;
+    Ok(out)
+}
+
+/// Load a standalone image file (PNG/JPEG/WebP) and re-encode it as PNG.
+///
+/// Returns `(base64_payload, png_bytes)` like [`PdfEngine::render_page_as_image`].
+pub fn load_image_as_png(
+    path: &Path,
+    enhance: bool,
+    encoding: ImageEncoding,
+) -> CoreResult<(String, Vec<u8>)> {
+    let mut img = image::open(path)
+        .map_err(|e| CoreError::Image(format!("Failed to open image '{}': {e}", path.display())))?;
+    if enhance {
+        img = enhance_image(img);
+    }
+
+    let mut png_bytes = Vec::new();
+    let mut cursor = std::io::Cursor::new(&mut png_bytes);
+    img.write_to(&mut cursor, image::ImageFormat::Png)
+        .map_err(|e| CoreError::Image(format!("Failed to encode PNG: {e}")))?;
+
+    let b64 = encode_payload(&img, &png_bytes, encoding)?;
+    Ok((b64, png_bytes))
+}
+
+/// Titled outline entries under `first` and its siblings, depth-first, as
+/// `(depth, title, page index)`.
+fn collect_bookmarks(first: Option<PdfBookmark>) -> Vec<(u32, String, u32)> {
+    let mut entries = Vec::new();
+    walk_outline(
+        first,
+        0,
+        &mut 0,
+        &|bookmark: &PdfBookmark| (bookmark.first_child(), bookmark.next_sibling()),
+        &mut |bookmark, depth| {
+            let title = bookmark.title().unwrap_or_default();
+            let title = title.trim();
+            let page = bookmark.destination().and_then(|d| d.page_index().ok());
+            if let Some(page) = page.filter(|_| !title.is_empty()) {
+                entries.push((depth, title.to_string(), u32::from(page)));
+            }
+        },
+    );
+    entries
+}
+
+/// Visit `first` and its siblings depth-first with their depth; `links`
+/// gives a node's `(first child, next sibling)`.
+///
+/// Stops after [`MAX_BOOKMARKS`] visited nodes and below [`MAX_BOOKMARK_DEPTH`],
+/// so a malformed (cyclic or endlessly nested) outline cannot loop forever or
+/// overflow the stack, however few of its entries have titles.
+fn walk_outline<N>(
+    first: Option<N>,
+    depth: u32,
+    visited: &mut usize,
+    links: &impl Fn(&N) -> (Option<N>, Option<N>),
+    visit: &mut impl FnMut(&N, u32),
+) {
+    if depth > MAX_BOOKMARK_DEPTH {
+        return;
+    }
+    let mut current = first;
+    while let Some(node) = current {
+        if *visited >= MAX_BOOKMARKS {
+            return;
+        }
+        *visited += 1;
+        visit(&node, depth);
+        let (child, next) = links(&node);
+        walk_outline(child, depth + 1, visited, links, visit);
+        current = next;
+    }
+}
+
+/// Split a PNG into a 2×2 grid of tiles, returned as base64 payloads in
+/// reading order (top-left, top-right, bottom-left, bottom-right).
+pub fn split_into_quadrants(png_bytes: &[u8], encoding: ImageEncoding) -> CoreResult<Vec<String>> {
+    let img = image::load_from_memory(png_bytes)
+        .map_err(|e| CoreError::Image(format!("Failed to decode image: {e}")))?;
+    let (w, h) = (img.width(), img.height());
+    let (half_w, half_h) = (w / 2, h / 2);
+
+    [
+        (0, 0, half_w, half_h),
+        (half_w, 0, w - half_w, half_h),
+        (0, half_h, half_w, h - half_h),
+        (half_w, half_h, w - half_w, h - half_h),
+    ]
+    .into_iter()
+    .map(|(x, y, tile_w, tile_h)| {
+        let tile = img.crop_imm(x, y, tile_w, tile_h);
+        let mut png = Vec::new();
+        tile.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| CoreError::Image(format!("Failed to encode PNG: {e}")))?;
+        encode_payload(&tile, &png, encoding)
+    })
+    .collect()
+}
+
 /// Wrapper around the pdfium library for PDF operations.
 pub struct PdfEngine {
     pdfium: Pdfium,
@@ -49,6 +222,14 @@ impl PdfEngine {
         Ok(Self { pdfium })
     }
 
+    /// Check once whether the pdfium library can be loaded.
+    ///
+    /// Used at startup to surface a missing `libpdfium` before any job runs;
+    /// this also starts the shared [`PdfWorker`] so the first job skips the bind.
+    pub fn check_available() -> CoreResult<()> {
+        PdfWorker::shared().map(|_| ())
+    }
+
     /// Open a PDF document from a file path.
     pub fn open_document(&self, path: &Path) -> CoreResult<PdfDocument<'_>> {
         self.pdfium
@@ -56,11 +237,50 @@ impl PdfEngine {
             .map_err(|e| CoreError::Pdf(format!("Failed to open PDF '{}': {e}", path.display())))
     }
 
+    /// Open a PDF with only the selected layers visible (see
+    /// [`crate::layers::select_layers`]); opens it unchanged when there is no
+    /// selection or the PDF has no layers.
+    pub fn open_document_with_layers(
+        &self,
+        path: &Path,
+        include: &[String],
+        exclude: &[String],
+    ) -> CoreResult<PdfDocument<'_>> {
+        if include.is_empty() && exclude.is_empty() {
+            return self.open_document(path);
+        }
+        let bytes = std::fs::read(path)?;
+        let bytes = crate::layers::select_layers(&bytes, include, exclude)?.unwrap_or(bytes);
+        self.pdfium
+            .load_pdf_from_byte_vec(bytes, None)
+            .map_err(|e| CoreError::Pdf(format!("Failed to open PDF '{}': {e}", path.display())))
+    }
+
     /// Get the total number of pages in a document.
     pub fn page_count(doc: &PdfDocument) -> u32 {
         doc.pages().len() as u32
     }
 
+    /// Read the document outline as `(depth, title, page)` entries in
+    /// reading order; depth 0 is top level and pages are 0-indexed.
+    ///
+    /// Bookmarks without a title or a page destination are skipped (their
+    /// children are kept). Returns an empty list when there is no outline.
+    pub fn extract_bookmarks(doc: &PdfDocument) -> Vec<(u32, String, u32)> {
+        collect_bookmarks(doc.bookmarks().root())
+    }
+
+    /// Open a PDF just to count its pages (on the shared [`PdfWorker`]).
+    pub async fn document_page_count(path: &Path) -> CoreResult<u32> {
+        let path = path.to_path_buf();
+        PdfWorker::shared()?
+            .run(move |engine| {
+                let doc = engine.open_document(&path)?;
+                Ok(Self::page_count(&doc))
+            })
+            .await
+    }
+
     /// Calculate what fraction of the page area is covered by images.
     pub fn get_image_coverage(page: &PdfPage) -> f64 {
         let page_width = page.width().value as f64;
@@ -89,15 +309,31 @@ impl PdfEngine {
     /// Render an entire page as a PNG image at the given DPI.
     ///
     /// When `enhance` is true, applies sharpening + contrast boost before encoding.
-    /// Returns (base64_string, raw_png_bytes).
+    /// When `auto_rotate` is true, turns the render by [`Self::upright_quarter_turns`].
+    /// Pages whose longer side would exceed `max_pixels` render at a lower DPI
+    /// that fits; smaller pages keep `dpi`.
+    /// Returns (base64_payload, raw_png_bytes): the payload is in `encoding`,
+    /// the bytes (saved to disk) are always PNG.
     pub fn render_page_as_image(
         page: &PdfPage,
         dpi: u32,
         enhance: bool,
+        auto_rotate: bool,
+        max_pixels: u32,
+        encoding: ImageEncoding,
     ) -> CoreResult<(String, Vec<u8>)> {
-        let scale = dpi as f32 / 72.0;
-        let width = (page.width().value * scale) as i32;
-        let height = (page.height().value * scale) as i32;
+        let (width_pt, height_pt) = (page.width().value, page.height().value);
+        let effective_dpi = fit_render_dpi(width_pt, height_pt, dpi, max_pixels);
+        if effective_dpi < dpi as f32 {
+            tracing::info!(
+                "{:.0}x{:.0}pt page exceeds {max_pixels}px at {dpi} DPI — rendering at {effective_dpi:.0} DPI",
+                width_pt,
+                height_pt
+            );
+        }
+        let scale = effective_dpi / 72.0;
+        let width = (width_pt * scale) as i32;
+        let height = (height_pt * scale) as i32;
 
         let config = PdfRenderConfig::new()
             .set_target_width(width)
@@ -108,6 +344,14 @@ impl PdfEngine {
             .map_err(|e| CoreError::Image(format!("Failed to render page: {e}")))?;
 
         let mut img: DynamicImage = bitmap.as_image();
+        if auto_rotate {
+            img = match Self::upright_quarter_turns(page) {
+                1 => img.rotate90(),
+                2 => img.rotate180(),
+                3 => img.rotate270(),
+                _ => img,
+            };
+        }
         if enhance {
             img = enhance_image(img);
         }
@@ -117,11 +361,72 @@ impl PdfEngine {
         img.write_to(&mut cursor, image::ImageFormat::Png)
             .map_err(|e| CoreError::Image(format!("Failed to encode PNG: {e}")))?;
 
-        let b64 = base64::engine::general_purpose::STANDARD.encode(&png_bytes);
+        let b64 = encode_payload(&img, &png_bytes, encoding)?;
 
         Ok((b64, png_bytes))
     }
 
+    /// Clockwise quarter turns (0–3) that bring a rendered page upright.
+    ///
+    /// pdfium renders a page the way PDF viewers show it, with its `/Rotate`
+    /// flag applied. Scans are often stored sideways or upside down, either
+    /// in the content itself or through that flag, so the orientation is
+    /// judged from the angle of the text-layer characters as displayed: at
+    /// least [`MIN_ORIENTATION_CHARS`] characters, two thirds of them turned
+    /// the same way. Upright pages and pages without a text layer get 0.
+    pub fn upright_quarter_turns(page: &PdfPage) -> u32 {
+        // `/Rotate` turns the page clockwise; character angles are counter-clockwise
+        let flag_turns = match page.rotation() {
+            Ok(PdfPageRenderRotation::Degrees90) => 1,
+            Ok(PdfPageRenderRotation::Degrees180) => 2,
+            Ok(PdfPageRenderRotation::Degrees270) => 3,
+            _ => 0,
+        };
+        let Ok(text) = page.text() else {
+            return 0;
+        };
+
+        let mut counts = [0usize; 4];
+        for ch in text.chars().iter() {
+            if ch.unicode_char().is_none_or(char::is_whitespace) {
+                continue;
+            }
+            if let Ok(angle) = ch.angle_degrees() {
+                let turns = (angle / 90.0).round() as i64 - flag_turns;
+                counts[turns.rem_euclid(4) as usize] += 1;
+            }
+        }
+
+        let total: usize = counts.iter().sum();
+        let (turns, &count) = counts
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, count)| **count)
+            .unwrap();
+        if total < MIN_ORIENTATION_CHARS || count * 3 < total * 2 {
+            return 0;
+        }
+        // Text turned counter-clockwise is put upright by turning the image clockwise
+        turns as u32
+    }
+
+    /// Fraction of non-white pixels in a quick low-resolution render (0.0–1.0).
+    ///
+    /// Used to tell genuinely blank pages from pages whose text layer pdfium
+    /// cannot decode.
+    pub fn non_white_ratio(page: &PdfPage) -> CoreResult<f64> {
+        let scale = 36.0 / 72.0;
+        let config = PdfRenderConfig::new()
+            .set_target_width(((page.width().value * scale) as i32).max(1))
+            .set_target_height(((page.height().value * scale) as i32).max(1));
+
+        let bitmap = page
+            .render_with_config(&config)
+            .map_err(|e| CoreError::Image(format!("Failed to render page: {e}")))?;
+
+        Ok(non_white_fraction(&bitmap.as_image()))
+    }
+
     /// Extract text content from a page.
     pub fn extract_page_text(page: &PdfPage) -> String {
         page.text()
@@ -131,13 +436,61 @@ impl PdfEngine {
             .to_string()
     }
 
+    /// Extract filled-in AcroForm field values from a page as `(name, value)` pairs.
+    ///
+    /// Empty text fields and unselected radio buttons are skipped; checkboxes
+    /// are reported as `Yes` / `No`.
+    pub fn extract_form_fields(page: &PdfPage) -> Vec<(String, String)> {
+        let mut fields = Vec::new();
+
+        for annotation in page.annotations().iter() {
+            let Some(field) = annotation.as_form_field() else {
+                continue;
+            };
+            let name = field.name().unwrap_or_default().trim().to_string();
+            if name.is_empty() {
+                continue;
+            }
+
+            let value = if let Some(text) = field.as_text_field() {
+                text.value()
+            } else if let Some(combo) = field.as_combo_box_field() {
+                combo.value()
+            } else if let Some(list) = field.as_list_box_field() {
+                list.value()
+            } else if let Some(checkbox) = field.as_checkbox_field() {
+                checkbox
+                    .is_checked()
+                    .ok()
+                    .map(|checked| if checked { "Yes" } else { "No" }.to_string())
+            } else if let Some(radio) = field.as_radio_button_field() {
+                radio
+                    .is_checked()
+                    .ok()
+                    .filter(|checked| *checked)
+                    .map(|_| "Yes".to_string())
+            } else {
+                None
+            };
+
+            if let Some(value) = value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+                fields.push((name, value));
+            }
+        }
+
+        fields
+    }
+
     /// Extract individual images from a page, filtering by minimum size.
     ///
     /// When `enhance` is true, applies sharpening + contrast boost before encoding.
+    /// `base64` is in `encoding` (PNG for images JPEG fails on); `bytes` are
+    /// always PNG.
     pub fn extract_page_images(
         page: &PdfPage,
         min_size: u32,
         enhance: bool,
+        encoding: ImageEncoding,
     ) -> CoreResult<Vec<ExtractedImage>> {
         let mut images = Vec::new();
         let mut idx: u32 = 0;
@@ -150,6 +503,14 @@ impl PdfEngine {
             let Some(image_object) = object.as_image_object() else {
                 continue;
             };
+            let bbox = object.bounds().ok().map(|b| {
+                [
+                    b.left().value,
+                    b.top().value,
+                    b.right().value,
+                    b.bottom().value,
+                ]
+            });
 
             let mut raw_image: DynamicImage = match image_object.get_raw_image() {
                 Ok(img) => img,
@@ -178,17 +539,191 @@ impl PdfEngine {
                 continue;
             }
 
-            let b64 = base64::engine::general_purpose::STANDARD.encode(&png_bytes);
+            let (b64, payload_encoding) = match encode_payload(&raw_image, &png_bytes, encoding) {
+                Ok(b64) => (b64, encoding),
+                Err(e) => {
+                    tracing::warn!("Image {idx}: {e} — sending it as PNG");
+                    let png = ImageEncoding::Png;
+                    (encode_payload(&raw_image, &png_bytes, png)?, png)
+                }
+            };
 
             images.push(ExtractedImage {
                 bytes: png_bytes,
                 base64: b64,
+                encoding: payload_encoding,
                 width: w,
                 height: h,
                 index: idx,
+                bbox,
             });
         }
 
         Ok(images)
     }
 }
+
+type PdfTask = Box<dyn FnOnce(&PdfEngine) + Send>;
+
+/// A dedicated thread owning one [`PdfEngine`], shared by the whole process.
+///
+/// Binding pdfium loads and initializes the shared library, which is paid on
+/// every [`PdfEngine::new`]. pdfium itself is not thread-safe (pdfium-render
+/// serializes calls behind a global lock anyway), so tasks sent to the worker
+/// run one at a time against the single binding instead.
+pub struct PdfWorker {
+    tasks: mpsc::Sender<PdfTask>,
+}
+
+static SHARED_WORKER: OnceLock<Result<PdfWorker, String>> = OnceLock::new();
+
+impl PdfWorker {
+    /// The process-wide worker, started (and pdfium bound) on first use.
+    ///
+    /// A failed bind is remembered: pdfium does not appear mid-run.
+    pub fn shared() -> CoreResult<&'static PdfWorker> {
+        SHARED_WORKER
+            .get_or_init(|| Self::spawn().map_err(|e| e.to_string()))
+            .as_ref()
+            .map_err(|e| CoreError::Pdfium(e.clone()))
+    }
+
+    /// Start a worker thread and wait until pdfium is bound.
+    pub fn spawn() -> CoreResult<Self> {
+        let (tasks, queue) = mpsc::channel::<PdfTask>();
+        let (ready_tx, ready_rx) = mpsc::sync_channel::<CoreResult<()>>(1);
+
+        std::thread::Builder::new()
+            .name("pdfium".to_string())
+            .spawn(move || {
+                let started = Instant::now();
+                let engine = match PdfEngine::new() {
+                    Ok(engine) => engine,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+                tracing::info!("pdfium bound in {:?}", started.elapsed());
+                let _ = ready_tx.send(Ok(()));
+
+                for task in queue {
+                    // A panicking task only fails its own caller
+                    let _ =
+                        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| task(&engine)));
+                }
+            })
+            .map_err(|e| CoreError::Pdfium(format!("Failed to start pdfium worker: {e}")))?;
+
+        ready_rx
+            .recv()
+            .map_err(|_| CoreError::Pdfium("pdfium worker exited during startup".to_string()))??;
+        Ok(Self { tasks })
+    }
+
+    /// Run `f` on the worker thread and await its result.
+    ///
+    /// Tasks queue behind each other, so keep them to pdfium work; the async
+    /// caller is not blocked while waiting.
+    pub async fn run<T, F>(&self, f: F) -> CoreResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&PdfEngine) -> CoreResult<T> + Send + 'static,
+    {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let queued = Instant::now();
+        let task: PdfTask = Box::new(move |engine| {
+            tracing::debug!("pdfium task waited {:?} in queue", queued.elapsed());
+            let _ = tx.send(f(engine));
+        });
+        self.tasks
+            .send(task)
+            .map_err(|_| CoreError::Pdfium("pdfium worker has stopped".to_string()))?;
+        rx.await
+            .map_err(|_| CoreError::Pdf("pdfium task panicked".to_string()))?
+    }
+}
+
+/// Fraction of pixels darker than near-white in an image.
+fn non_white_fraction(img: &DynamicImage) -> f64 {
+    let gray = img.to_luma8();
+    let total = gray.width() as usize * gray.height() as usize;
+    if total == 0 {
+        return 0.0;
+    }
+    let ink = gray.pixels().filter(|p| p.0[0] < 200).count();
+    ink as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walk_outline_stops_on_malformed_outlines() {
+        let mut visits = 0;
+        // Untitled entry whose next sibling is itself
+        let cyclic = |_: &u32| (None, Some(0));
+        walk_outline(Some(0), 0, &mut 0, &cyclic, &mut |_, _| visits += 1);
+        assert_eq!(visits, MAX_BOOKMARKS);
+
+        let mut deepest = 0;
+        // Each entry's first child is a new, deeper entry
+        let nested = |n: &u32| (Some(n + 1), None);
+        walk_outline(Some(0), 0, &mut 0, &nested, &mut |_, depth| {
+            deepest = deepest.max(depth)
+        });
+        assert_eq!(deepest, MAX_BOOKMARK_DEPTH);
+    }
+
+    #[test]
+    fn test_fit_render_dpi_caps_oversized_pages() {
+        // A4 at 300 DPI is 2480x3508 px: unchanged
+        assert_eq!(fit_render_dpi(595.0, 842.0, 300, 4096), 300.0);
+        // A2 (1191x1684pt) would be 7016 px tall: scaled to 4096 px
+        let dpi = fit_render_dpi(1191.0, 1684.0, 300, 4096);
+        assert!((1684.0 * dpi / 72.0 - 4096.0).abs() < 0.5);
+        assert_eq!(fit_render_dpi(0.0, 0.0, 150, 4096), 150.0);
+    }
+
+    #[test]
+    fn test_encode_payload_as_jpeg() {
+        let img = DynamicImage::new_rgba8(8, 8);
+        let mut png = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let decode = |b64: String| {
+            base64::engine::general_purpose::STANDARD
+                .decode(b64)
+                .unwrap()
+        };
+
+        let payload = decode(encode_payload(&img, &png, ImageEncoding::Png).unwrap());
+        assert_eq!(payload, png);
+        let payload = decode(encode_payload(&img, &png, ImageEncoding::Jpeg(80)).unwrap());
+        assert_eq!(
+            image::guess_format(&payload).unwrap(),
+            image::ImageFormat::Jpeg
+        );
+    }
+
+    #[test]
+    fn test_encode_saved_image() {
+        let mut png = Vec::new();
+        DynamicImage::new_luma8(8, 8)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        assert_eq!(
+            encode_saved_image(&png, SavedImageFormat::Png).unwrap(),
+            png
+        );
+        for (format, expected) in [
+            (SavedImageFormat::Jpeg, image::ImageFormat::Jpeg),
+            (SavedImageFormat::WebP, image::ImageFormat::WebP),
+        ] {
+            let bytes = encode_saved_image(&png, format).unwrap();
+            assert_eq!(image::guess_format(&bytes).unwrap(), expected);
+        }
+    }
+}
@@ -1,16 +1,40 @@
-use crate::config::{ProcessingConfig, Quality};
+use crate::config::{
+    CaptionPlacement, FigureNumbering, Language, PageFallbackStrategy, PageSelection,
+    ProcessingConfig, Quality, SavedImageFormat, WideTableStrategy,
+};
+use crate::cost::{cost_per_image, CostMeter, ImageRates};
+use crate::dedup::{Fingerprint, ImageDedup, PageDedup, PageFingerprint};
 use crate::error::{CoreError, CoreResult};
-use crate::metadata::{ImageMetadata, ImageType};
-use crate::pdf::{ExtractedImage, PdfEngine};
+use crate::metadata::{image_id, ImageMetadata, ImageType};
+use crate::pdf::{ExtractedImage, PdfEngine, PdfWorker};
+use crate::pages::PageRecord;
 use crate::progress::ProgressReporter;
-use crate::prompts::get_prompts;
-use crate::provider::VisionProvider;
-
+use crate::prompts::prompts_for;
+use crate::provider::{AskResult, LimitedProvider, RequestLimits, VisionProvider};
+use crate::table::parse_markdown_tables;
+use crate::template::{DocumentContext, OutputTemplate, PageContext};
+
+use futures::StreamExt;
+use jay_rag_storage::{LocalStorage, StorageBackend};
+use serde::Serialize;
 use std::collections::HashMap;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
+use tracing::Instrument;
+
+/// Placeholder for a figure label, filled in during final assembly when
+/// figures are numbered continuously across the document.
+const FIGURE_LABEL_PLACEHOLDER: &str = "\u{1}FIGURE\u{1}";
+
+/// Most page text (in bytes) given to a single-image prompt with `image_context`.
+const IMAGE_CONTEXT_MAX_BYTES: usize = 2000;
+
+/// Fewest characters expected from a full-page or table transcription.
+const LOW_CONFIDENCE_MIN_CHARS: usize = 20;
 
 /// Truncate a string to at most `max_bytes` bytes, ensuring the cut
 /// lands on a valid UTF-8 char boundary (safe for Thai multi-byte text).
@@ -28,6 +52,8 @@ fn truncate_str(s: &str, max_bytes: usize) -> &str {
 /// Clean up raw pdfium text for better RAG quality.
 ///
 /// Joins broken lines, normalizes whitespace, and preserves paragraph boundaries.
+/// Bulleted and numbered lines become markdown list items, nested by the
+/// indentation pdfium reports (ordered-list numbers are kept).
 fn cleanup_extracted_text(text: &str) -> String {
     if text.is_empty() {
         return String::new();
@@ -36,6 +62,8 @@ fn cleanup_extracted_text(text: &str) -> String {
     let raw_lines: Vec<&str> = text.split('\n').collect();
     let mut paragraphs: Vec<String> = Vec::new();
     let mut current_para = String::new();
+    // Open list levels: (raw indent width, rendered marker width)
+    let mut list_levels: Vec<(usize, usize)> = Vec::new();
 
     for line in &raw_lines {
         let trimmed = line.trim();
@@ -46,6 +74,28 @@ fn cleanup_extracted_text(text: &str) -> String {
                 paragraphs.push(current_para.clone());
                 current_para.clear();
             }
+            list_levels.clear();
+            continue;
+        }
+
+        // List items always start a new line, nested by their indentation
+        if let Some((marker, body)) = parse_list_item(trimmed) {
+            let indent = indent_width(line);
+            while list_levels.last().is_some_and(|(w, _)| *w > indent) {
+                list_levels.pop();
+            }
+            if list_levels.last().is_some_and(|(w, _)| *w == indent) {
+                list_levels.pop();
+            }
+            let pad: usize = list_levels.iter().map(|(_, m)| m).sum();
+            list_levels.push((indent, marker.chars().count() + 1));
+
+            let body = body.split_whitespace().collect::<Vec<_>>().join(" ");
+            let item = format!("{}{marker} {body}", " ".repeat(pad));
+            if !current_para.is_empty() {
+                current_para.push('\n');
+            }
+            current_para.push_str(&item);
             continue;
         }
 
@@ -60,12 +110,14 @@ fn cleanup_extracted_text(text: &str) -> String {
         // Decide whether to join with previous line or start a new line
         if current_para.is_empty() {
             current_para = normalized;
+            list_levels.clear();
         } else if should_break_before(&normalized) || should_break_after(&current_para) {
             // Keep the break — start a new line within the paragraph
             current_para.push('\n');
             current_para.push_str(&normalized);
+            list_levels.clear();
         } else {
-            // Join with previous line
+            // Join with previous line (continuation of a list item or sentence)
             current_para.push(' ');
             current_para.push_str(&normalized);
         }
@@ -78,6 +130,39 @@ fn cleanup_extracted_text(text: &str) -> String {
     paragraphs.join("\n\n")
 }
 
+/// Width of a line's leading whitespace (tabs count as 4 columns).
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// Split a trimmed line into a markdown list marker and its body.
+///
+/// Bullets (`-`, `*`, `•`, `◦`, `▪`) map to `-`; ordered markers such as `1.`,
+/// `2)` or Thai digits `๓.` keep their number and are rendered as `N.`.
+fn parse_list_item(line: &str) -> Option<(String, &str)> {
+    for bullet in ["- ", "* ", "• ", "◦ ", "▪ "] {
+        if let Some(body) = line.strip_prefix(bullet) {
+            return Some(("-".to_string(), body));
+        }
+    }
+
+    let digits_end = line
+        .char_indices()
+        .find(|(_, c)| !(c.is_ascii_digit() || ('๐'..='๙').contains(c)))
+        .map(|(i, _)| i)?;
+    if digits_end == 0 || line[..digits_end].chars().count() > 3 {
+        return None;
+    }
+    let rest = &line[digits_end..];
+    let body = rest
+        .strip_prefix(". ")
+        .or_else(|| rest.strip_prefix(") "))?;
+    Some((format!("{}.", &line[..digits_end]), body))
+}
+
 /// Check if a line looks like it's part of a table (has 3+ whitespace-separated columns).
 fn looks_like_table_line(line: &str) -> bool {
     // Count segments separated by 2+ spaces
@@ -111,9 +196,49 @@ fn should_break_after(line: &str) -> bool {
         || line.ends_with("นะครับ")
 }
 
+/// Longest word (in chars) a page-number footer may contain besides its numbers.
+const MAX_NUMBERED_FOOTER_WORD: usize = 10;
+
+/// Non-empty lines at the top (bottom) of a page where headers (footers) are
+/// looked for and stripped.
+const HEADER_FOOTER_LINES: usize = 3;
+
+/// Key used to count footer repetitions.
+///
+/// Page-number footers ("Page 3 of 120", "หน้า 3 จาก 120") differ only in their
+/// digits, so for lines of numbers plus at most three short words each digit
+/// run becomes `#`. Other lines are kept as-is.
+fn footer_key(line: &str) -> String {
+    let mut key = String::with_capacity(line.len());
+    let mut in_number = false;
+    for c in line.chars() {
+        if c.is_numeric() {
+            if !in_number {
+                key.push('#');
+            }
+            in_number = true;
+        } else {
+            key.push(c);
+            in_number = false;
+        }
+    }
+
+    let words: Vec<&str> = key
+        .split_whitespace()
+        .filter(|w| w.chars().any(char::is_alphabetic))
+        .collect();
+    let numbered = key != line
+        && words.len() <= 3
+        && words
+            .iter()
+            .all(|w| w.chars().count() <= MAX_NUMBERED_FOOTER_WORD);
+    if numbered { key } else { line.to_string() }
+}
+
 /// Detect repeated text across pages (headers/footers) without mutating.
 ///
-/// Returns `(headers, footers)` — lines that appear in >60% of pages.
+/// Returns `(headers, footers)` — lines that appear in >60% of pages. Footers
+/// are [`footer_key`]s, so page-number lines match across pages.
 fn detect_headers_footers(page_texts: &[(u32, String)]) -> (Vec<String>, Vec<String>) {
     if page_texts.len() < 3 {
         return (Vec::new(), Vec::new());
@@ -126,20 +251,18 @@ fn detect_headers_footers(page_texts: &[(u32, String)]) -> (Vec<String>, Vec<Str
     let mut last_lines: HashMap<String, usize> = HashMap::new();
 
     for (_, text) in page_texts.iter() {
-        let lines: Vec<&str> = text.lines().collect();
+        let lines: Vec<&str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && line.len() < 200)
+            .collect();
 
-        for line in lines.iter().take(3) {
-            let trimmed = line.trim().to_string();
-            if !trimmed.is_empty() && trimmed.len() < 200 {
-                *first_lines.entry(trimmed).or_insert(0) += 1;
-            }
+        for line in lines.iter().take(HEADER_FOOTER_LINES) {
+            *first_lines.entry(line.to_string()).or_insert(0) += 1;
         }
 
-        for line in lines.iter().rev().take(3) {
-            let trimmed = line.trim().to_string();
-            if !trimmed.is_empty() && trimmed.len() < 200 {
-                *last_lines.entry(trimmed).or_insert(0) += 1;
-            }
+        for line in lines.iter().rev().take(HEADER_FOOTER_LINES) {
+            *last_lines.entry(footer_key(line)).or_insert(0) += 1;
         }
     }
 
@@ -175,22 +298,134 @@ fn apply_strip_headers_footers(
     );
 
     for (_, text) in page_texts.iter_mut() {
-        let lines: Vec<&str> = text.lines().collect();
-        let filtered: Vec<&str> = lines
-            .into_iter()
-            .filter(|line| {
-                let trimmed = line.trim();
-                !headers.iter().any(|h| h == trimmed) && !footers.iter().any(|f| f == trimmed)
+        *text = strip_header_footer_lines(text, headers, footers);
+    }
+}
+
+/// Remove the lines of `text` that match a detected header or footer.
+///
+/// Only the first (last) `HEADER_FOOTER_LINES` non-empty lines are checked
+/// against the headers (footers), so a body line that happens to look like
+/// one (e.g. a lone number in a list) is kept.
+fn strip_header_footer_lines(text: &str, headers: &[String], footers: &[String]) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let non_empty: Vec<usize> = (0..lines.len())
+        .filter(|&i| !lines[i].trim().is_empty())
+        .collect();
+    let top = &non_empty[..non_empty.len().min(HEADER_FOOTER_LINES)];
+    let bottom = &non_empty[non_empty.len().saturating_sub(HEADER_FOOTER_LINES)..];
+
+    let filtered: Vec<&str> = lines
+        .iter()
+        .enumerate()
+        .filter(|&(i, line)| {
+            let trimmed = line.trim();
+            let header = top.contains(&i) && headers.iter().any(|h| h == trimmed);
+            let footer = bottom.contains(&i) && footers.iter().any(|f| *f == footer_key(trimmed));
+            !header && !footer
+        })
+        .map(|(_, line)| *line)
+        .collect();
+    filtered.join("\n").trim().to_string()
+}
+
+/// Word put before a figure number (`ภาพที่ 1`, `Figure 1`).
+fn figure_label(language: Language) -> &'static str {
+    match language {
+        Language::Th => "ภาพที่",
+        Language::En => "Figure",
+        Language::Lo => "ຮູບທີ",
+        Language::Km => "រូបភាពទី",
+        Language::Vi => "Hình",
+    }
+}
+
+/// Replace figure label placeholders with a document-wide sequence.
+///
+/// `counter` carries the last used figure number across pages.
+fn number_figures(content: &str, language: Language, counter: &mut u32) -> String {
+    let label = figure_label(language);
+    let mut parts = content.split(FIGURE_LABEL_PLACEHOLDER);
+    let mut output = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        *counter += 1;
+        output.push_str(&format!("{label} {counter}"));
+        output.push_str(part);
+    }
+    output
+}
+
+/// Minimum normalized length for a paragraph to be considered repeated boilerplate.
+const MIN_REPEATED_PARAGRAPH_LEN: usize = 20;
+
+/// Normalize a paragraph for repeat detection (collapse whitespace, lowercase).
+fn normalize_paragraph(paragraph: &str) -> String {
+    paragraph
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Detect whole paragraphs repeated across pages regardless of position.
+///
+/// Returns normalized paragraphs that appear on at least `ratio` of pages,
+/// each paired with the number of pages it appears on.
+fn detect_repeated_paragraphs(page_texts: &[(u32, String)], ratio: f64) -> Vec<(String, usize)> {
+    if page_texts.len() < 3 {
+        return Vec::new();
+    }
+
+    let threshold = ((page_texts.len() as f64 * ratio).ceil() as usize).max(2);
+    let mut counts: HashMap<String, usize> = HashMap::new();
+
+    for (_, text) in page_texts {
+        // Count each paragraph at most once per page
+        let mut seen = std::collections::HashSet::new();
+        for paragraph in text.split("\n\n") {
+            let normalized = normalize_paragraph(paragraph);
+            if normalized.chars().count() >= MIN_REPEATED_PARAGRAPH_LEN && seen.insert(normalized.clone()) {
+                *counts.entry(normalized).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut repeated: Vec<(String, usize)> = counts
+        .into_iter()
+        .filter(|(_, count)| *count >= threshold)
+        .collect();
+    repeated.sort();
+    repeated
+}
+
+/// Strip repeated paragraphs (as returned by `detect_repeated_paragraphs`) from page texts.
+fn apply_strip_repeated_paragraphs(page_texts: &mut [(u32, String)], repeated: &[(String, usize)]) {
+    if repeated.is_empty() {
+        return;
+    }
+
+    tracing::info!("Detected {} repeated paragraph(s) to strip", repeated.len());
+
+    for (_, text) in page_texts.iter_mut() {
+        let kept: Vec<&str> = text
+            .split("\n\n")
+            .filter(|paragraph| {
+                let normalized = normalize_paragraph(paragraph);
+                !repeated.iter().any(|(r, _)| *r == normalized)
             })
             .collect();
-        *text = filtered.join("\n").trim().to_string();
+        *text = kept.join("\n\n").trim().to_string();
     }
 }
 
 /// Result of processing a single PDF.
 pub struct ProcessingResult {
-    /// Path to the output enriched Markdown file.
-    pub markdown_path: PathBuf,
+    /// Path to the output enriched Markdown file (`None` when no markdown was
+    /// written: [`OutputFormat::JsonLines`](crate::config::OutputFormat::JsonLines)
+    /// or a dry run).
+    pub markdown_path: Option<PathBuf>,
+    /// Size-limited parts of the markdown (empty unless `max_markdown_bytes` was exceeded).
+    pub markdown_parts: Vec<PathBuf>,
     /// Path to the output image metadata JSON file.
     pub metadata_path: PathBuf,
     /// Number of images processed.
@@ -199,13 +434,217 @@ pub struct ProcessingResult {
     pub trash_path: Option<PathBuf>,
     /// Number of trash items detected.
     pub trash_count: u32,
+    /// Path to the per-page `{doc_stem}_rag.jsonl` (if `rag_jsonl` is enabled).
+    pub rag_jsonl_path: Option<PathBuf>,
+    /// Path to `{doc_stem}_pages.jsonl` (if `output_format` includes JSON Lines).
+    pub pages_jsonl_path: Option<PathBuf>,
+    /// Prompt tokens summed over all vision requests that reported usage.
+    pub input_tokens: u64,
+    /// Completion tokens summed over all vision requests that reported usage.
+    pub output_tokens: u64,
+    /// Estimated provider cost of the described images (0.0 for local providers).
+    pub estimated_cost_usd: f64,
+    /// Path to `{doc_stem}_plan.json` (dry-run mode only; no markdown is written then).
+    pub plan_path: Option<PathBuf>,
+    /// The page-by-page plan (dry-run mode only).
+    pub plan: Option<DryRunReport>,
+    /// The run was cancelled: the outputs hold only the pages finished before then.
+    pub partial: bool,
+    /// Last page (1-indexed) in the outputs of a partial run.
+    pub last_page: Option<u32>,
+    /// Wall-clock time spent on the document, in seconds (not measured in dry runs).
+    pub elapsed_secs: f64,
+    /// Path to `{doc_stem}_manifest.json` with per-page timings (vision modes only).
+    pub manifest_path: Option<PathBuf>,
+    /// The page that took longest (vision modes only).
+    pub slowest_page: Option<PageTiming>,
+}
+
+/// Timing summary of a processed document, written as `{doc_stem}_manifest.json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessingManifest {
+    pub doc_stem: String,
+    pub provider: String,
+    pub model: String,
+    /// Quality mode, as in the markdown header.
+    pub quality: String,
+    /// Wall-clock time spent on the document, in seconds.
+    pub elapsed_secs: f64,
+    pub image_count: u32,
+    /// The run was cancelled; `pages` holds the pages finished before then.
+    pub partial: bool,
+    pub pages: Vec<PageTiming>,
+}
+
+impl ProcessingManifest {
+    /// The page that took longest, if any.
+    pub fn slowest_page(&self) -> Option<&PageTiming> {
+        self.pages.iter().max_by_key(|p| p.duration_ms)
+    }
+}
+
+/// How long a single page took in [`process_pdf`].
+#[derive(Debug, Clone, Serialize)]
+pub struct PageTiming {
+    /// 1-indexed page number.
+    pub page: u32,
+    /// `full_page` (Strategy A), `mixed` (Strategy B), `high_quality` or `error`.
+    pub strategy: String,
+    /// Wall-clock time from the start of the page's LLM work to its result.
+    /// Pages sent together (`pages_per_request`) share their request's time.
+    pub duration_ms: u64,
+    /// Images described on the page (including page-level renders).
+    pub image_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What `process_pdf` would do with a document, without calling the LLM.
+#[derive(Debug, Clone, Serialize)]
+pub struct DryRunReport {
+    pub doc_stem: String,
+    pub pages: Vec<PagePlan>,
+    /// Images that would be sent to the vision LLM (page renders, tables and
+    /// individual images).
+    pub vision_images: usize,
+    /// Estimated provider cost of those images.
+    pub estimated_cost_usd: f64,
+}
+
+/// Planned handling of a single page in a dry run.
+#[derive(Debug, Clone, Serialize)]
+pub struct PagePlan {
+    /// 1-indexed page number.
+    pub page: u32,
+    /// `full_page` (Strategy A), `mixed` (Strategy B), `high_quality` or `error`.
+    pub strategy: String,
+    /// Share of the page covered by images (not measured in high-quality mode).
+    pub coverage: Option<f64>,
+    /// Individual images that would be described (mixed pages only).
+    pub image_count: usize,
+    /// Mixed page whose text looks tabular; rendered for table extraction.
+    pub table_candidate: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PagePlan {
+    fn new(page_num: u32, data: &CoreResult<PageData>) -> Self {
+        let (coverage, image_count, table_candidate) = match data {
+            Ok(PageData::FullPage { coverage, .. }) => (Some(*coverage), 0, false),
+            Ok(PageData::Mixed {
+                coverage,
+                images,
+                table_candidate,
+                ..
+            }) => (Some(*coverage), images.len(), *table_candidate),
+            Ok(PageData::HighQuality { .. }) | Err(_) => (None, 0, false),
+        };
+        Self {
+            page: page_num + 1,
+            strategy: page_strategy(data).to_string(),
+            coverage,
+            image_count,
+            table_candidate,
+            error: data.as_ref().err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// Strategy label of an extracted page, as in dry-run plans and manifests.
+fn page_strategy(data: &CoreResult<PageData>) -> &'static str {
+    match data {
+        Ok(PageData::FullPage { .. }) => "full_page",
+        Ok(PageData::Mixed { .. }) => "mixed",
+        Ok(PageData::HighQuality { .. }) => "high_quality",
+        Err(_) => "error",
+    }
+}
+
+/// Render extracted form fields as a markdown "Form Fields" section.
+///
+/// Returns an empty string when there are no fields.
+fn format_form_fields(fields: &[(String, String)]) -> String {
+    if fields.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from("\n### Form Fields\n\n");
+    for (name, value) in fields {
+        out.push_str(&format!("- **{name}:** {value}\n"));
+    }
+    out
+}
+
+/// Render the PDF outline as a markdown "Contents" section linking to the
+/// `## Page N` headings.
+///
+/// `bookmarks` are `(depth, title, 0-indexed page)` entries; those outside the
+/// processed `pages` are left out. Returns an empty string when none remain.
+fn format_toc(bookmarks: &[(u32, String, u32)], pages: Range<u32>) -> String {
+    let mut out = String::new();
+    let mut prev_depth: Option<u32> = None;
+    for (depth, title, page) in bookmarks {
+        if !pages.contains(page) {
+            continue;
+        }
+        // Never nest deeper than one level below the previous entry
+        let depth = (*depth).min(prev_depth.map_or(0, |d| d + 1));
+        prev_depth = Some(depth);
+        let title = title.replace('[', "\\[").replace(']', "\\]");
+        out.push_str(&format!(
+            "{}- [{title}](#page-{})\n",
+            "  ".repeat(depth as usize),
+            page + 1
+        ));
+    }
+    if out.is_empty() {
+        return out;
+    }
+    format!("## Contents\n\n{out}")
+}
+
+/// Build the error message when the share of failed pages reaches `max_ratio`.
+///
+/// `failed` holds `(1-indexed page, error)` pairs; the page is `None` when a
+/// task failed before reporting its page. Returns `None` if the run is usable.
+fn failed_pages_error(
+    failed: &[(Option<u32>, &str)],
+    total_pages: u32,
+    max_ratio: f64,
+) -> Option<String> {
+    if total_pages == 0 || failed.is_empty() {
+        return None;
+    }
+    let ratio = failed.len() as f64 / total_pages as f64;
+    if ratio < max_ratio {
+        return None;
+    }
+
+    let (page, error) = failed[0];
+    let example = match page {
+        Some(p) => format!("page {p}: {error}"),
+        None => error.to_string(),
+    };
+    Some(format!(
+        "{}/{total_pages} pages failed (e.g. {example})",
+        failed.len().min(total_pages as usize)
+    ))
 }
 
 /// Result of processing a single page (returned from async page processing).
 struct PageResult {
     page_num: u32,
     content: String,
+    /// Page text for templates and JSON Lines: the text layer written for
+    /// the page, or the transcription of a high-quality page.
+    text: String,
+    /// `text` is the transcription of the page's full-page render.
+    text_from_render: bool,
     metadata: Vec<ImageMetadata>,
+    /// First error hit on this page (extraction or LLM call), if any.
+    error: Option<String>,
+    /// Wall-clock time spent on the page; filled in by `process_pdf`.
+    duration_ms: u64,
 }
 
 /// Data extracted synchronously from a PDF page before async LLM calls.
@@ -217,9 +656,12 @@ enum PageData {
         img_filename: String,
         coverage: f64,
         pdfium_text: String,
+        /// Has a text layer and a non-blank render ([`expects_text`]).
+        expects_text: bool,
     },
     /// Strategy B: Mixed page with text and individual images.
     Mixed {
+        coverage: f64,
         text: String,
         images: Vec<ExtractedImage>,
         table_candidate: bool,
@@ -231,9 +673,19 @@ enum PageData {
         img_bytes: Vec<u8>,
         img_filename: String,
         pdfium_text: String,
+        /// Has a text layer and a non-blank render ([`expects_text`]).
+        expects_text: bool,
     },
 }
 
+/// DPI of high-quality page renders: `image_dpi`, but at least 300.
+fn high_quality_dpi(config: &ProcessingConfig) -> u32 {
+    config.image_dpi.max(300)
+}
+
+/// Minimum non-white pixel ratio for an empty-text page to count as having content.
+const EMPTY_TEXT_MIN_INK_RATIO: f64 = 0.01;
+
 /// Extract all data from a page synchronously (no await points).
 fn extract_page_data(
     doc: &pdfium_render::prelude::PdfDocument<'_>,
@@ -247,9 +699,15 @@ fn extract_page_data(
 
     // High Quality mode: render every page at 300+ DPI for Vision LLM OCR
     if config.quality == Quality::High {
-        let dpi = config.image_dpi.max(300);
-        let (img_b64, img_bytes) = PdfEngine::render_page_as_image(&page, dpi, config.enhance)?;
-        let img_filename = format!("{doc_stem}_page_{:03}_hq.png", page_num + 1);
+        let (img_b64, img_bytes) = PdfEngine::render_page_as_image(
+            &page,
+            high_quality_dpi(config),
+            config.enhance,
+            config.auto_rotate,
+            config.max_render_pixels,
+            config.image_encoding,
+        )?;
+        let img_filename = saved_image_name(doc_stem, page_num + 1, "hq", config);
         let text = PdfEngine::extract_page_text(&page);
         let text = cleanup_extracted_text(&text);
 
@@ -257,6 +715,7 @@ fn extract_page_data(
             img_b64,
             img_bytes,
             img_filename,
+            expects_text: expects_text(&page, &text)?,
             pdfium_text: text,
         });
     }
@@ -264,8 +723,15 @@ fn extract_page_data(
     let coverage = PdfEngine::get_image_coverage(&page);
     // Strategy A: Image-heavy page (hybrid: also extract text)
     if coverage >= config.page_as_image_threshold {
-        let (img_b64, img_bytes) = PdfEngine::render_page_as_image(&page, config.image_dpi, config.enhance)?;
-        let img_filename = format!("{doc_stem}_page_{:03}_full.png", page_num + 1);
+        let (img_b64, img_bytes) = PdfEngine::render_page_as_image(
+            &page,
+            config.image_dpi,
+            config.enhance,
+            config.auto_rotate,
+            config.max_render_pixels,
+            config.image_encoding,
+        )?;
+        let img_filename = saved_image_name(doc_stem, page_num + 1, "full", config);
         let text = PdfEngine::extract_page_text(&page);
         let text = cleanup_extracted_text(&text);
 
@@ -274,6 +740,7 @@ fn extract_page_data(
             img_bytes,
             img_filename,
             coverage,
+            expects_text: expects_text(&page, &text)?,
             pdfium_text: text,
         })
     }
@@ -281,19 +748,68 @@ fn extract_page_data(
     else {
         let text = PdfEngine::extract_page_text(&page);
         let text = cleanup_extracted_text(&text);
-        let images = PdfEngine::extract_page_images(&page, config.min_image_size, config.enhance)?;
+
+        // Empty text layer on a visibly non-blank page (broken font encoding):
+        // fall back to a full-page vision render.
+        if config.escalate_empty_text && text.trim().is_empty() {
+            let ink = PdfEngine::non_white_ratio(&page)?;
+            if ink >= EMPTY_TEXT_MIN_INK_RATIO {
+                tracing::info!(
+                    "[Page {}] empty pdfium text but {:.1}% non-white — escalating to full page render",
+                    page_num + 1,
+                    ink * 100.0
+                );
+                let (img_b64, img_bytes) = PdfEngine::render_page_as_image(
+                    &page,
+                    config.image_dpi,
+                    config.enhance,
+                    config.auto_rotate,
+                    config.max_render_pixels,
+                    config.image_encoding,
+                )?;
+                let img_filename = saved_image_name(doc_stem, page_num + 1, "full", config);
+                return Ok(PageData::FullPage {
+                    img_b64,
+                    img_bytes,
+                    img_filename,
+                    coverage,
+                    pdfium_text: text,
+                    expects_text: false,
+                });
+            }
+        }
+
+        let images = PdfEngine::extract_page_images(
+            &page,
+            config.min_image_size,
+            config.enhance,
+            config.image_encoding,
+        )?;
 
         // Table detection (check if text looks tabular)
-        let table_candidate = config.table_extraction && crate::table::looks_like_table(&text);
+        let table_candidate = config.table_extraction
+            && crate::table::looks_like_table(
+                &text,
+                config.table_multispace_ratio,
+                config.table_min_consistent_run,
+            );
         let table_img = if table_candidate {
-            let (b64, bytes) = PdfEngine::render_page_as_image(&page, config.image_dpi, config.enhance)?;
-            let filename = format!("{doc_stem}_page_{:03}_table.png", page_num + 1);
+            let (b64, bytes) = PdfEngine::render_page_as_image(
+                &page,
+                config.image_dpi,
+                config.enhance,
+                config.auto_rotate,
+                config.max_render_pixels,
+                config.image_encoding,
+            )?;
+            let filename = saved_image_name(doc_stem, page_num + 1, "table", config);
             Some((b64, bytes, filename))
         } else {
             None
         };
 
         Ok(PageData::Mixed {
+            coverage,
             text,
             images,
             table_candidate,
@@ -302,22 +818,150 @@ fn extract_page_data(
     }
 }
 
+/// Vision providers used for a page.
+///
+/// `premium` handles full-page, table-region and high-quality requests;
+/// `base` handles individual images. Both are the same provider unless
+/// `premium_provider` is configured. `dedup` holds the descriptions already
+/// requested for individual images when `dedup_images` is on, `page_dedup`
+/// those of full-page renders when `dedup_pages` is on.
+#[derive(Clone)]
+struct PageProviders {
+    base: Arc<dyn VisionProvider>,
+    premium: Arc<dyn VisionProvider>,
+    dedup: Option<Arc<ImageDedup>>,
+    page_dedup: Option<Arc<PageDedup>>,
+}
+
+/// Saves page images into the document's image directory.
+///
+/// Writes from all pages share one I/O semaphore (`max_concurrent_io`), kept
+/// separate from the provider-call limits so slow disks or NFS mounts are
+/// not hit by every in-flight page at once.
+#[derive(Clone)]
+struct ImageWriter {
+    storage: Arc<dyn StorageBackend>,
+    /// Key of the document's image directory in `storage`.
+    dir: String,
+    io: Arc<Semaphore>,
+    format: SavedImageFormat,
+}
+
+impl ImageWriter {
+    /// Images go to `config.storage` under `images/{doc_stem}`, or by default
+    /// to `{images_root}/{doc_stem}` on local disk.
+    fn new(images_root: &Path, doc_stem: &str, config: &ProcessingConfig) -> Self {
+        let (storage, dir): (Arc<dyn StorageBackend>, _) = match &config.storage {
+            Some(storage) => (storage.clone(), format!("images/{doc_stem}")),
+            None => (
+                Arc::new(LocalStorage::new(images_root.to_path_buf(), String::new())),
+                doc_stem.to_string(),
+            ),
+        };
+        Self {
+            storage,
+            dir,
+            io: Arc::new(Semaphore::new(config.max_concurrent_io.max(1))),
+            format: config.saved_image_format,
+        }
+    }
+
+    /// Save PNG `bytes` as `filename`, re-encoded in `saved_image_format`
+    /// (`filename` carries the matching extension, see [`saved_image_name`]).
+    async fn save_image(&self, filename: &str, png_bytes: &[u8]) -> CoreResult<()> {
+        if self.format == SavedImageFormat::Png {
+            return self.save(filename, png_bytes).await;
+        }
+        let (png, format) = (png_bytes.to_vec(), self.format);
+        let bytes =
+            tokio::task::spawn_blocking(move || crate::pdf::encode_saved_image(&png, format))
+                .await
+                .map_err(|e| CoreError::Image(format!("Image encoding task failed: {e}")))??;
+        self.save(filename, &bytes).await
+    }
+
+    async fn save(&self, filename: &str, bytes: &[u8]) -> CoreResult<()> {
+        let _permit = self.io.acquire().await.unwrap();
+        let key = format!("{}/{filename}", self.dir);
+        self.storage.write_bytes(&key, bytes).await?;
+        Ok(())
+    }
+}
+
+/// File name of a saved image: its [`image_id`] with the extension of
+/// `config.saved_image_format`.
+fn saved_image_name(doc_stem: &str, page: u32, kind: &str, config: &ProcessingConfig) -> String {
+    format!(
+        "{}.{}",
+        image_id(doc_stem, page, kind),
+        config.saved_image_format.extension()
+    )
+}
+
+/// Where a run writes its markdown, metadata and other document outputs:
+/// `config.storage`, or by default local files under `output_dir`.
+struct Outputs {
+    storage: Arc<dyn StorageBackend>,
+    /// Local directory the keys resolve to (`None` with `config.storage`).
+    dir: Option<PathBuf>,
+}
+
+impl Outputs {
+    fn new(output_dir: &Path, config: &ProcessingConfig) -> Self {
+        match &config.storage {
+            Some(storage) => Self {
+                storage: storage.clone(),
+                dir: None,
+            },
+            None => Self {
+                storage: Arc::new(LocalStorage::new(output_dir.to_path_buf(), String::new())),
+                dir: Some(output_dir.to_path_buf()),
+            },
+        }
+    }
+
+    /// Path reported in [`ProcessingResult`] for `key`: the local file, or
+    /// the key itself with `config.storage`.
+    fn path(&self, key: &str) -> PathBuf {
+        match &self.dir {
+            Some(dir) => dir.join(key),
+            None => PathBuf::from(key),
+        }
+    }
+
+    /// Write `content` to `key`, returning its [`Outputs::path`].
+    async fn write(&self, key: &str, content: &str) -> CoreResult<PathBuf> {
+        self.storage.write_text(key, content).await?;
+        Ok(self.path(key))
+    }
+}
+
 /// Process a single page asynchronously with LLM calls.
 ///
 /// Returns a `PageResult` with content and metadata (no shared mutable state).
 async fn process_page_async(
     page_data: PageData,
     page_num: u32,
-    provider: Arc<dyn VisionProvider>,
-    images_dir: PathBuf,
+    pdf_path: PathBuf,
+    providers: PageProviders,
+    image_writer: ImageWriter,
     doc_stem: String,
     config: ProcessingConfig,
     reporter: Arc<dyn ProgressReporter>,
 ) -> CoreResult<PageResult> {
-    let prompts = get_prompts(config.language);
+    let PageProviders {
+        base: provider,
+        premium,
+        dedup,
+        page_dedup,
+    } = providers;
+    let prompts = prompts_for(&config);
     let page_label = format!("Page {}", page_num + 1);
     let mut lines = vec![format!("\n\n---\n## {page_label}\n")];
     let mut metadata = Vec::new();
+    let mut page_error: Option<String> = None;
+    let mut page_text = String::new();
+    let mut text_from_render = false;
 
     match page_data {
         PageData::FullPage {
@@ -326,24 +970,66 @@ async fn process_page_async(
             img_filename,
             coverage,
             pdfium_text,
+            expects_text,
         } => {
+            let provider = &premium;
             tracing::info!(
                 "[Page {}] image-heavy ({:.0}%) — full page render (hybrid)",
                 page_num + 1,
                 coverage * 100.0
             );
 
-            let img_path = images_dir.join(&img_filename);
-            tokio::fs::create_dir_all(img_path.parent().unwrap()).await?;
-            tokio::fs::write(&img_path, &img_bytes).await?;
-
-            let description = match provider
-                .ask(&img_b64, prompts.full_page, config.max_retries)
-                .await
-            {
-                Ok(desc) => desc,
+            image_writer.save_image(&img_filename, &img_bytes).await?;
+
+            let fingerprint = page_dedup
+                .as_ref()
+                .and_then(|_| PageFingerprint::of_page(&img_bytes, &pdfium_text));
+            let cached = page_dedup.as_ref().zip(fingerprint);
+            let reused = find_duplicate_page(cached.as_ref(), page_num);
+            let deduped_from = reused.as_ref().map(|(page, _)| *page);
+
+            let started = Instant::now();
+            let (result, fallback) = match reused {
+                Some((_, text)) => (
+                    Ok(AskResult {
+                        text,
+                        ..Default::default()
+                    }),
+                    None,
+                ),
+                None => {
+                    ask_page_with_fallback(
+                        provider.as_ref(),
+                        &img_b64,
+                        &img_bytes,
+                        render_high_quality(pdf_path.clone(), page_num, &config),
+                        &prompts.full_page,
+                        false,
+                        &config,
+                    )
+                    .await
+                }
+            };
+            let latency_ms = started.elapsed().as_millis() as u64;
+            let mut tokens = (None, None);
+            let mut cost = None;
+            let mut low_confidence = false;
+            let description = match result {
+                Ok(result) if deduped_from.is_some() => result.text,
+                Ok(result) => {
+                    tokens = (result.input_tokens, result.output_tokens);
+                    cost = Some(page_request_cost(provider.as_ref(), fallback));
+                    low_confidence = expects_text
+                        && check_low_confidence(&result.text, page_num, reporter.as_ref());
+                    let description = reshape_description(result.text, &config);
+                    if let Some((dedup, fp)) = cached {
+                        dedup.insert(fp, page_num + 1, description.clone());
+                    }
+                    description
+                }
                 Err(e) => {
                     reporter.on_error(page_num + 1, &format!("{e}"));
+                    page_error.get_or_insert_with(|| e.to_string());
                     tracing::warn!("Full-page description failed on page {}: {e}", page_num + 1);
                     format!("[ไม่สามารถอธิบายภาพได้: {e}]")
                 }
@@ -352,16 +1038,28 @@ async fn process_page_async(
             let image_ref = format!("{doc_stem}/{img_filename}");
 
             metadata.push(ImageMetadata {
+                image_id: ImageMetadata::id_for_file(&img_filename),
                 image_file: image_ref.clone(),
+                image_url: image_url(&image_ref, config.storage.as_ref()),
                 page: page_num + 1,
                 index: None,
                 image_type: ImageType::FullPage,
                 width: None,
                 height: None,
+                bbox: None,
                 description: description.clone(),
                 source_doc: doc_stem.clone(),
                 provider: provider.provider_name().to_string(),
                 model: provider.model_name().to_string(),
+                fallback: fallback.map(|f| f.to_string()),
+                input_tokens: tokens.0,
+                output_tokens: tokens.1,
+                latency_ms: Some(latency_ms),
+                cost_usd: cost,
+                deduped: deduped_from.is_some(),
+                deduped_from,
+                low_confidence,
+                tables: Vec::new(),
             });
 
             reporter.on_image_processed(
@@ -372,11 +1070,11 @@ async fn process_page_async(
 
             // Strategy A hybrid: include pdfium text alongside LLM description
             if !pdfium_text.is_empty() {
+                page_text.clone_from(&pdfium_text);
                 lines.push(pdfium_text);
                 lines.push(String::new());
             }
-            lines.push(format!("[IMAGE:{image_ref}]\n"));
-            lines.push(description);
+            lines.extend(page_image_block(&image_ref, description, &config));
         }
 
         PageData::Mixed {
@@ -384,32 +1082,63 @@ async fn process_page_async(
             images,
             table_candidate,
             table_img,
+            ..
         } => {
+            let image_prompt = if config.image_context && !text.is_empty() {
+                let context = truncate_str(&text, IMAGE_CONTEXT_MAX_BYTES);
+                prompts
+                    .single_image_with_context
+                    .replace("{context_text}", context)
+            } else {
+                prompts.single_image.to_string()
+            };
+
             // When table detected, skip raw text — the LLM full-page extraction
             // will include both regular text and properly formatted tables
             if !table_candidate && !text.is_empty() {
+                page_text.clone_from(&text);
                 lines.push(text);
             }
 
             // Table extraction
             if table_candidate {
                 if let Some((b64, bytes, filename)) = table_img {
+                    let provider = &premium;
                     tracing::info!(
                         "[Page {}] Table-like content detected — extracting",
                         page_num + 1
                     );
 
-                    let img_path = images_dir.join(&filename);
-                    tokio::fs::create_dir_all(img_path.parent().unwrap()).await?;
-                    tokio::fs::write(&img_path, &bytes).await?;
-
-                    let description = match provider
-                        .ask(&b64, prompts.table_extraction, config.max_retries)
-                        .await
-                    {
-                        Ok(desc) => desc,
+                    image_writer.save_image(&filename, &bytes).await?;
+
+                    let started = Instant::now();
+                    let (result, fallback) = ask_page_with_fallback(
+                        provider.as_ref(),
+                        &b64,
+                        &bytes,
+                        render_high_quality(pdf_path.clone(), page_num, &config),
+                        &prompts.table_extraction,
+                        false,
+                        &config,
+                    )
+                    .await;
+                    let latency_ms = started.elapsed().as_millis() as u64;
+                    let mut tokens = (None, None);
+                    let mut cost = None;
+                    let mut tables = Vec::new();
+                    let mut low_confidence = false;
+                    let description = match result {
+                        Ok(result) => {
+                            tokens = (result.input_tokens, result.output_tokens);
+                            cost = Some(page_request_cost(provider.as_ref(), fallback));
+                            tables = parse_markdown_tables(&result.text);
+                            low_confidence =
+                                check_low_confidence(&result.text, page_num, reporter.as_ref());
+                            reshape_description(result.text, &config)
+                        }
                         Err(e) => {
                             reporter.on_error(page_num + 1, &format!("{e}"));
+                            page_error.get_or_insert_with(|| e.to_string());
                             tracing::warn!(
                                 "Table extraction failed on page {}: {e}",
                                 page_num + 1
@@ -421,19 +1150,32 @@ async fn process_page_async(
                     let image_ref = format!("{doc_stem}/{filename}");
 
                     metadata.push(ImageMetadata {
+                        image_id: ImageMetadata::id_for_file(&filename),
                         image_file: image_ref.clone(),
+                        image_url: image_url(&image_ref, config.storage.as_ref()),
                         page: page_num + 1,
                         index: None,
                         image_type: ImageType::TableRegion,
                         width: None,
                         height: None,
+                        bbox: None,
                         description: description.clone(),
                         source_doc: doc_stem.clone(),
                         provider: provider.provider_name().to_string(),
                         model: provider.model_name().to_string(),
+                        fallback: fallback.map(|f| f.to_string()),
+                        input_tokens: tokens.0,
+                        output_tokens: tokens.1,
+                        latency_ms: Some(latency_ms),
+                        cost_usd: cost,
+                        deduped: false,
+                        deduped_from: None,
+                        low_confidence,
+                        tables,
                     });
 
-                    lines.push(format!("\n[IMAGE:{image_ref}]\n\n{description}\n"));
+                    let [first, second] = page_image_block(&image_ref, description, &config);
+                    lines.push(format!("\n{first}\n{second}\n"));
                 }
             }
 
@@ -451,29 +1193,56 @@ async fn process_page_async(
                 for img in images {
                     let permit = img_semaphore.clone().acquire_owned().await.unwrap();
                     let provider = provider.clone();
-                    let prompt = prompts.single_image.to_string();
-                    let images_dir = images_dir.clone();
+                    let dedup = dedup.clone();
+                    let prompt = image_prompt.clone();
+                    let image_writer = image_writer.clone();
+                    let storage = config.storage.clone();
                     let doc_stem = doc_stem.clone();
                     let max_retries = config.max_retries;
+                    let extension = config.saved_image_format.extension();
                     let page_num = page_num;
                     let reporter = reporter.clone();
 
                     img_join_set.spawn(async move {
                         let _permit = permit;
 
-                        let img_filename = format!(
-                            "{doc_stem}_page_{:03}_img{}.png",
-                            page_num + 1,
-                            img.index
-                        );
-                        let img_path = images_dir.join(&img_filename);
-
-                        tokio::fs::create_dir_all(img_path.parent().unwrap()).await?;
-                        tokio::fs::write(&img_path, &img.bytes).await?;
-
-                        let description = match provider.ask(&img.base64, &prompt, max_retries).await
-                        {
-                            Ok(desc) => desc,
+                        let kind = format!("img{}", img.index);
+                        let img_filename =
+                            format!("{}.{extension}", image_id(&doc_stem, page_num + 1, &kind));
+                        image_writer.save_image(&img_filename, &img.bytes).await?;
+
+                        let mut img_error = None;
+                        let mut tokens = (None, None);
+                        let mut cost = None;
+                        let fingerprint = dedup
+                            .as_ref()
+                            .and_then(|_| Fingerprint::of_image(&img.bytes));
+                        let cached = dedup.as_ref().zip(fingerprint.as_ref());
+                        let reused = cached.and_then(|(dedup, fp)| dedup.find(fp));
+                        let deduped = reused.is_some();
+                        let started = Instant::now();
+                        let result = match reused {
+                            Some(text) => Ok(AskResult {
+                                text,
+                                ..Default::default()
+                            }),
+                            None => {
+                                provider
+                                    .ask(&img.base64, img.encoding, &prompt, max_retries)
+                                    .await
+                            }
+                        };
+                        let latency_ms = started.elapsed().as_millis() as u64;
+                        let description = match result {
+                            Ok(result) if deduped => result.text,
+                            Ok(result) => {
+                                tokens = (result.input_tokens, result.output_tokens);
+                                cost = Some(cost_per_image(provider.as_ref()));
+                                if let Some((dedup, fp)) = cached {
+                                    dedup.insert(*fp, result.text.clone());
+                                }
+                                result.text
+                            }
                             Err(e) => {
                                 reporter.on_error(page_num + 1, &format!("{e}"));
                                 tracing::warn!(
@@ -481,6 +1250,7 @@ async fn process_page_async(
                                     page_num + 1,
                                     img.index
                                 );
+                                img_error = Some(e.to_string());
                                 format!("[ไม่สามารถอธิบายภาพได้: {e}]")
                             }
                         };
@@ -488,16 +1258,28 @@ async fn process_page_async(
                         let image_ref = format!("{doc_stem}/{img_filename}");
 
                         let meta = ImageMetadata {
+                            image_id: ImageMetadata::id_for_file(&img_filename),
                             image_file: image_ref.clone(),
+                            image_url: image_url(&image_ref, storage.as_ref()),
                             page: page_num + 1,
                             index: Some(img.index),
                             image_type: ImageType::ExtractedImage,
                             width: Some(img.width),
                             height: Some(img.height),
+                            bbox: img.bbox,
                             description: description.clone(),
                             source_doc: doc_stem.clone(),
                             provider: provider.provider_name().to_string(),
                             model: provider.model_name().to_string(),
+                            fallback: None,
+                            input_tokens: tokens.0,
+                            output_tokens: tokens.1,
+                            latency_ms: Some(latency_ms),
+                            cost_usd: cost,
+                            deduped,
+                            deduped_from: None,
+                            low_confidence: false,
+                            tables: Vec::new(),
                         };
 
                         reporter.on_image_processed(
@@ -506,7 +1288,7 @@ async fn process_page_async(
                             truncate_str(&description, 80),
                         );
 
-                        Ok::<_, CoreError>((img.index, image_ref, description, meta))
+                        Ok::<_, CoreError>((img.index, image_ref, description, meta, img_error))
                     });
                 }
 
@@ -517,6 +1299,7 @@ async fn process_page_async(
                         Ok(Ok(img_result)) => img_results.push(img_result),
                         Ok(Err(e)) => {
                             tracing::error!("Image task error on page {}: {e}", page_num + 1);
+                            page_error.get_or_insert_with(|| e.to_string());
                         }
                         Err(e) => {
                             tracing::error!("Image task panicked on page {}: {e}", page_num + 1);
@@ -525,12 +1308,23 @@ async fn process_page_async(
                 }
 
                 // Sort by image index to maintain order
-                img_results.sort_by_key(|(idx, _, _, _)| *idx);
+                img_results.sort_by_key(|(idx, _, _, _, _)| *idx);
 
-                for (idx, image_ref, description, meta) in img_results {
+                for (idx, image_ref, description, meta, img_error) in img_results {
+                    if let Some(e) = img_error {
+                        page_error.get_or_insert(e);
+                    }
                     metadata.push(meta);
+                    let label = match config.figure_numbering {
+                        FigureNumbering::PerPage => {
+                            format!("{} {idx}", figure_label(config.language))
+                        }
+                        // Numbered during final assembly, where page order is known
+                        FigureNumbering::Continuous => FIGURE_LABEL_PLACEHOLDER.to_string(),
+                    };
                     lines.push(format!(
-                        "\n[IMAGE:{image_ref}]\n**[ภาพที่ {idx}]:** {description}\n"
+                        "\n{}\n",
+                        image_caption_block(&image_ref, &label, &description, &config)
                     ));
                 }
             }
@@ -541,15 +1335,15 @@ async fn process_page_async(
             img_bytes,
             img_filename,
             pdfium_text,
+            expects_text,
         } => {
+            let provider = &premium;
             tracing::info!(
                 "[Page {}] High-quality mode — full page Vision LLM OCR",
                 page_num + 1
             );
 
-            let img_path = images_dir.join(&img_filename);
-            tokio::fs::create_dir_all(img_path.parent().unwrap()).await?;
-            tokio::fs::write(&img_path, &img_bytes).await?;
+            image_writer.save_image(&img_filename, &img_bytes).await?;
 
             // Build prompt: use hint variant if pdfium text is non-empty
             let prompt = if !pdfium_text.is_empty() {
@@ -561,10 +1355,69 @@ async fn process_page_async(
                 prompts.high_quality.to_string()
             };
 
-            let description = match provider.ask(&img_b64, &prompt, config.max_retries).await {
-                Ok(desc) => desc,
+            let fingerprint = page_dedup
+                .as_ref()
+                .and_then(|_| PageFingerprint::of_page(&img_bytes, &pdfium_text));
+            let cached = page_dedup.as_ref().zip(fingerprint);
+            let reused = find_duplicate_page(cached.as_ref(), page_num);
+            let deduped_from = reused.as_ref().map(|(page, _)| *page);
+
+            let started = Instant::now();
+            let streamed = if reused.is_none() && config.stream_text {
+                stream_page_text(
+                    provider.as_ref(),
+                    &img_b64,
+                    &prompt,
+                    page_num,
+                    &config,
+                    reporter.as_ref(),
+                )
+                .await
+            } else {
+                None
+            };
+            let (result, fallback) = match (reused, streamed) {
+                (Some((_, text)), _) => (
+                    Ok(AskResult {
+                        text,
+                        ..Default::default()
+                    }),
+                    None,
+                ),
+                (None, Some(streamed)) => (Ok(streamed), None),
+                (None, None) => {
+                    ask_page_with_fallback(
+                        provider.as_ref(),
+                        &img_b64,
+                        &img_bytes,
+                        async { Ok(img_b64.clone()) },
+                        &prompt,
+                        true,
+                        &config,
+                    )
+                    .await
+                }
+            };
+            let latency_ms = started.elapsed().as_millis() as u64;
+            let mut tokens = (None, None);
+            let mut cost = None;
+            let mut low_confidence = false;
+            let description = match result {
+                Ok(result) if deduped_from.is_some() => result.text,
+                Ok(result) => {
+                    tokens = (result.input_tokens, result.output_tokens);
+                    cost = Some(page_request_cost(provider.as_ref(), fallback));
+                    low_confidence = expects_text
+                        && check_low_confidence(&result.text, page_num, reporter.as_ref());
+                    let description = reshape_description(result.text, &config);
+                    if let Some((dedup, fp)) = cached {
+                        dedup.insert(fp, page_num + 1, description.clone());
+                    }
+                    description
+                }
                 Err(e) => {
                     reporter.on_error(page_num + 1, &format!("{e}"));
+                    page_error.get_or_insert_with(|| e.to_string());
                     tracing::warn!(
                         "High-quality OCR failed on page {}: {e} — falling back to pdfium text",
                         page_num + 1
@@ -578,136 +1431,1008 @@ async fn process_page_async(
                 }
             };
 
-            let image_ref = format!("{doc_stem}/{img_filename}");
-
-            metadata.push(ImageMetadata {
-                image_file: image_ref.clone(),
-                page: page_num + 1,
-                index: None,
-                image_type: ImageType::FullPage,
-                width: None,
-                height: None,
-                description: truncate_str(&description, 200).to_string(),
-                source_doc: doc_stem.clone(),
-                provider: provider.provider_name().to_string(),
-                model: provider.model_name().to_string(),
-            });
-
-            reporter.on_image_processed(
-                page_num + 1,
-                1,
-                truncate_str(&description, 80),
+            let (page_lines, mut meta) = high_quality_page_output(
+                page_num,
+                &img_filename,
+                description,
+                &doc_stem,
+                &**provider,
+                reporter.as_ref(),
+                &config,
             );
+            meta.fallback = fallback.map(|f| f.to_string());
+            (meta.input_tokens, meta.output_tokens) = tokens;
+            meta.latency_ms = Some(latency_ms);
+            meta.cost_usd = cost;
+            meta.low_confidence = low_confidence;
+            meta.deduped = deduped_from.is_some();
+            meta.deduped_from = deduped_from;
+            page_text.clone_from(&meta.description);
+            text_from_render = true;
+            metadata.push(meta);
+            lines.extend(page_lines);
+        }
+    }
 
-            // LLM output IS the page content (no separate pdfium text to avoid duplication)
-            lines.push(format!("[IMAGE:{image_ref}]\n"));
-            lines.push(description);
+    if config.emit_image_sidecars {
+        for meta in &metadata {
+            write_image_sidecar(&image_writer, meta).await?;
         }
     }
 
     Ok(PageResult {
         page_num,
         content: lines.join("\n"),
+        text: page_text,
+        text_from_render,
         metadata,
+        error: page_error,
+        duration_ms: 0,
     })
 }
 
-/// Process an entire PDF file.
+/// Ask for a page-level description (full page, table or high-quality OCR);
+/// on total failure, try once more with the configured `page_fallback_strategy`.
 ///
-/// All pdfium operations happen synchronously (in spawn_blocking),
-/// then async LLM calls are made concurrently for each page's extracted data.
-pub async fn process_pdf(
-    pdf_path: &Path,
-    output_dir: &Path,
-    provider: Option<Arc<dyn VisionProvider>>,
+/// `hq_render` yields the page re-rendered at the high-quality DPI; it is
+/// only awaited when the high-quality fallback runs.
+///
+/// Returns the description (or the original error) and the fallback that
+/// produced it, if any.
+async fn ask_page_with_fallback(
+    provider: &dyn VisionProvider,
+    img_b64: &str,
+    img_bytes: &[u8],
+    hq_render: impl Future<Output = CoreResult<String>>,
+    prompt: &str,
+    high_quality_page: bool,
     config: &ProcessingConfig,
-    reporter: Arc<dyn ProgressReporter>,
-    start_page: Option<u32>,
-    end_page: Option<u32>,
-) -> CoreResult<ProcessingResult> {
-    let doc_stem = pdf_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("document")
-        .to_string();
+) -> (CoreResult<AskResult>, Option<PageFallbackStrategy>) {
+    let encoding = config.image_encoding;
+    let err = match provider
+        .ask(img_b64, encoding, prompt, config.max_retries)
+        .await
+    {
+        Ok(desc) => return (Ok(desc), None),
+        Err(e) => e,
+    };
+    let Some(fallback) = config.page_fallback_strategy.resolve(high_quality_page) else {
+        return (Err(err), None);
+    };
 
-    // Text-only mode: extract text only, no images, no LLM calls
-    if config.text_only {
-        return process_pdf_text_only(
-            pdf_path, output_dir, &doc_stem, config, reporter.as_ref(), start_page, end_page,
-        )
-        .await;
+    tracing::warn!("Page request failed: {err} — retrying with {fallback} fallback");
+    let retry = match fallback {
+        PageFallbackStrategy::Quadrants => ask_quadrants(provider, img_bytes, prompt, config).await,
+        _ => match hq_render.await {
+            Ok(hq_b64) => {
+                let prompts = prompts_for(config);
+                provider
+                    .ask(&hq_b64, encoding, &prompts.high_quality, config.max_retries)
+                    .await
+            }
+            Err(e) => Err(e),
+        },
+    };
+    match retry {
+        Ok(desc) => (Ok(desc), Some(fallback)),
+        Err(e) => {
+            tracing::warn!("{fallback} fallback failed too: {e}");
+            (Err(err), None)
+        }
     }
+}
 
-    let provider = provider.ok_or_else(|| {
-        CoreError::Config("Vision LLM provider required when text_only is false".into())
-    })?;
-
-    let images_dir = output_dir.join("images").join(&doc_stem);
-    tokio::fs::create_dir_all(&images_dir).await?;
-
-    // Extract all page data synchronously in a blocking task
-    let pdf_path_owned = pdf_path.to_path_buf();
-    let config_clone = config.clone();
-    let doc_stem_clone = doc_stem.clone();
+/// Render a page of `pdf_path` at the high-quality DPI (on the shared
+/// [`PdfWorker`]), returning the payload sent to the vision LLM.
+async fn render_high_quality(
+    pdf_path: PathBuf,
+    page_num: u32,
+    config: &ProcessingConfig,
+) -> CoreResult<String> {
+    let config = config.clone();
+    PdfWorker::shared()?
+        .run(move |engine| {
+            let doc = engine.open_document_with_layers(
+                &pdf_path,
+                &config.include_layers,
+                &config.exclude_layers,
+            )?;
+            let page = doc
+                .pages()
+                .get(page_num as u16)
+                .map_err(|e| CoreError::Pdf(format!("Failed to get page {}: {e}", page_num + 1)))?;
+            let (img_b64, _) = PdfEngine::render_page_as_image(
+                &page,
+                high_quality_dpi(&config),
+                config.enhance,
+                config.auto_rotate,
+                config.max_render_pixels,
+                config.image_encoding,
+            )?;
+            Ok(img_b64)
+        })
+        .await
+}
 
-    // Returns (page_data_results, page_texts_for_trash_detection)
-    let (page_data_results, page_texts_for_trash): (
-        Vec<(u32, CoreResult<PageData>)>,
-        Vec<(u32, String)>,
-    ) = tokio::task::spawn_blocking(move || {
-        let engine = PdfEngine::new()?;
-        let doc = engine.open_document(&pdf_path_owned)?;
-        let total_pages = PdfEngine::page_count(&doc);
+/// Stream a page transcription, reporting each chunk as it arrives. The
+/// result carries the token usage reported with the stream, if any.
+///
+/// Returns `None` when the stream fails or stays empty, so the caller can
+/// fall back to a normal request.
+async fn stream_page_text(
+    provider: &dyn VisionProvider,
+    img_b64: &str,
+    prompt: &str,
+    page_num: u32,
+    config: &ProcessingConfig,
+    reporter: &dyn ProgressReporter,
+) -> Option<AskResult> {
+    let mut chunks =
+        provider.ask_stream(img_b64, config.image_encoding, prompt, config.max_retries);
+    let mut streamed = AskResult::default();
+    loop {
+        match chunks.next().await.transpose() {
+            Ok(Some(chunk)) => {
+                if !chunk.text.is_empty() {
+                    reporter.on_partial_text(page_num + 1, &chunk.text);
+                    streamed.text.push_str(&chunk.text);
+                }
+                streamed.input_tokens = chunk.input_tokens.or(streamed.input_tokens);
+                streamed.output_tokens = chunk.output_tokens.or(streamed.output_tokens);
+            }
+            Ok(None) => break,
+            Err(e) => {
+                tracing::warn!(
+                    "Streaming failed on page {}: {e} — retrying without streaming",
+                    page_num + 1
+                );
+                return None;
+            }
+        }
+    }
+    let text = streamed.text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    Some(AskResult {
+        text: text.to_string(),
+        ..streamed
+    })
+}
 
-        let start = start_page.unwrap_or(0);
-        let end = end_page.unwrap_or(total_pages).min(total_pages);
+/// Describe a page render as four quadrant requests, joined in reading order.
+async fn ask_quadrants(
+    provider: &dyn VisionProvider,
+    img_bytes: &[u8],
+    prompt: &str,
+    config: &ProcessingConfig,
+) -> CoreResult<AskResult> {
+    let mut parts = Vec::with_capacity(4);
+    for tile in crate::pdf::split_into_quadrants(img_bytes, config.image_encoding)? {
+        let part = provider.ask(&tile, config.image_encoding, prompt, config.max_retries);
+        parts.push(part.await?);
+    }
+    Ok(AskResult::join(parts, "\n\n"))
+}
 
-        tracing::info!(
-            "Processing: {} | Pages: {}-{} (of {})",
-            doc_stem_clone,
-            start + 1,
-            end,
-            total_pages
-        );
+/// Estimated cost of a successful page-level request: four tiles for the
+/// quadrant fallback, one image otherwise.
+fn page_request_cost(
+    provider: &dyn VisionProvider,
+    fallback: Option<PageFallbackStrategy>,
+) -> f64 {
+    let images = if fallback == Some(PageFallbackStrategy::Quadrants) {
+        4.0
+    } else {
+        1.0
+    };
+    images * cost_per_image(provider)
+}
+
+/// Whether a page or table transcription is suspiciously short
+/// (`LOW_CONFIDENCE_MIN_CHARS`), usually a silent vision failure on a page
+/// with content. Warns through `reporter` so the page can be re-run.
+///
+/// Only call it for pages expected to hold text (see [`expects_text`]): a
+/// short answer for a blank page is right.
+fn check_low_confidence(description: &str, page_num: u32, reporter: &dyn ProgressReporter) -> bool {
+    let chars = description.trim().chars().count();
+    if chars >= LOW_CONFIDENCE_MIN_CHARS {
+        return false;
+    }
+    tracing::warn!(
+        "[Page {}] Vision output is only {chars} character(s) — low confidence",
+        page_num + 1
+    );
+    reporter.on_warning(
+        page_num + 1,
+        &format!("Low-confidence output ({chars} characters) — consider re-running this page"),
+    );
+    true
+}
 
-        let mut results = Vec::new();
-        let mut texts = Vec::new();
-        for page_num in start..end {
-            // Extract text for trash detection before full page data extraction
-            let page = doc.pages().get(page_num as u16).map_err(|e| {
-                CoreError::Pdf(format!("Failed to get page {}: {e}", page_num + 1))
-            })?;
-            let raw_text = PdfEngine::extract_page_text(&page);
-            let clean_text = cleanup_extracted_text(&raw_text);
-            texts.push((page_num, clean_text));
+/// Whether a page should transcribe to some text: its text layer is not
+/// empty and its render is not blank.
+fn expects_text(page: &pdfium_render::prelude::PdfPage<'_>, text: &str) -> CoreResult<bool> {
+    if text.trim().is_empty() {
+        return Ok(false);
+    }
+    Ok(PdfEngine::non_white_ratio(page)? >= EMPTY_TEXT_MIN_INK_RATIO)
+}
 
-            let data = extract_page_data(&doc, page_num, &doc_stem_clone, &config_clone);
-            results.push((page_num, data));
+/// Description of an earlier page that `cached`'s fingerprint matches
+/// (`dedup_pages`), with that page's 1-indexed number.
+fn find_duplicate_page(
+    cached: Option<&(&Arc<PageDedup>, PageFingerprint)>,
+    page_num: u32,
+) -> Option<(u32, String)> {
+    let (dedup, fingerprint) = cached?;
+    let (page, description) = dedup.find(fingerprint)?;
+    tracing::info!(
+        "[Page {}] same render and text as page {page} — reusing its description",
+        page_num + 1
+    );
+    Some((page, description))
+}
+
+/// Apply `wide_table_strategy` to tables in an LLM page/table description.
+fn reshape_description(description: String, config: &ProcessingConfig) -> String {
+    if config.wide_table_strategy == WideTableStrategy::Keep {
+        return description;
+    }
+    crate::table::reshape_wide_tables(
+        &description,
+        config.wide_table_max_columns,
+        config.wide_table_strategy,
+    )
+}
+
+/// Output for a high-quality (vision OCR) page: the page image and the
+/// transcription (ordered by `caption_placement`), plus the page image's
+/// metadata entry.
+fn high_quality_page_output(
+    page_num: u32,
+    img_filename: &str,
+    description: String,
+    doc_stem: &str,
+    provider: &dyn VisionProvider,
+    reporter: &dyn ProgressReporter,
+    config: &ProcessingConfig,
+) -> (Vec<String>, ImageMetadata) {
+    let image_ref = format!("{doc_stem}/{img_filename}");
+
+    let meta = ImageMetadata {
+        image_id: ImageMetadata::id_for_file(img_filename),
+        image_file: image_ref.clone(),
+        image_url: image_url(&image_ref, config.storage.as_ref()),
+        page: page_num + 1,
+        index: None,
+        image_type: ImageType::FullPage,
+        width: None,
+        height: None,
+        bbox: None,
+        description: truncate_str(&description, 200).to_string(),
+        source_doc: doc_stem.to_string(),
+        provider: provider.provider_name().to_string(),
+        model: provider.model_name().to_string(),
+        fallback: None,
+        input_tokens: None,
+        output_tokens: None,
+        latency_ms: None,
+        cost_usd: None,
+        deduped: false,
+        deduped_from: None,
+        low_confidence: false,
+        tables: Vec::new(),
+    };
+
+    reporter.on_image_processed(page_num + 1, 1, truncate_str(&description, 80));
+
+    // LLM output IS the page content (no separate pdfium text to avoid duplication)
+    let lines = page_image_block(&image_ref, description, config).to_vec();
+    (lines, meta)
+}
+
+/// Public URL of an image written to `config.storage`; `None` for local
+/// output, where images are linked by path.
+fn image_url(image_ref: &str, storage: Option<&Arc<dyn StorageBackend>>) -> Option<String> {
+    storage.map(|storage| storage.public_url(&format!("images/{image_ref}")))
+}
+
+/// Target of `![...](path)` links: the image's public URL with
+/// `config.storage`, otherwise its path relative to the markdown file.
+fn image_link(image_ref: &str, config: &ProcessingConfig) -> String {
+    if let Some(url) = image_url(image_ref, config.storage.as_ref()) {
+        return url;
+    }
+    match &config.images_output_dir {
+        Some(root) => format!("{}/{image_ref}", root.display()).replace('\\', "/"),
+        None => format!("images/{image_ref}"),
+    }
+}
+
+/// Markdown for an image: an `[IMAGE:...]` tag, or `![alt](path)` for
+/// `CaptionPlacement::AsAltText`.
+fn image_markup(image_ref: &str, alt: &str, config: &ProcessingConfig) -> String {
+    match config.caption_placement {
+        CaptionPlacement::AsAltText => {
+            let alt = alt
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .replace('[', "\\[")
+                .replace(']', "\\]");
+            format!("![{alt}]({})", image_link(image_ref, config))
         }
+        CaptionPlacement::After | CaptionPlacement::Before => format!("[IMAGE:{image_ref}]"),
+    }
+}
 
-        Ok::<_, CoreError>((results, texts))
-    })
-    .await
-    .map_err(|e| CoreError::Pdf(format!("Blocking task panicked: {e}")))?
-    ?;
+/// A page or table render and its transcription, as two blocks in output order.
+///
+/// Transcriptions are multi-line markdown, so even with `AsAltText` they stay
+/// body text and the alt text is the image id.
+fn page_image_block(
+    image_ref: &str,
+    description: String,
+    config: &ProcessingConfig,
+) -> [String; 2] {
+    let alt = image_ref
+        .rsplit('/')
+        .next()
+        .and_then(|f| f.rsplit_once('.'))
+        .map_or(image_ref, |(stem, _)| stem);
+    let image = format!("{}\n", image_markup(image_ref, alt, config));
+    match config.caption_placement {
+        CaptionPlacement::Before => [format!("{description}\n"), image],
+        CaptionPlacement::After | CaptionPlacement::AsAltText => [image, description],
+    }
+}
+
+/// An individual image with its labelled caption (`**[ภาพที่ 1]:** ...`).
+fn image_caption_block(
+    image_ref: &str,
+    label: &str,
+    description: &str,
+    config: &ProcessingConfig,
+) -> String {
+    match config.caption_placement {
+        CaptionPlacement::After => {
+            format!("[IMAGE:{image_ref}]\n**[{label}]:** {description}")
+        }
+        CaptionPlacement::Before => {
+            format!("**[{label}]:** {description}\n[IMAGE:{image_ref}]")
+        }
+        CaptionPlacement::AsAltText => {
+            image_markup(image_ref, &format!("{label}: {description}"), config)
+        }
+    }
+}
+
+/// Pages sent to the LLM together: one page, or a batch of consecutive
+/// high-quality pages when `pages_per_request > 1`.
+enum PageRequest {
+    Single(u32, CoreResult<PageData>),
+    Batch(Vec<(u32, PageData)>),
+}
+
+impl PageRequest {
+    /// 0-indexed number of the first page in the request.
+    fn first_page(&self) -> u32 {
+        match self {
+            Self::Single(page_num, _) => *page_num,
+            Self::Batch(pages) => pages.first().map(|(n, _)| *n).unwrap_or(0),
+        }
+    }
+
+    /// Estimated cost of the images this request will send to the LLM.
+    fn estimated_cost(&self, rates: ImageRates) -> f64 {
+        match self {
+            Self::Single(_, Ok(data)) => data.estimated_cost(rates),
+            Self::Single(_, Err(_)) => 0.0,
+            Self::Batch(pages) => pages.iter().map(|(_, d)| d.estimated_cost(rates)).sum(),
+        }
+    }
+}
+
+impl PageData {
+    /// Strip detected headers/footers from the pdfium text written to the
+    /// output. High-quality pages keep theirs: it is only a prompt hint.
+    fn strip_header_footer_lines(&mut self, headers: &[String], footers: &[String]) {
+        let text = match self {
+            Self::FullPage { pdfium_text, .. } => pdfium_text,
+            Self::Mixed { text, .. } => text,
+            Self::HighQuality { .. } => return,
+        };
+        *text = strip_header_footer_lines(text, headers, footers);
+    }
+
+    /// Images this page sends to the LLM: `(premium, base)` request counts.
+    fn vision_images(&self) -> (usize, usize) {
+        match self {
+            Self::FullPage { .. } | Self::HighQuality { .. } => (1, 0),
+            Self::Mixed {
+                images, table_img, ..
+            } => (usize::from(table_img.is_some()), images.len()),
+        }
+    }
+
+    fn estimated_cost(&self, rates: ImageRates) -> f64 {
+        let (premium, base) = self.vision_images();
+        rates.premium * premium as f64 + rates.base * base as f64
+    }
+}
+
+/// Group extracted pages into LLM requests.
+///
+/// Only consecutive `HighQuality` pages are batched; everything else (and
+/// extraction errors) stays a single-page request.
+fn group_page_requests(
+    pages: Vec<(u32, CoreResult<PageData>)>,
+    per_request: usize,
+) -> Vec<PageRequest> {
+    fn flush(batch: &mut Vec<(u32, PageData)>, requests: &mut Vec<PageRequest>) {
+        match batch.len() {
+            0 => {}
+            1 => {
+                let (page_num, data) = batch.remove(0);
+                requests.push(PageRequest::Single(page_num, Ok(data)));
+            }
+            _ => requests.push(PageRequest::Batch(std::mem::take(batch))),
+        }
+    }
+
+    let mut requests = Vec::new();
+    let mut batch: Vec<(u32, PageData)> = Vec::new();
+
+    for (page_num, data) in pages {
+        match data {
+            Ok(data @ PageData::HighQuality { .. }) if per_request > 1 => {
+                let consecutive = batch.last().is_none_or(|(last, _)| *last + 1 == page_num);
+                if !consecutive || batch.len() >= per_request {
+                    flush(&mut batch, &mut requests);
+                }
+                batch.push((page_num, data));
+            }
+            other => {
+                flush(&mut batch, &mut requests);
+                requests.push(PageRequest::Single(page_num, other));
+            }
+        }
+    }
+    flush(&mut batch, &mut requests);
+
+    requests
+}
+
+/// Split a batched transcription on its `=== PAGE n ===` separators.
+///
+/// Returns one entry per page in the batch; `None` for pages the response does
+/// not cover (missing or empty section), which the caller retries on their own.
+/// Only the `=== PAGE n ===` marker (optionally in bold) separates pages, so a
+/// printed "Page 12" footer stays in its page; a marker for a page outside the
+/// batch is kept as content of the page before it.
+fn split_batch_response(response: &str, page_count: usize) -> Vec<Option<String>> {
+    let mut sections: Vec<String> = vec![String::new(); page_count];
+    let mut current: Option<usize> = None;
+
+    for line in response.lines() {
+        let separator = parse_page_separator(line)
+            .and_then(|n| n.checked_sub(1))
+            .filter(|&idx| idx < page_count);
+        if separator.is_some() {
+            current = separator;
+            continue;
+        }
+        if let Some(idx) = current {
+            sections[idx].push_str(line);
+            sections[idx].push('\n');
+        }
+    }
+
+    sections
+        .into_iter()
+        .map(|s| Some(s.trim().to_string()).filter(|s| !s.is_empty()))
+        .collect()
+}
+
+/// Parse a `=== PAGE n ===` separator line, returning `n`.
+fn parse_page_separator(line: &str) -> Option<usize> {
+    let line = line.trim();
+    let line = line
+        .strip_prefix("**")
+        .and_then(|l| l.strip_suffix("**"))
+        .unwrap_or(line);
+    let inner = line.strip_prefix("===")?.strip_suffix("===")?.trim();
+    let (word, number) = inner.split_once(' ')?;
+    if !word.eq_ignore_ascii_case("page") {
+        return None;
+    }
+    number.trim().parse().ok()
+}
+
+/// Transcribe consecutive high-quality pages with one multi-image request.
+///
+/// Pages missing from the response, or every page when the provider rejects
+/// the batch, fall back to a normal per-page request.
+async fn process_hq_batch(
+    pages: Vec<(u32, PageData)>,
+    pdf_path: PathBuf,
+    providers: PageProviders,
+    image_writer: ImageWriter,
+    doc_stem: String,
+    config: ProcessingConfig,
+    reporter: Arc<dyn ProgressReporter>,
+    total_pages: u32,
+) -> CoreResult<Vec<PageResult>> {
+    let prompts = prompts_for(&config);
+    let first = pages[0].0 + 1;
+    let last = pages[pages.len() - 1].0 + 1;
+    tracing::info!(
+        "[Pages {first}-{last}] High-quality mode — {} pages in one Vision LLM request",
+        pages.len()
+    );
+
+    let images: Vec<String> = pages
+        .iter()
+        .filter_map(|(_, data)| match data {
+            PageData::HighQuality { img_b64, .. } => Some(img_b64.clone()),
+            _ => None,
+        })
+        .collect();
+    for (page_num, _) in &pages {
+        reporter.on_page_start(page_num + 1, total_pages);
+    }
+
+    let prompt = prompts
+        .high_quality_batch
+        .replace("{page_count}", &pages.len().to_string());
+    let started = Instant::now();
+    let batch = providers
+        .premium
+        .ask_multi(&images, config.image_encoding, &prompt, config.max_retries)
+        .await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let (sections, usage) = match batch {
+        Ok(response) => {
+            tracing::debug!(
+                input_tokens = ?response.input_tokens,
+                output_tokens = ?response.output_tokens,
+                "Batch request for pages {first}-{last} finished"
+            );
+            let sections = split_batch_response(&response.text, pages.len());
+            (sections, (response.input_tokens, response.output_tokens))
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Batch request for pages {first}-{last} failed: {e} — falling back to per-page requests"
+            );
+            (vec![None; pages.len()], (None, None))
+        }
+    };
+
+    // The batch's usage and cost are shared by the pages it answered
+    let answered = sections.iter().filter(|s| s.is_some()).count();
+    let batch_cost = cost_per_image(providers.premium.as_ref()) * images.len() as f64;
+    let mut answered_index = 0;
+
+    let mut results = Vec::with_capacity(pages.len());
+    for ((page_num, page_data), section) in pages.into_iter().zip(sections) {
+        let result = match (page_data, section) {
+            (
+                PageData::HighQuality {
+                    img_bytes,
+                    img_filename,
+                    expects_text,
+                    ..
+                },
+                Some(description),
+            ) => {
+                image_writer.save_image(&img_filename, &img_bytes).await?;
+
+                let low_confidence =
+                    expects_text && check_low_confidence(&description, page_num, reporter.as_ref());
+                let (page_lines, mut meta) = high_quality_page_output(
+                    page_num,
+                    &img_filename,
+                    reshape_description(description, &config),
+                    &doc_stem,
+                    providers.premium.as_ref(),
+                    reporter.as_ref(),
+                    &config,
+                );
+                meta.latency_ms = Some(latency_ms);
+                meta.input_tokens = token_share(usage.0, answered, answered_index);
+                meta.output_tokens = token_share(usage.1, answered, answered_index);
+                meta.cost_usd = Some(batch_cost / answered as f64);
+                meta.low_confidence = low_confidence;
+                answered_index += 1;
+                if config.emit_image_sidecars {
+                    write_image_sidecar(&image_writer, &meta).await?;
+                }
+
+                let mut lines = vec![format!("\n\n---\n## Page {}\n", page_num + 1)];
+                lines.extend(page_lines);
+                PageResult {
+                    page_num,
+                    content: lines.join("\n"),
+                    text: meta.description.clone(),
+                    text_from_render: true,
+                    metadata: vec![meta],
+                    error: None,
+                    duration_ms: 0,
+                }
+            }
+            (page_data, _) => {
+                tracing::warn!(
+                    "[Page {}] missing from batch response — sending it on its own",
+                    page_num + 1
+                );
+                process_page_async(
+                    page_data,
+                    page_num,
+                    pdf_path.clone(),
+                    providers.clone(),
+                    image_writer.clone(),
+                    doc_stem.clone(),
+                    config.clone(),
+                    reporter.clone(),
+                )
+                .await?
+            }
+        };
+
+        reporter.on_page_complete(page_num + 1, total_pages);
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+/// Share `index` of `total` tokens split evenly over `parts` pages; the
+/// first pages take the remainder, so the shares add up to `total`.
+fn token_share(total: Option<u32>, parts: usize, index: usize) -> Option<u32> {
+    let parts = u32::try_from(parts.max(1)).unwrap_or(u32::MAX);
+    let index = u32::try_from(index).unwrap_or(u32::MAX);
+    total.map(|total| total / parts + u32::from(index < total % parts))
+}
+
+/// Write `<image file>.json` with the image's metadata next to the image.
+///
+/// Storage backends write whole files, so readers never see a partially
+/// written sidecar.
+async fn write_image_sidecar(image_writer: &ImageWriter, meta: &ImageMetadata) -> CoreResult<()> {
+    let file_name = meta.image_file.rsplit('/').next().unwrap_or(&meta.image_file);
+    let json = serde_json::to_string_pretty(meta)?;
+    image_writer
+        .save(&format!("{file_name}.json"), json.as_bytes())
+        .await
+}
+
+/// File extensions accepted as standalone page images instead of a PDF.
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp"];
+
+/// Whether `path` is a standalone image (by extension) rather than a PDF.
+pub fn is_image_input(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Pages sampled by [`is_scanned_pdf`].
+const SCANNED_SAMPLE_PAGES: u32 = 5;
+
+/// Average non-whitespace pdfium characters per sampled page below which a
+/// PDF counts as scanned.
+const SCANNED_MAX_CHARS_PER_PAGE: usize = 30;
+
+/// Whether pages spread evenly over the range have (almost) no text layer,
+/// as in a scanned PDF. Checked before any page is extracted.
+async fn is_scanned_pdf(
+    pdf_path: &Path,
+    start_page: Option<u32>,
+    end_page: Option<u32>,
+) -> CoreResult<bool> {
+    let pdf_path = pdf_path.to_path_buf();
+    PdfWorker::shared()?
+        .run(move |engine| {
+            let doc = engine.open_document(&pdf_path)?;
+            let total_pages = PdfEngine::page_count(&doc);
+            let start = start_page.unwrap_or(0);
+            let end = end_page.unwrap_or(total_pages).min(total_pages);
+            let sampled = sample_pages(start, end, SCANNED_SAMPLE_PAGES);
+            if sampled.is_empty() {
+                return Ok(false);
+            }
+            let chars: usize = sampled
+                .iter()
+                .filter_map(|&page_num| doc.pages().get(page_num as u16).ok())
+                .map(|page| {
+                    PdfEngine::extract_page_text(&page)
+                        .chars()
+                        .filter(|c| !c.is_whitespace())
+                        .count()
+                })
+                .sum();
+            tracing::debug!(
+                "Scanned check: {chars} character(s) on {} sampled page(s)",
+                sampled.len()
+            );
+            Ok(chars < SCANNED_MAX_CHARS_PER_PAGE * sampled.len())
+        })
+        .await
+}
+
+/// Up to `count` 0-indexed pages spread evenly over `start..end`, including
+/// the first and last.
+fn sample_pages(start: u32, end: u32, count: u32) -> Vec<u32> {
+    let len = end.saturating_sub(start);
+    if len <= count {
+        return (start..end).collect();
+    }
+    if count <= 1 {
+        return vec![start; count as usize];
+    }
+    (0..count)
+        .map(|i| start + i * (len - 1) / (count - 1))
+        .collect()
+}
+
+/// Process an entire PDF file.
+///
+/// All pdfium operations happen synchronously (on the shared [`PdfWorker`]),
+/// then async LLM calls are made concurrently for each page's extracted data.
+///
+/// A PNG/JPEG/WebP input (see [`is_image_input`]) skips pdfium and is sent
+/// as a single high-quality page, producing the same outputs.
+pub async fn process_pdf(
+    pdf_path: &Path,
+    output_dir: &Path,
+    provider: Option<Arc<dyn VisionProvider>>,
+    config: &ProcessingConfig,
+    reporter: Arc<dyn ProgressReporter>,
+    start_page: Option<u32>,
+    end_page: Option<u32>,
+) -> CoreResult<ProcessingResult> {
+    let started = Instant::now();
+    let doc_stem = pdf_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("document")
+        .to_string();
+
+    // Reject invalid noise regexes and templates before any pages are processed
+    crate::noise::NoiseFilter::new(&config.chunk_noise_patterns)?;
+    let template = config
+        .output_template
+        .as_deref()
+        .map(OutputTemplate::new)
+        .transpose()?;
+
+    let image_input = is_image_input(pdf_path);
+    let outputs = Outputs::new(output_dir, config);
+
+    // Text-only mode: extract text only, no images, no LLM calls
+    if config.text_only {
+        if image_input {
+            return Err(CoreError::Config(format!(
+                "Text-only mode cannot process image input '{}' (no text layer)",
+                pdf_path.display()
+            )));
+        }
+        return process_pdf_text_only(
+            pdf_path, &outputs, &doc_stem, config, reporter.as_ref(), start_page, end_page,
+        )
+        .await;
+    }
+
+    let provider = provider.ok_or_else(|| {
+        CoreError::Config("Vision LLM provider required when text_only is false".into())
+    })?;
+
+    // Scanned PDFs have no text layer for standard mode to work with
+    let high_quality_config;
+    let config = if !image_input
+        && config.quality != Quality::High
+        && is_scanned_pdf(pdf_path, start_page, end_page).await?
+    {
+        if config.auto_high_quality_on_scanned {
+            tracing::warn!(
+                "{doc_stem}: sampled pages have almost no text layer (scanned PDF?) — \
+                 switching to high quality"
+            );
+            high_quality_config = ProcessingConfig {
+                quality: Quality::High,
+                ..config.clone()
+            };
+            &high_quality_config
+        } else {
+            tracing::warn!(
+                "{doc_stem}: sampled pages have almost no text layer (scanned PDF?) — \
+                 the markdown may be nearly empty; use high quality or \
+                 auto_high_quality_on_scanned"
+            );
+            config
+        }
+    } else {
+        config
+    };
+
+    // Premium provider for full-page / table / high-quality requests
+    let premium: Arc<dyn VisionProvider> = match &config.premium_provider {
+        Some((name, model)) => {
+            tracing::info!("Premium provider for full-page/table pages: {name} / {model}");
+            Arc::from(crate::provider::create_provider_with_examples(
+                name,
+                model,
+                config.few_shot.as_deref().unwrap_or_default(),
+            )?)
+        }
+        None => provider.clone(),
+    };
+
+    // One set of request limits for the whole run, shared by both providers
+    let limits = Arc::new(RequestLimits::new(
+        config.max_concurrent_requests,
+        config.requests_per_minute,
+        config.page_timeout_secs,
+    ));
+    let provider = LimitedProvider::wrap(provider, &limits);
+    let premium = LimitedProvider::wrap(premium, &limits);
+
+    // `[IMAGE:{doc_stem}/...]` tags are relative to the images root, so they
+    // stay valid whether images live under `output_dir` or elsewhere.
+    let images_root = config
+        .images_output_dir
+        .clone()
+        .unwrap_or_else(|| output_dir.join("images"));
+    let images_dir = images_root.join(&doc_stem);
+    let image_writer = ImageWriter::new(&images_root, &doc_stem, config);
+    image_writer.storage.create_dir(&image_writer.dir).await?;
+
+    // Extract all page data synchronously in a blocking task
+    let pdf_path_owned = pdf_path.to_path_buf();
+    let config_clone = config.clone();
+    let doc_stem_clone = doc_stem.clone();
+
+    // Returns (page_data_results, page_texts_for_trash_detection, form_sections, toc)
+    let (mut page_data_results, page_texts_for_trash, form_sections, toc): (
+        Vec<(u32, CoreResult<PageData>)>,
+        Vec<(u32, String)>,
+        HashMap<u32, String>,
+        String,
+    ) = if image_input {
+        // Standalone image: one vision-first page, no text layer or trash detection
+        tokio::task::spawn_blocking(move || {
+            tracing::info!("Processing: {} | Image input", doc_stem_clone);
+            let (img_b64, img_bytes) = crate::pdf::load_image_as_png(
+                &pdf_path_owned,
+                config_clone.enhance,
+                config_clone.image_encoding,
+            )?;
+            let data = PageData::HighQuality {
+                img_b64,
+                img_bytes,
+                img_filename: saved_image_name(&doc_stem_clone, 1, "hq", &config_clone),
+                pdfium_text: String::new(),
+                expects_text: false,
+            };
+            Ok::<_, CoreError>((
+                vec![(0, Ok(data))],
+                Vec::new(),
+                HashMap::new(),
+                String::new(),
+            ))
+        })
+        .await
+        .map_err(|e| CoreError::Pdf(format!("Blocking task panicked: {e}")))??
+    } else {
+        PdfWorker::shared()?
+            .run(move |engine| {
+                let doc = engine.open_document_with_layers(
+                    &pdf_path_owned,
+                    &config_clone.include_layers,
+                    &config_clone.exclude_layers,
+                )?;
+                let total_pages = PdfEngine::page_count(&doc);
+
+                let start = start_page.unwrap_or(0);
+                let end = end_page.unwrap_or(total_pages).min(total_pages);
+
+                tracing::info!(
+                    "Processing: {} | Pages: {}-{} (of {})",
+                    doc_stem_clone,
+                    start + 1,
+                    end,
+                    total_pages
+                );
+
+                let mut results = Vec::new();
+                let mut texts = Vec::new();
+                let mut forms = HashMap::new();
+                let pages = (start..end).filter(|p| !config_clone.skip_pages.contains(p + 1));
+                for page_num in pages {
+                    // Extract text for trash detection before full page data extraction
+                    let page = doc.pages().get(page_num as u16).map_err(|e| {
+                        CoreError::Pdf(format!("Failed to get page {}: {e}", page_num + 1))
+                    })?;
+                    let raw_text = PdfEngine::extract_page_text(&page);
+                    let clean_text = cleanup_extracted_text(&raw_text);
+                    texts.push((page_num, clean_text));
+
+                    if config_clone.extract_form_fields {
+                        let section = format_form_fields(&PdfEngine::extract_form_fields(&page));
+                        if !section.is_empty() {
+                            forms.insert(page_num, section);
+                        }
+                    }
+
+                    let data = extract_page_data(&doc, page_num, &doc_stem_clone, &config_clone);
+                    results.push((page_num, data));
+                }
+
+                let toc = if config_clone.emit_toc {
+                    format_toc(&PdfEngine::extract_bookmarks(&doc), start..end)
+                } else {
+                    String::new()
+                };
+
+                Ok((results, texts, forms, toc))
+            })
+            .await?
+    };
+
+    let rates = ImageRates {
+        base: cost_per_image(provider.as_ref()),
+        premium: cost_per_image(premium.as_ref()),
+    };
+    if config.dry_run {
+        return write_dry_run_plan(&outputs, &doc_stem, &page_data_results, rates).await;
+    }
 
     // Trash detection on extracted text
     let (headers, footers) = detect_headers_footers(&page_texts_for_trash);
-    let trash_items = if config.detect_trash {
-        let mut items = crate::trash::detect_trash(&page_texts_for_trash);
+    if config.strip_repeated_lines && !(headers.is_empty() && footers.is_empty()) {
+        tracing::info!(
+            "Detected {} header(s) and {} footer(s) to strip",
+            headers.len(),
+            footers.len()
+        );
+        for data in page_data_results
+            .iter_mut()
+            .filter_map(|(_, d)| d.as_mut().ok())
+        {
+            data.strip_header_footer_lines(&headers, &footers);
+        }
+    }
+    let repeated = if config.detect_repeated_paragraphs {
+        detect_repeated_paragraphs(&page_texts_for_trash, config.repeated_paragraph_ratio)
+    } else {
+        vec![]
+    };
+    let mut trash_items = if config.detect_trash {
+        let mut items = crate::trash::detect_trash(&page_texts_for_trash, &config.trash_preset.config());
         items.extend(crate::trash::create_header_footer_detections(
             &page_texts_for_trash, &headers, &footers,
         ));
+        items.extend(crate::trash::create_repeated_paragraph_detections(
+            page_texts_for_trash.len(),
+            &repeated,
+        ));
         items
     } else {
         vec![]
     };
 
+    let strategies: HashMap<u32, &str> = page_data_results
+        .iter()
+        .map(|(page_num, data)| (*page_num, page_strategy(data)))
+        .collect();
     let total_pages = page_data_results.len() as u32;
     reporter.on_pdf_start(&doc_stem, total_pages);
 
     let quality_label = match config.quality {
+        _ if image_input => "image (vision-first)",
         Quality::High => "high (vision-first)",
         Quality::Standard => "standard",
     };
@@ -718,24 +2443,95 @@ pub async fn process_pdf(
             provider.provider_name(),
             provider.model_name()
         ),
-        format!("> Images: `images/{doc_stem}/`\n"),
     ];
+    if config.premium_provider.is_some() {
+        all_content.push(format!(
+            "> Premium: `{}` | Model: `{}` (full-page / table pages)\n",
+            premium.provider_name(),
+            premium.model_name()
+        ));
+    }
+    // `images_output_dir` is ignored with `config.storage`
+    let images_output_dir = config
+        .images_output_dir
+        .as_ref()
+        .filter(|_| config.storage.is_none());
+    match images_output_dir {
+        Some(root) => all_content.push(format!(
+            "> Images: `{}/`\n",
+            root.join(&doc_stem).display()
+        )),
+        None => all_content.push(format!("> Images: `images/{doc_stem}/`\n")),
+    }
+    if !toc.is_empty() {
+        all_content.push(toc);
+    }
+    let mut header_len = all_content.len();
     let mut metadata_catalog: Vec<ImageMetadata> = Vec::new();
 
     // Process pages concurrently with semaphore
     let page_semaphore = Arc::new(Semaphore::new(config.max_concurrent_pages));
     let mut join_set = JoinSet::new();
 
-    for (page_num, page_data_result) in page_data_results {
+    let providers = PageProviders {
+        base: provider.clone(),
+        premium: premium.clone(),
+        dedup: config.dedup_images.then(|| Arc::new(ImageDedup::default())),
+        page_dedup: config.dedup_pages.then(|| Arc::new(PageDedup::default())),
+    };
+    let cost = Arc::new(CostMeter::default());
+    // On cancellation, pages already finished are still written out
+    let mut cancelled = false;
+    for request in group_page_requests(page_data_results, config.pages_per_request) {
         let permit = page_semaphore.clone().acquire_owned().await.unwrap();
-        let images_dir = images_dir.clone();
+        if config.is_cancelled() {
+            cancelled = true;
+            join_set.abort_all();
+            break;
+        }
+
+        // Budget check: finished + in-flight pages + this request
+        let estimate = request.estimated_cost(rates);
+        if let Some(max_cost) = config.max_cost_usd {
+            let projected = cost.projected(estimate);
+            if projected > max_cost {
+                join_set.abort_all();
+                return Err(CoreError::CostLimit(format!(
+                    "estimated ${projected:.2} would exceed the ${max_cost:.2} budget at page {}",
+                    request.first_page() + 1
+                )));
+            }
+        }
+        cost.reserve(estimate);
+
+        let pdf_path = pdf_path.to_path_buf();
+        let image_writer = image_writer.clone();
         let doc_stem = doc_stem.clone();
         let config = config.clone();
-        let provider = provider.clone();
+        let providers = providers.clone();
         let reporter = reporter.clone();
+        let span = tracing::info_span!("page", page = request.first_page() + 1);
 
-        join_set.spawn(async move {
+        let task = async move {
             let _permit = permit;
+
+            let (page_num, page_data_result) = match request {
+                PageRequest::Batch(pages) => {
+                    return process_hq_batch(
+                        pages,
+                        pdf_path,
+                        providers,
+                        image_writer,
+                        doc_stem,
+                        config,
+                        reporter,
+                        total_pages,
+                    )
+                    .await;
+                }
+                PageRequest::Single(page_num, page_data_result) => (page_num, page_data_result),
+            };
+
             reporter.on_page_start(page_num + 1, total_pages);
 
             let result = match page_data_result {
@@ -743,8 +2539,9 @@ pub async fn process_pdf(
                     process_page_async(
                         page_data,
                         page_num,
-                        provider,
-                        images_dir,
+                        pdf_path,
+                        providers,
+                        image_writer,
                         doc_stem,
                         config,
                         reporter.clone(),
@@ -757,135 +2554,555 @@ pub async fn process_pdf(
                         "\n\n---\n## Page {}\n[Error: {e}]\n",
                         page_num + 1
                     ),
+                    text: String::new(),
+                    text_from_render: false,
                     metadata: vec![],
+                    error: Some(e.to_string()),
+                    duration_ms: 0,
                 }),
             };
 
             reporter.on_page_complete(page_num + 1, total_pages);
+            result.map(|r| vec![r])
+        };
+        let cost = cost.clone();
+        let task = async move {
+            let page_started = Instant::now();
+            let mut result = task.await;
+            let duration_ms = page_started.elapsed().as_millis() as u64;
+            for page in result.iter_mut().flatten() {
+                page.duration_ms = duration_ms;
+            }
+            let actual = result.as_ref().map_or(0.0, |pages| {
+                pages.iter().map(|p| rates.cost_of(&p.metadata)).sum()
+            });
+            cost.settle(estimate, actual);
             result
-        });
+        };
+        join_set.spawn(task.instrument(span));
+    }
+
+    let (page_results, lost_pages, cancelled) =
+        collect_page_results(join_set, config, cancelled).await;
+
+    let last_page = page_results.last().map(|r| r.page_num + 1);
+    if cancelled {
+        let Some(last_page) = last_page else {
+            return Err(CoreError::Cancelled);
+        };
+        tracing::info!(
+            "Cancelled — writing {} finished page(s) of {total_pages}",
+            page_results.len()
+        );
+        all_content.insert(
+            header_len,
+            format!(
+                "> Partial: cancelled with {} of {total_pages} pages done (last page {last_page})\n",
+                page_results.len()
+            ),
+        );
+        header_len += 1;
+        // Keep document-level detections and those of written pages only
+        trash_items
+            .retain(|t| t.page == 0 || page_results.iter().any(|r| r.page_num + 1 == t.page));
+    }
+
+    // Refuse to write a "successful" output when (nearly) every page failed
+    let mut failed: Vec<(Option<u32>, &str)> = page_results
+        .iter()
+        .filter_map(|r| r.error.as_deref().map(|e| (Some(r.page_num + 1), e)))
+        .collect();
+    failed.extend(lost_pages.iter().map(|e| (None, e.as_str())));
+    if let Some(message) = failed_pages_error(&failed, total_pages, config.max_failed_page_ratio) {
+        return Err(CoreError::Provider(message));
+    }
+
+    // Assemble content and metadata
+    let mut figure_counter = 0;
+    let mut template_figures = 0;
+    let mut rag_pages: Vec<(u32, String)> = Vec::new();
+    let mut template_pages: Vec<PageContext> = Vec::new();
+    let mut page_records: Vec<PageRecord> = Vec::new();
+    for pr in &page_results {
+        let mut content = match config.figure_numbering {
+            FigureNumbering::PerPage => pr.content.clone(),
+            FigureNumbering::Continuous => {
+                number_figures(&pr.content, config.language, &mut figure_counter)
+            }
+        };
+        if let Some(section) = form_sections.get(&pr.page_num) {
+            content.push('\n');
+            content.push_str(section);
+        }
+        if template.is_some() {
+            template_pages.push(PageContext {
+                text: pr.text.clone(),
+                form_fields: form_sections.get(&pr.page_num).cloned().unwrap_or_default(),
+                error: pr.error.clone(),
+                content: content.clone(),
+                ..PageContext::new(pr.page_num + 1, &pr.metadata, &mut template_figures)
+            });
+        }
+        if config.output_format.json_lines() {
+            let mut text = pr.text.clone();
+            if let Some(section) = form_sections.get(&pr.page_num) {
+                text.push('\n');
+                text.push_str(section);
+            }
+            page_records.push(PageRecord::new(
+                pr.page_num + 1,
+                text,
+                &pr.metadata,
+                pr.text_from_render,
+            ));
+        }
+        rag_pages.push((pr.page_num + 1, content.clone()));
+        all_content.push(content);
+        metadata_catalog.extend(pr.metadata.iter().cloned());
+    }
+
+    // Save outputs
+    let md_key = format!("{doc_stem}_enriched.md");
+    let md_path = outputs.path(&md_key);
+
+    let (header, pages) = all_content.split_at(header_len);
+    let markdown_content = match &template {
+        Some(template) => template.render(&DocumentContext {
+            doc_stem: doc_stem.clone(),
+            provider: provider.provider_name().to_string(),
+            model: provider.model_name().to_string(),
+            total_pages,
+            header: header.to_vec(),
+            pages: template_pages,
+        })?,
+        None => all_content.join("\n"),
+    };
+    if config.output_format.markdown() {
+        outputs
+            .write(&md_key, &config.line_ending.apply(&markdown_content))
+            .await?;
+    }
+
+    // Parts are cut at built-in page boundaries, which a template may not keep
+    let markdown_parts = if !config.output_format.markdown() {
+        Vec::new()
+    } else if template.is_some() {
+        if config.max_markdown_bytes.is_some() {
+            tracing::warn!("max_markdown_bytes is ignored with an output template");
+        }
+        Vec::new()
+    } else {
+        write_markdown_parts(&outputs, &doc_stem, header, pages, config).await?
+    };
+
+    let metadata_json = serde_json::to_string_pretty(&metadata_catalog)?;
+    let meta_path = outputs
+        .write(&format!("{doc_stem}_images_metadata.json"), &metadata_json)
+        .await?;
+
+    let rag_jsonl_path =
+        write_rag_output(&outputs, &doc_stem, config, &rag_pages, &metadata_catalog).await?;
+    let pages_jsonl_path = write_pages_output(&outputs, &doc_stem, config, &page_records).await?;
+
+    let image_count = metadata_catalog.len() as u32;
+    reporter.on_pdf_complete(&doc_stem, image_count);
+
+    let input_tokens = metadata_catalog
+        .iter()
+        .filter_map(|m| m.input_tokens)
+        .map(u64::from)
+        .sum();
+    let output_tokens = metadata_catalog
+        .iter()
+        .filter_map(|m| m.output_tokens)
+        .map(u64::from)
+        .sum();
+
+    // Save trash detection results
+    let trash_count = trash_items.len() as u32;
+    let trash_path = if !trash_items.is_empty() {
+        let json = serde_json::to_string_pretty(&trash_items)?;
+        let path = outputs
+            .write(&format!("{doc_stem}_trash.json"), &json)
+            .await?;
+        tracing::info!("Trash detected: {} items -> {}", trash_count, path.display());
+        Some(path)
+    } else {
+        None
+    };
+
+    if config.output_format.markdown() {
+        tracing::info!(
+            "Markdown: {} ({:.1} KB)",
+            md_path.display(),
+            markdown_content.len() as f64 / 1024.0
+        );
+    }
+    tracing::info!("Metadata: {} ({} images)", meta_path.display(), image_count);
+
+    // Integrity check: warn-only, never fails the run (local output only)
+    if config.output_format.markdown() && config.storage.is_none() {
+        match crate::verify::verify_output(&md_path, &images_dir).await {
+            Ok(report) if !report.is_ok() => tracing::warn!(
+                "Output verification: {} dangling image tag(s), {} orphan image file(s)",
+                report.dangling_tags.len(),
+                report.orphan_files.len()
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Output verification failed: {e}"),
+        }
     }
 
-    // Collect results
+    let manifest = ProcessingManifest {
+        doc_stem: doc_stem.clone(),
+        provider: provider.provider_name().to_string(),
+        model: provider.model_name().to_string(),
+        quality: quality_label.to_string(),
+        elapsed_secs: started.elapsed().as_secs_f64(),
+        image_count,
+        partial: cancelled,
+        pages: page_results
+            .iter()
+            .map(|pr| PageTiming {
+                page: pr.page_num + 1,
+                strategy: strategies
+                    .get(&pr.page_num)
+                    .copied()
+                    .unwrap_or("error")
+                    .to_string(),
+                duration_ms: pr.duration_ms,
+                image_count: pr.metadata.len(),
+                error: pr.error.clone(),
+            })
+            .collect(),
+    };
+    let manifest_path = outputs
+        .write(
+            &format!("{doc_stem}_manifest.json"),
+            &serde_json::to_string_pretty(&manifest)?,
+        )
+        .await?;
+    let slowest_page = manifest.slowest_page().cloned();
+    if let Some(slowest) = &slowest_page {
+        tracing::info!(
+            "Done in {:.1}s, slowest page {} ({:.1}s) -> {}",
+            manifest.elapsed_secs,
+            slowest.page,
+            slowest.duration_ms as f64 / 1000.0,
+            manifest_path.display()
+        );
+    }
+
+    Ok(ProcessingResult {
+        markdown_path: config.output_format.markdown().then_some(md_path),
+        markdown_parts,
+        metadata_path: meta_path,
+        image_count,
+        trash_path,
+        trash_count,
+        rag_jsonl_path,
+        pages_jsonl_path,
+        input_tokens,
+        output_tokens,
+        estimated_cost_usd: cost.spent(),
+        plan_path: None,
+        plan: None,
+        partial: cancelled,
+        last_page: last_page.filter(|_| cancelled),
+        elapsed_secs: manifest.elapsed_secs,
+        manifest_path: Some(manifest_path),
+        slowest_page,
+    })
+}
+
+/// Wait for every page task and return the finished pages in page order,
+/// the errors of pages lost without a result, and whether the run was
+/// cancelled.
+///
+/// A cancellation that lands after the last page finished still counts, so
+/// the outputs are then written as partial rather than complete.
+async fn collect_page_results(
+    mut join_set: JoinSet<CoreResult<Vec<PageResult>>>,
+    config: &ProcessingConfig,
+    mut cancelled: bool,
+) -> (Vec<PageResult>, Vec<String>, bool) {
     let mut page_results: Vec<PageResult> = Vec::new();
+    let mut lost_pages: Vec<String> = Vec::new();
     while let Some(result) = join_set.join_next().await {
+        if !cancelled && config.is_cancelled() {
+            cancelled = true;
+            join_set.abort_all();
+        }
         match result {
-            Ok(Ok(page_result)) => page_results.push(page_result),
+            Ok(Ok(results)) => page_results.extend(results),
             Ok(Err(e)) => {
                 tracing::error!("Page processing error: {e}");
                 // We don't know the page_num here, but we log the error
+                lost_pages.push(e.to_string());
             }
+            // Aborted on cancellation: the page is left out of the partial output
+            Err(e) if e.is_cancelled() => {}
             Err(e) => {
                 tracing::error!("Page task panicked: {e}");
+                lost_pages.push(format!("page task panicked: {e}"));
             }
         }
     }
 
     // Sort by page number to maintain order
     page_results.sort_by_key(|r| r.page_num);
+    (page_results, lost_pages, cancelled || config.is_cancelled())
+}
+
+/// Dry run: write `{doc_stem}_plan.json` describing how each page would be
+/// handled, without calling the LLM or writing markdown.
+async fn write_dry_run_plan(
+    outputs: &Outputs,
+    doc_stem: &str,
+    pages: &[(u32, CoreResult<PageData>)],
+    rates: ImageRates,
+) -> CoreResult<ProcessingResult> {
+    let plan = DryRunReport {
+        doc_stem: doc_stem.to_string(),
+        pages: pages
+            .iter()
+            .map(|(n, data)| PagePlan::new(*n, data))
+            .collect(),
+        vision_images: pages
+            .iter()
+            .filter_map(|(_, data)| data.as_ref().ok())
+            .map(|data| {
+                let (premium, base) = data.vision_images();
+                premium + base
+            })
+            .sum(),
+        estimated_cost_usd: pages
+            .iter()
+            .filter_map(|(_, data)| data.as_ref().ok())
+            .map(|data| data.estimated_cost(rates))
+            .sum(),
+    };
+
+    let plan_path = outputs
+        .write(
+            &format!("{doc_stem}_plan.json"),
+            &serde_json::to_string_pretty(&plan)?,
+        )
+        .await?;
+    tracing::info!(
+        "Dry run plan: {} ({} vision image(s))",
+        plan_path.display(),
+        plan.vision_images
+    );
+
+    Ok(ProcessingResult {
+        markdown_path: None,
+        markdown_parts: Vec::new(),
+        metadata_path: outputs.path(&format!("{doc_stem}_images_metadata.json")),
+        image_count: 0,
+        trash_path: None,
+        trash_count: 0,
+        rag_jsonl_path: None,
+        pages_jsonl_path: None,
+        input_tokens: 0,
+        output_tokens: 0,
+        estimated_cost_usd: plan.estimated_cost_usd,
+        plan_path: Some(plan_path),
+        plan: Some(plan),
+        partial: false,
+        last_page: None,
+        elapsed_secs: 0.0,
+        manifest_path: None,
+        slowest_page: None,
+    })
+}
 
-    // Assemble content and metadata
-    for pr in &page_results {
-        all_content.push(pr.content.clone());
-        metadata_catalog.extend(pr.metadata.iter().cloned());
+/// Write `{doc_stem}_rag.jsonl` (one record per page) when `config.rag_jsonl` is set.
+async fn write_rag_output(
+    outputs: &Outputs,
+    doc_stem: &str,
+    config: &ProcessingConfig,
+    pages: &[(u32, String)],
+    metadata: &[ImageMetadata],
+) -> CoreResult<Option<PathBuf>> {
+    if !config.rag_jsonl {
+        return Ok(None);
     }
+    let noise = crate::noise::NoiseFilter::new(&config.chunk_noise_patterns)?;
+    let language = config.language.to_string();
+    let records = crate::rag::build_rag_records(doc_stem, &language, pages, metadata, &noise);
+
+    let path = outputs
+        .write(
+            &format!("{doc_stem}_rag.jsonl"),
+            &crate::rag::rag_jsonl(&records)?,
+        )
+        .await?;
+    tracing::info!("RAG JSONL: {} ({} records)", path.display(), records.len());
+    Ok(Some(path))
+}
 
-    // Save outputs
-    let md_path = output_dir.join(format!("{doc_stem}_enriched.md"));
-    let meta_path = output_dir.join(format!("{doc_stem}_images_metadata.json"));
+/// Write `{doc_stem}_pages.jsonl` when `output_format` includes JSON Lines.
+async fn write_pages_output(
+    outputs: &Outputs,
+    doc_stem: &str,
+    config: &ProcessingConfig,
+    records: &[PageRecord],
+) -> CoreResult<Option<PathBuf>> {
+    if !config.output_format.json_lines() {
+        return Ok(None);
+    }
+    let path = outputs
+        .write(
+            &format!("{doc_stem}_pages.jsonl"),
+            &crate::pages::pages_jsonl(records)?,
+        )
+        .await?;
+    tracing::info!("Pages JSONL: {} ({} pages)", path.display(), records.len());
+    Ok(Some(path))
+}
 
-    let markdown_content = all_content.join("\n");
-    tokio::fs::write(&md_path, &markdown_content).await?;
+/// Split the assembled markdown into `{doc_stem}_part{n}_enriched.md` files
+/// at page boundaries when it exceeds `max_markdown_bytes`.
+///
+/// Every part repeats the document header. A single page larger than the
+/// limit gets a part of its own. Returns an empty list when no split is needed.
+async fn write_markdown_parts(
+    outputs: &Outputs,
+    doc_stem: &str,
+    header: &[String],
+    pages: &[String],
+    config: &ProcessingConfig,
+) -> CoreResult<Vec<PathBuf>> {
+    let Some(max_bytes) = config.max_markdown_bytes else {
+        return Ok(Vec::new());
+    };
 
-    let metadata_json = serde_json::to_string_pretty(&metadata_catalog)?;
-    tokio::fs::write(&meta_path, &metadata_json).await?;
+    let header_content = header.join("\n");
+    let total_len = header_content.len() + pages.iter().map(|p| p.len() + 1).sum::<usize>();
+    if total_len <= max_bytes {
+        return Ok(Vec::new());
+    }
 
-    let image_count = metadata_catalog.len() as u32;
-    reporter.on_pdf_complete(&doc_stem, image_count);
+    let mut parts: Vec<String> = Vec::new();
+    let mut current = header_content.clone();
+    let mut current_pages = 0;
+    for page in pages {
+        if current_pages > 0 && current.len() + page.len() + 1 > max_bytes {
+            parts.push(std::mem::replace(&mut current, header_content.clone()));
+            current_pages = 0;
+        }
+        current.push('\n');
+        current.push_str(page);
+        current_pages += 1;
+    }
+    if current_pages > 0 {
+        parts.push(current);
+    }
 
-    // Save trash detection results
-    let trash_count = trash_items.len() as u32;
-    let trash_path = if !trash_items.is_empty() {
-        let path = output_dir.join(format!("{doc_stem}_trash.json"));
-        let json = serde_json::to_string_pretty(&trash_items)?;
-        tokio::fs::write(&path, &json).await?;
-        tracing::info!("Trash detected: {} items -> {}", trash_count, path.display());
-        Some(path)
-    } else {
-        None
-    };
+    let mut paths = Vec::with_capacity(parts.len());
+    for (i, part) in parts.iter().enumerate() {
+        let key = format!("{doc_stem}_part{}_enriched.md", i + 1);
+        paths.push(outputs.write(&key, &config.line_ending.apply(part)).await?);
+    }
 
     tracing::info!(
-        "Markdown: {} ({:.1} KB)",
-        md_path.display(),
-        markdown_content.len() as f64 / 1024.0
+        "Markdown exceeds {} bytes — split into {} part(s)",
+        max_bytes,
+        paths.len()
     );
-    tracing::info!("Metadata: {} ({} images)", meta_path.display(), image_count);
-
-    Ok(ProcessingResult {
-        markdown_path: md_path,
-        metadata_path: meta_path,
-        image_count,
-        trash_path,
-        trash_count,
-    })
+    Ok(paths)
 }
 
 /// Text-only processing: extract text via pdfium only, no images, no LLM calls.
 async fn process_pdf_text_only(
     pdf_path: &Path,
-    output_dir: &Path,
+    outputs: &Outputs,
     doc_stem: &str,
     config: &ProcessingConfig,
     reporter: &dyn ProgressReporter,
     start_page: Option<u32>,
     end_page: Option<u32>,
 ) -> CoreResult<ProcessingResult> {
+    let started = Instant::now();
     let pdf_path_owned = pdf_path.to_path_buf();
     let doc_stem_clone = doc_stem.to_string();
+    let extract_forms = config.extract_form_fields;
+    let emit_toc = config.emit_toc;
+    let skip_pages = config.skip_pages.clone();
+    let cancel = config.cancel.clone();
 
-    let mut page_texts: Vec<(u32, String)> = tokio::task::spawn_blocking(move || {
-        let engine = PdfEngine::new()?;
-        let doc = engine.open_document(&pdf_path_owned)?;
-        let total_pages = PdfEngine::page_count(&doc);
+    let (mut page_texts, form_sections, toc) = PdfWorker::shared()?
+        .run(move |engine| {
+            let doc = engine.open_document(&pdf_path_owned)?;
+            let total_pages = PdfEngine::page_count(&doc);
 
-        let start = start_page.unwrap_or(0);
-        let end = end_page.unwrap_or(total_pages).min(total_pages);
+            let start = start_page.unwrap_or(0);
+            let end = end_page.unwrap_or(total_pages).min(total_pages);
 
-        tracing::info!(
-            "Text-only processing: {} | Pages: {}-{} (of {})",
-            doc_stem_clone,
-            start + 1,
-            end,
-            total_pages
-        );
+            tracing::info!(
+                "Text-only processing: {} | Pages: {}-{} (of {})",
+                doc_stem_clone,
+                start + 1,
+                end,
+                total_pages
+            );
 
-        let mut results = Vec::new();
-        for page_num in start..end {
-            let page = doc.pages().get(page_num as u16).map_err(|e| {
-                CoreError::Pdf(format!("Failed to get page {}: {e}", page_num + 1))
-            })?;
-            let text = PdfEngine::extract_page_text(&page);
-            let text = cleanup_extracted_text(&text);
-            results.push((page_num, text));
-        }
+            let mut results: Vec<(u32, String)> = Vec::new();
+            let mut forms: HashMap<u32, String> = HashMap::new();
+            for page_num in (start..end).filter(|p| !skip_pages.contains(p + 1)) {
+                if cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+                    return Err(CoreError::Cancelled);
+                }
+                let page = doc.pages().get(page_num as u16).map_err(|e| {
+                    CoreError::Pdf(format!("Failed to get page {}: {e}", page_num + 1))
+                })?;
+                let text = PdfEngine::extract_page_text(&page);
+                let text = cleanup_extracted_text(&text);
+                results.push((page_num, text));
+
+                if extract_forms {
+                    let section = format_form_fields(&PdfEngine::extract_form_fields(&page));
+                    if !section.is_empty() {
+                        forms.insert(page_num, section);
+                    }
+                }
+            }
 
-        Ok::<_, CoreError>(results)
-    })
-    .await
-    .map_err(|e| CoreError::Pdf(format!("Blocking task panicked: {e}")))?
-    ?;
+            let toc = if emit_toc {
+                format_toc(&PdfEngine::extract_bookmarks(&doc), start..end)
+            } else {
+                String::new()
+            };
+
+            Ok((results, forms, toc))
+        })
+        .await?;
 
     // Detect and strip repeated headers/footers
     let (headers, footers) = detect_headers_footers(&page_texts);
-    apply_strip_headers_footers(&mut page_texts, &headers, &footers);
+    if config.strip_repeated_lines {
+        apply_strip_headers_footers(&mut page_texts, &headers, &footers);
+    }
+
+    // Detect and strip paragraphs repeated anywhere on the page
+    let repeated = if config.detect_repeated_paragraphs {
+        detect_repeated_paragraphs(&page_texts, config.repeated_paragraph_ratio)
+    } else {
+        vec![]
+    };
+    apply_strip_repeated_paragraphs(&mut page_texts, &repeated);
 
     // Trash detection
     let trash_items = if config.detect_trash {
-        let mut items = crate::trash::detect_trash(&page_texts);
+        let mut items = crate::trash::detect_trash(&page_texts, &config.trash_preset.config());
         items.extend(crate::trash::create_header_footer_detections(
             &page_texts, &headers, &footers,
         ));
+        items.extend(crate::trash::create_repeated_paragraph_detections(
+            page_texts.len(),
+            &repeated,
+        ));
         items
     } else {
         vec![]
@@ -894,15 +3111,18 @@ async fn process_pdf_text_only(
     let total_pages = page_texts.len() as u32;
     reporter.on_pdf_start(doc_stem, total_pages);
 
-    let lang_label = match config.language {
-        crate::config::Language::Th => "th",
-        crate::config::Language::En => "en",
-    };
+    let lang_label = config.language;
 
     let mut all_content = vec![
         format!("# {doc_stem}\n"),
         format!("> Mode: `text-only` | Language: `{lang_label}` | Pages: {total_pages}\n"),
     ];
+    if !toc.is_empty() {
+        all_content.push(toc);
+    }
+    let header_len = all_content.len();
+    let mut rag_pages: Vec<(u32, String)> = Vec::new();
+    let mut page_records: Vec<PageRecord> = Vec::new();
 
     for (page_num, text) in &page_texts {
         reporter.on_page_start(page_num + 1, total_pages);
@@ -911,27 +3131,55 @@ async fn process_pdf_text_only(
         if !text.is_empty() {
             lines.push(text.clone());
         }
-        all_content.push(lines.join("\n"));
+        if let Some(section) = form_sections.get(page_num) {
+            lines.push(section.clone());
+        }
+        let content = lines.join("\n");
+        if config.output_format.json_lines() {
+            let text = lines[1..].join("\n");
+            page_records.push(PageRecord::new(page_num + 1, text, &[], false));
+        }
+        rag_pages.push((page_num + 1, content.clone()));
+        all_content.push(content);
 
         reporter.on_page_complete(page_num + 1, total_pages);
     }
 
+    // Text-only runs are quick, so a cancelled one keeps nothing
+    if config.is_cancelled() {
+        return Err(CoreError::Cancelled);
+    }
+
     // Save outputs
-    let md_path = output_dir.join(format!("{doc_stem}_enriched.md"));
-    let meta_path = output_dir.join(format!("{doc_stem}_images_metadata.json"));
+    let md_key = format!("{doc_stem}_enriched.md");
+    let md_path = outputs.path(&md_key);
 
     let markdown_content = all_content.join("\n");
-    tokio::fs::write(&md_path, &markdown_content).await?;
+    let markdown_parts = if config.output_format.markdown() {
+        outputs
+            .write(&md_key, &config.line_ending.apply(&markdown_content))
+            .await?;
+        let (header, pages) = all_content.split_at(header_len);
+        write_markdown_parts(outputs, doc_stem, header, pages, config).await?
+    } else {
+        Vec::new()
+    };
 
     // Empty metadata for text-only mode
-    tokio::fs::write(&meta_path, "[]").await?;
+    let meta_path = outputs
+        .write(&format!("{doc_stem}_images_metadata.json"), "[]")
+        .await?;
+
+    let rag_jsonl_path = write_rag_output(outputs, doc_stem, config, &rag_pages, &[]).await?;
+    let pages_jsonl_path = write_pages_output(outputs, doc_stem, config, &page_records).await?;
 
     // Save trash detection results
     let trash_count = trash_items.len() as u32;
     let trash_path = if !trash_items.is_empty() {
-        let path = output_dir.join(format!("{doc_stem}_trash.json"));
         let json = serde_json::to_string_pretty(&trash_items)?;
-        tokio::fs::write(&path, &json).await?;
+        let path = outputs
+            .write(&format!("{doc_stem}_trash.json"), &json)
+            .await?;
         tracing::info!("Trash detected: {} items -> {}", trash_count, path.display());
         Some(path)
     } else {
@@ -940,18 +3188,33 @@ async fn process_pdf_text_only(
 
     reporter.on_pdf_complete(doc_stem, 0);
 
-    tracing::info!(
-        "Text-only markdown: {} ({:.1} KB)",
-        md_path.display(),
-        markdown_content.len() as f64 / 1024.0
-    );
+    if config.output_format.markdown() {
+        tracing::info!(
+            "Text-only markdown: {} ({:.1} KB)",
+            md_path.display(),
+            markdown_content.len() as f64 / 1024.0
+        );
+    }
 
     Ok(ProcessingResult {
-        markdown_path: md_path,
+        markdown_path: config.output_format.markdown().then_some(md_path),
+        markdown_parts,
         metadata_path: meta_path,
         image_count: 0,
         trash_path,
         trash_count,
+        rag_jsonl_path,
+        pages_jsonl_path,
+        input_tokens: 0,
+        output_tokens: 0,
+        estimated_cost_usd: 0.0,
+        plan_path: None,
+        plan: None,
+        partial: false,
+        last_page: None,
+        elapsed_secs: started.elapsed().as_secs_f64(),
+        manifest_path: None,
+        slowest_page: None,
     })
 }
 
@@ -968,45 +3231,14 @@ pub async fn clean_markdown(
     let content = tokio::fs::read_to_string(markdown_path).await?;
     let remove_set: HashSet<u32> = pages_to_remove.iter().copied().collect();
 
-    let mut cleaned_sections = Vec::new();
-    let mut current_section = String::new();
-    let mut current_page: Option<u32> = None;
-    let mut in_header = true; // True until we hit the first ## Page section
-
-    for line in content.lines() {
-        // Check if this is a page section header: "## Page N"
-        if let Some(page_num) = parse_page_header(line) {
-            // Flush previous section
-            if in_header {
-                // Everything before first ## Page is the document header — always keep
-                cleaned_sections.push(current_section.clone());
-                current_section.clear();
-                in_header = false;
-            } else if let Some(prev_page) = current_page {
-                if !remove_set.contains(&prev_page) {
-                    cleaned_sections.push(current_section.clone());
-                }
-                current_section.clear();
-            }
-            current_page = Some(page_num);
-        }
-
-        current_section.push_str(line);
-        current_section.push('\n');
-    }
-
-    // Flush last section
-    if let Some(prev_page) = current_page {
-        if !remove_set.contains(&prev_page) {
-            cleaned_sections.push(current_section);
+    let (header, sections) = split_page_sections(&content);
+    let mut cleaned_content = header;
+    for (page_num, section) in sections {
+        if !remove_set.contains(&page_num) {
+            cleaned_content.push_str(&section);
         }
-    } else {
-        // No page sections found, keep everything
-        cleaned_sections.push(current_section);
     }
 
-    let cleaned_content = cleaned_sections.join("");
-
     // Build cleaned path: replace _enriched.md with _cleaned.md
     let stem = markdown_path
         .file_stem()
@@ -1026,6 +3258,78 @@ pub async fn clean_markdown(
     Ok((cleaned_path, cleaned_content))
 }
 
+/// Keep only the specified pages of an enriched markdown document.
+///
+/// The document header (everything before the first `## Page N` section) is
+/// always kept.
+pub fn select_markdown_pages(content: &str, pages: &PageSelection) -> String {
+    let (header, sections) = split_page_sections(content);
+
+    let mut selected = header;
+    for (page_num, section) in sections {
+        if pages.contains(page_num) {
+            selected.push_str(&section);
+        }
+    }
+    selected
+}
+
+/// Concatenate enriched markdown documents, in order, into one document
+/// titled `# {title}`.
+///
+/// Each part's document header is dropped. Parts processed as page ranges
+/// of the same PDF keep their `## Page N` headings; a part whose numbering
+/// restarts (at or below the pages already merged) is shifted to follow on.
+pub fn merge_markdown_parts(title: &str, parts: &[String]) -> String {
+    let mut merged = format!("# {title}\n");
+    let mut last_page = 0;
+    for part in parts {
+        let (_, sections) = split_page_sections(part);
+        let offset = match sections.first() {
+            Some(&(first, _)) if first <= last_page => last_page + 1 - first,
+            _ => 0,
+        };
+        for (page_num, section) in sections {
+            let page = page_num + offset;
+            last_page = last_page.max(page);
+            // Drop the heading and the separator that precedes the next page
+            let body = section
+                .split_once('\n')
+                .map_or("", |(_, body)| body)
+                .trim_end();
+            let body = body.strip_suffix("---").unwrap_or(body).trim_end();
+            merged.push_str(&format!("\n---\n## Page {page}\n{body}\n"));
+        }
+    }
+    merged
+}
+
+/// Split enriched markdown into the document header and `## Page N` sections.
+///
+/// Returns `(header, [(page_num, section)])`. When no page sections exist,
+/// the whole document is returned as the header.
+fn split_page_sections(content: &str) -> (String, Vec<(u32, String)>) {
+    let mut header = String::new();
+    let mut sections: Vec<(u32, String)> = Vec::new();
+
+    for line in content.lines() {
+        // Check if this is a page section header: "## Page N"
+        if let Some(page_num) = parse_page_header(line) {
+            sections.push((page_num, String::new()));
+        }
+
+        let target = match sections.last_mut() {
+            Some((_, section)) => section,
+            // Everything before first ## Page is the document header
+            None => &mut header,
+        };
+        target.push_str(line);
+        target.push('\n');
+    }
+
+    (header, sections)
+}
+
 /// Parse "## Page N" header and return N (1-indexed).
 fn parse_page_header(line: &str) -> Option<u32> {
     let trimmed = line.trim();
@@ -1037,3 +3341,622 @@ fn parse_page_header(line: &str) -> Option<u32> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ImageEncoding;
+
+    #[test]
+    fn test_cleanup_nested_thai_bullets() {
+        let raw = "วิธีตั้งค่า\n- เปิดเครื่อง\n    - กดปุ่มเปิด\n    - รอสักครู่\n- เชื่อมต่อ Wi-Fi";
+        let cleaned = cleanup_extracted_text(raw);
+        assert_eq!(
+            cleaned,
+            "วิธีตั้งค่า\n- เปิดเครื่อง\n  - กดปุ่มเปิด\n  - รอสักครู่\n- เชื่อมต่อ Wi-Fi"
+        );
+    }
+
+    #[test]
+    fn test_cleanup_nested_ordered_list_keeps_numbers() {
+        let raw = "1. ติดตั้งแอป\n   1) ดาวน์โหลดจาก Store\n   2) กดติดตั้ง\n2. ลงชื่อเข้าใช้\n3. เริ่มใช้งาน";
+        let cleaned = cleanup_extracted_text(raw);
+        assert_eq!(
+            cleaned,
+            "1. ติดตั้งแอป\n   1. ดาวน์โหลดจาก Store\n   2. กดติดตั้ง\n2. ลงชื่อเข้าใช้\n3. เริ่มใช้งาน"
+        );
+    }
+
+    #[test]
+    fn test_number_figures_uses_language_label() {
+        let content = format!("{FIGURE_LABEL_PLACEHOLDER} a {FIGURE_LABEL_PLACEHOLDER} b");
+        let mut counter = 2;
+        assert_eq!(
+            number_figures(&content, Language::En, &mut counter),
+            "Figure 3 a Figure 4 b"
+        );
+        assert_eq!(counter, 4);
+        assert_eq!(figure_label(Language::Th), "ภาพที่");
+    }
+
+    #[test]
+    fn test_cleanup_thai_digit_list_items() {
+        assert_eq!(
+            parse_list_item("๑. บทนำ"),
+            Some(("๑.".to_string(), "บทนำ"))
+        );
+        assert_eq!(parse_list_item("2024. ปีนี้"), None);
+        assert_eq!(parse_list_item("ข้อความธรรมดา"), None);
+    }
+
+    #[test]
+    fn test_repeated_mid_page_paragraph_stripped() {
+        let disclaimer = "ดูรายละเอียดเพิ่มเติมได้ที่เว็บไซต์ของบริษัท ข้อมูลอาจเปลี่ยนแปลงได้โดยไม่ต้องแจ้งให้ทราบล่วงหน้า";
+        let mut pages: Vec<(u32, String)> = (0..4)
+            .map(|i| {
+                (
+                    i,
+                    format!("เนื้อหาส่วนแรกของหน้า {i}\n\n{disclaimer}\n\nเนื้อหาส่วนท้ายของหน้า {i}"),
+                )
+            })
+            .collect();
+
+        let repeated = detect_repeated_paragraphs(&pages, 0.6);
+        assert_eq!(repeated.len(), 1);
+        assert_eq!(repeated[0].1, 4);
+
+        apply_strip_repeated_paragraphs(&mut pages, &repeated);
+        for (i, text) in &pages {
+            assert!(!text.contains("ดูรายละเอียดเพิ่มเติม"));
+            assert!(text.contains(&format!("เนื้อหาส่วนแรกของหน้า {i}")));
+            assert!(text.contains(&format!("เนื้อหาส่วนท้ายของหน้า {i}")));
+        }
+    }
+
+    #[test]
+    fn test_numbered_page_footers_stripped() {
+        for (template, pattern) in [
+            ("Page {} of 120", "Page # of #"),
+            ("หน้า {} จาก 120", "หน้า # จาก #"),
+        ] {
+            let mut pages: Vec<(u32, String)> = (1..=5)
+                .map(|i| {
+                    let footer = template.replace("{}", &i.to_string());
+                    let body = format!("เนื้อหาหน้านี้ไม่ซ้ำกับหน้าอื่น {}", "ก".repeat(i as usize));
+                    (i, format!("{body}\n{footer}"))
+                })
+                .collect();
+
+            let (_, footers) = detect_headers_footers(&pages);
+            assert_eq!(footers, vec![pattern.to_string()]);
+
+            apply_strip_headers_footers(&mut pages, &[], &footers);
+            for (i, text) in &pages {
+                assert!(!text.contains("120"), "footer left on page {i}: {text}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_number_only_body_line_survives_footer_strip() {
+        let mut pages: Vec<(u32, String)> = (1..=5)
+            .map(|i| {
+                let n = i as usize;
+                let body = format!(
+                    "ขั้นตอนที่ {i}: {}\n{}\nตรวจสอบค่า {}\nบันทึกผล {}",
+                    "ก".repeat(n),
+                    i * 10,
+                    "ข".repeat(n),
+                    "ค".repeat(n)
+                );
+                (i, format!("คู่มือการติดตั้ง\n{body}\n{i}"))
+            })
+            .collect();
+
+        let (headers, footers) = detect_headers_footers(&pages);
+        assert_eq!(headers, vec!["คู่มือการติดตั้ง".to_string()]);
+        assert_eq!(footers, vec!["#".to_string()]);
+
+        // The lone page number goes, the lone number in the body stays
+        apply_strip_headers_footers(&mut pages, &headers, &footers);
+        assert_eq!(pages[2].1, "ขั้นตอนที่ 3: กกก\n30\nตรวจสอบค่า ขขข\nบันทึกผล คคค");
+    }
+
+    #[test]
+    fn test_footer_key_keeps_body_lines() {
+        assert_eq!(footer_key("- 12 -"), "- # -");
+        assert_eq!(footer_key("Page 3 of 120"), "Page # of #");
+        assert_eq!(footer_key("เนื้อหาส่วนท้ายของหน้า 3"), "เนื้อหาส่วนท้ายของหน้า 3");
+        assert_eq!(footer_key("Company Confidential"), "Company Confidential");
+    }
+
+    #[test]
+    fn test_repeated_paragraph_below_ratio_kept() {
+        let pages: Vec<(u32, String)> = vec![
+            (0, "ข้อความที่ซ้ำกันแค่สองหน้าเท่านั้นนะ".to_string()),
+            (1, "ข้อความที่ซ้ำกันแค่สองหน้าเท่านั้นนะ".to_string()),
+            (2, "เนื้อหาอื่นที่ไม่ซ้ำกับหน้าอื่นเลย".to_string()),
+            (3, "เนื้อหาอีกแบบที่ไม่ซ้ำกับหน้าไหน".to_string()),
+        ];
+        assert!(detect_repeated_paragraphs(&pages, 0.6).is_empty());
+    }
+
+    #[test]
+    fn test_cleanup_joins_list_item_continuation() {
+        let raw = "- ขั้นตอนแรกที่ยาวมาก\n  ต่อบรรทัดถัดไป\n- ขั้นตอนที่สอง";
+        let cleaned = cleanup_extracted_text(raw);
+        assert_eq!(cleaned, "- ขั้นตอนแรกที่ยาวมาก ต่อบรรทัดถัดไป\n- ขั้นตอนที่สอง");
+    }
+
+    #[test]
+    fn test_format_form_fields() {
+        assert_eq!(format_form_fields(&[]), "");
+        let fields = vec![
+            ("ชื่อ-นามสกุล".to_string(), "สมชาย ใจดี".to_string()),
+            ("agree".to_string(), "Yes".to_string()),
+        ];
+        assert_eq!(
+            format_form_fields(&fields),
+            "\n### Form Fields\n\n- **ชื่อ-นามสกุล:** สมชาย ใจดี\n- **agree:** Yes\n"
+        );
+    }
+
+    #[test]
+    fn test_format_toc() {
+        let bookmarks = vec![
+            (0, "Setup".to_string(), 0),
+            (1, "Wi-Fi [2.4 GHz]".to_string(), 2),
+            (2, "Appendix note".to_string(), 9),
+            (0, "Troubleshooting".to_string(), 4),
+        ];
+        assert_eq!(
+            format_toc(&bookmarks, 0..5),
+            "## Contents\n\n- [Setup](#page-1)\n  - [Wi-Fi \\[2.4 GHz\\]](#page-3)\n- [Troubleshooting](#page-5)\n"
+        );
+        // A child whose parent is outside the range moves up to the top level
+        assert_eq!(
+            format_toc(&bookmarks, 1..3),
+            "## Contents\n\n- [Wi-Fi \\[2.4 GHz\\]](#page-3)\n"
+        );
+        assert_eq!(format_toc(&[], 0..5), "");
+    }
+
+    #[tokio::test]
+    async fn test_cancel_after_last_page_keeps_pages_as_partial() {
+        let cancel = tokio_util::sync::CancellationToken::new();
+        let config = ProcessingConfig {
+            cancel: Some(cancel.clone()),
+            ..Default::default()
+        };
+        let page = |page_num| PageResult {
+            page_num,
+            content: format!("\n\n---\n## Page {}\n", page_num + 1),
+            text: String::new(),
+            text_from_render: false,
+            metadata: vec![],
+            error: None,
+            duration_ms: 0,
+        };
+        let mut join_set = JoinSet::new();
+        for page_num in [1, 0] {
+            join_set.spawn(async move { Ok(vec![page(page_num)]) });
+        }
+        // Let every page finish before the cancellation arrives
+        tokio::task::yield_now().await;
+        cancel.cancel();
+
+        let (pages, lost, cancelled) = collect_page_results(join_set, &config, false).await;
+        assert!(cancelled);
+        assert!(lost.is_empty());
+        assert_eq!(
+            pages.iter().map(|p| p.page_num).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_page_text_keeps_usage() {
+        /// Streams two chunks, then the usage on its own item.
+        struct StreamingProvider;
+
+        #[async_trait::async_trait]
+        impl VisionProvider for StreamingProvider {
+            async fn ask(
+                &self,
+                _image_b64: &str,
+                _encoding: ImageEncoding,
+                _prompt: &str,
+                _retries: u32,
+            ) -> CoreResult<AskResult> {
+                unreachable!("the page is streamed")
+            }
+
+            fn ask_stream<'a>(
+                &'a self,
+                _image_b64: &'a str,
+                _encoding: ImageEncoding,
+                _prompt: &'a str,
+                _retries: u32,
+            ) -> futures::stream::BoxStream<'a, CoreResult<AskResult>> {
+                let chunk = |text: &str| AskResult {
+                    text: text.to_string(),
+                    ..Default::default()
+                };
+                let usage = AskResult {
+                    text: String::new(),
+                    input_tokens: Some(1200),
+                    output_tokens: Some(45),
+                };
+                futures::stream::iter([Ok(chunk("## หน้า")), Ok(chunk("แรก\n")), Ok(usage)]).boxed()
+            }
+
+            async fn check(&self) -> CoreResult<()> {
+                Ok(())
+            }
+
+            fn provider_name(&self) -> &str {
+                "mock"
+            }
+
+            fn model_name(&self) -> &str {
+                "mock"
+            }
+        }
+
+        let config = ProcessingConfig::default();
+        let reporter = crate::progress::SilentReporter;
+        let result = stream_page_text(&StreamingProvider, "", "", 0, &config, &reporter)
+            .await
+            .unwrap();
+        assert_eq!(result.text, "## หน้าแรก");
+        assert_eq!(
+            (result.input_tokens, result.output_tokens),
+            (Some(1200), Some(45))
+        );
+    }
+
+    /// Records every image it is sent; fails the first request when `fail_first`.
+    #[derive(Default)]
+    struct RecordingProvider {
+        fail_first: bool,
+        sent: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl RecordingProvider {
+        fn failing_once() -> Self {
+            Self {
+                fail_first: true,
+                ..Default::default()
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl VisionProvider for RecordingProvider {
+        async fn ask(
+            &self,
+            image_b64: &str,
+            _encoding: ImageEncoding,
+            _prompt: &str,
+            _retries: u32,
+        ) -> CoreResult<AskResult> {
+            let mut sent = self.sent.lock().unwrap();
+            sent.push(image_b64.to_string());
+            if self.fail_first && sent.len() == 1 {
+                return Err(CoreError::Provider("503 Service Unavailable".into()));
+            }
+            Ok(AskResult {
+                text: "คำอธิบายหน้า".into(),
+                ..Default::default()
+            })
+        }
+
+        async fn check(&self) -> CoreResult<()> {
+            Ok(())
+        }
+
+        fn provider_name(&self) -> &str {
+            "mock"
+        }
+
+        fn model_name(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_high_quality_fallback_sends_rerendered_page() {
+        let config = ProcessingConfig {
+            page_fallback_strategy: PageFallbackStrategy::HighQuality,
+            ..Default::default()
+        };
+        let provider = RecordingProvider::failing_once();
+        let hq_render = async { Ok("hq-render".to_string()) };
+
+        let (result, fallback) =
+            ask_page_with_fallback(&provider, "std", &[], hq_render, "prompt", false, &config)
+                .await;
+        assert!(result.is_ok());
+        assert_eq!(fallback, Some(PageFallbackStrategy::HighQuality));
+        assert_eq!(*provider.sent.lock().unwrap(), vec!["std", "hq-render"]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_rerender_keeps_original_error() {
+        let config = ProcessingConfig {
+            page_fallback_strategy: PageFallbackStrategy::HighQuality,
+            ..Default::default()
+        };
+        let provider = RecordingProvider::failing_once();
+        let hq_render = async { Err(CoreError::Pdf("page is gone".into())) };
+
+        let (result, fallback) =
+            ask_page_with_fallback(&provider, "std", &[], hq_render, "prompt", false, &config)
+                .await;
+        assert!(result.unwrap_err().to_string().contains("503"));
+        assert_eq!(fallback, None);
+        assert_eq!(provider.sent.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_quadrant_fallback_skips_rerender() {
+        let config = ProcessingConfig {
+            page_fallback_strategy: PageFallbackStrategy::Auto,
+            ..Default::default()
+        };
+        let mut png = Vec::new();
+        image::DynamicImage::new_rgb8(8, 8)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let provider = RecordingProvider::failing_once();
+        let hq_render = async { unreachable!("high-quality pages are not re-rendered") };
+
+        let (result, fallback) =
+            ask_page_with_fallback(&provider, "hq", &png, hq_render, "prompt", true, &config).await;
+        assert!(result.is_ok());
+        assert_eq!(fallback, Some(PageFallbackStrategy::Quadrants));
+        // The failed request plus one per quadrant
+        assert_eq!(provider.sent.lock().unwrap().len(), 5);
+    }
+
+    /// A PDF of `pages` blank A4 pages.
+    fn blank_pdf(pages: usize) -> Vec<u8> {
+        use lopdf::{dictionary, Document, Object};
+
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let kids: Vec<Object> = (0..pages)
+            .map(|_| {
+                doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+                })
+                .into()
+            })
+            .collect();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => pages as i64,
+                "Kids" => kids,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+
+        let mut out = Vec::new();
+        doc.save_to(&mut out).unwrap();
+        out
+    }
+
+    #[tokio::test]
+    async fn test_skipped_pages_are_never_sent() {
+        if PdfEngine::check_available().is_err() {
+            eprintln!("pdfium not available; skipping");
+            return;
+        }
+        let dir = std::env::temp_dir().join(format!("jay-rag-skip-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pdf_path = dir.join("manual.pdf");
+        std::fs::write(&pdf_path, blank_pdf(4)).unwrap();
+
+        let provider = Arc::new(RecordingProvider::default());
+        let config = ProcessingConfig {
+            quality: Quality::High,
+            skip_pages: crate::config::parse_page_selection("2-3").unwrap(),
+            ..Default::default()
+        };
+        let result = process_pdf(
+            &pdf_path,
+            &dir,
+            Some(provider.clone()),
+            &config,
+            Arc::new(crate::progress::SilentReporter),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(provider.sent.lock().unwrap().len(), 2);
+        let metadata: Vec<ImageMetadata> =
+            serde_json::from_str(&std::fs::read_to_string(&result.metadata_path).unwrap()).unwrap();
+        let pages: Vec<u32> = metadata.iter().map(|m| m.page).collect();
+        assert_eq!(pages, vec![1, 4]);
+        let markdown = std::fs::read_to_string(result.markdown_path.unwrap()).unwrap();
+        assert!(markdown.contains("## Page 4"));
+        assert!(!markdown.contains("## Page 2") && !markdown.contains("## Page 3"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_pages_error() {
+        let failed = [(Some(1), "connection refused"), (Some(2), "connection refused")];
+        assert_eq!(
+            failed_pages_error(&failed, 2, 1.0).as_deref(),
+            Some("2/2 pages failed (e.g. page 1: connection refused)")
+        );
+        assert!(failed_pages_error(&failed[..1], 2, 1.0).is_none());
+        assert!(failed_pages_error(&failed[..1], 2, 0.5).is_some());
+        assert!(failed_pages_error(&[], 0, 1.0).is_none());
+    }
+
+    #[test]
+    fn test_split_batch_response() {
+        let response = "=== PAGE 1 ===\n# หัวข้อ\nเนื้อหาหน้าแรก\n\n**=== Page 2 ===**\nหน้าสอง\n=== PAGE 3 ===\n";
+        assert_eq!(
+            split_batch_response(response, 3),
+            vec![
+                Some("# หัวข้อ\nเนื้อหาหน้าแรก".to_string()),
+                Some("หน้าสอง".to_string()),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_batch_response_keeps_page_footers() {
+        let response = "=== PAGE 1 ===\nเนื้อหา\nPage 12\n## Page 13\n=== PAGE 2 ===\nสอง\n\
+                        === PAGE 7 ===\nต่อ\n";
+        assert_eq!(
+            split_batch_response(response, 2),
+            vec![
+                Some("เนื้อหา\nPage 12\n## Page 13".to_string()),
+                Some("สอง\n=== PAGE 7 ===\nต่อ".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_token_share() {
+        let shares: Vec<_> = (0..3).map(|i| token_share(Some(1000), 3, i)).collect();
+        assert_eq!(shares, vec![Some(334), Some(333), Some(333)]);
+        assert_eq!(token_share(Some(7), 1, 0), Some(7));
+        assert_eq!(token_share(None, 3, 0), None);
+    }
+
+    #[test]
+    fn test_split_batch_response_without_separators() {
+        assert_eq!(split_batch_response("just some text", 2), vec![None, None]);
+    }
+
+    #[test]
+    fn test_is_image_input() {
+        assert!(is_image_input(Path::new("scans/page1.PNG")));
+        assert!(is_image_input(Path::new("photo.jpeg")));
+        assert!(is_image_input(Path::new("page.webp")));
+        assert!(!is_image_input(Path::new("manual.pdf")));
+        assert!(!is_image_input(Path::new("README")));
+    }
+
+    #[test]
+    fn test_check_low_confidence() {
+        /// Records warnings; any error fails the test.
+        #[derive(Default)]
+        struct WarningReporter(std::sync::Mutex<Vec<u32>>);
+        impl ProgressReporter for WarningReporter {
+            fn on_pdf_start(&self, _filename: &str, _total_pages: u32) {}
+            fn on_page_start(&self, _page_num: u32, _total_pages: u32) {}
+            fn on_page_complete(&self, _page_num: u32, _total_pages: u32) {}
+            fn on_image_processed(&self, _page_num: u32, _image_index: u32, _desc: &str) {}
+            fn on_pdf_complete(&self, _filename: &str, _total_images: u32) {}
+            fn on_error(&self, page_num: u32, error: &str) {
+                panic!("unexpected error on page {page_num}: {error}");
+            }
+            fn on_warning(&self, page_num: u32, _message: &str) {
+                self.0.lock().unwrap().push(page_num);
+            }
+        }
+
+        let reporter = WarningReporter::default();
+        assert!(check_low_confidence("", 0, &reporter));
+        assert!(check_low_confidence("  หน้าว่าง\n", 4, &reporter));
+        assert!(!check_low_confidence(
+            "## คู่มือการติดตั้ง\nขั้นตอนที่ 1",
+            0,
+            &reporter
+        ));
+        assert_eq!(*reporter.0.lock().unwrap(), vec![1, 5]);
+    }
+
+    #[test]
+    fn test_image_caption_block_placements() {
+        let mut config = ProcessingConfig::default();
+        let block = |config: &ProcessingConfig| {
+            image_caption_block("doc/p1_img1.png", "ภาพที่ 1", "ปุ่ม [OK]\nสีเขียว", config)
+        };
+        assert_eq!(
+            block(&config),
+            "[IMAGE:doc/p1_img1.png]\n**[ภาพที่ 1]:** ปุ่ม [OK]\nสีเขียว"
+        );
+
+        config.caption_placement = CaptionPlacement::Before;
+        assert!(block(&config).ends_with("\n[IMAGE:doc/p1_img1.png]"));
+
+        config.caption_placement = CaptionPlacement::AsAltText;
+        assert_eq!(
+            block(&config),
+            "![ภาพที่ 1: ปุ่ม \\[OK\\] สีเขียว](images/doc/p1_img1.png)"
+        );
+
+        // Images written to a storage backend are linked by public URL
+        config.storage = Some(Arc::new(LocalStorage::new(
+            PathBuf::from("/srv/rag"),
+            "https://cdn.example.com/rag/".to_string(),
+        )));
+        assert!(block(&config).ends_with("(https://cdn.example.com/rag/images/doc/p1_img1.png)"));
+    }
+
+    #[test]
+    fn test_sample_pages() {
+        assert_eq!(sample_pages(0, 3, 5), vec![0, 1, 2]);
+        assert_eq!(sample_pages(0, 100, 5), vec![0, 24, 49, 74, 99]);
+        assert_eq!(sample_pages(10, 20, 2), vec![10, 19]);
+        assert!(sample_pages(5, 5, 5).is_empty());
+    }
+
+    #[test]
+    fn test_manifest_slowest_page() {
+        let timing = |page, duration_ms| PageTiming {
+            page,
+            strategy: "mixed".to_string(),
+            duration_ms,
+            image_count: 0,
+            error: None,
+        };
+        let mut manifest = ProcessingManifest {
+            doc_stem: "doc".to_string(),
+            provider: "ollama".to_string(),
+            model: "llava".to_string(),
+            quality: "standard".to_string(),
+            elapsed_secs: 9.5,
+            image_count: 0,
+            partial: false,
+            pages: vec![timing(1, 1200), timing(2, 4800), timing(3, 900)],
+        };
+        assert_eq!(manifest.slowest_page().map(|p| p.page), Some(2));
+        manifest.pages.clear();
+        assert!(manifest.slowest_page().is_none());
+    }
+
+    #[test]
+    fn test_merge_markdown_parts() {
+        // Page ranges of one PDF: headings kept, including a skipped page 5
+        let first = "# report\n> Pages: 3\n\n---\n## Page 3\nสาม\n\n---\n## Page 4\nสี่\n";
+        let second = "# report\n> Pages: 2\n\n---\n## Page 6\nหก\n\n---\n## Page 7\nเจ็ด\n";
+        assert_eq!(
+            merge_markdown_parts("report", &[first.to_string(), second.to_string()]),
+            "# report\n\n---\n## Page 3\nสาม\n\n---\n## Page 4\nสี่\n\n---\n## Page 6\nหก\n\n---\n## Page 7\nเจ็ด\n"
+        );
+
+        // A part numbered from 1 again follows on from the previous one
+        let restarted = "# report_p2\n\n---\n## Page 1\nหนึ่ง\n\n---\n## Page 3\nสาม\n";
+        assert_eq!(
+            merge_markdown_parts("report", &[first.to_string(), restarted.to_string()]),
+            "# report\n\n---\n## Page 3\nสาม\n\n---\n## Page 4\nสี่\n\n---\n## Page 5\nหนึ่ง\n\n---\n## Page 7\nสาม\n"
+        );
+    }
+}
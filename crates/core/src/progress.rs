@@ -19,6 +19,15 @@ pub trait ProgressReporter: Send + Sync {
 
     /// Called on non-fatal errors.
     fn on_error(&self, page_num: u32, error: &str);
+
+    /// Called when a page finished but its output looks doubtful (e.g. a
+    /// suspiciously short transcription). Unlike `on_error`, the run carries
+    /// on as normal. Ignored by default.
+    fn on_warning(&self, _page_num: u32, _message: &str) {}
+
+    /// Called with each chunk of a page transcription as it streams in
+    /// (only with `stream_text`). Ignored by default.
+    fn on_partial_text(&self, _page_num: u32, _chunk: &str) {}
 }
 
 /// A no-op progress reporter that discards all events.
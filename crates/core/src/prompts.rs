@@ -1,4 +1,5 @@
-use crate::config::Language;
+use crate::config::{Language, ProcessingConfig};
+use std::borrow::Cow;
 
 /// Thai prompt for full-page render (Strategy A).
 pub const TH_FULL_PAGE: &str = "\
@@ -20,6 +21,22 @@ pub const TH_SINGLE_IMAGE: &str = "\
 - คำแนะนำที่เป็นภาพ\n\
 หากมีข้อความในภาพให้คัดลอกออกมาด้วย ตอบเป็นภาษาไทยในรูปแบบย่อหน้าสั้นๆ";
 
+/// Thai prompt for individual image description with the surrounding page text.
+pub const TH_SINGLE_IMAGE_WITH_CONTEXT: &str = "\
+ภาพนี้มาจากคู่มือการใช้งานอุปกรณ์มือถือภาษาไทย\n\
+ด้านล่างนี้คือข้อความบนหน้าเดียวกับภาพ ใช้เพื่อเข้าใจว่าภาพนี้ประกอบเนื้อหาส่วนใด\n\
+\n\
+--- ข้อความบนหน้า ---\n\
+{context_text}\n\
+--- สิ้นสุดข้อความ ---\n\
+\n\
+กรุณาอธิบายสิ่งที่เห็นในภาพอย่างละเอียดเป็นภาษาไทย และระบุว่าภาพเกี่ยวข้องกับขั้นตอนหรือหัวข้อใดในข้อความ:\n\
+- ภาพหน้าจอ UI หรือเมนู\n\
+- ไดอะแกรมหรือแผนภาพ\n\
+- ป้ายกำกับปุ่ม ลูกศร หรือตัวเลขขั้นตอน\n\
+- คำแนะนำที่เป็นภาพ\n\
+หากมีข้อความในภาพให้คัดลอกออกมาด้วย ห้ามคัดลอกข้อความบนหน้าซ้ำ ตอบเป็นภาษาไทยในรูปแบบย่อหน้าสั้นๆ";
+
 /// English prompt for full-page render (Strategy A).
 pub const EN_FULL_PAGE: &str = "\
 This page is from a device manual. \
@@ -36,6 +53,21 @@ button labels, arrows, step indicators, or visual instructions. \
 If there is text in the image, transcribe it. \
 Be specific and technical. Output as a short paragraph.";
 
+/// English prompt for individual image description with the surrounding page text.
+pub const EN_SINGLE_IMAGE_WITH_CONTEXT: &str = "\
+This image is from a device manual. \
+Below is the text on the same page, to help you understand what the image illustrates.\n\
+\n\
+--- Page text ---\n\
+{context_text}\n\
+--- End page text ---\n\
+\n\
+Describe what you see in detail: UI screenshots, diagrams, \
+button labels, arrows, step indicators, or visual instructions, \
+and say which step or topic of the page text the image relates to. \
+If there is text in the image, transcribe it; do not repeat the page text. \
+Be specific and technical. Output as a short paragraph.";
+
 /// Thai prompt for table extraction (full-page content + table formatting).
 pub const TH_TABLE_EXTRACTION: &str = "\
 หน้านี้มาจากเอกสาร PDF ภาษาไทยและมีตารางอยู่ด้วย\n\
@@ -124,32 +156,184 @@ Rules:\n\
 5. Mark unclear text as [unclear]\n\
 6. Output clean Markdown only — no commentary or explanation";
 
+/// Thai high-quality prompt for a batch of page images in one request.
+pub const TH_HIGH_QUALITY_BATCH: &str = "\
+คุณเป็นผู้เชี่ยวชาญด้าน OCR ภาษาไทย ภาพที่แนบมามี {page_count} หน้าเรียงตามลำดับ กรุณาถอดข้อความจากทุกหน้าอย่างละเอียดและแม่นยำที่สุด\n\
+\n\
+รูปแบบคำตอบ: ขึ้นต้นแต่ละหน้าด้วยบรรทัดคั่น === PAGE n === (n คือลำดับภาพ เริ่มจาก 1) แล้วตามด้วยเนื้อหาของหน้านั้น ต้องมีครบทั้ง {page_count} หน้า\n\
+\n\
+กฎที่ต้องปฏิบัติตาม:\n\
+1. คัดลอกข้อความทุกตัวอักษรตามที่ปรากฏในภาพ รวมถึงวรรณยุกต์ สระ และตัวเลขทั้งหมด\n\
+2. รักษาโครงสร้างเอกสาร: หัวข้อใช้ #/##/### ตามลำดับชั้น, รายการใช้ - หรือตัวเลข, ย่อหน้าคั่นด้วยบรรทัดว่าง\n\
+3. ตารางให้แปลงเป็น Markdown Table พร้อมหัวคอลัมน์ให้ครบถ้วน\n\
+4. ภาพ ไดอะแกรม หรือภาพหน้าจอ ให้อธิบายรายละเอียดเป็นภาษาไทย\n\
+5. ข้อความที่อ่านไม่ชัดให้ใส่ [ไม่ชัดเจน]\n\
+6. ห้ามแปลภาษา คงภาษาไทยไว้ทั้งหมด\n\
+7. ตอบเฉพาะเนื้อหา Markdown และบรรทัดคั่นหน้าเท่านั้น ห้ามใส่คำอธิบายเพิ่มเติม";
+
+/// English high-quality prompt for a batch of page images in one request.
+pub const EN_HIGH_QUALITY_BATCH: &str = "\
+You are an expert document OCR system. The attached {page_count} images are consecutive pages. Transcribe every page with maximum accuracy.\n\
+\n\
+Output format: start each page with a separator line === PAGE n === (n is the image position, starting at 1), followed by that page's content. All {page_count} pages must be present.\n\
+\n\
+Rules:\n\
+1. Transcribe every character exactly as shown in the image, including numbers, symbols, and punctuation\n\
+2. Preserve document structure: headings as #/##/###, lists as - or numbered, paragraphs separated by blank lines\n\
+3. Convert tables to Markdown tables with complete column headers\n\
+4. Describe images, diagrams, or screenshots in detail\n\
+5. Mark unclear text as [unclear]\n\
+6. Output clean Markdown and page separators only — no commentary or explanation";
+
+// --- Other languages ---
+//
+// Lao, Khmer and Vietnamese use the English prompts followed by a note that
+// names the document language, so text is kept and images are described in it.
+
+/// Language note for Lao documents.
+pub const LO_LANGUAGE_NOTE: &str = "\n\n\
+The document is in Lao (ພາສາລາວ). Transcribe Lao text exactly as shown, including all vowels \
+and tone marks, and write every description in Lao. Do not translate.";
+
+/// Language note for Khmer documents.
+pub const KM_LANGUAGE_NOTE: &str = "\n\n\
+The document is in Khmer (ភាសាខ្មែរ). Transcribe Khmer text exactly as shown, including all \
+subscript consonants and vowel signs, and write every description in Khmer. Do not translate.";
+
+/// Language note for Vietnamese documents.
+pub const VI_LANGUAGE_NOTE: &str = "\n\n\
+The document is in Vietnamese (Tiếng Việt). Transcribe Vietnamese text exactly as shown, \
+including all diacritics and tone marks, and write every description in Vietnamese. \
+Do not translate.";
+
 /// A set of prompts for a specific language.
 #[derive(Debug, Clone)]
 pub struct Prompts {
-    pub full_page: &'static str,
-    pub single_image: &'static str,
-    pub table_extraction: &'static str,
-    pub high_quality: &'static str,
-    pub high_quality_with_hint: &'static str,
+    pub full_page: Cow<'static, str>,
+    pub single_image: Cow<'static, str>,
+    pub single_image_with_context: Cow<'static, str>,
+    pub table_extraction: Cow<'static, str>,
+    pub high_quality: Cow<'static, str>,
+    pub high_quality_with_hint: Cow<'static, str>,
+    pub high_quality_batch: Cow<'static, str>,
 }
 
 /// Get the prompt set for the given language.
 pub fn get_prompts(lang: Language) -> Prompts {
-    match lang {
-        Language::Th => Prompts {
-            full_page: TH_FULL_PAGE,
-            single_image: TH_SINGLE_IMAGE,
-            table_extraction: TH_TABLE_EXTRACTION,
-            high_quality: TH_HIGH_QUALITY,
-            high_quality_with_hint: TH_HIGH_QUALITY_WITH_HINT,
-        },
-        Language::En => Prompts {
-            full_page: EN_FULL_PAGE,
-            single_image: EN_SINGLE_IMAGE,
-            table_extraction: EN_TABLE_EXTRACTION,
-            high_quality: EN_HIGH_QUALITY,
-            high_quality_with_hint: EN_HIGH_QUALITY_WITH_HINT,
-        },
+    let (
+        full_page,
+        single_image,
+        single_image_with_context,
+        table_extraction,
+        high_quality,
+        high_quality_with_hint,
+        high_quality_batch,
+    ) = match lang {
+        Language::Th => (
+            TH_FULL_PAGE,
+            TH_SINGLE_IMAGE,
+            TH_SINGLE_IMAGE_WITH_CONTEXT,
+            TH_TABLE_EXTRACTION,
+            TH_HIGH_QUALITY,
+            TH_HIGH_QUALITY_WITH_HINT,
+            TH_HIGH_QUALITY_BATCH,
+        ),
+        Language::En | Language::Lo | Language::Km | Language::Vi => (
+            EN_FULL_PAGE,
+            EN_SINGLE_IMAGE,
+            EN_SINGLE_IMAGE_WITH_CONTEXT,
+            EN_TABLE_EXTRACTION,
+            EN_HIGH_QUALITY,
+            EN_HIGH_QUALITY_WITH_HINT,
+            EN_HIGH_QUALITY_BATCH,
+        ),
+    };
+    let note = match lang {
+        Language::Th | Language::En => "",
+        Language::Lo => LO_LANGUAGE_NOTE,
+        Language::Km => KM_LANGUAGE_NOTE,
+        Language::Vi => VI_LANGUAGE_NOTE,
+    };
+    let prompt = |base: &'static str| -> Cow<'static, str> {
+        if note.is_empty() {
+            base.into()
+        } else {
+            format!("{base}{note}").into()
+        }
+    };
+    Prompts {
+        full_page: prompt(full_page),
+        single_image: prompt(single_image),
+        single_image_with_context: prompt(single_image_with_context),
+        table_extraction: prompt(table_extraction),
+        high_quality: prompt(high_quality),
+        high_quality_with_hint: prompt(high_quality_with_hint),
+        high_quality_batch: prompt(high_quality_batch),
+    }
+}
+
+/// Get the prompt set for a run: the language defaults, with any custom
+/// prompts from the config in their place.
+///
+/// A custom full-page prompt also replaces the high-quality prompts (not the
+/// multi-page batch one). Placeholders are optional: `{hint_text}` receives
+/// the PDF text in high-quality mode and `{context_text}` the page text when
+/// `image_context` is on; they are removed where there is nothing to fill in.
+pub fn prompts_for(config: &ProcessingConfig) -> Prompts {
+    let mut prompts = get_prompts(config.language);
+    if let Some(custom) = non_empty(&config.custom_full_page) {
+        let plain = custom.replace("{hint_text}", "");
+        prompts.full_page = plain.clone().into();
+        prompts.high_quality = plain.into();
+        prompts.high_quality_with_hint = custom.to_string().into();
+    }
+    if let Some(custom) = non_empty(&config.custom_single_image) {
+        prompts.single_image = custom.replace("{context_text}", "").into();
+        prompts.single_image_with_context = custom.to_string().into();
+    }
+    if let Some(custom) = non_empty(&config.custom_table) {
+        prompts.table_extraction = custom.to_string().into();
+    }
+    prompts
+}
+
+fn non_empty(prompt: &Option<String>) -> Option<&str> {
+    prompt.as_deref().filter(|p| !p.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_prompts_adds_language_note() {
+        assert_eq!(get_prompts(Language::En).full_page, EN_FULL_PAGE);
+        assert_eq!(get_prompts(Language::Th).high_quality, TH_HIGH_QUALITY);
+
+        let prompts = get_prompts(Language::Km);
+        assert!(prompts.full_page.starts_with(EN_FULL_PAGE));
+        assert!(prompts.full_page.ends_with(KM_LANGUAGE_NOTE));
+        assert!(prompts.high_quality_with_hint.contains("{hint_text}"));
+    }
+
+    #[test]
+    fn test_prompts_for_prefers_custom_prompts() {
+        let config = ProcessingConfig {
+            language: Language::En,
+            custom_full_page: Some("Transcribe this contract page.\n{hint_text}".to_string()),
+            custom_table: Some(" ".to_string()),
+            ..Default::default()
+        };
+        let prompts = prompts_for(&config);
+        assert_eq!(prompts.full_page, "Transcribe this contract page.\n");
+        assert_eq!(
+            prompts
+                .high_quality_with_hint
+                .replace("{hint_text}", "clause 4"),
+            "Transcribe this contract page.\nclause 4"
+        );
+        // Blank overrides keep the defaults
+        assert_eq!(prompts.table_extraction, EN_TABLE_EXTRACTION);
+        assert_eq!(prompts.single_image, EN_SINGLE_IMAGE);
     }
 }
@@ -0,0 +1,288 @@
+use super::{AskResult, VisionProvider};
+use crate::config::ImageEncoding;
+use crate::error::{CoreError, CoreResult};
+use futures::stream::{self, BoxStream, StreamExt};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Token bucket allowing `per_minute` requests per minute, with bursts of up
+/// to one second's worth of requests.
+///
+/// Callers that find the bucket empty reserve the next free slot and sleep
+/// until then, so waiting requests go out evenly spaced in arrival order.
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Duration,
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    /// Tokens left; negative when requests are waiting for future tokens.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32) -> Self {
+        let per_minute = per_minute.max(1);
+        let capacity = f64::from((per_minute / 60).max(1));
+        Self {
+            interval: Duration::from_secs(60) / per_minute,
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until a request may be sent.
+    pub async fn acquire(&self) {
+        let wait = self.reserve(Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token at `now`, returning how long to wait for it.
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let elapsed = now.saturating_duration_since(state.refilled_at);
+        state.tokens =
+            (state.tokens + elapsed.as_secs_f64() / self.interval.as_secs_f64()).min(self.capacity);
+        state.refilled_at = now;
+        state.tokens -= 1.0;
+        if state.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            self.interval.mul_f64(-state.tokens)
+        }
+    }
+}
+
+/// Limits on the vision requests of one run, shared by all its pages and
+/// images (`max_concurrent_requests`, `requests_per_minute`,
+/// `page_timeout_secs`).
+#[derive(Debug, Default)]
+pub struct RequestLimits {
+    in_flight: Option<Semaphore>,
+    rate: Option<RateLimiter>,
+    timeout_secs: Option<u64>,
+}
+
+impl RequestLimits {
+    pub fn new(
+        max_concurrent: Option<usize>,
+        per_minute: Option<u32>,
+        timeout_secs: Option<u64>,
+    ) -> Self {
+        Self {
+            in_flight: max_concurrent.map(|n| Semaphore::new(n.max(1))),
+            rate: per_minute.map(RateLimiter::new),
+            timeout_secs,
+        }
+    }
+
+    /// No limit is set.
+    pub fn is_unlimited(&self) -> bool {
+        self.in_flight.is_none() && self.rate.is_none() && self.timeout_secs.is_none()
+    }
+
+    /// Wait for a request slot; the request may run while the permit is held.
+    async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let permit = match &self.in_flight {
+            Some(semaphore) => Some(semaphore.acquire().await.unwrap()),
+            None => None,
+        };
+        if let Some(rate) = &self.rate {
+            rate.acquire().await;
+        }
+        permit
+    }
+}
+
+/// Await a vision request, failing it after `timeout_secs` (`page_timeout_secs`)
+/// so a provider that never answers cannot hang its page.
+pub(crate) async fn with_timeout<T>(
+    timeout_secs: Option<u64>,
+    request: impl Future<Output = CoreResult<T>>,
+) -> CoreResult<T> {
+    let Some(secs) = timeout_secs else {
+        return request.await;
+    };
+    tokio::time::timeout(Duration::from_secs(secs), request)
+        .await
+        .map_err(|_| CoreError::Provider(format!("No response from the provider within {secs}s")))?
+}
+
+/// A provider whose calls go through shared [`RequestLimits`].
+///
+/// Limits apply per call: retries inside a call reuse its slot. The timeout
+/// starts once the slot is granted, so time spent queued behind the
+/// concurrency or rate limit never counts against a request.
+pub struct LimitedProvider {
+    inner: Arc<dyn VisionProvider>,
+    limits: Arc<RequestLimits>,
+}
+
+impl LimitedProvider {
+    /// Wrap `inner`; returns it unchanged when `limits` sets no limit.
+    pub fn wrap(
+        inner: Arc<dyn VisionProvider>,
+        limits: &Arc<RequestLimits>,
+    ) -> Arc<dyn VisionProvider> {
+        if limits.is_unlimited() {
+            return inner;
+        }
+        Arc::new(Self {
+            inner,
+            limits: limits.clone(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl VisionProvider for LimitedProvider {
+    async fn ask(
+        &self,
+        image_b64: &str,
+        encoding: ImageEncoding,
+        prompt: &str,
+        retries: u32,
+    ) -> CoreResult<AskResult> {
+        let _permit = self.limits.acquire().await;
+        let ask = self.inner.ask(image_b64, encoding, prompt, retries);
+        with_timeout(self.limits.timeout_secs, ask).await
+    }
+
+    async fn ask_multi(
+        &self,
+        images_b64: &[String],
+        encoding: ImageEncoding,
+        prompt: &str,
+        retries: u32,
+    ) -> CoreResult<AskResult> {
+        let _permit = self.limits.acquire().await;
+        let ask = self.inner.ask_multi(images_b64, encoding, prompt, retries);
+        with_timeout(self.limits.timeout_secs, ask).await
+    }
+
+    /// The slot is held until the stream is dropped. The timeout applies to
+    /// the wait for each chunk; the stream ends after one times out.
+    fn ask_stream<'a>(
+        &'a self,
+        image_b64: &'a str,
+        encoding: ImageEncoding,
+        prompt: &'a str,
+        retries: u32,
+    ) -> BoxStream<'a, CoreResult<AskResult>> {
+        let timeout_secs = self.limits.timeout_secs;
+        stream::once(self.limits.acquire())
+            .flat_map(move |permit| {
+                let chunks = self.inner.ask_stream(image_b64, encoding, prompt, retries);
+                stream::unfold(Some((chunks, permit)), move |state| async move {
+                    let (mut chunks, permit) = state?;
+                    let next = async { chunks.next().await.transpose() };
+                    match with_timeout(timeout_secs, next).await {
+                        Ok(Some(chunk)) => Some((Ok(chunk), Some((chunks, permit)))),
+                        Ok(None) => None,
+                        Err(e) => Some((Err(e), None)),
+                    }
+                })
+            })
+            .boxed()
+    }
+
+    async fn check(&self) -> CoreResult<()> {
+        self.inner.check().await
+    }
+
+    fn provider_name(&self) -> &str {
+        self.inner.provider_name()
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_spaces_requests_after_burst() {
+        // 120/min: bursts of 2, then one request every 500ms
+        let limiter = RateLimiter::new(120);
+        let start = limiter.state.lock().unwrap().refilled_at;
+        assert_eq!(limiter.reserve(start), Duration::ZERO);
+        assert_eq!(limiter.reserve(start), Duration::ZERO);
+        assert_eq!(limiter.reserve(start), Duration::from_millis(500));
+        assert_eq!(limiter.reserve(start), Duration::from_millis(1000));
+
+        // Two seconds later the waiting requests have gone out and the bucket refilled
+        let later = start + Duration::from_secs(2);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+        assert_eq!(limiter.reserve(later), Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_with_timeout_fails_hung_request() {
+        let hung = std::future::pending::<CoreResult<()>>();
+        let err = with_timeout(Some(0), hung).await.unwrap_err();
+        assert!(err.to_string().contains("within 0s"), "{err}");
+
+        let answered = async { Ok::<_, CoreError>(7) };
+        assert_eq!(with_timeout(None, answered).await.unwrap(), 7);
+    }
+
+    /// Answers every request after half a second.
+    struct SlowProvider;
+
+    #[async_trait::async_trait]
+    impl VisionProvider for SlowProvider {
+        async fn ask(
+            &self,
+            _image_b64: &str,
+            _encoding: ImageEncoding,
+            _prompt: &str,
+            _retries: u32,
+        ) -> CoreResult<AskResult> {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            Ok(AskResult {
+                text: "หน้า".to_string(),
+                ..Default::default()
+            })
+        }
+
+        async fn check(&self) -> CoreResult<()> {
+            Ok(())
+        }
+
+        fn provider_name(&self) -> &str {
+            "mock"
+        }
+
+        fn model_name(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timeout_excludes_rate_limit_wait() {
+        // 1 request per minute: the third page waits two minutes for its turn
+        let limits = Arc::new(RequestLimits::new(None, Some(1), Some(1)));
+        let provider = LimitedProvider::wrap(Arc::new(SlowProvider), &limits);
+        let started = tokio::time::Instant::now();
+
+        let pages = (0..3).map(|_| provider.ask("", ImageEncoding::Png, "", 1));
+        for page in futures::future::join_all(pages).await {
+            assert_eq!(page.unwrap().text, "หน้า");
+        }
+        assert!(started.elapsed() >= Duration::from_secs(120));
+    }
+}
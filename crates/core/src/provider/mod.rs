@@ -1,14 +1,124 @@
+use crate::config::ImageEncoding;
 use crate::error::{CoreError, CoreResult};
-use genai::chat::{ChatMessage, ChatRequest, ContentPart, MessageContent};
-use genai::Client;
+use crate::http::{send_with_retry, HttpConfig};
+use crate::redact::redact_secrets;
+use futures::stream::{self, BoxStream, StreamExt};
+use genai::adapter::AdapterKind;
+use genai::chat::{
+    ChatMessage, ChatOptions, ChatRequest, ChatStreamEvent, ContentPart, MessageContent,
+};
+use genai::resolver::{AuthData, Endpoint, ServiceTargetResolver};
+use genai::{Client, ModelIden, ServiceTarget};
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::Instrument;
+
+mod limit;
+mod policy;
+
+pub use limit::{LimitedProvider, RateLimiter, RequestLimits};
+pub use policy::ModelPolicy;
+
+/// Text returned by a vision request, with the token usage the provider
+/// reported (`None` when it reports none, e.g. some Ollama models).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AskResult {
+    pub text: String,
+    pub input_tokens: Option<u32>,
+    pub output_tokens: Option<u32>,
+}
+
+impl AskResult {
+    /// Combine the results of several requests (e.g. page quadrants):
+    /// texts joined by `separator`, token counts summed.
+    pub fn join(results: Vec<AskResult>, separator: &str) -> Self {
+        let sum = |tokens: fn(&AskResult) -> Option<u32>| {
+            results.iter().filter_map(tokens).reduce(|a, b| a + b)
+        };
+        let input_tokens = sum(|r| r.input_tokens);
+        let output_tokens = sum(|r| r.output_tokens);
+        let text = results
+            .into_iter()
+            .map(|r| r.text)
+            .collect::<Vec<_>>()
+            .join(separator);
+        Self {
+            text,
+            input_tokens,
+            output_tokens,
+        }
+    }
+}
+
+impl From<genai::chat::ChatResponse> for AskResult {
+    fn from(response: genai::chat::ChatResponse) -> Self {
+        Self {
+            text: response.first_text().unwrap_or_default().to_string(),
+            ..Self::from(response.usage)
+        }
+    }
+}
+
+/// Token counts only, e.g. the usage reported at the end of a stream.
+impl From<genai::chat::Usage> for AskResult {
+    fn from(usage: genai::chat::Usage) -> Self {
+        let tokens = |count: Option<i32>| count.and_then(|c| u32::try_from(c).ok());
+        Self {
+            text: String::new(),
+            input_tokens: tokens(usage.prompt_tokens),
+            output_tokens: tokens(usage.completion_tokens),
+        }
+    }
+}
 
 /// Trait for vision LLM providers that can describe images.
 #[async_trait::async_trait]
 pub trait VisionProvider: Send + Sync {
-    /// Send a base64-encoded image to the vision model with a prompt.
+    /// Send a base64-encoded image, in `encoding`, to the vision model with a prompt.
     ///
     /// Returns the text description/transcription from the model.
-    async fn ask(&self, image_b64: &str, prompt: &str, retries: u32) -> CoreResult<String>;
+    async fn ask(
+        &self,
+        image_b64: &str,
+        encoding: ImageEncoding,
+        prompt: &str,
+        retries: u32,
+    ) -> CoreResult<AskResult>;
+
+    /// Send several base64-encoded images, all in `encoding`, in a single
+    /// request (e.g. a batch of pages).
+    ///
+    /// The default only handles a single image; providers that accept
+    /// multiple images per request override this.
+    async fn ask_multi(
+        &self,
+        images_b64: &[String],
+        encoding: ImageEncoding,
+        prompt: &str,
+        retries: u32,
+    ) -> CoreResult<AskResult> {
+        match images_b64 {
+            [single] => self.ask(single, encoding, prompt, retries).await,
+            _ => Err(CoreError::Provider(format!(
+                "{} does not support multi-image requests",
+                self.provider_name()
+            ))),
+        }
+    }
+
+    /// Like [`ask`](Self::ask), but yields the text in chunks as it is generated.
+    ///
+    /// Token usage, when the provider reports it, comes on its own item with
+    /// empty text. The default runs `ask` and yields its result as a single item.
+    fn ask_stream<'a>(
+        &'a self,
+        image_b64: &'a str,
+        encoding: ImageEncoding,
+        prompt: &'a str,
+        retries: u32,
+    ) -> BoxStream<'a, CoreResult<AskResult>> {
+        stream::once(self.ask(image_b64, encoding, prompt, retries)).boxed()
+    }
 
     /// Verify that this provider is available and correctly configured.
     async fn check(&self) -> CoreResult<()>;
@@ -37,6 +147,12 @@ pub enum ProviderKind {
         api_key_env: &'static str,
         env_hint: &'static str,
     },
+    /// Any OpenAI-compatible API (vLLM, LM Studio, LiteLLM) at the base URL in
+    /// `base_url_env`, with an optional API key — checked via `{base}/models`.
+    OpenAiCompat {
+        base_url_env: &'static str,
+        api_key_env: &'static str,
+    },
 }
 
 /// Static metadata for a registered provider.
@@ -54,6 +170,10 @@ pub struct ProviderMeta {
     pub models: &'static [&'static str],
     /// Approximate cost per image in USD (0.0 for free/local).
     pub cost_per_image_usd: f64,
+    /// Pages to process concurrently when the user does not set it.
+    pub recommended_concurrency: usize,
+    /// Typical requests-per-minute limit of the API (`None` = no limit, e.g. local).
+    pub rate_limit_rpm: Option<u32>,
 }
 
 /// All registered providers.
@@ -68,6 +188,8 @@ pub static PROVIDERS: &[ProviderMeta] = &[
         default_model: "qwen2.5vl",
         models: &["qwen2.5vl", "qwen2.5vl:72b", "llama3.2-vision", "minicpm-v"],
         cost_per_image_usd: 0.0,
+        recommended_concurrency: 1,
+        rate_limit_rpm: None,
     },
     ProviderMeta {
         name: "openai",
@@ -79,6 +201,8 @@ pub static PROVIDERS: &[ProviderMeta] = &[
         default_model: "gpt-4o",
         models: &["gpt-4o", "gpt-4o-mini"],
         cost_per_image_usd: 0.01,
+        recommended_concurrency: 8,
+        rate_limit_rpm: Some(500),
     },
     ProviderMeta {
         name: "claude",
@@ -90,6 +214,8 @@ pub static PROVIDERS: &[ProviderMeta] = &[
         default_model: "claude-sonnet-4-6",
         models: &["claude-sonnet-4-6", "claude-haiku-4-5-20251001"],
         cost_per_image_usd: 0.01,
+        recommended_concurrency: 4,
+        rate_limit_rpm: Some(50),
     },
     ProviderMeta {
         name: "gemini",
@@ -101,6 +227,8 @@ pub static PROVIDERS: &[ProviderMeta] = &[
         default_model: "gemini-2.0-flash",
         models: &["gemini-2.0-flash", "gemini-2.5-flash", "gemini-2.5-pro"],
         cost_per_image_usd: 0.0025,
+        recommended_concurrency: 4,
+        rate_limit_rpm: Some(60),
     },
     ProviderMeta {
         name: "xai",
@@ -112,6 +240,8 @@ pub static PROVIDERS: &[ProviderMeta] = &[
         default_model: "grok-2-vision",
         models: &["grok-2-vision"],
         cost_per_image_usd: 0.005,
+        recommended_concurrency: 4,
+        rate_limit_rpm: Some(60),
     },
     ProviderMeta {
         name: "groq",
@@ -126,6 +256,24 @@ pub static PROVIDERS: &[ProviderMeta] = &[
             "groq::llama-3.2-11b-vision-preview",
         ],
         cost_per_image_usd: 0.002,
+        recommended_concurrency: 2,
+        rate_limit_rpm: Some(30),
+    },
+    ProviderMeta {
+        name: "openai-compat",
+        display_name: "OpenAI-compatible (Custom)",
+        kind: ProviderKind::OpenAiCompat {
+            base_url_env: "OPENAI_COMPAT_BASE_URL",
+            api_key_env: "OPENAI_COMPAT_API_KEY",
+        },
+        default_model: "Qwen/Qwen2.5-VL-7B-Instruct",
+        models: &[
+            "Qwen/Qwen2.5-VL-7B-Instruct",
+            "Qwen/Qwen2.5-VL-72B-Instruct",
+        ],
+        cost_per_image_usd: 0.0,
+        recommended_concurrency: 4,
+        rate_limit_rpm: None,
     },
 ];
 
@@ -146,10 +294,29 @@ pub fn default_model(provider_name: &str) -> &'static str {
         .unwrap_or("qwen2.5vl")
 }
 
+/// Recommended page concurrency for a given provider name.
+pub fn recommended_concurrency(provider_name: &str) -> usize {
+    find_provider(provider_name)
+        .map(|p| p.recommended_concurrency)
+        .unwrap_or(4)
+}
+
 /// Factory: create a provider by name and model.
 pub fn create_provider(
     provider_name: &str,
     model: &str,
+) -> CoreResult<Box<dyn VisionProvider>> {
+    create_provider_with_examples(provider_name, model, &[])
+}
+
+/// Like [`create_provider`], with few-shot `(example image, expected output)`
+/// pairs sent ahead of every single-image request.
+///
+/// Example images are loaded and encoded once here, not per call.
+pub fn create_provider_with_examples(
+    provider_name: &str,
+    model: &str,
+    few_shot: &[(PathBuf, String)],
 ) -> CoreResult<Box<dyn VisionProvider>> {
     let meta = find_provider(provider_name).ok_or_else(|| {
         let names: Vec<&str> = PROVIDERS.iter().map(|p| p.name).collect();
@@ -159,64 +326,384 @@ pub fn create_provider(
         ))
     })?;
 
+    // Org model policy is enforced here so CLI and server both honor it
+    ModelPolicy::from_env()?.check(provider_name, model)?;
+
+    let few_shot = few_shot
+        .iter()
+        .map(|(path, expected)| {
+            let (image_b64, _) = crate::pdf::load_image_as_png(path, false, ImageEncoding::Png)?;
+            Ok(FewShotExample {
+                image_b64,
+                expected: expected.clone(),
+            })
+        })
+        .collect::<CoreResult<Vec<_>>>()?;
+
     Ok(Box::new(GenaiProvider {
         meta,
         model: model.to_string(),
-        client: Client::default(),
+        client: client_for(meta)?,
+        few_shot,
     }))
 }
 
+/// genai client for a provider: the default one, or for OpenAI-compatible
+/// endpoints one that sends every request to the configured base URL.
+fn client_for(meta: &ProviderMeta) -> CoreResult<Client> {
+    let ProviderKind::OpenAiCompat {
+        base_url_env,
+        api_key_env,
+    } = meta.kind
+    else {
+        return Ok(Client::default());
+    };
+    // genai joins `chat/completions` onto the endpoint, which needs a trailing slash
+    let endpoint = format!("{}/", openai_compat_base_url(base_url_env)?);
+    let api_key = std::env::var(api_key_env).unwrap_or_default();
+
+    let resolver = ServiceTargetResolver::from_resolver_fn(
+        move |target: ServiceTarget| -> Result<ServiceTarget, genai::resolver::Error> {
+            Ok(ServiceTarget {
+                endpoint: Endpoint::from_owned(endpoint.clone()),
+                auth: AuthData::from_single(api_key.clone()),
+                model: ModelIden::new(AdapterKind::OpenAI, target.model.model_name),
+            })
+        },
+    );
+    Ok(Client::builder()
+        .with_service_target_resolver(resolver)
+        .build())
+}
+
+/// Base URL of an OpenAI-compatible endpoint (e.g. `http://localhost:8000/v1`),
+/// without a trailing slash.
+fn openai_compat_base_url(base_url_env: &str) -> CoreResult<String> {
+    let base = std::env::var(base_url_env).unwrap_or_default();
+    let base = base.trim().trim_end_matches('/');
+    if base.is_empty() {
+        return Err(CoreError::Config(format!(
+            "Missing {base_url_env} environment variable.\n\
+             Run: export {base_url_env}='http://localhost:8000/v1'"
+        )));
+    }
+    Ok(base.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Retry loop shared by providers
+// ---------------------------------------------------------------------------
+
+/// Base delay between LLM retries, doubled per attempt.
+const RETRY_BASE_DELAY_MS: u64 = 1000;
+
+/// Longest `Retry-After` we honor; longer requests fall back to the backoff.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+/// Delay before retry number `attempt + 1`.
+///
+/// A provider's `Retry-After` is waited out in full; otherwise the delay is
+/// `base_delay_ms * 2^attempt`. Either way up to ±20% jitter is added (only
+/// upwards for `Retry-After`) so concurrent page tasks do not retry in lockstep.
+fn retry_delay(base_delay_ms: u64, attempt: u32, retry_after: Option<Duration>) -> Duration {
+    let jitter = random_unit();
+    match retry_after.filter(|d| *d <= MAX_RETRY_AFTER) {
+        Some(after) => after.mul_f64(1.0 + 0.2 * jitter),
+        None => Duration::from_millis(base_delay_ms.saturating_mul(2u64.saturating_pow(attempt)))
+            .mul_f64(0.8 + 0.4 * jitter),
+    }
+}
+
+/// A number in `[0, 1)` that differs between calls (good enough for jitter).
+fn random_unit() -> f64 {
+    use std::hash::BuildHasher;
+    let bits = std::collections::hash_map::RandomState::new().hash_one(std::time::Instant::now());
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Parse the wait a 429 response asks for: `retry-after-ms` (OpenAI) or
+/// `retry-after` in seconds. HTTP-date values are not supported.
+fn parse_retry_after(retry_after_ms: Option<&str>, retry_after: Option<&str>) -> Option<Duration> {
+    let millis = retry_after_ms
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .map(|ms| ms / 1000.0);
+    let secs = retry_after.and_then(|v| v.trim().parse::<f64>().ok());
+    millis
+        .or(secs)
+        .filter(|s| s.is_finite() && *s >= 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+/// Map a genai error, keeping the `Retry-After` of a 429 response.
+fn chat_error(e: genai::Error) -> CoreError {
+    let message = redact_secrets(&e.to_string());
+    let rate_limited = match &e {
+        genai::Error::WebModelCall {
+            webc_error:
+                genai::webc::Error::ResponseFailedStatus {
+                    status, headers, ..
+                },
+            ..
+        } if status.as_u16() == 429 => {
+            let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+            Some(parse_retry_after(
+                header("retry-after-ms"),
+                header("retry-after"),
+            ))
+        }
+        _ => None,
+    };
+    match rate_limited {
+        Some(retry_after) => CoreError::RateLimited {
+            message,
+            retry_after,
+        },
+        None => CoreError::Provider(message),
+    }
+}
+
+/// Call `attempt_fn` up to `retries` times with exponential backoff.
+///
+/// An empty or whitespace-only response counts as a failed attempt, so a model
+/// that silently returns nothing is retried and finally reported as an error
+/// instead of becoming an empty description.
+async fn retry_ask<F, Fut>(
+    display_name: &str,
+    retries: u32,
+    base_delay_ms: u64,
+    mut attempt_fn: F,
+) -> CoreResult<AskResult>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = CoreResult<AskResult>>,
+{
+    let retries = retries.max(1);
+    let mut last_error = String::new();
+
+    for attempt in 0..retries {
+        let mut retry_after = None;
+        match attempt_fn().await {
+            Ok(result) if !result.text.trim().is_empty() => {
+                return Ok(AskResult {
+                    text: result.text.trim().to_string(),
+                    ..result
+                });
+            }
+            Ok(_) => last_error = "model returned an empty response".to_string(),
+            Err(e) => {
+                retry_after = e.retry_after();
+                last_error = redact_secrets(&e.to_string());
+            }
+        }
+
+        if attempt < retries - 1 {
+            let delay = retry_delay(base_delay_ms, attempt, retry_after);
+            tracing::warn!(
+                "{} error (attempt {}/{}): {} — retrying in {}ms",
+                display_name,
+                attempt + 1,
+                retries,
+                last_error,
+                delay.as_millis()
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    Err(CoreError::Provider(format!(
+        "{display_name} failed after {retries} attempts: {last_error}"
+    )))
+}
+
 // ---------------------------------------------------------------------------
 // Unified genai-backed provider
 // ---------------------------------------------------------------------------
 
+/// An example image (PNG) with the output expected for it.
+struct FewShotExample {
+    image_b64: String,
+    expected: String,
+}
+
 /// Single VisionProvider implementation that handles all providers via genai.
 struct GenaiProvider {
     meta: &'static ProviderMeta,
     model: String,
     client: Client,
+    few_shot: Vec<FewShotExample>,
+}
+
+impl GenaiProvider {
+    /// Span around one vision call (all retries), exported as a child of the page span.
+    fn call_span(&self, images: usize) -> tracing::Span {
+        tracing::info_span!(
+            "provider_call",
+            provider = self.meta.display_name,
+            model = %self.model,
+            images
+        )
+    }
+
+    /// Messages for a single-image request: few-shot examples go first as
+    /// prior user/assistant turns.
+    fn image_messages(
+        &self,
+        image_b64: &str,
+        encoding: ImageEncoding,
+        prompt: &str,
+    ) -> Vec<ChatMessage> {
+        let image_part =
+            ContentPart::from_binary_base64(encoding.mime_type(), image_b64, None::<String>);
+
+        let mut messages = Vec::with_capacity(self.few_shot.len() * 2 + 1);
+        for example in &self.few_shot {
+            let example_image = ContentPart::from_binary_base64(
+                ImageEncoding::Png.mime_type(),
+                example.image_b64.as_str(),
+                None::<String>,
+            );
+            messages.push(ChatMessage::user(
+                MessageContent::from_text(prompt).append(example_image),
+            ));
+            messages.push(ChatMessage::assistant(example.expected.as_str()));
+        }
+        messages.push(ChatMessage::user(
+            MessageContent::from_text(prompt).append(image_part),
+        ));
+        messages
+    }
 }
 
 #[async_trait::async_trait]
 impl VisionProvider for GenaiProvider {
-    async fn ask(&self, image_b64: &str, prompt: &str, retries: u32) -> CoreResult<String> {
-        let mut last_error = String::new();
+    async fn ask(
+        &self,
+        image_b64: &str,
+        encoding: ImageEncoding,
+        prompt: &str,
+        retries: u32,
+    ) -> CoreResult<AskResult> {
+        let attempt = || async move {
+            let messages = self.image_messages(image_b64, encoding, prompt);
+            let request = ChatRequest::from_messages(messages);
 
-        for attempt in 0..retries {
-            let image_part =
-                ContentPart::from_binary_base64("image/png", image_b64, None::<String>);
+            let response = self
+                .client
+                .exec_chat(&self.model, request, None)
+                .await
+                .map_err(chat_error)?;
+            Ok(AskResult::from(response))
+        };
 
-            let message =
-                ChatMessage::user(MessageContent::from_text(prompt).append(image_part));
+        retry_ask(
+            self.meta.display_name,
+            retries,
+            RETRY_BASE_DELAY_MS,
+            attempt,
+        )
+        .instrument(self.call_span(1))
+        .await
+    }
 
-            let request = ChatRequest::from_messages(vec![message]);
+    async fn ask_multi(
+        &self,
+        images_b64: &[String],
+        encoding: ImageEncoding,
+        prompt: &str,
+        retries: u32,
+    ) -> CoreResult<AskResult> {
+        let attempt = || async move {
+            let mut content = MessageContent::from_text(prompt);
+            for image_b64 in images_b64 {
+                content = content.append(ContentPart::from_binary_base64(
+                    encoding.mime_type(),
+                    image_b64.as_str(),
+                    None::<String>,
+                ));
+            }
 
-            match self.client.exec_chat(&self.model, request, None).await {
-                Ok(response) => {
-                    let text = response.first_text().unwrap_or_default().to_string();
-                    return Ok(text.trim().to_string());
+            let request = ChatRequest::from_messages(vec![ChatMessage::user(content)]);
+
+            let response = self
+                .client
+                .exec_chat(&self.model, request, None)
+                .await
+                .map_err(chat_error)?;
+            Ok(AskResult::from(response))
+        };
+
+        retry_ask(
+            self.meta.display_name,
+            retries,
+            RETRY_BASE_DELAY_MS,
+            attempt,
+        )
+        .instrument(self.call_span(images_b64.len()))
+        .await
+    }
+
+    /// Streams via `exec_chat_stream`, with usage captured for the final
+    /// item. Only opening the stream is retried: chunks already handed out
+    /// can't be taken back, so a failure mid-stream ends it with an error.
+    fn ask_stream<'a>(
+        &'a self,
+        image_b64: &'a str,
+        encoding: ImageEncoding,
+        prompt: &'a str,
+        retries: u32,
+    ) -> BoxStream<'a, CoreResult<AskResult>> {
+        let open = async move {
+            let mut last_error = String::new();
+            let mut retry_after = None;
+            for attempt in 0..retries.max(1) {
+                if attempt > 0 {
+                    let delay = retry_delay(RETRY_BASE_DELAY_MS, attempt - 1, retry_after);
+                    tokio::time::sleep(delay).await;
                 }
-                Err(e) => {
-                    last_error = format!("{e}");
-                    if attempt < retries - 1 {
-                        tracing::warn!(
-                            "{} error (attempt {}/{}): {}",
-                            self.meta.display_name,
-                            attempt + 1,
-                            retries,
-                            e
-                        );
-                        let delay = std::time::Duration::from_millis(1000 * 2u64.pow(attempt));
-                        tokio::time::sleep(delay).await;
+                let messages = self.image_messages(image_b64, encoding, prompt);
+                let request = ChatRequest::from_messages(messages);
+                let options = ChatOptions::default().with_capture_usage(true);
+                let opened = self
+                    .client
+                    .exec_chat_stream(&self.model, request, Some(&options))
+                    .await;
+                match opened {
+                    Ok(response) => return Ok(response.stream),
+                    Err(e) => {
+                        let e = chat_error(e);
+                        retry_after = e.retry_after();
+                        last_error = e.to_string();
                     }
                 }
             }
-        }
+            Err(CoreError::Provider(format!(
+                "{} stream failed after {} attempts: {last_error}",
+                self.meta.display_name,
+                retries.max(1)
+            )))
+        };
 
-        Err(CoreError::Provider(format!(
-            "{} failed after {} attempts: {last_error}",
-            self.meta.display_name, retries
-        )))
+        stream::once(open.instrument(self.call_span(1)))
+            .map(|opened| match opened {
+                Ok(events) => events
+                    .filter_map(|event| async move {
+                        match event {
+                            Ok(ChatStreamEvent::Chunk(chunk)) => Some(Ok(AskResult {
+                                text: chunk.content,
+                                ..Default::default()
+                            })),
+                            Ok(ChatStreamEvent::End(end)) => {
+                                end.captured_usage.map(|usage| Ok(AskResult::from(usage)))
+                            }
+                            Ok(_) => None,
+                            Err(e) => Some(Err(chat_error(e))),
+                        }
+                    })
+                    .boxed(),
+                Err(e) => stream::once(async move { Err(e) }).boxed(),
+            })
+            .flatten()
+            .boxed()
     }
 
     async fn check(&self) -> CoreResult<()> {
@@ -235,19 +722,21 @@ impl VisionProvider for GenaiProvider {
                     .unwrap_or_else(|_| default_host.to_string());
                 let url = format!("{host}/api/tags");
 
-                let resp = reqwest::get(&url).await.map_err(|e| {
-                    CoreError::Provider(format!(
+                let http = HttpConfig::from_env();
+                let client = http.client()?;
+                let resp = send_with_retry(&http, || client.get(&url)).await.map_err(|e| {
+                    CoreError::Provider(redact_secrets(&format!(
                         "Cannot connect to {} at {host}: {e}\n\
                          Make sure Ollama is running: ollama serve",
                         self.meta.display_name
-                    ))
+                    )))
                 })?;
 
                 let body: serde_json::Value = resp.json().await.map_err(|e| {
-                    CoreError::Provider(format!(
+                    CoreError::Provider(redact_secrets(&format!(
                         "Invalid response from {}: {e}",
                         self.meta.display_name
-                    ))
+                    )))
                 })?;
 
                 let empty = vec![];
@@ -297,6 +786,76 @@ impl VisionProvider for GenaiProvider {
                 );
                 Ok(())
             }
+            ProviderKind::OpenAiCompat {
+                base_url_env,
+                api_key_env,
+            } => {
+                let base = openai_compat_base_url(base_url_env)?;
+                let url = format!("{base}/models");
+                tracing::info!(
+                    "Checking {} model '{}' at {base}...",
+                    self.meta.display_name,
+                    self.model
+                );
+
+                let api_key = std::env::var(api_key_env)
+                    .ok()
+                    .filter(|k| !k.trim().is_empty());
+                let http = HttpConfig::from_env();
+                let client = http.client()?;
+                let resp = send_with_retry(&http, || match &api_key {
+                    Some(key) => client.get(&url).bearer_auth(key),
+                    None => client.get(&url),
+                })
+                .await
+                .map_err(|e| {
+                    CoreError::Provider(redact_secrets(&format!(
+                        "Cannot connect to {} at {base}: {e}",
+                        self.meta.display_name
+                    )))
+                })?;
+                if !resp.status().is_success() {
+                    return Err(CoreError::Provider(format!(
+                        "{} returned HTTP {} for {url} (check {base_url_env} and {api_key_env})",
+                        self.meta.display_name,
+                        resp.status()
+                    )));
+                }
+
+                let body: serde_json::Value = resp.json().await.map_err(|e| {
+                    CoreError::Provider(redact_secrets(&format!(
+                        "Invalid response from {}: {e}",
+                        self.meta.display_name
+                    )))
+                })?;
+                let models = body["data"]
+                    .as_array()
+                    .map(|data| {
+                        data.iter()
+                            .filter_map(|m| m["id"].as_str())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+
+                if !models.contains(&self.model.as_str()) {
+                    return Err(CoreError::Provider(format!(
+                        "Model '{}' not served at {base}.\nAvailable: {}",
+                        self.model,
+                        if models.is_empty() {
+                            "none".to_string()
+                        } else {
+                            models.join(", ")
+                        }
+                    )));
+                }
+
+                tracing::info!(
+                    "{} model '{}' is ready.",
+                    self.meta.display_name,
+                    self.model
+                );
+                Ok(())
+            }
         }
     }
 
@@ -308,3 +867,150 @@ impl VisionProvider for GenaiProvider {
         &self.model
     }
 }
+
+// ---------------------------------------------------------------------------
+// Mock provider (benchmarks / tests)
+// ---------------------------------------------------------------------------
+
+/// Offline provider that returns a canned response without any network call.
+///
+/// Used by `jay-rag bench --providers mock` to measure pipeline overhead
+/// (rendering, extraction, I/O) independently of LLM latency.
+pub struct MockProvider {
+    response: String,
+}
+
+impl MockProvider {
+    /// Create a mock provider that answers every request with `response`.
+    pub fn new(response: impl Into<String>) -> Self {
+        Self {
+            response: response.into(),
+        }
+    }
+}
+
+impl Default for MockProvider {
+    fn default() -> Self {
+        Self::new("[mock] image description")
+    }
+}
+
+#[async_trait::async_trait]
+impl VisionProvider for MockProvider {
+    async fn ask(
+        &self,
+        _image_b64: &str,
+        _encoding: ImageEncoding,
+        _prompt: &str,
+        _retries: u32,
+    ) -> CoreResult<AskResult> {
+        Ok(AskResult {
+            text: self.response.clone(),
+            ..Default::default()
+        })
+    }
+
+    async fn check(&self) -> CoreResult<()> {
+        Ok(())
+    }
+
+    fn provider_name(&self) -> &str {
+        "mock"
+    }
+
+    fn model_name(&self) -> &str {
+        "mock"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_empty_response_is_retried_then_fails() {
+        let mock = MockProvider::new("   ");
+        let mut calls = 0;
+        let result = retry_ask("Mock", 3, 0, || {
+            calls += 1;
+            mock.ask("", ImageEncoding::Png, "", 1)
+        })
+        .await;
+
+        assert_eq!(calls, 3);
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("empty response"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_empty_response_recovers_on_retry() {
+        let empty = MockProvider::new("");
+        let ok = MockProvider::new(" คำอธิบายภาพ ");
+        let mut calls = 0;
+        let result = retry_ask("Mock", 3, 0, || {
+            calls += 1;
+            if calls == 1 {
+                empty.ask("", ImageEncoding::Png, "", 1)
+            } else {
+                ok.ask("", ImageEncoding::Png, "", 1)
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap().text, "คำอธิบายภาพ");
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_join_sums_reported_tokens() {
+        let part = |text: &str, input, output| AskResult {
+            text: text.to_string(),
+            input_tokens: input,
+            output_tokens: output,
+        };
+        let joined = AskResult::join(
+            vec![part("a", Some(100), Some(20)), part("b", Some(50), None)],
+            "\n\n",
+        );
+        assert_eq!(joined.text, "a\n\nb");
+        assert_eq!(joined.input_tokens, Some(150));
+        assert_eq!(joined.output_tokens, Some(20));
+        assert_eq!(
+            AskResult::join(vec![part("c", None, None)], "").input_tokens,
+            None
+        );
+    }
+
+    #[test]
+    fn test_retry_delay_honors_retry_after() {
+        assert_eq!(
+            parse_retry_after(None, Some("7")),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(
+            parse_retry_after(Some("1500"), Some("2")),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            parse_retry_after(None, Some("Wed, 21 Oct 2026 07:28:00 GMT")),
+            None
+        );
+
+        let ms = |d: Duration| d.as_millis();
+        for _ in 0..20 {
+            let after = ms(retry_delay(1000, 0, Some(Duration::from_secs(7))));
+            assert!((7000..=8400).contains(&after), "{after}");
+            let backoff = ms(retry_delay(1000, 2, None));
+            assert!((3200..=4800).contains(&backoff), "{backoff}");
+        }
+        // An unreasonably long Retry-After falls back to the backoff
+        let capped = ms(retry_delay(1000, 0, Some(Duration::from_secs(3600))));
+        assert!(capped <= 1200, "{capped}");
+    }
+
+    #[test]
+    fn test_missing_few_shot_image_is_error() {
+        let few_shot = vec![(PathBuf::from("/nonexistent/example.png"), "| a |".to_string())];
+        assert!(create_provider_with_examples("ollama", "qwen2.5vl", &few_shot).is_err());
+    }
+}
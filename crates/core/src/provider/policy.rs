@@ -0,0 +1,129 @@
+use crate::error::{CoreError, CoreResult};
+use serde::Deserialize;
+
+/// Organization policy restricting which models may be used.
+///
+/// Entries are a model name (`gpt-4o`) or provider-qualified
+/// (`openai/gpt-4o`); a trailing `*` matches by prefix (`claude-opus*`).
+/// When `allowed_models` is non-empty only matching models are accepted;
+/// `denied_models` always wins.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ModelPolicy {
+    #[serde(default)]
+    pub allowed_models: Vec<String>,
+    #[serde(default)]
+    pub denied_models: Vec<String>,
+    /// Shown in the rejection error (e.g. "Data residency policy DP-7").
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl ModelPolicy {
+    /// Load the policy from `JAY_RAG_MODEL_POLICY_FILE` (JSON), then add
+    /// entries from `JAY_RAG_ALLOWED_MODELS` / `JAY_RAG_DENIED_MODELS`
+    /// (comma-separated) and `JAY_RAG_MODEL_POLICY_REASON`.
+    pub fn from_env() -> CoreResult<Self> {
+        let mut policy = match std::env::var("JAY_RAG_MODEL_POLICY_FILE") {
+            Ok(path) if !path.trim().is_empty() => {
+                let json = std::fs::read_to_string(path.trim()).map_err(|e| {
+                    CoreError::Config(format!("Cannot read model policy file '{path}': {e}"))
+                })?;
+                serde_json::from_str(&json).map_err(|e| {
+                    CoreError::Config(format!("Invalid model policy file '{path}': {e}"))
+                })?
+            }
+            _ => Self::default(),
+        };
+
+        policy.allowed_models.extend(env_list("JAY_RAG_ALLOWED_MODELS"));
+        policy.denied_models.extend(env_list("JAY_RAG_DENIED_MODELS"));
+        let reason = std::env::var("JAY_RAG_MODEL_POLICY_REASON").unwrap_or_default();
+        if !reason.trim().is_empty() {
+            policy.reason = Some(reason.trim().to_string());
+        }
+        Ok(policy)
+    }
+
+    /// Reject `model` for `provider` if the policy forbids it.
+    pub fn check(&self, provider: &str, model: &str) -> CoreResult<()> {
+        let denied = self
+            .denied_models
+            .iter()
+            .any(|e| entry_matches(e, provider, model));
+        let not_allowed = !self.allowed_models.is_empty()
+            && !self
+                .allowed_models
+                .iter()
+                .any(|e| entry_matches(e, provider, model));
+
+        if denied || not_allowed {
+            let why = if denied { "denied" } else { "not in the allowed list" };
+            let reason = self
+                .reason
+                .as_deref()
+                .map(|r| format!(" — {r}"))
+                .unwrap_or_default();
+            return Err(CoreError::Config(format!(
+                "Model '{model}' for provider '{provider}' is {why} by the model policy{reason}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn env_list(key: &str) -> Vec<String> {
+    std::env::var(key)
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Match a policy entry against a provider/model pair.
+fn entry_matches(entry: &str, provider: &str, model: &str) -> bool {
+    let entry = entry.trim();
+    let pattern = match entry.split_once('/') {
+        Some((p, m)) if super::find_provider(p).is_some() => {
+            if p != provider {
+                return false;
+            }
+            m
+        }
+        _ => entry,
+    };
+
+    match pattern.strip_suffix('*') {
+        Some(prefix) => model.starts_with(prefix),
+        None => model == pattern,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_denied_model_rejected_with_reason() {
+        let policy = ModelPolicy {
+            denied_models: vec!["openai/gpt-4o".to_string(), "grok-*".to_string()],
+            reason: Some("PDPA compliance".to_string()),
+            ..Default::default()
+        };
+        let err = policy.check("openai", "gpt-4o").unwrap_err().to_string();
+        assert!(err.contains("denied") && err.contains("PDPA compliance"), "{err}");
+        assert!(policy.check("xai", "grok-2-vision").is_err());
+        assert!(policy.check("openai", "gpt-4o-mini").is_ok());
+    }
+
+    #[test]
+    fn test_allow_list_restricts_models() {
+        let policy = ModelPolicy {
+            allowed_models: vec!["ollama/*".to_string(), "claude-sonnet-4-6".to_string()],
+            ..Default::default()
+        };
+        assert!(policy.check("ollama", "qwen2.5vl").is_ok());
+        assert!(policy.check("claude", "claude-sonnet-4-6").is_ok());
+        assert!(policy.check("openai", "gpt-4o").is_err());
+    }
+}
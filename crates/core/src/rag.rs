@@ -0,0 +1,137 @@
+use crate::error::CoreResult;
+use crate::metadata::ImageMetadata;
+use crate::noise::NoiseFilter;
+use serde::Serialize;
+
+/// One line of the `{doc_stem}_rag.jsonl` output: a whole page, with image
+/// descriptions folded into the text where the images appear.
+#[derive(Debug, Clone, Serialize)]
+pub struct RagRecord {
+    /// Stable record id: `{doc_stem}_p{page}`.
+    pub id: String,
+    /// 1-indexed page number.
+    pub page: u32,
+    /// Page text with `[IMAGE:...]` tags removed and descriptions inlined.
+    pub content: String,
+    pub metadata: RagRecordMetadata,
+}
+
+/// Per-record metadata for filtering in the vector store.
+#[derive(Debug, Clone, Serialize)]
+pub struct RagRecordMetadata {
+    /// Source PDF filename (without extension).
+    pub source_doc: String,
+    /// Document language code (`th`, `en`, `lo`, `km` or `vi`).
+    pub language: String,
+    /// Images on this page, as `{doc_stem}/{file}` paths relative to the images root.
+    pub images: Vec<String>,
+}
+
+/// Build one record per page from the assembled markdown page sections.
+///
+/// `pages` pairs each 1-indexed page number with its markdown section
+/// (including the `## Page N` heading). Pages with no content left after
+/// folding and noise filtering are skipped.
+pub fn build_rag_records(
+    doc_stem: &str,
+    language: &str,
+    pages: &[(u32, String)],
+    metadata: &[ImageMetadata],
+    noise: &NoiseFilter,
+) -> Vec<RagRecord> {
+    pages
+        .iter()
+        .filter_map(|(page, section)| {
+            let content = noise.filter(&fold_page_content(section));
+            let content = content.trim();
+            if content.is_empty() {
+                return None;
+            }
+            Some(RagRecord {
+                id: format!("{doc_stem}_p{page}"),
+                page: *page,
+                content: content.to_string(),
+                metadata: RagRecordMetadata {
+                    source_doc: doc_stem.to_string(),
+                    language: language.to_string(),
+                    images: metadata
+                        .iter()
+                        .filter(|m| m.page == *page)
+                        .map(|m| m.image_file.clone())
+                        .collect(),
+                },
+            })
+        })
+        .collect()
+}
+
+/// Serialize records as JSON Lines.
+pub fn rag_jsonl(records: &[RagRecord]) -> CoreResult<String> {
+    let mut out = String::new();
+    for record in records {
+        out.push_str(&serde_json::to_string(record)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Turn a markdown page section into plain embedding text.
+///
+/// Drops the `---` / `## Page N` heading and `[IMAGE:...]` tag lines, and
+/// rewrites `**[ภาพที่ 1]:** description` as `[ภาพที่ 1: description]` so the
+/// description stays at the image's position in the text.
+fn fold_page_content(section: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for line in section.lines() {
+        let trimmed = line.trim();
+        if trimmed == "---" || trimmed.starts_with("## Page ") {
+            continue;
+        }
+        if trimmed.starts_with("[IMAGE:") && trimmed.ends_with(']') {
+            continue;
+        }
+        let folded = trimmed
+            .strip_prefix("**[")
+            .and_then(|rest| rest.split_once("]:**"))
+            .map(|(label, desc)| format!("[{label}: {}]", desc.trim()));
+        match folded {
+            Some(text) => lines.push(text),
+            None => {
+                // Collapse runs of blank lines left behind by removed tags
+                if trimmed.is_empty() && lines.last().is_none_or(|l| l.is_empty()) {
+                    continue;
+                }
+                lines.push(line.trim_end().to_string());
+            }
+        }
+    }
+    lines.join("\n").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_page_content_inlines_descriptions() {
+        let section = "\n\n---\n## Page 2\n\nขั้นตอนการติดตั้ง\n\n[IMAGE:manual/page2_img1.png]\n**[ภาพที่ 1]:** หน้าจอเข้าสู่ระบบ\n\nกดปุ่มตกลง\n";
+        assert_eq!(
+            fold_page_content(section),
+            "ขั้นตอนการติดตั้ง\n\n[ภาพที่ 1: หน้าจอเข้าสู่ระบบ]\n\nกดปุ่มตกลง"
+        );
+    }
+
+    #[test]
+    fn test_build_rag_records_skips_empty_pages() {
+        let pages = vec![
+            (1, "\n\n---\n## Page 1\n".to_string()),
+            (2, "\n\n---\n## Page 2\nHello".to_string()),
+        ];
+        let records = build_rag_records("doc", "en", &pages, &[], &NoiseFilter::default());
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, "doc_p2");
+        let json = serde_json::to_value(&records[0]).unwrap();
+        assert_eq!(json["content"], "Hello");
+        assert_eq!(json["metadata"]["source_doc"], "doc");
+    }
+}
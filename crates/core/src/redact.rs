@@ -0,0 +1,82 @@
+/// Key prefixes used by supported providers (OpenAI/Anthropic, xAI, Groq, Gemini).
+const SECRET_PREFIXES: &[&str] = &["sk-", "xai-", "gsk_", "AIza"];
+
+/// Minimum token length after a prefix before it is treated as a secret.
+const MIN_SECRET_LEN: usize = 8;
+
+/// Mask API keys and bearer tokens in a string before logging or returning it.
+///
+/// Matches provider key prefixes (`sk-...`, `sk-ant-...`, `xai-...`, `gsk_...`,
+/// `AIza...`) and `Bearer <token>`; the prefix is kept so the key type is still
+/// recognizable, the rest is replaced with `****`.
+pub fn redact_secrets(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut output = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < text.len() {
+        let at_boundary = i == 0 || !is_token_byte(bytes[i - 1]);
+        let rest = &text[i..];
+
+        if at_boundary {
+            if let Some(prefix) = SECRET_PREFIXES.iter().find(|p| rest.starts_with(**p)) {
+                let len = token_len(&rest[prefix.len()..]);
+                if len >= MIN_SECRET_LEN {
+                    output.push_str(prefix);
+                    output.push_str("****");
+                    i += prefix.len() + len;
+                    continue;
+                }
+            }
+
+            if rest.len() > 7 && rest.as_bytes()[..7].eq_ignore_ascii_case(b"bearer ") {
+                let len = token_len(&rest[7..]);
+                if len > 0 {
+                    output.push_str(&rest[..7]);
+                    output.push_str("****");
+                    i += 7 + len;
+                    continue;
+                }
+            }
+        }
+
+        let ch = rest.chars().next().unwrap_or_default();
+        output.push(ch);
+        i += ch.len_utf8();
+    }
+
+    output
+}
+
+/// Length in bytes of the leading run of token characters.
+fn token_len(s: &str) -> usize {
+    s.bytes().take_while(|b| is_token_byte(*b)).count()
+}
+
+fn is_token_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_api_keys() {
+        let s = "key sk-ant-api03-abcdefghijkl failed";
+        assert_eq!(redact_secrets(s), "key sk-**** failed");
+        assert_eq!(redact_secrets("gsk_1234567890abc"), "gsk_****");
+    }
+
+    #[test]
+    fn test_redacts_bearer_tokens() {
+        let s = "Authorization: Bearer abc.def-123";
+        assert_eq!(redact_secrets(s), "Authorization: Bearer ****");
+    }
+
+    #[test]
+    fn test_keeps_normal_text() {
+        let s = "export OPENAI_API_KEY='sk-...' — ภาษาไทย task-runner";
+        assert_eq!(redact_secrets(s), s);
+    }
+}
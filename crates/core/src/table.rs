@@ -1,10 +1,26 @@
+use crate::config::WideTableStrategy;
+use serde::{Deserialize, Serialize};
+
+/// A markdown pipe table as structured data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Table {
+    pub headers: Vec<String>,
+    /// Body rows, each padded or truncated to the header width.
+    pub rows: Vec<Vec<String>>,
+}
+
 /// Heuristic to detect if text content looks like a table.
 ///
 /// Uses two detection methods:
-/// 1. Multi-space columns: lines with 2+ groups of 2+ consecutive spaces
-/// 2. Row consistency: consecutive lines with similar token counts (≥3 tokens),
-///    which catches tables where pdfium collapses column gaps to single spaces
-pub fn looks_like_table(text: &str) -> bool {
+/// 1. Multi-space columns: at least `multispace_ratio` of the lines have 2+
+///    groups of 2+ consecutive spaces
+/// 2. Row consistency: `min_consistent_run` consecutive lines with similar
+///    token counts (≥3 tokens), which catches tables where pdfium collapses
+///    column gaps to single spaces
+///
+/// Both thresholds come from [`crate::config::ProcessingConfig`]
+/// (`table_multispace_ratio`, `table_min_consistent_run`).
+pub fn looks_like_table(text: &str, multispace_ratio: f64, min_consistent_run: usize) -> bool {
     let non_empty: Vec<&str> = text
         .lines()
         .map(|l| l.trim())
@@ -38,15 +54,15 @@ pub fn looks_like_table(text: &str) -> bool {
         })
         .count();
 
-    if (tabular_lines as f64 / non_empty.len() as f64) >= 0.4 {
+    if (tabular_lines as f64 / non_empty.len() as f64) >= multispace_ratio {
         return true;
     }
 
     // Method 2: Row consistency — consecutive lines with similar token counts.
     // pdfium often extracts table columns with single spaces, making multi-space
-    // detection fail. Instead, check if 6+ consecutive lines each have ≥3
-    // whitespace-separated tokens with counts varying by at most 2.
-    // Threshold of 6 avoids false positives from bullet lists and TOC entries.
+    // detection fail. Instead, check if `min_consistent_run` consecutive lines
+    // each have ≥3 whitespace-separated tokens with counts varying by at most 2.
+    // The default of 6 avoids false positives from bullet lists and TOC entries.
     let token_counts: Vec<usize> = non_empty
         .iter()
         .map(|line| line.split_whitespace().count())
@@ -66,5 +82,287 @@ pub fn looks_like_table(text: &str) -> bool {
         }
     }
 
-    best_run >= 6
+    best_run >= min_consistent_run
+}
+
+/// Rewrite markdown tables with more than `max_columns` columns.
+///
+/// Only pipe tables with a `|---|` separator row are touched; everything
+/// else (including narrower tables) is returned unchanged.
+pub fn reshape_wide_tables(
+    markdown: &str,
+    max_columns: usize,
+    strategy: WideTableStrategy,
+) -> String {
+    if strategy == WideTableStrategy::Keep {
+        return markdown.to_string();
+    }
+
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut i = 0;
+    while i < lines.len() {
+        let start = i;
+        while i < lines.len() && lines[i].trim_start().starts_with('|') {
+            i += 1;
+        }
+        if i == start {
+            out.push(lines[i].to_string());
+            i += 1;
+            continue;
+        }
+
+        let block = &lines[start..i];
+        let reshaped = parse_table(block)
+            .filter(|(header, _)| header.len() > max_columns)
+            .and_then(|(header, rows)| {
+                tracing::debug!(
+                    "Reshaping {}-column table ({} rows) as {strategy}",
+                    header.len(),
+                    rows.len()
+                );
+                match strategy {
+                    WideTableStrategy::Keep => None,
+                    WideTableStrategy::Transpose => Some(transpose_table(&header, &rows)),
+                    WideTableStrategy::Split => Some(split_table(&header, &rows, max_columns)),
+                    WideTableStrategy::Records => Some(table_to_records(&header, &rows)),
+                }
+            });
+        match reshaped {
+            Some(table) => out.push(table),
+            None => out.extend(block.iter().map(|l| l.to_string())),
+        }
+    }
+
+    let mut result = out.join("\n");
+    if markdown.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Parse every pipe table (with a `|---|` separator row) in `markdown`.
+///
+/// Escaped pipes (`\|`) in cells are unescaped; text outside tables is ignored.
+pub fn parse_markdown_tables(markdown: &str) -> Vec<Table> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut tables = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let start = i;
+        while i < lines.len() && lines[i].trim_start().starts_with('|') {
+            i += 1;
+        }
+        if i == start {
+            i += 1;
+            continue;
+        }
+        if let Some((headers, rows)) = parse_table(&lines[start..i]) {
+            let unescape = |cells: Vec<String>| -> Vec<String> {
+                cells.into_iter().map(|c| c.replace("\\|", "|")).collect()
+            };
+            tables.push(Table {
+                headers: unescape(headers),
+                rows: rows.into_iter().map(unescape).collect(),
+            });
+        }
+    }
+    tables
+}
+
+/// Parse a block of `|`-prefixed lines into header cells and body rows.
+///
+/// Rows are padded/truncated to the header width.
+fn parse_table(block: &[&str]) -> Option<(Vec<String>, Vec<Vec<String>>)> {
+    if block.len() < 2 {
+        return None;
+    }
+    let header = split_row(block[0]);
+    let separator = split_row(block[1]);
+    let is_separator = !separator.is_empty()
+        && separator.iter().all(|c| {
+            let c = c.trim();
+            !c.is_empty() && c.chars().all(|ch| ch == '-' || ch == ':')
+        });
+    if !is_separator {
+        return None;
+    }
+
+    let rows = block[2..]
+        .iter()
+        .map(|line| {
+            let mut cells = split_row(line);
+            cells.resize(header.len(), String::new());
+            cells
+        })
+        .collect();
+    Some((header, rows))
+}
+
+/// Split a `| a | b |` row into trimmed cells (escaped `\|` stays in the cell).
+fn split_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' if chars.peek() == Some(&'|') => {
+                current.push_str("\\|");
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut current).trim().to_string()),
+            _ => current.push(ch),
+        }
+    }
+    cells.push(current.trim().to_string());
+    cells
+}
+
+fn render_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut lines = vec![
+        format!("| {} |", header.join(" | ")),
+        format!("|{}|", vec!["---"; header.len()].join("|")),
+    ];
+    lines.extend(rows.iter().map(|r| format!("| {} |", r.join(" | "))));
+    lines.join("\n")
+}
+
+/// Swap rows and columns: each original column becomes a row keyed by its header.
+fn transpose_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let new_header: Vec<String> = std::iter::once(header[0].clone())
+        .chain(rows.iter().map(|r| r[0].clone()))
+        .collect();
+    let new_rows: Vec<Vec<String>> = (1..header.len())
+        .map(|c| {
+            std::iter::once(header[c].clone())
+                .chain(rows.iter().map(|r| r[c].clone()))
+                .collect()
+        })
+        .collect();
+    render_table(&new_header, &new_rows)
+}
+
+/// Split into several tables of at most `max_columns` columns, repeating the
+/// first (key) column in each.
+fn split_table(header: &[String], rows: &[Vec<String>], max_columns: usize) -> String {
+    let per_table = max_columns.saturating_sub(1).max(1);
+    (1..header.len())
+        .step_by(per_table)
+        .map(|from| {
+            let to = (from + per_table).min(header.len());
+            let pick = |cells: &[String]| -> Vec<String> {
+                std::iter::once(cells[0].clone())
+                    .chain(cells[from..to].iter().cloned())
+                    .collect()
+            };
+            let sub_rows: Vec<Vec<String>> = rows.iter().map(|r| pick(r)).collect();
+            render_table(&pick(header), &sub_rows)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Render each row as a block of `header: value` lines, skipping empty cells.
+fn table_to_records(header: &[String], rows: &[Vec<String>]) -> String {
+    rows.iter()
+        .map(|row| {
+            let mut lines = vec![format!("**{}:** {}", header[0], row[0])];
+            lines.extend(
+                header
+                    .iter()
+                    .zip(row)
+                    .skip(1)
+                    .filter(|(_, v)| !v.is_empty())
+                    .map(|(h, v)| format!("- {h}: {v}")),
+            );
+            lines.join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Synthetic 16-column table: a key column plus spec columns C1..C15.
+    fn wide_table() -> String {
+        let header: Vec<String> = std::iter::once("Model".to_string())
+            .chain((1..=15).map(|i| format!("C{i}")))
+            .collect();
+        let row = |name: &str| -> String {
+            let cells: Vec<String> = std::iter::once(name.to_string())
+                .chain((1..=15).map(|i| format!("{name}{i}")))
+                .collect();
+            format!("| {} |", cells.join(" | "))
+        };
+        format!(
+            "Specs:\n\n| {} |\n|{}|\n{}\n{}\n\nEnd",
+            header.join(" | "),
+            vec!["---"; 16].join("|"),
+            row("A"),
+            row("B")
+        )
+    }
+
+    #[test]
+    fn test_parse_markdown_tables() {
+        let markdown = "ตารางสเปก\n\n| รุ่น | แรงดัน |\n|:---|---:|\n| A-1 | 220V |\n| B\\|2 |\n\nหมายเหตุ\n| not | a table |\n";
+        assert_eq!(
+            parse_markdown_tables(markdown),
+            vec![Table {
+                headers: vec!["รุ่น".to_string(), "แรงดัน".to_string()],
+                rows: vec![
+                    vec!["A-1".to_string(), "220V".to_string()],
+                    vec!["B|2".to_string(), String::new()],
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_looks_like_table_thresholds() {
+        // Single-spaced rows, as pdfium extracts many tables
+        let rows = "รุ่น แรงดัน กำลังไฟ\nA-1 220V 1200W\nA-2 220V 1500W\nB-1 110V 800W\nB-2 110V 950W";
+        assert!(!looks_like_table(rows, 0.4, 6));
+        assert!(looks_like_table(rows, 0.4, 5));
+
+        let spaced = "รุ่น  แรงดัน  กำลังไฟ\nA-1  220V  1200W\nหมายเหตุ\nดูคู่มือ\nรับประกัน 1 ปี";
+        assert!(looks_like_table(spaced, 0.4, 6));
+        assert!(!looks_like_table(spaced, 0.5, 6));
+    }
+
+    #[test]
+    fn test_narrow_table_unchanged() {
+        let md = "| a | b |\n|---|---|\n| 1 | 2 |";
+        assert_eq!(reshape_wide_tables(md, 14, WideTableStrategy::Records), md);
+    }
+
+    #[test]
+    fn test_transpose_wide_table() {
+        let out = reshape_wide_tables(&wide_table(), 14, WideTableStrategy::Transpose);
+        assert!(out.starts_with("Specs:\n\n| Model | A | B |\n|---|---|---|\n| C1 | A1 | B1 |"));
+        assert!(out.contains("| C15 | A15 | B15 |"));
+        assert!(out.ends_with("\n\nEnd"));
+    }
+
+    #[test]
+    fn test_split_wide_table() {
+        let out = reshape_wide_tables(&wide_table(), 8, WideTableStrategy::Split);
+        // 15 value columns, 7 per table -> 3 tables, each keyed by Model
+        assert_eq!(out.matches("| Model |").count(), 3);
+        assert!(out.contains("| Model | C15 |\n|---|---|\n| A | A15 |"));
+    }
+
+    #[test]
+    fn test_wide_table_to_records() {
+        let out = reshape_wide_tables(&wide_table(), 14, WideTableStrategy::Records);
+        assert!(out.contains("**Model:** A\n- C1: A1\n- C2: A2"));
+        assert!(out.contains("**Model:** B\n- C1: B1"));
+        assert!(!out.contains("|---|"));
+    }
 }
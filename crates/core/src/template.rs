@@ -0,0 +1,180 @@
+use crate::error::{CoreError, CoreResult};
+use crate::metadata::{ImageMetadata, ImageType};
+use handlebars::Handlebars;
+use serde::Serialize;
+
+/// Template reproducing the built-in layout: the header lines, then each
+/// page's pre-rendered markdown, joined by newlines.
+pub const DEFAULT_TEMPLATE: &str = "{{#each header}}{{this}}{{#unless @last}}\n{{/unless}}{{/each}}\
+{{#each pages}}\n{{content}}{{/each}}";
+
+/// Data a template renders the document from.
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentContext {
+    pub doc_stem: String,
+    pub provider: String,
+    pub model: String,
+    pub total_pages: u32,
+    /// Built-in header lines (title, provider and images notes).
+    pub header: Vec<String>,
+    pub pages: Vec<PageContext>,
+}
+
+/// One page of the document, as structured data.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PageContext {
+    /// 1-indexed page number.
+    pub number: u32,
+    /// Text layer of the page (empty for tables), or the transcription of a
+    /// high-quality page.
+    pub text: String,
+    /// Individual images with their descriptions, in page order.
+    pub images: Vec<ImageContext>,
+    /// Table renders with their markdown transcription.
+    pub tables: Vec<ImageContext>,
+    /// Full-page renders with their description (image-heavy pages) or
+    /// transcription (high-quality pages).
+    pub renders: Vec<ImageContext>,
+    /// Filled-in form fields section (empty unless `extract_form_fields` found any).
+    pub form_fields: String,
+    /// First error hit on this page, if any.
+    pub error: Option<String>,
+    /// The page as the built-in layout renders it.
+    pub content: String,
+}
+
+/// An image and what the vision LLM made of it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageContext {
+    /// `{doc_stem}/{file}` path relative to the images root, as in `[IMAGE:]` tags.
+    pub file: String,
+    pub description: String,
+    /// Position on the page (individual images only).
+    pub index: Option<u32>,
+    /// Figure number across the whole document (individual images only).
+    pub number: Option<u32>,
+}
+
+impl PageContext {
+    /// A page with its images sorted by kind; `figure_counter` numbers the
+    /// individual images across pages.
+    pub fn new(number: u32, metadata: &[ImageMetadata], figure_counter: &mut u32) -> Self {
+        let mut page = Self {
+            number,
+            ..Default::default()
+        };
+        for meta in metadata {
+            let mut image = ImageContext {
+                file: meta.image_file.clone(),
+                description: meta.description.clone(),
+                index: meta.index,
+                number: None,
+            };
+            match meta.image_type {
+                ImageType::ExtractedImage => {
+                    *figure_counter += 1;
+                    image.number = Some(*figure_counter);
+                    page.images.push(image);
+                }
+                ImageType::TableRegion => page.tables.push(image),
+                ImageType::FullPage => page.renders.push(image),
+            }
+        }
+        page
+    }
+}
+
+/// A parsed Handlebars output template.
+///
+/// Output is markdown, so values are inserted as-is (no HTML escaping).
+pub struct OutputTemplate {
+    registry: Handlebars<'static>,
+}
+
+impl OutputTemplate {
+    /// Parse a template, failing on syntax errors.
+    pub fn new(source: &str) -> CoreResult<Self> {
+        let mut registry = Handlebars::new();
+        registry.register_escape_fn(handlebars::no_escape);
+        registry
+            .register_template_string("document", source)
+            .map_err(|e| CoreError::Config(format!("Invalid output template: {e}")))?;
+        Ok(Self { registry })
+    }
+
+    /// Render the document markdown.
+    pub fn render(&self, document: &DocumentContext) -> CoreResult<String> {
+        self.registry
+            .render("document", document)
+            .map_err(|e| CoreError::Config(format!("Output template failed: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(pages: Vec<PageContext>) -> DocumentContext {
+        DocumentContext {
+            doc_stem: "manual".to_string(),
+            provider: "openai".to_string(),
+            model: "gpt-4o".to_string(),
+            total_pages: pages.len() as u32,
+            header: vec![
+                "# manual\n".to_string(),
+                "> Images: `images/manual/`\n".to_string(),
+            ],
+            pages,
+        }
+    }
+
+    #[test]
+    fn test_default_template_matches_builtin_layout() {
+        let pages = vec![
+            PageContext {
+                number: 1,
+                content: "\n\n---\n## Page 1\n\nขั้นตอนที่ 1 <กดปุ่ม> & รอ".to_string(),
+                ..Default::default()
+            },
+            PageContext {
+                number: 2,
+                content: "\n\n---\n## Page 2\n".to_string(),
+                ..Default::default()
+            },
+        ];
+        let doc = document(pages);
+        let mut expected = doc.header.clone();
+        expected.extend(doc.pages.iter().map(|p| p.content.clone()));
+
+        let rendered = OutputTemplate::new(DEFAULT_TEMPLATE)
+            .unwrap()
+            .render(&doc)
+            .unwrap();
+        assert_eq!(rendered, expected.join("\n"));
+    }
+
+    #[test]
+    fn test_custom_template_uses_page_data() {
+        let page = PageContext {
+            number: 3,
+            text: "Intro".to_string(),
+            images: vec![ImageContext {
+                file: "manual/manual_p3_img1.png".to_string(),
+                description: "Login screen".to_string(),
+                index: Some(1),
+                number: Some(4),
+            }],
+            ..Default::default()
+        };
+        let template = OutputTemplate::new(
+            "{{#each pages}}# Page {{number}}\n{{text}}\n{{#each images}}![Figure {{number}}]({{file}}) {{description}}\n{{/each}}{{/each}}",
+        )
+        .unwrap();
+        assert_eq!(
+            template.render(&document(vec![page])).unwrap(),
+            "# Page 3\nIntro\n![Figure 4](manual/manual_p3_img1.png) Login screen\n"
+        );
+
+        assert!(OutputTemplate::new("{{#each pages}}").is_err());
+    }
+}
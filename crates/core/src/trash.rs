@@ -21,6 +21,135 @@ impl std::fmt::Display for TrashType {
     }
 }
 
+/// Machine-readable cause of a detection, for filtering and automation.
+///
+/// `reason` stays the human-readable text; `code` is what logic should match on.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashReasonCode {
+    /// "สารบัญ" / "Table of Contents" heading found.
+    TocHeading,
+    /// Many dot-leader lines ("Chapter 1 ..... 5") without a TOC heading.
+    DotLeaders,
+    /// Copyright / disclaimer / confidentiality keywords.
+    BoilerplateKeywords,
+    /// Page has almost no text.
+    ShortPage,
+    /// Explicit "intentionally left blank" marker.
+    BlankMarker,
+    /// Header/footer or paragraph repeated across pages.
+    RepeatedText,
+    /// Detections written before reason codes existed.
+    #[default]
+    Other,
+}
+
+impl TrashReasonCode {
+    /// Serialized (snake_case) name, e.g. `"dot_leaders"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TocHeading => "toc_heading",
+            Self::DotLeaders => "dot_leaders",
+            Self::BoilerplateKeywords => "boilerplate_keywords",
+            Self::ShortPage => "short_page",
+            Self::BlankMarker => "blank_marker",
+            Self::RepeatedText => "repeated_text",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Thresholds for the page-level trash detectors.
+///
+/// Usually built from a [`TrashPreset`]; the default equals `Balanced`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrashConfig {
+    /// Page-level detections below this confidence are dropped.
+    pub min_confidence: f64,
+    /// Dot-leader lines needed to flag a TOC page without a TOC heading.
+    pub toc_min_dot_leaders: usize,
+    /// A page with a single boilerplate keyword is flagged only below this length.
+    pub boilerplate_max_chars: usize,
+    /// Pages with fewer characters count as nearly blank.
+    pub blank_max_chars: usize,
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        TrashPreset::default().config()
+    }
+}
+
+/// Named trash detection sensitivity.
+///
+/// | Preset | Min confidence | TOC dot leaders | Single-keyword boilerplate | Nearly blank |
+/// |---|---|---|---|---|
+/// | `conservative` | 0.85 | 8 | never | < 20 chars |
+/// | `balanced` | any | 5 | < 500 chars | < 50 chars |
+/// | `aggressive` | any | 3 | < 1500 chars | < 120 chars |
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashPreset {
+    /// High-confidence detections only; fewest false positives.
+    Conservative,
+    /// The standard thresholds (default).
+    #[default]
+    Balanced,
+    /// Flags more pages as trash; review before removing.
+    Aggressive,
+}
+
+impl TrashPreset {
+    /// Detector thresholds for this preset.
+    pub fn config(self) -> TrashConfig {
+        match self {
+            Self::Conservative => TrashConfig {
+                min_confidence: 0.85,
+                toc_min_dot_leaders: 8,
+                boilerplate_max_chars: 0,
+                blank_max_chars: 20,
+            },
+            Self::Balanced => TrashConfig {
+                min_confidence: 0.0,
+                toc_min_dot_leaders: 5,
+                boilerplate_max_chars: 500,
+                blank_max_chars: 50,
+            },
+            Self::Aggressive => TrashConfig {
+                min_confidence: 0.0,
+                toc_min_dot_leaders: 3,
+                boilerplate_max_chars: 1500,
+                blank_max_chars: 120,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for TrashPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Conservative => write!(f, "conservative"),
+            Self::Balanced => write!(f, "balanced"),
+            Self::Aggressive => write!(f, "aggressive"),
+        }
+    }
+}
+
+impl std::str::FromStr for TrashPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "conservative" => Ok(Self::Conservative),
+            "balanced" => Ok(Self::Balanced),
+            "aggressive" => Ok(Self::Aggressive),
+            other => Err(format!(
+                "Unknown trash preset: {other}. Use: conservative | balanced | aggressive"
+            )),
+        }
+    }
+}
+
 /// A detected trash item on a specific page.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrashDetection {
@@ -30,30 +159,66 @@ pub struct TrashDetection {
     pub trash_type: TrashType,
     /// Confidence score (0.0–1.0).
     pub confidence: f64,
+    /// Structured cause of the detection.
+    #[serde(default)]
+    pub code: TrashReasonCode,
     /// Human-readable explanation.
     pub reason: String,
     /// First ~200 chars of the page content.
     pub preview: String,
 }
 
+impl TrashDetection {
+    /// Whether the detection matches a strip filter: comma-separated types
+    /// (`toc`, `boilerplate`, `blank`, `header_footer`) or reason codes
+    /// (`short_page`, `dot_leaders`, ...). `None` matches every detection.
+    pub fn matches_filter(&self, filter: Option<&str>) -> bool {
+        let Some(filter) = filter else {
+            return true;
+        };
+        filter.split(',').map(str::trim).any(|t| match t {
+            "toc" => self.trash_type == TrashType::TableOfContents,
+            "boilerplate" => self.trash_type == TrashType::Boilerplate,
+            "blank" => self.trash_type == TrashType::BlankPage,
+            "header_footer" => self.trash_type == TrashType::HeaderFooter,
+            code => self.code.as_str() == code,
+        })
+    }
+}
+
+/// Sorted 1-indexed pages of the detections matching `filter`.
+///
+/// Document-level detections (page 0) never remove a page.
+pub fn pages_to_strip(detections: &[TrashDetection], filter: Option<&str>) -> Vec<u32> {
+    let mut pages: Vec<u32> = detections
+        .iter()
+        .filter(|d| d.page > 0 && d.matches_filter(filter))
+        .map(|d| d.page)
+        .collect();
+    pages.sort_unstable();
+    pages.dedup();
+    pages
+}
+
 /// Run all trash detectors on a set of page texts.
 ///
 /// `page_texts` is a slice of `(page_num_0indexed, text)` pairs.
 /// Returns detections with 1-indexed page numbers.
-pub fn detect_trash(page_texts: &[(u32, String)]) -> Vec<TrashDetection> {
+pub fn detect_trash(page_texts: &[(u32, String)], config: &TrashConfig) -> Vec<TrashDetection> {
     let mut detections = Vec::new();
     for (page_num, text) in page_texts {
         let page_1indexed = page_num + 1;
-        if let Some(d) = detect_toc(page_1indexed, text) {
+        if let Some(d) = detect_toc(page_1indexed, text, config) {
             detections.push(d);
         }
-        if let Some(d) = detect_boilerplate(page_1indexed, text) {
+        if let Some(d) = detect_boilerplate(page_1indexed, text, config) {
             detections.push(d);
         }
-        if let Some(d) = detect_blank(page_1indexed, text) {
+        if let Some(d) = detect_blank(page_1indexed, text, config) {
             detections.push(d);
         }
     }
+    detections.retain(|d| d.confidence >= config.min_confidence);
     detections
 }
 
@@ -89,15 +254,48 @@ pub fn create_header_footer_detections(
         page: 0, // 0 = document-level
         trash_type: TrashType::HeaderFooter,
         confidence: 1.0,
+        code: TrashReasonCode::RepeatedText,
         reason,
         preview,
     }]
 }
 
+/// Create a document-level Boilerplate detection for paragraphs repeated across pages.
+///
+/// `repeated` pairs each normalized paragraph with the number of pages it appears on.
+pub fn create_repeated_paragraph_detections(
+    total_pages: usize,
+    repeated: &[(String, usize)],
+) -> Vec<TrashDetection> {
+    if repeated.is_empty() {
+        return Vec::new();
+    }
+
+    let parts: Vec<String> = repeated
+        .iter()
+        .map(|(paragraph, count)| format!("\"{}\" ({count}/{total_pages} pages)", truncate_preview(paragraph)))
+        .collect();
+    let reason = format!(
+        "{} paragraph(s) repeated across pages: {}",
+        repeated.len(),
+        parts.join(", ")
+    );
+
+    vec![TrashDetection {
+        page: 0, // 0 = document-level
+        trash_type: TrashType::Boilerplate,
+        confidence: 0.9,
+        code: TrashReasonCode::RepeatedText,
+        reason,
+        preview: truncate_preview(&parts.join("; ")),
+    }]
+}
+
 /// Detect Table of Contents pages.
 ///
-/// Looks for "สารบัญ" / "Table of Contents" heading or 5+ dot-leader lines.
-fn detect_toc(page: u32, text: &str) -> Option<TrashDetection> {
+/// Looks for "สารบัญ" / "Table of Contents" heading or `toc_min_dot_leaders`
+/// dot-leader lines.
+fn detect_toc(page: u32, text: &str, config: &TrashConfig) -> Option<TrashDetection> {
     let lower = text.to_lowercase();
 
     // Thai TOC heading
@@ -125,6 +323,7 @@ fn detect_toc(page: u32, text: &str) -> Option<TrashDetection> {
             page,
             trash_type: TrashType::TableOfContents,
             confidence: 0.95,
+            code: TrashReasonCode::TocHeading,
             reason: format!(
                 "TOC heading keyword found with {dot_leader_count} dot-leader lines"
             ),
@@ -135,14 +334,16 @@ fn detect_toc(page: u32, text: &str) -> Option<TrashDetection> {
             page,
             trash_type: TrashType::TableOfContents,
             confidence: 0.90,
+            code: TrashReasonCode::TocHeading,
             reason: "TOC heading keyword found".to_string(),
             preview: truncate_preview(text),
         })
-    } else if dot_leader_count >= 5 {
+    } else if dot_leader_count >= config.toc_min_dot_leaders {
         Some(TrashDetection {
             page,
             trash_type: TrashType::TableOfContents,
             confidence: 0.70,
+            code: TrashReasonCode::DotLeaders,
             reason: format!("{dot_leader_count} dot-leader lines detected (possible TOC)"),
             preview: truncate_preview(text),
         })
@@ -152,7 +353,7 @@ fn detect_toc(page: u32, text: &str) -> Option<TrashDetection> {
 }
 
 /// Detect boilerplate/legal pages (copyright, disclaimer, etc.).
-fn detect_boilerplate(page: u32, text: &str) -> Option<TrashDetection> {
+fn detect_boilerplate(page: u32, text: &str, config: &TrashConfig) -> Option<TrashDetection> {
     let lower = text.to_lowercase();
 
     let keywords = [
@@ -182,17 +383,19 @@ fn detect_boilerplate(page: u32, text: &str) -> Option<TrashDetection> {
             page,
             trash_type: TrashType::Boilerplate,
             confidence: 0.85,
+            code: TrashReasonCode::BoilerplateKeywords,
             reason: format!(
                 "Multiple boilerplate keywords: {}",
                 matched.join(", ")
             ),
             preview: truncate_preview(text),
         })
-    } else if match_count == 1 && text.len() < 500 {
+    } else if match_count == 1 && text.len() < config.boilerplate_max_chars {
         Some(TrashDetection {
             page,
             trash_type: TrashType::Boilerplate,
             confidence: 0.65,
+            code: TrashReasonCode::BoilerplateKeywords,
             reason: format!(
                 "Boilerplate keyword \"{}\" on short page ({} chars)",
                 matched[0],
@@ -206,7 +409,7 @@ fn detect_boilerplate(page: u32, text: &str) -> Option<TrashDetection> {
 }
 
 /// Detect blank or nearly-blank pages.
-fn detect_blank(page: u32, text: &str) -> Option<TrashDetection> {
+fn detect_blank(page: u32, text: &str, config: &TrashConfig) -> Option<TrashDetection> {
     let trimmed = text.trim();
     let lower = trimmed.to_lowercase();
 
@@ -220,14 +423,16 @@ fn detect_blank(page: u32, text: &str) -> Option<TrashDetection> {
             page,
             trash_type: TrashType::BlankPage,
             confidence: 0.95,
+            code: TrashReasonCode::BlankMarker,
             reason: "Explicit blank page marker found".to_string(),
             preview: truncate_preview(trimmed),
         })
-    } else if trimmed.len() < 50 {
+    } else if trimmed.len() < config.blank_max_chars {
         Some(TrashDetection {
             page,
             trash_type: TrashType::BlankPage,
             confidence: 0.80,
+            code: TrashReasonCode::ShortPage,
             reason: format!("Nearly blank page ({} chars)", trimmed.len()),
             preview: truncate_preview(trimmed),
         })
@@ -253,46 +458,55 @@ fn truncate_preview(text: &str) -> String {
 mod tests {
     use super::*;
 
+    fn cfg() -> TrashConfig {
+        TrashConfig::default()
+    }
+
     #[test]
     fn test_detect_blank_empty() {
-        let result = detect_blank(1, "   ");
+        let result = detect_blank(1, "   ", &cfg());
         assert!(result.is_some());
         let d = result.unwrap();
         assert_eq!(d.trash_type, TrashType::BlankPage);
+        assert_eq!(d.code, TrashReasonCode::ShortPage);
         assert!(d.confidence >= 0.80);
     }
 
     #[test]
     fn test_detect_blank_marker() {
-        let result = detect_blank(1, "This page intentionally left blank");
+        let result = detect_blank(1, "This page intentionally left blank", &cfg());
         assert!(result.is_some());
-        assert_eq!(result.unwrap().confidence, 0.95);
+        let d = result.unwrap();
+        assert_eq!(d.confidence, 0.95);
+        assert_eq!(d.code, TrashReasonCode::BlankMarker);
     }
 
     #[test]
     fn test_detect_toc_heading() {
         let text = "สารบัญ\nบทที่ 1 ..... 5\nบทที่ 2 ..... 12\nบทที่ 3 ..... 20";
-        let result = detect_toc(1, text);
+        let result = detect_toc(1, text, &cfg());
         assert!(result.is_some());
         let d = result.unwrap();
         assert_eq!(d.trash_type, TrashType::TableOfContents);
+        assert_eq!(d.code, TrashReasonCode::TocHeading);
         assert!(d.confidence >= 0.90);
     }
 
     #[test]
     fn test_detect_boilerplate_multiple_keywords() {
         let text = "Copyright 2024 Company. All rights reserved. สงวนลิขสิทธิ์";
-        let result = detect_boilerplate(1, text);
+        let result = detect_boilerplate(1, text, &cfg());
         assert!(result.is_some());
         let d = result.unwrap();
         assert_eq!(d.trash_type, TrashType::Boilerplate);
+        assert_eq!(d.code, TrashReasonCode::BoilerplateKeywords);
         assert!(d.confidence >= 0.85);
     }
 
     #[test]
     fn test_detect_boilerplate_single_keyword_long_page() {
         let text = format!("Copyright 2024. {}", "x".repeat(600));
-        let result = detect_boilerplate(1, &text);
+        let result = detect_boilerplate(1, &text, &cfg());
         assert!(result.is_none()); // Long page with single keyword = no detection
     }
 
@@ -300,9 +514,9 @@ mod tests {
     fn test_no_false_positive_on_normal_text() {
         let text = "This is a normal paragraph about the product features. \
                      It describes how to install and configure the system.";
-        assert!(detect_toc(1, text).is_none());
-        assert!(detect_boilerplate(1, text).is_none());
-        assert!(detect_blank(1, text).is_none());
+        assert!(detect_toc(1, text, &cfg()).is_none());
+        assert!(detect_boilerplate(1, text, &cfg()).is_none());
+        assert!(detect_blank(1, text, &cfg()).is_none());
     }
 
     #[test]
@@ -312,10 +526,29 @@ mod tests {
             (1, "Normal content here with enough text to pass blank detection.".to_string()),
             (2, "  ".to_string()),
         ];
-        let results = detect_trash(&pages);
+        let results = detect_trash(&pages, &TrashConfig::default());
         assert_eq!(results.len(), 2); // TOC + blank
     }
 
+    #[test]
+    fn test_presets_change_sensitivity() {
+        let copyright = "Copyright 2024 Company.";
+        let sparse = "Notes:\n\n(continued on the next page)";
+        let conservative = TrashPreset::Conservative.config();
+        let aggressive = TrashPreset::Aggressive.config();
+
+        assert!(detect_boilerplate(1, copyright, &cfg()).is_some());
+        assert!(detect_boilerplate(1, copyright, &conservative).is_none());
+        assert!(detect_blank(1, sparse, &cfg()).is_some());
+        assert!(detect_blank(1, sparse, &conservative).is_none());
+
+        let pages = vec![(0, "   ".to_string()), (1, copyright.to_string())];
+        assert_eq!(detect_trash(&pages, &conservative).len(), 0); // 0.80 / 0.65 < 0.85
+        // Aggressive also counts the short copyright page as nearly blank
+        assert_eq!(detect_trash(&pages, &aggressive).len(), 3);
+        assert_eq!("Aggressive".parse::<TrashPreset>(), Ok(TrashPreset::Aggressive));
+    }
+
     #[test]
     fn test_header_footer_detections() {
         let pages = vec![
@@ -328,5 +561,47 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].trash_type, TrashType::HeaderFooter);
         assert_eq!(results[0].page, 0); // document-level
+        assert_eq!(results[0].code, TrashReasonCode::RepeatedText);
+    }
+
+    #[test]
+    fn test_reason_code_serde() {
+        let text = (1..=6)
+            .map(|i| format!("Section {i} ........ {}", i * 3))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let d = detect_toc(2, &text, &cfg()).unwrap();
+        assert_eq!(d.code, TrashReasonCode::DotLeaders);
+        let json = serde_json::to_value(&d).unwrap();
+        assert_eq!(json["code"], d.code.as_str());
+
+        // Older trash.json files have no `code` field.
+        let legacy: TrashDetection = serde_json::from_str(
+            r#"{"page":1,"trash_type":"blank_page","confidence":0.8,"reason":"x","preview":""}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.code, TrashReasonCode::Other);
+    }
+
+    #[test]
+    fn test_pages_to_strip_filters_by_type_and_code() {
+        let detection = |page, trash_type, code| TrashDetection {
+            page,
+            trash_type,
+            confidence: 0.9,
+            code,
+            reason: String::new(),
+            preview: String::new(),
+        };
+        let items = vec![
+            detection(3, TrashType::BlankPage, TrashReasonCode::ShortPage),
+            detection(2, TrashType::TableOfContents, TrashReasonCode::DotLeaders),
+            detection(0, TrashType::HeaderFooter, TrashReasonCode::RepeatedText),
+            detection(2, TrashType::Boilerplate, TrashReasonCode::Other),
+        ];
+        assert_eq!(pages_to_strip(&items, None), vec![2, 3]);
+        assert_eq!(pages_to_strip(&items, Some("blank")), vec![3]);
+        assert_eq!(pages_to_strip(&items, Some("dot_leaders, blank")), vec![2, 3]);
+        assert!(pages_to_strip(&items, Some("header_footer")).is_empty());
     }
 }
@@ -0,0 +1,108 @@
+use crate::error::CoreResult;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Result of cross-checking markdown `[IMAGE:...]` tags against image files.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+    /// Number of image tags found in the markdown.
+    pub referenced: usize,
+    /// Number of image files found in the images directory.
+    pub files: usize,
+    /// Tags whose image file does not exist.
+    pub dangling_tags: Vec<String>,
+    /// Image files not referenced by any tag (as `{dir}/{file}`, matching tag form).
+    pub orphan_files: Vec<String>,
+}
+
+impl VerifyReport {
+    /// True when every tag resolves and every image file is referenced.
+    pub fn is_ok(&self) -> bool {
+        self.dangling_tags.is_empty() && self.orphan_files.is_empty()
+    }
+}
+
+/// Cross-reference `[IMAGE:path]` tags in a markdown file with the files in
+/// the document's images directory (`output/images/{doc_stem}`).
+///
+/// Tag paths are relative to the parent `images/` directory, e.g.
+/// `[IMAGE:report/page3_img1.png]`. Metadata sidecars (`*.json`) and hidden
+/// temp files are not counted as images.
+pub async fn verify_output(markdown_path: &Path, images_dir: &Path) -> CoreResult<VerifyReport> {
+    let markdown = tokio::fs::read_to_string(markdown_path).await?;
+    let refs: BTreeSet<String> = extract_image_refs(&markdown).into_iter().collect();
+
+    let images_root = images_dir.parent().unwrap_or(images_dir);
+    let dir_name = images_dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+
+    let mut files = BTreeSet::new();
+    if let Ok(mut entries) = tokio::fs::read_dir(images_dir).await {
+        while let Some(entry) = entries.next_entry().await? {
+            if !entry.file_type().await?.is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') || name.ends_with(".json") {
+                continue;
+            }
+            files.insert(format!("{dir_name}/{name}"));
+        }
+    }
+
+    let mut dangling_tags = Vec::new();
+    for r in &refs {
+        if !files.contains(r) && !images_root.join(r).is_file() {
+            dangling_tags.push(r.clone());
+        }
+    }
+
+    let orphan_files = files.difference(&refs).cloned().collect();
+
+    Ok(VerifyReport {
+        referenced: refs.len(),
+        files: files.len(),
+        dangling_tags,
+        orphan_files,
+    })
+}
+
+/// Extract all `[IMAGE:path]` references from markdown, in order of appearance.
+pub fn extract_image_refs(markdown: &str) -> Vec<String> {
+    let mut refs = Vec::new();
+    let mut rest = markdown;
+    while let Some(start) = rest.find("[IMAGE:") {
+        rest = &rest[start + "[IMAGE:".len()..];
+        let Some(end) = rest.find(']') else {
+            break;
+        };
+        let path = rest[..end].trim();
+        if !path.is_empty() && !path.contains('\n') {
+            refs.push(path.to_string());
+        }
+        rest = &rest[end + 1..];
+    }
+    refs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_image_refs() {
+        let md = "## Page 1\n[IMAGE:doc/page1_img1.png]\n**[ภาพที่ 1]:** x\n\n[IMAGE:doc/page1_full.png]\n";
+        assert_eq!(
+            extract_image_refs(md),
+            vec!["doc/page1_img1.png", "doc/page1_full.png"]
+        );
+    }
+
+    #[test]
+    fn test_extract_ignores_unterminated_tag() {
+        assert!(extract_image_refs("text [IMAGE:broken\nmore").is_empty());
+    }
+}
@@ -1,5 +1,5 @@
 use axum::extract::DefaultBodyLimit;
-use axum::routing::{delete, get, post, put};
+use axum::routing::{delete, get, patch, post, put};
 use axum::Router;
 use std::sync::Arc;
 use tower_http::cors::CorsLayer;
@@ -7,25 +7,42 @@ use tower_http::services::{ServeDir, ServeFile};
 
 use crate::routes;
 use crate::state::AppState;
-use crate::ws;
+use crate::{sse, ws};
 
 /// Build the Axum application router.
 pub fn create_app(state: Arc<AppState>) -> Router {
     let api_routes = Router::new()
         .route("/api/health", get(routes::health::health_check))
+        .route("/api/admin/pause", post(routes::admin::pause))
+        .route("/api/admin/resume", post(routes::admin::resume))
         .route("/api/upload", post(routes::upload::upload_pdf))
         .route("/api/jobs", get(routes::jobs::list_jobs))
+        .route("/api/jobs/cleanup", post(routes::jobs::cleanup_jobs))
         .route("/api/jobs/{id}", get(routes::jobs::get_job))
         .route("/api/jobs/{id}", delete(routes::jobs::delete_job))
+        .route("/api/jobs/{id}/children", get(routes::jobs::list_children))
+        .route("/api/jobs/{id}/merge", post(routes::jobs::merge_job))
         .route("/api/jobs/{id}/cancel", post(routes::jobs::cancel_job))
+        .route("/api/jobs/{id}/retry", post(routes::jobs::retry_job))
+        .route("/api/jobs/{id}/config", patch(routes::jobs::update_job_config))
+        .route("/api/jobs/{id}/events", get(sse::sse_handler))
         .route("/api/results/{job_id}", get(routes::results::get_results))
+        .route("/api/results/{job_id}/trash", get(routes::results::get_trash))
         .route("/api/results/{job_id}/clean", post(routes::clean::clean_results))
+        .route("/api/results/{job_id}/strip-trash", post(routes::clean::strip_trash))
         .route("/api/results/{job_id}/export", get(routes::export::export_zip))
+        .route("/api/results/{job_id}/export.docx", get(routes::docx::export_docx))
+        .route("/api/results/{job_id}/export.html", get(routes::html::export_html))
         .route("/api/results/{job_id}/deploy", post(routes::deploy::deploy_handler))
+        .route("/api/results/{job_id}/markdown", get(routes::markdown::get_markdown))
         .route("/api/results/{job_id}/markdown", post(routes::markdown::save_markdown))
+        .route("/api/results/{job_id}/tables.csv", get(routes::tables::export_tables_csv))
+        .route("/api/results/{job_id}/tables.xlsx", get(routes::tables::export_tables_xlsx))
         .route("/api/results/{job_id}/images/delete", post(routes::images::delete_images))
+        .route("/api/catalog", get(routes::catalog::get_catalog))
         .route("/api/pdf/{job_id}", get(routes::pdf::serve_pdf))
         .route("/api/config", get(routes::config::get_config))
+        .route("/api/providers/check", post(routes::providers::check_provider))
         .route("/api/settings/notifications", get(routes::settings::get_notification_settings))
         .route("/api/settings/notifications", put(routes::settings::update_notification_settings))
         .route("/api/settings/notifications/test", post(routes::settings::test_notification));
@@ -1,3 +1,5 @@
+use jay_rag_core::http::{send_with_retry, HttpConfig};
+use jay_rag_core::redact_secrets;
 use serde_json::json;
 use tracing::info;
 
@@ -23,18 +25,17 @@ pub async fn upsert_document(
         }]
     });
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+    let http = HttpConfig::from_env();
+    let client = http.client().map_err(|e| e.to_string())?;
 
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {api_key}"))
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| format!("Flowise API request failed: {e}"))?;
+    let response = send_with_retry(&http, || {
+        client
+            .post(&url)
+            .header("Authorization", format!("Bearer {api_key}"))
+            .json(&body)
+    })
+    .await
+    .map_err(|e| redact_secrets(&format!("Flowise API request failed: {e}")))?;
 
     let status = response.status();
     let response_text = response
@@ -43,9 +44,9 @@ pub async fn upsert_document(
         .map_err(|e| format!("Failed to read Flowise response: {e}"))?;
 
     if !status.is_success() {
-        return Err(format!(
+        return Err(redact_secrets(&format!(
             "Flowise API returned {status}: {response_text}"
-        ));
+        )));
     }
 
     info!("Successfully upserted document to Flowise store {store_id}");
@@ -1,8 +1,16 @@
-use std::path::Path;
+use base64::Engine;
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
 use tracing::info;
 
 use crate::routes::deploy::ImageTarget;
 
+/// SSH session timeout for blocking reads/writes.
+const SSH_TIMEOUT_MS: u32 = 30_000;
+
+/// Known hosts file checked when a target pins no host key.
+const DEFAULT_KNOWN_HOSTS: &str = "~/.ssh/known_hosts";
+
 /// Deploy images to the chosen target. Returns a summary string.
 pub async fn deploy_images(target: &ImageTarget, images_dir: &Path) -> Result<String, String> {
     match target {
@@ -12,13 +20,33 @@ pub async fn deploy_images(target: &ImageTarget, images_dir: &Path) -> Result<St
             prefix,
             region,
         } => deploy_to_s3(images_dir, bucket, prefix, region.as_deref()).await,
+        ImageTarget::Gcs { bucket, prefix } => deploy_to_gcs(images_dir, bucket, prefix).await,
         ImageTarget::Scp {
             host,
             port,
             username,
+            private_key_path,
             remote_path,
-            ..
-        } => deploy_to_scp(images_dir, host, *port, username, remote_path).await,
+            known_hosts_path,
+            host_key_fingerprint,
+        } => {
+            let host_key = match host_key_fingerprint {
+                Some(fingerprint) => HostKeyCheck::Fingerprint(fingerprint.clone()),
+                None => HostKeyCheck::KnownHosts(expand_home(
+                    known_hosts_path.as_deref().unwrap_or(DEFAULT_KNOWN_HOSTS),
+                )),
+            };
+            deploy_to_scp(
+                images_dir,
+                host,
+                *port,
+                username,
+                private_key_path.as_deref(),
+                remote_path,
+                host_key,
+            )
+            .await
+        }
     }
 }
 
@@ -105,15 +133,232 @@ async fn deploy_to_s3(
     Ok(format!("{count} images uploaded to s3://{bucket}/{prefix}"))
 }
 
+async fn deploy_to_gcs(images_dir: &Path, bucket: &str, prefix: &str) -> Result<String, String> {
+    use jay_rag_storage::{GcsStorage, StorageBackend};
+
+    // GcsStorage uses Application Default Credentials
+    let storage = GcsStorage::new(bucket.to_string(), prefix.to_string(), String::new())
+        .await
+        .map_err(|e| format!("Failed to initialize GCS storage: {e}"))?;
+
+    let mut entries = tokio::fs::read_dir(images_dir)
+        .await
+        .map_err(|e| format!("Failed to read images directory: {e}"))?;
+
+    let mut count = 0u32;
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| format!("Failed to read dir entry: {e}"))?
+    {
+        let path = entry.path();
+        if path.is_file() {
+            let file_name = path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("image.png");
+            let bytes = tokio::fs::read(&path)
+                .await
+                .map_err(|e| format!("Failed to read {file_name}: {e}"))?;
+
+            storage
+                .write_bytes(file_name, &bytes)
+                .await
+                .map_err(|e| format!("Failed to upload {file_name} to GCS: {e}"))?;
+            count += 1;
+        }
+    }
+
+    info!("Deployed {count} images to GCS gs://{bucket}/{prefix}");
+    Ok(format!("{count} images uploaded to gs://{bucket}/{prefix}"))
+}
+
 async fn deploy_to_scp(
-    _images_dir: &Path,
+    images_dir: &Path,
     host: &str,
     port: Option<u16>,
     username: &str,
+    private_key_path: Option<&str>,
     remote_path: &str,
+    host_key: HostKeyCheck,
 ) -> Result<String, String> {
-    let _port = port.unwrap_or(22);
-    Err(format!(
-        "SCP/SFTP deployment to {username}@{host}:{remote_path} is not yet implemented (Stage 3)"
-    ))
+    let images_dir = images_dir.to_path_buf();
+    let host = host.to_string();
+    let port = port.unwrap_or(22);
+    let username = username.to_string();
+    let key_path = private_key_path.map(expand_home);
+    let remote_path = remote_path.to_string();
+
+    // ssh2 is blocking: run the whole session on a blocking thread
+    tokio::task::spawn_blocking(move || {
+        sftp_upload(
+            &images_dir,
+            &host,
+            port,
+            &username,
+            key_path.as_deref(),
+            &remote_path,
+            &host_key,
+        )
+    })
+    .await
+    .map_err(|e| format!("SFTP upload task failed: {e}"))?
+}
+
+/// How the server's host key is verified before authenticating.
+enum HostKeyCheck {
+    /// OpenSSH-style SHA256 fingerprint (`SHA256:<base64>`) the key must have.
+    Fingerprint(String),
+    /// Known hosts file that must list the host with this key.
+    KnownHosts(PathBuf),
+}
+
+/// Upload every file in `images_dir` to `remote_path` over SFTP (blocking).
+///
+/// Refuses to authenticate unless the server's host key passes `host_key`.
+/// Authenticates with `key_path` when given, otherwise with the running
+/// ssh-agent.
+fn sftp_upload(
+    images_dir: &Path,
+    host: &str,
+    port: u16,
+    username: &str,
+    key_path: Option<&Path>,
+    remote_path: &str,
+    host_key: &HostKeyCheck,
+) -> Result<String, String> {
+    let target = format!("{username}@{host}:{remote_path}");
+
+    if let Some(key) = key_path.filter(|key| !key.is_file()) {
+        return Err(format!("Private key not found: {}", key.display()));
+    }
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(images_dir)
+        .map_err(|e| format!("Failed to read images directory: {e}"))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+
+    let tcp = TcpStream::connect((host, port))
+        .map_err(|e| format!("Failed to connect to {host}:{port}: {e}"))?;
+    let mut session =
+        ssh2::Session::new().map_err(|e| format!("Failed to start SSH session: {e}"))?;
+    session.set_tcp_stream(tcp);
+    session.set_timeout(SSH_TIMEOUT_MS);
+    session
+        .handshake()
+        .map_err(|e| format!("SSH handshake with {host}:{port} failed: {e}"))?;
+    verify_host_key(&session, host, port, host_key)?;
+
+    match key_path {
+        Some(key) => session.userauth_pubkey_file(username, None, key, None),
+        None => session.userauth_agent(username),
+    }
+    .map_err(|e| format!("SSH authentication failed for {username}@{host}: {e}"))?;
+    if !session.authenticated() {
+        return Err(format!("SSH authentication failed for {username}@{host}"));
+    }
+
+    let sftp = session
+        .sftp()
+        .map_err(|e| format!("Failed to open SFTP channel: {e}"))?;
+    let remote_dir = Path::new(remote_path);
+    create_remote_dir(&sftp, remote_dir)?;
+
+    let mut count = 0u32;
+    for path in &files {
+        let file_name = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("image.png");
+        let mut local =
+            std::fs::File::open(path).map_err(|e| format!("Failed to read {file_name}: {e}"))?;
+        let mut remote = sftp
+            .create(&remote_dir.join(file_name))
+            .map_err(|e| format!("Failed to create {file_name} on {host}: {e}"))?;
+        std::io::copy(&mut local, &mut remote)
+            .map_err(|e| format!("Failed to upload {file_name}: {e}"))?;
+        count += 1;
+    }
+
+    info!("Deployed {count} images over SFTP to {target}");
+    Ok(format!("{count} images uploaded to {target}"))
+}
+
+/// Check the server's host key against `check`. Any key that cannot be
+/// verified is rejected.
+fn verify_host_key(
+    session: &ssh2::Session,
+    host: &str,
+    port: u16,
+    check: &HostKeyCheck,
+) -> Result<(), String> {
+    match check {
+        HostKeyCheck::Fingerprint(expected) => {
+            let hash = session
+                .host_key_hash(ssh2::HashType::Sha256)
+                .ok_or_else(|| format!("{host}:{port} sent no host key"))?;
+            let actual = base64::engine::general_purpose::STANDARD_NO_PAD.encode(hash);
+            let expected = expected.trim();
+            let expected = expected.strip_prefix("SHA256:").unwrap_or(expected);
+            if expected.trim_end_matches('=') != actual {
+                return Err(format!(
+                    "Host key of {host}:{port} (SHA256:{actual}) does not match the pinned one"
+                ));
+            }
+        }
+        HostKeyCheck::KnownHosts(path) => {
+            let (key, _) = session
+                .host_key()
+                .ok_or_else(|| format!("{host}:{port} sent no host key"))?;
+            let mut known_hosts = session
+                .known_hosts()
+                .map_err(|e| format!("Failed to check host key: {e}"))?;
+            known_hosts
+                .read_file(path, ssh2::KnownHostFileKind::OpenSSH)
+                .map_err(|e| format!("Failed to read known hosts file {}: {e}", path.display()))?;
+            match known_hosts.check_port(host, port, key) {
+                ssh2::CheckResult::Match => {}
+                ssh2::CheckResult::Mismatch => {
+                    return Err(format!(
+                        "Host key of {host}:{port} does not match {}",
+                        path.display()
+                    ));
+                }
+                ssh2::CheckResult::NotFound | ssh2::CheckResult::Failure => {
+                    return Err(format!(
+                        "Host {host}:{port} is not listed in {}",
+                        path.display()
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `mkdir -p` over SFTP.
+fn create_remote_dir(sftp: &ssh2::Sftp, dir: &Path) -> Result<(), String> {
+    let mut current = PathBuf::new();
+    for component in dir.components() {
+        current.push(component);
+        if sftp.stat(&current).is_err() {
+            sftp.mkdir(&current, 0o755).map_err(|e| {
+                format!(
+                    "Failed to create remote directory {}: {e}",
+                    current.display()
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Expand a leading `~/` to `$HOME`.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
 }
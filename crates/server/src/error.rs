@@ -27,6 +27,7 @@ impl IntoResponse for ApiError {
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
             ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
         };
+        let message = jay_rag_core::redact_secrets(&message);
 
         let body = axum::Json(ErrorResponse { error: message });
         (status, body).into_response()
@@ -1,3 +1,5 @@
 pub mod models;
 pub mod queue;
+pub mod retention;
 pub mod runner;
+pub mod storage;
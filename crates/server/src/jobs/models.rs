@@ -1,4 +1,5 @@
 use chrono::NaiveDateTime;
+use jay_rag_core::config::PageSelection;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -25,6 +26,15 @@ pub struct JobConfig {
     pub start_page: Option<u32>,
     #[serde(default)]
     pub end_page: Option<u32>,
+    /// 1-indexed pages left out of the output entirely.
+    #[serde(default)]
+    pub skip_pages: PageSelection,
+    /// PDF layers to render (empty: the document's default layers).
+    #[serde(default)]
+    pub include_layers: Vec<String>,
+    /// PDF layers to hide when rendering.
+    #[serde(default)]
+    pub exclude_layers: Vec<String>,
     #[serde(default)]
     pub table_extraction: bool,
     #[serde(default)]
@@ -36,15 +46,167 @@ pub struct JobConfig {
     #[serde(default)]
     pub s3_prefix: Option<String>,
     #[serde(default)]
+    pub gcs_bucket: Option<String>,
+    #[serde(default)]
+    pub gcs_prefix: Option<String>,
+    #[serde(default)]
+    pub azure_account: Option<String>,
+    #[serde(default)]
+    pub azure_container: Option<String>,
+    #[serde(default)]
+    pub azure_prefix: Option<String>,
+    /// Local or NFS target directory, relative to the server's storage root.
+    #[serde(default)]
     pub storage_path: Option<String>,
     #[serde(default = "default_quality")]
     pub quality: String,
     #[serde(default)]
     pub dpi: Option<u32>,
+    #[serde(default = "default_max_render_pixels")]
+    pub max_render_pixels: u32,
+    #[serde(default = "default_image_encoding")]
+    pub image_encoding: String,
+    #[serde(default = "default_saved_image_format")]
+    pub saved_image_format: String,
     #[serde(default = "default_true")]
     pub notify: bool,
     #[serde(default)]
     pub enhance: bool,
+    #[serde(default)]
+    pub auto_rotate: bool,
+    #[serde(default = "default_figure_numbering")]
+    pub figure_numbering: String,
+    #[serde(default)]
+    pub emit_image_sidecars: bool,
+    #[serde(default)]
+    pub image_context: bool,
+    /// Stream high-quality transcriptions to the dashboard as they arrive.
+    #[serde(default)]
+    pub stream_text: bool,
+    #[serde(default)]
+    pub custom_full_page: Option<String>,
+    #[serde(default)]
+    pub custom_single_image: Option<String>,
+    #[serde(default)]
+    pub custom_table: Option<String>,
+    #[serde(default = "default_true")]
+    pub strip_repeated_lines: bool,
+    #[serde(default)]
+    pub max_markdown_bytes: Option<usize>,
+    #[serde(default)]
+    pub premium_provider: Option<String>,
+    #[serde(default)]
+    pub premium_model: Option<String>,
+    #[serde(default)]
+    pub extract_form_fields: bool,
+    #[serde(default)]
+    pub emit_toc: bool,
+    #[serde(default)]
+    pub chunk_noise_patterns: Vec<String>,
+    #[serde(default)]
+    pub pages_per_request: Option<usize>,
+    #[serde(default)]
+    pub escalate_empty_text: bool,
+    #[serde(default)]
+    pub auto_high_quality_on_scanned: bool,
+    #[serde(default)]
+    pub dedup_images: bool,
+    #[serde(default)]
+    pub dedup_pages: bool,
+    #[serde(default)]
+    pub max_concurrent_requests: Option<usize>,
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    #[serde(default)]
+    pub page_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub rag_jsonl: bool,
+    #[serde(default = "default_wide_table_strategy")]
+    pub wide_table_strategy: String,
+    #[serde(default)]
+    pub wide_table_max_columns: Option<usize>,
+    #[serde(default)]
+    pub table_multispace_ratio: Option<f64>,
+    #[serde(default)]
+    pub table_min_consistent_run: Option<usize>,
+    #[serde(default = "default_line_ending")]
+    pub line_ending: String,
+    #[serde(default = "default_trash_preset")]
+    pub trash_preset: String,
+    #[serde(default = "default_page_fallback_strategy")]
+    pub page_fallback_strategy: String,
+    #[serde(default = "default_caption_placement")]
+    pub caption_placement: String,
+    /// Split uploads longer than this many pages into child jobs of this
+    /// many pages each, tracked under a parent job.
+    #[serde(default)]
+    pub split_every: Option<usize>,
+}
+
+impl Default for JobConfig {
+    fn default() -> Self {
+        Self {
+            provider: "ollama".to_string(),
+            model: None,
+            language: default_language(),
+            start_page: None,
+            end_page: None,
+            skip_pages: PageSelection::default(),
+            include_layers: Vec::new(),
+            exclude_layers: Vec::new(),
+            table_extraction: false,
+            text_only: false,
+            storage: default_storage(),
+            s3_bucket: None,
+            s3_prefix: None,
+            gcs_bucket: None,
+            gcs_prefix: None,
+            azure_account: None,
+            azure_container: None,
+            azure_prefix: None,
+            storage_path: None,
+            quality: default_quality(),
+            dpi: None,
+            max_render_pixels: default_max_render_pixels(),
+            image_encoding: default_image_encoding(),
+            saved_image_format: default_saved_image_format(),
+            notify: true,
+            enhance: false,
+            auto_rotate: false,
+            figure_numbering: default_figure_numbering(),
+            emit_image_sidecars: false,
+            image_context: false,
+            stream_text: false,
+            custom_full_page: None,
+            custom_single_image: None,
+            custom_table: None,
+            strip_repeated_lines: true,
+            max_markdown_bytes: None,
+            premium_provider: None,
+            premium_model: None,
+            extract_form_fields: false,
+            emit_toc: false,
+            chunk_noise_patterns: Vec::new(),
+            pages_per_request: None,
+            escalate_empty_text: false,
+            auto_high_quality_on_scanned: false,
+            dedup_images: false,
+            dedup_pages: false,
+            max_concurrent_requests: None,
+            requests_per_minute: None,
+            page_timeout_secs: None,
+            rag_jsonl: false,
+            wide_table_strategy: default_wide_table_strategy(),
+            wide_table_max_columns: None,
+            table_multispace_ratio: None,
+            table_min_consistent_run: None,
+            line_ending: default_line_ending(),
+            trash_preset: default_trash_preset(),
+            page_fallback_strategy: default_page_fallback_strategy(),
+            caption_placement: default_caption_placement(),
+            split_every: None,
+        }
+    }
 }
 
 fn default_true() -> bool {
@@ -63,6 +225,42 @@ fn default_quality() -> String {
     "standard".to_string()
 }
 
+fn default_max_render_pixels() -> u32 {
+    4096
+}
+
+fn default_image_encoding() -> String {
+    "png".to_string()
+}
+
+fn default_saved_image_format() -> String {
+    "png".to_string()
+}
+
+fn default_figure_numbering() -> String {
+    "per_page".to_string()
+}
+
+fn default_wide_table_strategy() -> String {
+    "keep".to_string()
+}
+
+fn default_line_ending() -> String {
+    "lf".to_string()
+}
+
+fn default_trash_preset() -> String {
+    "balanced".to_string()
+}
+
+fn default_page_fallback_strategy() -> String {
+    "none".to_string()
+}
+
+fn default_caption_placement() -> String {
+    "after".to_string()
+}
+
 /// Progress update for a job.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobProgress {
@@ -71,6 +269,25 @@ pub struct JobProgress {
     pub images_processed: u32,
     pub phase: String,
     pub message: String,
+    /// Transcription of a page still in progress, when streaming (`phase: "transcribing"`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partial: Option<PartialText>,
+}
+
+impl JobProgress {
+    /// Last update of a run: progress streams end after it. `"warning"` and
+    /// per-page `"page_error"` updates are not final.
+    pub fn is_final(&self) -> bool {
+        matches!(self.phase.as_str(), "complete" | "error" | "cancelled")
+    }
+}
+
+/// Text streamed so far for one page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialText {
+    /// 1-indexed page number.
+    pub page: u32,
+    pub text: String,
 }
 
 /// Result of a completed job.
@@ -84,6 +301,27 @@ pub struct JobResult {
     pub trash_path: Option<String>,
     #[serde(default)]
     pub trash_count: u32,
+    #[serde(default)]
+    pub markdown_parts: Vec<String>,
+    #[serde(default)]
+    pub rag_jsonl_path: Option<String>,
+    /// Per-page JSON Lines output (when the output format includes it).
+    #[serde(default)]
+    pub pages_jsonl_path: Option<String>,
+    /// Per-page timings (vision modes only).
+    #[serde(default)]
+    pub manifest_path: Option<String>,
+    /// Vision LLM token usage, as reported by the provider.
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    /// The job was cancelled: the outputs hold only the pages finished before then.
+    #[serde(default)]
+    pub partial: bool,
+    /// Last page (1-indexed) in the outputs of a partial run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_page: Option<u32>,
 }
 
 /// A processing job.
@@ -101,6 +339,10 @@ pub struct Job {
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
     pub duration_seconds: Option<f64>,
+    /// Job this one is a page range of, when an upload was split
+    /// (`split_every`). The parent itself runs nothing: its status and
+    /// progress aggregate its children.
+    pub parent_id: Option<Uuid>,
 }
 
 impl Job {
@@ -119,6 +361,25 @@ impl Job {
             started_at: None,
             completed_at: None,
             duration_seconds: None,
+            parent_id: None,
+        }
+    }
+
+    /// Child job of `parent` processing pages `start_page..=end_page`.
+    pub fn new_child(parent: &Job, start_page: u32, end_page: u32) -> Self {
+        let stem = parent
+            .filename
+            .strip_suffix(".pdf")
+            .unwrap_or(&parent.filename);
+        let config = JobConfig {
+            start_page: Some(start_page),
+            end_page: Some(end_page),
+            split_every: None,
+            ..parent.config.clone()
+        };
+        Self {
+            parent_id: Some(parent.id),
+            ..Self::new(format!("{stem}_p{start_page}-{end_page}.pdf"), config)
         }
     }
 }
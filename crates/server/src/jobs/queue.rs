@@ -3,18 +3,79 @@ use super::models::{
     NotificationSettings,
 };
 use rusqlite::{params, Connection};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::{broadcast, Mutex};
 use uuid::Uuid;
 
+/// Which jobs [`JobQueue::list_jobs`] returns.
+#[derive(Debug, Clone, Default)]
+pub struct JobFilter {
+    pub status: Option<JobStatus>,
+    /// Case-insensitive substring of the filename.
+    pub filename: Option<String>,
+    /// Most jobs returned (default: all).
+    pub limit: Option<u32>,
+    /// Matching jobs skipped, newest first.
+    pub offset: u32,
+}
+
+/// Columns read by [`row_to_job`], in order.
+const JOB_COLUMNS: &str = "id, filename, status, config, progress, result, error, created_at, \
+     updated_at, started_at, completed_at, parent_id";
+
+/// `WHERE` clause applying a [`JobFilter`]: `?1` status, `?2` filename substring.
+const JOB_FILTER_SQL: &str = "WHERE (?1 IS NULL OR status = ?1)
+     AND (?2 IS NULL OR instr(lower(filename), lower(?2)) > 0)";
+
+/// Stored progress updates kept per job for clients that reconnect.
+const RECENT_PROGRESS_LEN: usize = 50;
+
 /// SQLite-backed job queue with broadcast channels for progress updates.
 #[derive(Clone)]
 pub struct JobQueue {
     db: Arc<std::sync::Mutex<Connection>>,
-    /// Per-job broadcast senders for live progress events (in-memory only).
-    progress_senders: Arc<Mutex<HashMap<Uuid, broadcast::Sender<JobProgress>>>>,
+    /// Per-job live progress events (in-memory only).
+    progress_channels: Arc<Mutex<HashMap<Uuid, ProgressChannel>>>,
+}
+
+/// A progress update sent on a job's channel.
+#[derive(Debug, Clone)]
+pub struct ProgressEvent {
+    /// Position of the update among all updates sent for the job, so
+    /// listeners can skip live updates already seen in a replay.
+    pub seq: u64,
+    pub progress: JobProgress,
+}
+
+/// Broadcast sender for a job's progress, with its latest stored updates.
+struct ProgressChannel {
+    tx: broadcast::Sender<ProgressEvent>,
+    /// Last [`RECENT_PROGRESS_LEN`] updates from `update_progress`, oldest first.
+    recent: VecDeque<ProgressEvent>,
+    next_seq: u64,
+}
+
+impl ProgressChannel {
+    fn new() -> Self {
+        Self {
+            tx: broadcast::channel(64).0,
+            recent: VecDeque::with_capacity(RECENT_PROGRESS_LEN),
+            next_seq: 0,
+        }
+    }
+
+    /// Number `progress` and send it to listeners.
+    fn send(&mut self, progress: JobProgress) -> ProgressEvent {
+        let event = ProgressEvent {
+            seq: self.next_seq,
+            progress,
+        };
+        self.next_seq += 1;
+        let _ = self.tx.send(event.clone());
+        event
+    }
 }
 
 impl JobQueue {
@@ -45,6 +106,8 @@ impl JobQueue {
             .ok();
         conn.execute("ALTER TABLE jobs ADD COLUMN completed_at TEXT", [])
             .ok();
+        conn.execute("ALTER TABLE jobs ADD COLUMN parent_id TEXT", [])
+            .ok();
 
         // Notification settings singleton table
         conn.execute_batch(
@@ -66,7 +129,7 @@ impl JobQueue {
 
         Ok(Self {
             db: Arc::new(std::sync::Mutex::new(conn)),
-            progress_senders: Arc::new(Mutex::new(HashMap::new())),
+            progress_channels: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
@@ -79,8 +142,8 @@ impl JobQueue {
         {
             let db = self.db.lock().expect("db lock poisoned");
             db.execute(
-                "INSERT INTO jobs (id, filename, status, config, created_at, updated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                "INSERT INTO jobs (id, filename, status, config, created_at, updated_at, parent_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
                 params![
                     id.to_string(),
                     job.filename,
@@ -88,13 +151,16 @@ impl JobQueue {
                     config_json,
                     job.created_at,
                     job.updated_at,
+                    job.parent_id.map(|p| p.to_string()),
                 ],
             )
             .expect("Failed to insert job");
         }
 
-        let (tx, _) = broadcast::channel(64);
-        self.progress_senders.lock().await.insert(id, tx);
+        self.progress_channels
+            .lock()
+            .await
+            .insert(id, ProgressChannel::new());
         id
     }
 
@@ -103,47 +169,102 @@ impl JobQueue {
         let id_str = id.to_string();
         let db = self.db.lock().expect("db lock poisoned");
         db.query_row(
-            "SELECT id, filename, status, config, progress, result, error, created_at, updated_at, started_at, completed_at
-             FROM jobs WHERE id = ?1",
+            &format!("SELECT {JOB_COLUMNS} FROM jobs WHERE id = ?1"),
             params![id_str],
             |row| row_to_job(row),
         )
         .ok()
     }
 
-    /// List all jobs, newest first.
-    pub async fn list_jobs(&self) -> Vec<Job> {
+    /// List the jobs matching `filter`, newest first.
+    pub async fn list_jobs(&self, filter: &JobFilter) -> Vec<Job> {
         let db = self.db.lock().expect("db lock poisoned");
         let mut stmt = db
-            .prepare(
-                "SELECT id, filename, status, config, progress, result, error, created_at, updated_at, started_at, completed_at
-                 FROM jobs ORDER BY created_at DESC",
-            )
+            .prepare(&format!(
+                "SELECT {JOB_COLUMNS} FROM jobs {JOB_FILTER_SQL}
+                 ORDER BY created_at DESC, rowid DESC LIMIT ?3 OFFSET ?4"
+            ))
             .expect("Failed to prepare list_jobs query");
 
-        stmt.query_map([], |row| row_to_job(row))
+        // SQLite treats a negative LIMIT as no limit
+        let limit = filter.limit.map_or(-1, i64::from);
+        stmt.query_map(
+            params![
+                filter.status.as_ref().map(status_to_str),
+                filter.filename,
+                limit,
+                filter.offset
+            ],
+            |row| row_to_job(row),
+        )
+        .expect("Failed to query jobs")
+        .filter_map(|r| r.ok())
+        .collect()
+    }
+
+    /// Completed, failed or cancelled jobs that finished before `cutoff` (an
+    /// [`iso_now`] timestamp). Pending and processing jobs never expire.
+    pub async fn list_expired_jobs(&self, cutoff: &str) -> Vec<Job> {
+        let db = self.db.lock().expect("db lock poisoned");
+        let mut stmt = db
+            .prepare(&format!(
+                "SELECT {JOB_COLUMNS} FROM jobs
+                 WHERE status IN ('completed', 'failed', 'cancelled') AND completed_at < ?1"
+            ))
+            .expect("Failed to prepare list_expired_jobs query");
+
+        stmt.query_map(params![cutoff], |row| row_to_job(row))
             .expect("Failed to query jobs")
             .filter_map(|r| r.ok())
             .collect()
     }
 
+    /// Child jobs of a split upload, in page order.
+    pub async fn list_children(&self, parent_id: &Uuid) -> Vec<Job> {
+        let db = self.db.lock().expect("db lock poisoned");
+        let mut stmt = db
+            .prepare(&format!(
+                "SELECT {JOB_COLUMNS} FROM jobs WHERE parent_id = ?1 ORDER BY rowid"
+            ))
+            .expect("Failed to prepare list_children query");
+
+        stmt.query_map(params![parent_id.to_string()], |row| row_to_job(row))
+            .expect("Failed to query jobs")
+            .filter_map(|r| r.ok())
+            .collect()
+    }
+
+    /// Number of jobs matching `filter`, ignoring its `limit` and `offset`.
+    pub async fn count_jobs(&self, filter: &JobFilter) -> u64 {
+        let db = self.db.lock().expect("db lock poisoned");
+        db.query_row(
+            &format!("SELECT COUNT(*) FROM jobs {JOB_FILTER_SQL}"),
+            params![filter.status.as_ref().map(status_to_str), filter.filename],
+            |row| row.get(0),
+        )
+        .unwrap_or(0)
+    }
+
     /// Update a job's status.
     pub async fn update_status(&self, id: &Uuid, status: JobStatus) {
         let now = iso_now();
-        let db = self.db.lock().expect("db lock poisoned");
-        if status == JobStatus::Processing {
-            db.execute(
-                "UPDATE jobs SET status = ?1, started_at = ?2, updated_at = ?2 WHERE id = ?3",
-                params![status_to_str(&status), now, id.to_string()],
-            )
-            .ok();
-        } else {
-            db.execute(
-                "UPDATE jobs SET status = ?1, updated_at = ?2 WHERE id = ?3",
-                params![status_to_str(&status), now, id.to_string()],
-            )
-            .ok();
+        {
+            let db = self.db.lock().expect("db lock poisoned");
+            if status == JobStatus::Processing {
+                db.execute(
+                    "UPDATE jobs SET status = ?1, started_at = ?2, updated_at = ?2 WHERE id = ?3",
+                    params![status_to_str(&status), now, id.to_string()],
+                )
+                .ok();
+            } else {
+                db.execute(
+                    "UPDATE jobs SET status = ?1, updated_at = ?2 WHERE id = ?3",
+                    params![status_to_str(&status), now, id.to_string()],
+                )
+                .ok();
+            }
         }
+        self.sync_parent(id).await;
     }
 
     /// Update a job's progress and broadcast to listeners.
@@ -160,8 +281,27 @@ impl JobQueue {
             .ok();
         }
 
-        if let Some(tx) = self.progress_senders.lock().await.get(id) {
-            let _ = tx.send(progress);
+        self.push_progress(id, progress).await;
+        self.sync_parent(id).await;
+    }
+
+    /// Store `progress` in a job's recent updates and send it to listeners.
+    async fn push_progress(&self, id: &Uuid, progress: JobProgress) {
+        if let Some(channel) = self.progress_channels.lock().await.get_mut(id) {
+            if channel.recent.len() == RECENT_PROGRESS_LEN {
+                channel.recent.pop_front();
+            }
+            let event = channel.send(progress);
+            channel.recent.push_back(event);
+        }
+    }
+
+    /// Broadcast a progress update to listeners without storing it.
+    ///
+    /// For frequent updates such as streamed text, not worth a DB write each.
+    pub async fn broadcast_progress(&self, id: &Uuid, progress: JobProgress) {
+        if let Some(channel) = self.progress_channels.lock().await.get_mut(id) {
+            channel.send(progress);
         }
     }
 
@@ -171,10 +311,24 @@ impl JobQueue {
             serde_json::to_string(&result).expect("JobResult serialization failed");
         let now = iso_now();
 
+        {
+            let db = self.db.lock().expect("db lock poisoned");
+            db.execute(
+                "UPDATE jobs SET status = 'completed', result = ?1, completed_at = ?2, updated_at = ?2 WHERE id = ?3",
+                params![result_json, now, id.to_string()],
+            )
+            .ok();
+        }
+        self.sync_parent(id).await;
+    }
+
+    /// Replace a job's result without changing its status.
+    pub async fn set_result(&self, id: &Uuid, result: &JobResult) {
+        let result_json = serde_json::to_string(result).expect("JobResult serialization failed");
         let db = self.db.lock().expect("db lock poisoned");
         db.execute(
-            "UPDATE jobs SET status = 'completed', result = ?1, completed_at = ?2, updated_at = ?2 WHERE id = ?3",
-            params![result_json, now, id.to_string()],
+            "UPDATE jobs SET result = ?1, updated_at = ?2 WHERE id = ?3",
+            params![result_json, iso_now(), id.to_string()],
         )
         .ok();
     }
@@ -182,23 +336,105 @@ impl JobQueue {
     /// Set a job as failed with an error message.
     pub async fn set_failed(&self, id: &Uuid, error: String) {
         let now = iso_now();
-        let db = self.db.lock().expect("db lock poisoned");
-        db.execute(
-            "UPDATE jobs SET status = 'failed', error = ?1, completed_at = ?2, updated_at = ?2 WHERE id = ?3",
-            params![error, now, id.to_string()],
-        )
-        .ok();
+        {
+            let db = self.db.lock().expect("db lock poisoned");
+            db.execute(
+                "UPDATE jobs SET status = 'failed', error = ?1, completed_at = ?2, updated_at = ?2 WHERE id = ?3",
+                params![error, now, id.to_string()],
+            )
+            .ok();
+        }
+        self.finish_progress(id, "error", error).await;
+        self.sync_parent(id).await;
     }
 
     /// Set a job as cancelled.
     pub async fn set_cancelled(&self, id: &Uuid) {
         let now = iso_now();
+        {
+            let db = self.db.lock().expect("db lock poisoned");
+            db.execute(
+                "UPDATE jobs SET status = 'cancelled', completed_at = ?1, updated_at = ?1 WHERE id = ?2",
+                params![now, id.to_string()],
+            )
+            .ok();
+        }
+        self.finish_progress(id, "cancelled", "Job cancelled".to_string())
+            .await;
+        self.sync_parent(id).await;
+    }
+
+    /// Store and send a job's last progress update, which ends its progress
+    /// streams. Keeps the page and image counts of the previous update.
+    async fn finish_progress(&self, id: &Uuid, phase: &str, message: String) {
+        let last = self.get_job(id).await.and_then(|job| job.progress);
+        let progress = JobProgress {
+            current_page: last.as_ref().map_or(0, |p| p.current_page),
+            total_pages: last.as_ref().map_or(0, |p| p.total_pages),
+            images_processed: last.as_ref().map_or(0, |p| p.images_processed),
+            phase: phase.to_string(),
+            message,
+            partial: None,
+        };
+        let progress_json =
+            serde_json::to_string(&progress).expect("JobProgress serialization failed");
+        {
+            let db = self.db.lock().expect("db lock poisoned");
+            db.execute(
+                "UPDATE jobs SET progress = ?1 WHERE id = ?2",
+                params![progress_json, id.to_string()],
+            )
+            .ok();
+        }
+        self.push_progress(id, progress).await;
+    }
+
+    /// Put a failed or cancelled job back to `pending` so it can run again.
+    ///
+    /// Clears the previous progress, result, error and timings. Returns
+    /// `false` if the job does not exist or is not failed/cancelled.
+    pub async fn reset_for_retry(&self, id: &Uuid) -> bool {
+        let reset = {
+            let db = self.db.lock().expect("db lock poisoned");
+            db.execute(
+                "UPDATE jobs SET status = 'pending', progress = NULL, result = NULL, error = NULL,
+                 started_at = NULL, completed_at = NULL, updated_at = ?1
+                 WHERE id = ?2 AND status IN ('failed', 'cancelled')",
+                params![iso_now(), id.to_string()],
+            )
+            .map(|rows| rows > 0)
+            .unwrap_or(false)
+        };
+        if reset {
+            // Jobs from before a server restart have no progress channel yet;
+            // the previous run's updates must not be replayed
+            self.progress_channels
+                .lock()
+                .await
+                .entry(*id)
+                .or_insert_with(ProgressChannel::new)
+                .recent
+                .clear();
+            self.sync_parent(id).await;
+        }
+        reset
+    }
+
+    /// Replace the config of a job that has not started yet.
+    ///
+    /// Returns `false` if the job does not exist or is no longer pending; the
+    /// status check and update happen in one statement so a starting worker
+    /// cannot race it.
+    pub async fn update_pending_config(&self, id: &Uuid, config: &JobConfig) -> bool {
+        let config_json =
+            serde_json::to_string(config).expect("JobConfig serialization failed");
         let db = self.db.lock().expect("db lock poisoned");
         db.execute(
-            "UPDATE jobs SET status = 'cancelled', completed_at = ?1, updated_at = ?1 WHERE id = ?2",
-            params![now, id.to_string()],
+            "UPDATE jobs SET config = ?1, updated_at = ?2 WHERE id = ?3 AND status = 'pending'",
+            params![config_json, iso_now(), id.to_string()],
         )
-        .ok();
+        .map(|rows| rows > 0)
+        .unwrap_or(false)
     }
 
     /// Update a job's result (e.g. after image deletion changes image_count).
@@ -215,26 +451,95 @@ impl JobQueue {
 
     /// Delete a job.
     pub async fn delete_job(&self, id: &Uuid) -> bool {
+        let parent_id = self.parent_id(id);
         let removed = {
             let db = self.db.lock().expect("db lock poisoned");
             db.execute("DELETE FROM jobs WHERE id = ?1", params![id.to_string()])
                 .map(|n| n > 0)
                 .unwrap_or(false)
         };
-        self.progress_senders.lock().await.remove(id);
+        self.progress_channels.lock().await.remove(id);
+        if let Some(parent_id) = parent_id {
+            self.refresh_split_job(&parent_id).await;
+        }
         removed
     }
 
+    /// Parent of a child job of a split upload.
+    fn parent_id(&self, id: &Uuid) -> Option<Uuid> {
+        let db = self.db.lock().expect("db lock poisoned");
+        db.query_row(
+            "SELECT parent_id FROM jobs WHERE id = ?1",
+            params![id.to_string()],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .ok()
+        .flatten()
+        .and_then(|parent_id| Uuid::parse_str(&parent_id).ok())
+    }
+
+    /// After a job changed, update its parent if it is part of a split upload.
+    async fn sync_parent(&self, id: &Uuid) {
+        if let Some(parent_id) = self.parent_id(id) {
+            self.refresh_split_job(&parent_id).await;
+        }
+    }
+
+    /// Recompute a split upload's parent job from its children and send its
+    /// new progress to listeners.
+    async fn refresh_split_job(&self, parent_id: &Uuid) {
+        let children = self.list_children(parent_id).await;
+        if children.is_empty() {
+            return;
+        }
+        let (status, progress) = split_job_state(&children);
+        let error = (status == JobStatus::Failed).then(|| progress.message.clone());
+        let progress_json =
+            serde_json::to_string(&progress).expect("JobProgress serialization failed");
+
+        {
+            let db = self.db.lock().expect("db lock poisoned");
+            db.execute(
+                "UPDATE jobs SET status = ?1, progress = ?2, error = ?3, updated_at = ?4,
+                 started_at = CASE WHEN ?1 = 'pending' THEN NULL ELSE COALESCE(started_at, ?4) END,
+                 completed_at = CASE WHEN ?1 IN ('completed', 'failed', 'cancelled')
+                                THEN COALESCE(completed_at, ?4) END
+                 WHERE id = ?5",
+                params![
+                    status_to_str(&status),
+                    progress_json,
+                    error,
+                    iso_now(),
+                    parent_id.to_string()
+                ],
+            )
+            .ok();
+        }
+
+        self.push_progress(parent_id, progress).await;
+    }
+
     /// Subscribe to progress updates for a job.
     pub async fn subscribe_progress(
         &self,
         id: &Uuid,
-    ) -> Option<broadcast::Receiver<JobProgress>> {
-        self.progress_senders
+    ) -> Option<broadcast::Receiver<ProgressEvent>> {
+        self.progress_channels
             .lock()
             .await
             .get(id)
-            .map(|tx| tx.subscribe())
+            .map(|channel| channel.tx.subscribe())
+    }
+
+    /// Latest stored progress updates of a job, oldest first, so a client
+    /// that reconnects can catch up on what it missed.
+    pub async fn recent_progress(&self, id: &Uuid) -> Vec<ProgressEvent> {
+        self.progress_channels
+            .lock()
+            .await
+            .get(id)
+            .map(|channel| channel.recent.iter().cloned().collect())
+            .unwrap_or_default()
     }
 
     /// Get global notification settings.
@@ -264,6 +569,84 @@ impl JobQueue {
     }
 }
 
+/// Status and progress of a split upload's parent job, from its children.
+///
+/// Pages count as done for completed children and as reported by running
+/// ones. The parent runs until no child is pending or processing, then ends
+/// failed if any child failed, else cancelled if any was cancelled.
+fn split_job_state(children: &[Job]) -> (JobStatus, JobProgress) {
+    let mut pages_done = 0;
+    let mut total_pages = 0;
+    let mut images_processed = 0;
+    let count = |status: JobStatus| children.iter().filter(|c| c.status == status).count();
+    for child in children {
+        let pages = match (child.config.start_page, child.config.end_page) {
+            (Some(start), Some(end)) => (end + 1).saturating_sub(start),
+            _ => 0,
+        };
+        total_pages += pages;
+        match (&child.status, &child.result, &child.progress) {
+            (JobStatus::Completed, Some(result), _) => {
+                pages_done += pages;
+                images_processed += result.image_count;
+            }
+            (_, _, Some(progress)) if !progress.is_final() => {
+                pages_done += progress.current_page.min(pages);
+                images_processed += progress.images_processed;
+            }
+            _ => {}
+        }
+    }
+
+    let parts = children.len();
+    let pending = count(JobStatus::Pending);
+    let running = pending + count(JobStatus::Processing);
+    let completed = count(JobStatus::Completed);
+    let failed = count(JobStatus::Failed);
+    let cancelled = count(JobStatus::Cancelled);
+    let (status, phase, message) = if pending == parts {
+        (
+            JobStatus::Pending,
+            "processing",
+            format!("Waiting to start {parts} parts"),
+        )
+    } else if running > 0 {
+        (
+            JobStatus::Processing,
+            "processing",
+            format!("{completed}/{parts} parts done ({pages_done}/{total_pages} pages)"),
+        )
+    } else if failed > 0 {
+        (
+            JobStatus::Failed,
+            "error",
+            format!("{failed} of {parts} parts failed"),
+        )
+    } else if cancelled > 0 {
+        (
+            JobStatus::Cancelled,
+            "cancelled",
+            format!("{cancelled} of {parts} parts cancelled"),
+        )
+    } else {
+        (
+            JobStatus::Completed,
+            "complete",
+            format!("Complete: {parts} parts ({images_processed} images)"),
+        )
+    };
+
+    let progress = JobProgress {
+        current_page: pages_done,
+        total_pages,
+        images_processed,
+        phase: phase.to_string(),
+        message,
+        partial: None,
+    };
+    (status, progress)
+}
+
 /// Convert a rusqlite Row into a Job.
 fn row_to_job(row: &rusqlite::Row) -> rusqlite::Result<Job> {
     let id_str: String = row.get(0)?;
@@ -277,6 +660,7 @@ fn row_to_job(row: &rusqlite::Row) -> rusqlite::Result<Job> {
     let updated_at: String = row.get(8)?;
     let started_at: Option<String> = row.get(9)?;
     let completed_at: Option<String> = row.get(10)?;
+    let parent_id: Option<String> = row.get(11)?;
 
     let duration_seconds = match (&started_at, &completed_at) {
         (Some(s), Some(e)) => compute_duration_seconds(s, e),
@@ -296,6 +680,7 @@ fn row_to_job(row: &rusqlite::Row) -> rusqlite::Result<Job> {
         started_at,
         completed_at,
         duration_seconds,
+        parent_id: parent_id.and_then(|p| Uuid::parse_str(&p).ok()),
     })
 }
 
@@ -322,21 +707,184 @@ fn parse_status(s: &str) -> JobStatus {
 
 /// Fallback config when deserialization fails (should not happen in practice).
 fn default_config() -> JobConfig {
-    JobConfig {
-        provider: "ollama".to_string(),
-        model: None,
-        language: "th".to_string(),
-        start_page: None,
-        end_page: None,
-        table_extraction: false,
-        text_only: false,
-        storage: "local".to_string(),
-        s3_bucket: None,
-        s3_prefix: None,
-        storage_path: None,
-        quality: "standard".to_string(),
-        dpi: None,
-        notify: true,
-        enhance: false,
+    JobConfig::default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_jobs_filters_and_paginates() {
+        let queue = JobQueue::new(Path::new(":memory:")).unwrap();
+        for name in ["คู่มือ_A.pdf", "manual_b.pdf", "Manual_C.pdf"] {
+            queue
+                .add_job(Job::new(name.to_string(), JobConfig::default()))
+                .await;
+        }
+        let first = queue.list_jobs(&JobFilter::default()).await;
+        queue
+            .update_status(&first[0].id, JobStatus::Completed)
+            .await;
+
+        let manuals = JobFilter {
+            filename: Some("MANUAL".to_string()),
+            limit: Some(1),
+            offset: 1,
+            ..Default::default()
+        };
+        let page = queue.list_jobs(&manuals).await;
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].filename, "manual_b.pdf");
+        assert_eq!(queue.count_jobs(&manuals).await, 2);
+
+        let completed = JobFilter {
+            status: Some(JobStatus::Completed),
+            ..Default::default()
+        };
+        let jobs = queue.list_jobs(&completed).await;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].filename, "Manual_C.pdf");
+        assert_eq!(queue.count_jobs(&completed).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_split_job_aggregates_children() {
+        let queue = JobQueue::new(Path::new(":memory:")).unwrap();
+        let parent = Job::new("manual.pdf".to_string(), JobConfig::default());
+        queue.add_job(parent.clone()).await;
+        let first = Job::new_child(&parent, 1, 500);
+        let second = Job::new_child(&parent, 501, 800);
+        assert_eq!(second.filename, "manual_p501-800.pdf");
+        queue.add_job(first.clone()).await;
+        queue.add_job(second.clone()).await;
+
+        let children = queue.list_children(&parent.id).await;
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].id, first.id);
+        assert_eq!(children[1].parent_id, Some(parent.id));
+
+        queue.update_status(&first.id, JobStatus::Processing).await;
+        queue
+            .update_progress(
+                &first.id,
+                JobProgress {
+                    current_page: 120,
+                    total_pages: 500,
+                    images_processed: 7,
+                    phase: "processing".to_string(),
+                    message: String::new(),
+                    partial: None,
+                },
+            )
+            .await;
+        let job = queue.get_job(&parent.id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Processing);
+        let progress = job.progress.unwrap();
+        assert_eq!((progress.current_page, progress.total_pages), (120, 800));
+        assert_eq!(progress.images_processed, 7);
+
+        queue.set_failed(&first.id, "boom".to_string()).await;
+        queue.set_cancelled(&second.id).await;
+        let job = queue.get_job(&parent.id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.error.as_deref(), Some("1 of 2 parts failed"));
+        assert!(job.progress.unwrap().is_final());
+    }
+
+    #[tokio::test]
+    async fn test_expired_jobs_skip_unfinished_jobs() {
+        let queue = JobQueue::new(Path::new(":memory:")).unwrap();
+        let mut ids = Vec::new();
+        for status in ["pending", "processing", "done", "failed", "cancelled"] {
+            ids.push(
+                queue
+                    .add_job(Job::new(format!("{status}.pdf"), JobConfig::default()))
+                    .await,
+            );
+        }
+        queue.update_status(&ids[1], JobStatus::Processing).await;
+        let result: JobResult = serde_json::from_value(serde_json::json!({
+            "markdown_path": "output/done_enriched.md",
+            "metadata_path": "output/done_images_metadata.json",
+            "image_count": 0,
+            "images_dir": "output/images",
+        }))
+        .unwrap();
+        queue.set_completed(&ids[2], result).await;
+        queue.set_failed(&ids[3], "boom".to_string()).await;
+        queue.set_cancelled(&ids[4]).await;
+
+        let mut expired: Vec<String> = queue
+            .list_expired_jobs("9999-12-31T00:00:00Z")
+            .await
+            .into_iter()
+            .map(|job| job.filename)
+            .collect();
+        expired.sort();
+        assert_eq!(expired, ["cancelled.pdf", "done.pdf", "failed.pdf"]);
+        let cutoff = "2000-01-01T00:00:00Z";
+        assert!(queue.list_expired_jobs(cutoff).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replayed_progress_matches_live_sequence() {
+        let queue = JobQueue::new(Path::new(":memory:")).unwrap();
+        let id = queue
+            .add_job(Job::new("manual.pdf".to_string(), JobConfig::default()))
+            .await;
+        let mut rx = queue.subscribe_progress(&id).await.unwrap();
+        let progress = |phase: &str| JobProgress {
+            current_page: 1,
+            total_pages: 3,
+            images_processed: 0,
+            phase: phase.to_string(),
+            message: String::new(),
+            partial: None,
+        };
+        queue.update_progress(&id, progress("processing")).await;
+        queue
+            .broadcast_progress(&id, progress("transcribing"))
+            .await;
+        queue.update_progress(&id, progress("processing")).await;
+
+        // Streamed updates are numbered but not kept for replay
+        let recent: Vec<u64> = queue
+            .recent_progress(&id)
+            .await
+            .iter()
+            .map(|event| event.seq)
+            .collect();
+        assert_eq!(recent, [0, 2]);
+        let live: Vec<u64> = (0..3).map(|_| rx.try_recv().unwrap().seq).collect();
+        assert_eq!(live, [0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_runs_to_job_level_failure() {
+        let queue = JobQueue::new(Path::new(":memory:")).unwrap();
+        let id = queue
+            .add_job(Job::new("manual.pdf".to_string(), JobConfig::default()))
+            .await;
+        let progress = |phase: &str| JobProgress {
+            current_page: 2,
+            total_pages: 3,
+            images_processed: 1,
+            phase: phase.to_string(),
+            message: String::new(),
+            partial: None,
+        };
+        queue.update_progress(&id, progress("page_error")).await;
+        queue.update_progress(&id, progress("processing")).await;
+        queue.set_failed(&id, "provider unreachable".to_string()).await;
+
+        // A failed page does not end the replay; the job's failure does
+        let recent = queue.recent_progress(&id).await;
+        let finals: Vec<bool> = recent.iter().map(|e| e.progress.is_final()).collect();
+        assert_eq!(finals, [false, false, true]);
+        let last = &recent[2].progress;
+        assert_eq!(last.phase, "error");
+        assert_eq!(last.message, "provider unreachable");
+        assert_eq!(last.current_page, 2);
     }
 }
@@ -0,0 +1,91 @@
+use super::models::Job;
+use crate::state::AppState;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the retention task looks for expired jobs.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Delete a job with its uploaded PDF and output files, stopping it first if
+/// it is still running. The parts of a split upload are deleted with it.
+///
+/// Returns `false` if the job row was already gone.
+pub async fn remove_job(state: &AppState, job: &Job) -> bool {
+    for child in state.job_queue.list_children(&job.id).await {
+        remove_single_job(state, &child).await;
+    }
+    remove_single_job(state, job).await
+}
+
+async fn remove_single_job(state: &AppState, job: &Job) -> bool {
+    let id = job.id;
+
+    // Stop an in-flight run between pages; it removes its own partial output
+    if let Some(cancel) = state.cancel_tokens.lock().await.remove(&id) {
+        cancel.cancel();
+        tracing::info!("Job {id} cancelled for deletion");
+    }
+
+    // Clean up uploaded PDF
+    let pdf_path = state.upload_dir.join(format!("{id}.pdf"));
+    let _ = tokio::fs::remove_file(&pdf_path).await;
+
+    // Clean up output files: everything named after the job id (images,
+    // markdown and its parts, metadata, trash, JSON Lines, manifest), plus
+    // whatever local files the result records
+    if let Some(result) = &job.result {
+        let recorded = [&result.markdown_path, &result.metadata_path]
+            .into_iter()
+            .chain(&result.markdown_parts)
+            .chain(&result.trash_path)
+            .chain(&result.rag_jsonl_path)
+            .chain(&result.pages_jsonl_path)
+            .chain(&result.manifest_path)
+            .map(Path::new)
+            .filter(|path| path.starts_with(&state.output_dir));
+        for path in recorded {
+            let _ = tokio::fs::remove_file(path).await;
+        }
+    }
+    super::runner::remove_job_outputs(&state.output_dir, &id).await;
+    crate::routes::export::remove_cached_exports(&state.output_dir, &id).await;
+
+    // Delete the DB row
+    state.job_queue.delete_job(&id).await
+}
+
+/// Delete every completed, failed or cancelled job that finished more than
+/// `retention_days` ago. Queued and running jobs are kept.
+///
+/// Returns the number of jobs deleted.
+pub async fn cleanup_expired(state: &AppState, retention_days: u32) -> usize {
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(i64::from(retention_days));
+    let cutoff = cutoff.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    let mut deleted = 0;
+    for job in state.job_queue.list_expired_jobs(&cutoff).await {
+        if remove_job(state, &job).await {
+            deleted += 1;
+        }
+    }
+    if deleted > 0 {
+        tracing::info!("Retention: deleted {deleted} job(s) older than {retention_days} day(s)");
+    }
+    deleted
+}
+
+/// Run [`cleanup_expired`] now and then every [`RETENTION_INTERVAL`], when
+/// the server has a `retention_days` policy.
+pub fn spawn_retention_task(state: Arc<AppState>) {
+    let Some(retention_days) = state.retention_days else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RETENTION_INTERVAL);
+        loop {
+            interval.tick().await;
+            cleanup_expired(&state, retention_days).await;
+        }
+    });
+}
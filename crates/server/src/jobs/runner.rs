@@ -1,17 +1,40 @@
-use super::models::{JobProgress, JobResult, JobStatus};
+use super::models::{JobConfig, JobProgress, JobResult, JobStatus, PartialText};
 use super::queue::JobQueue;
 use jay_rag_core::config::{Language, ProcessingConfig, Quality};
 use jay_rag_core::progress::ProgressReporter;
 use jay_rag_core::provider;
-use std::path::PathBuf;
+use jay_rag_core::CoreError;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 /// Progress reporter that broadcasts updates via the job queue.
+///
+/// Pages complete out of order under concurrency, so `current_page` reports
+/// the number of completed pages rather than the last page number.
 struct WebSocketReporter {
     job_id: Uuid,
     queue: JobQueue,
     images_processed: Arc<Mutex<u32>>,
+    pages_completed: Arc<Mutex<u32>>,
+    total_pages: Arc<Mutex<u32>>,
+    /// Streamed text of pages still being transcribed, by page number.
+    partial_text: Arc<Mutex<HashMap<u32, String>>>,
+}
+
+impl WebSocketReporter {
+    fn new(job_id: Uuid, queue: JobQueue) -> Self {
+        Self {
+            job_id,
+            queue,
+            images_processed: Arc::new(Mutex::new(0)),
+            pages_completed: Arc::new(Mutex::new(0)),
+            total_pages: Arc::new(Mutex::new(0)),
+            partial_text: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
 }
 
 impl ProgressReporter for WebSocketReporter {
@@ -19,12 +42,15 @@ impl ProgressReporter for WebSocketReporter {
         let queue = self.queue.clone();
         let id = self.job_id;
         let msg = format!("Starting: {filename}");
+        *self.pages_completed.lock().unwrap() = 0;
+        *self.total_pages.lock().unwrap() = total_pages;
         let progress = JobProgress {
             current_page: 0,
             total_pages,
             images_processed: 0,
             phase: "starting".to_string(),
             message: msg,
+            partial: None,
         };
         tokio::spawn(async move {
             queue.update_progress(&id, progress).await;
@@ -35,12 +61,14 @@ impl ProgressReporter for WebSocketReporter {
         let queue = self.queue.clone();
         let id = self.job_id;
         let imgs = *self.images_processed.lock().unwrap();
+        let done = *self.pages_completed.lock().unwrap();
         let progress = JobProgress {
-            current_page: page_num,
+            current_page: done,
             total_pages,
             images_processed: imgs,
             phase: "processing".to_string(),
             message: format!("Processing page {page_num}/{total_pages}"),
+            partial: None,
         };
         tokio::spawn(async move {
             queue.update_progress(&id, progress).await;
@@ -51,12 +79,19 @@ impl ProgressReporter for WebSocketReporter {
         let queue = self.queue.clone();
         let id = self.job_id;
         let imgs = *self.images_processed.lock().unwrap();
+        let done = {
+            let mut count = self.pages_completed.lock().unwrap();
+            *count += 1;
+            *count
+        };
+        self.partial_text.lock().unwrap().remove(&page_num);
         let progress = JobProgress {
-            current_page: page_num,
+            current_page: done,
             total_pages,
             images_processed: imgs,
             phase: "processing".to_string(),
-            message: format!("Completed page {page_num}/{total_pages}"),
+            message: format!("Completed page {page_num} ({done}/{total_pages} done)"),
+            partial: None,
         };
         tokio::spawn(async move {
             queue.update_progress(&id, progress).await;
@@ -78,6 +113,7 @@ impl ProgressReporter for WebSocketReporter {
             images_processed: total_images,
             phase: "complete".to_string(),
             message: msg,
+            partial: None,
         };
         tokio::spawn(async move {
             queue.update_progress(&id, progress).await;
@@ -92,60 +128,172 @@ impl ProgressReporter for WebSocketReporter {
             current_page: page_num,
             total_pages: 0,
             images_processed: imgs,
-            phase: "error".to_string(),
+            phase: "page_error".to_string(),
             message: format!("Error on page {page_num}: {error}"),
+            partial: None,
+        };
+        tokio::spawn(async move {
+            queue.update_progress(&id, progress).await;
+        });
+    }
+
+    fn on_warning(&self, page_num: u32, message: &str) {
+        let queue = self.queue.clone();
+        let id = self.job_id;
+        let progress = JobProgress {
+            current_page: *self.pages_completed.lock().unwrap(),
+            total_pages: *self.total_pages.lock().unwrap(),
+            images_processed: *self.images_processed.lock().unwrap(),
+            phase: "warning".to_string(),
+            message: format!("Warning on page {page_num}: {message}"),
+            partial: None,
         };
         tokio::spawn(async move {
             queue.update_progress(&id, progress).await;
         });
     }
+
+    fn on_partial_text(&self, page_num: u32, chunk: &str) {
+        let queue = self.queue.clone();
+        let id = self.job_id;
+        // Send the whole text so far: a client that missed a chunk still catches up
+        let text = {
+            let mut partial = self.partial_text.lock().unwrap();
+            let text = partial.entry(page_num).or_default();
+            text.push_str(chunk);
+            text.clone()
+        };
+        let progress = JobProgress {
+            current_page: *self.pages_completed.lock().unwrap(),
+            total_pages: *self.total_pages.lock().unwrap(),
+            images_processed: *self.images_processed.lock().unwrap(),
+            phase: "transcribing".to_string(),
+            message: format!("Transcribing page {page_num}"),
+            partial: Some(PartialText {
+                page: page_num,
+                text,
+            }),
+        };
+        tokio::spawn(async move {
+            queue.broadcast_progress(&id, progress).await;
+        });
+    }
 }
 
 /// Run a processing job in the background.
+///
+/// `job_config` is the config given at upload; the stored config is re-read
+/// once the job leaves `Pending`, so edits made via
+/// `PATCH /api/jobs/{id}/config` before that point take effect.
 pub async fn run_job(
     job_id: Uuid,
     pdf_path: PathBuf,
     output_dir: PathBuf,
+    storage_root: PathBuf,
     queue: JobQueue,
-    provider_name: String,
-    model: String,
-    language: String,
-    start_page: Option<u32>,
-    end_page: Option<u32>,
-    table_extraction: bool,
-    text_only: bool,
-    quality: String,
-    dpi: Option<u32>,
-    enhance: bool,
+    job_config: JobConfig,
+    cancel: CancellationToken,
 ) {
     queue
         .update_status(&job_id, JobStatus::Processing)
         .await;
 
-    let lang = language.parse::<Language>().unwrap_or_default();
-    let quality = quality.parse::<Quality>().unwrap_or_default();
+    let job_config = queue
+        .get_job(&job_id)
+        .await
+        .map(|job| job.config)
+        .unwrap_or(job_config);
+    let model = job_config
+        .model
+        .clone()
+        .unwrap_or_else(|| provider::default_model(&job_config.provider).to_string());
+
+    // Resolve the storage backend first so misconfiguration fails before any work
+    let storage = match super::storage::create_storage(&job_config, &storage_root).await {
+        Ok(s) => s,
+        Err(e) => {
+            queue.set_failed(&job_id, e).await;
+            return;
+        }
+    };
+
+    let text_only = job_config.text_only;
+    let lang = job_config.language.parse::<Language>().unwrap_or_default();
+    let quality = job_config.quality.parse::<Quality>().unwrap_or_default();
 
     let config = ProcessingConfig {
         language: lang,
-        table_extraction: if text_only { false } else { table_extraction },
+        table_extraction: if text_only { false } else { job_config.table_extraction },
         text_only,
         quality,
-        image_dpi: match dpi {
+        image_dpi: match job_config.dpi {
             Some(d) => d,
-            None if lang == Language::Th => {
-                tracing::info!("Thai language selected — auto DPI upgrade: 150 → 200");
+            None if lang.needs_high_dpi() => {
+                tracing::info!(
+                    "{} language selected — auto DPI upgrade: 150 → 200",
+                    lang.name()
+                );
                 200
             }
             None => 150,
         },
-        enhance,
+        max_render_pixels: job_config.max_render_pixels,
+        image_encoding: job_config.image_encoding.parse().unwrap_or_default(),
+        saved_image_format: job_config.saved_image_format.parse().unwrap_or_default(),
+        enhance: job_config.enhance,
+        auto_rotate: job_config.auto_rotate,
+        max_concurrent_pages: provider::recommended_concurrency(&job_config.provider),
+        figure_numbering: job_config.figure_numbering.parse().unwrap_or_default(),
+        emit_image_sidecars: job_config.emit_image_sidecars,
+        image_context: job_config.image_context,
+        stream_text: job_config.stream_text,
+        custom_full_page: job_config.custom_full_page.clone(),
+        custom_single_image: job_config.custom_single_image.clone(),
+        custom_table: job_config.custom_table.clone(),
+        strip_repeated_lines: job_config.strip_repeated_lines,
+        max_markdown_bytes: job_config.max_markdown_bytes,
+        premium_provider: job_config.premium_provider.as_ref().map(|p| {
+            let model = job_config
+                .premium_model
+                .clone()
+                .unwrap_or_else(|| provider::default_model(p).to_string());
+            (p.clone(), model)
+        }),
+        extract_form_fields: job_config.extract_form_fields,
+        emit_toc: job_config.emit_toc,
+        chunk_noise_patterns: job_config.chunk_noise_patterns.clone(),
+        pages_per_request: job_config.pages_per_request.unwrap_or(1).max(1),
+        escalate_empty_text: job_config.escalate_empty_text,
+        auto_high_quality_on_scanned: job_config.auto_high_quality_on_scanned,
+        dedup_images: job_config.dedup_images,
+        dedup_pages: job_config.dedup_pages,
+        max_concurrent_requests: job_config.max_concurrent_requests,
+        requests_per_minute: job_config.requests_per_minute,
+        page_timeout_secs: job_config.page_timeout_secs,
+        rag_jsonl: job_config.rag_jsonl,
+        wide_table_strategy: job_config.wide_table_strategy.parse().unwrap_or_default(),
+        wide_table_max_columns: job_config.wide_table_max_columns.unwrap_or(14).max(1),
+        table_multispace_ratio: job_config
+            .table_multispace_ratio
+            .unwrap_or(0.4)
+            .clamp(0.0, 1.0),
+        table_min_consistent_run: job_config.table_min_consistent_run.unwrap_or(6).max(2),
+        line_ending: job_config.line_ending.parse().unwrap_or_default(),
+        trash_preset: job_config.trash_preset.parse().unwrap_or_default(),
+        page_fallback_strategy: job_config.page_fallback_strategy.parse().unwrap_or_default(),
+        caption_placement: job_config.caption_placement.parse().unwrap_or_default(),
+        skip_pages: job_config.skip_pages.clone(),
+        include_layers: job_config.include_layers.clone(),
+        exclude_layers: job_config.exclude_layers.clone(),
+        storage: storage.map(Arc::from),
+        cancel: Some(cancel),
         ..Default::default()
     };
 
     let vision_provider: Option<Arc<dyn jay_rag_core::VisionProvider>> = if text_only {
         None
     } else {
-        match provider::create_provider(&provider_name, &model) {
+        match provider::create_provider(&job_config.provider, &model) {
             Ok(p) => Some(Arc::from(p)),
             Err(e) => {
                 queue.set_failed(&job_id, e.to_string()).await;
@@ -154,36 +302,71 @@ pub async fn run_job(
         }
     };
 
-    let reporter: Arc<dyn ProgressReporter> = Arc::new(WebSocketReporter {
-        job_id,
-        queue: queue.clone(),
-        images_processed: Arc::new(Mutex::new(0)),
-    });
+    let reporter: Arc<dyn ProgressReporter> =
+        Arc::new(WebSocketReporter::new(job_id, queue.clone()));
 
-    match jay_rag_core::process_pdf(
+    let outcome = jay_rag_core::process_pdf(
         &pdf_path,
         &output_dir,
         vision_provider,
         &config,
         reporter,
-        start_page,
-        end_page,
+        job_config.start_page,
+        job_config.end_page,
     )
-    .await
-    {
+    .await;
+
+    match outcome {
+        Err(CoreError::Cancelled) => {
+            remove_job_outputs(&output_dir, &job_id).await;
+            tracing::info!("Job {job_id} cancelled before any page finished");
+            queue.set_cancelled(&job_id).await;
+            return;
+        }
+        // Deleted while running: nobody is left to want the output, even if
+        // every page finished before the cancellation landed
+        Ok(_) if queue.get_job(&job_id).await.is_none() => {
+            remove_job_outputs(&output_dir, &job_id).await;
+            tracing::info!("Job {job_id} deleted; output removed");
+            return;
+        }
         Ok(result) => {
+            if let Some(last_page) = result.last_page {
+                tracing::info!("Job {job_id} cancelled; kept pages up to {last_page}");
+            }
             let job_result = JobResult {
-                markdown_path: result.markdown_path.to_string_lossy().to_string(),
+                markdown_path: result
+                    .markdown_path
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_default(),
                 metadata_path: result.metadata_path.to_string_lossy().to_string(),
                 image_count: result.image_count,
-                images_dir: output_dir
-                    .join("images")
-                    .to_string_lossy()
-                    .to_string(),
+                images_dir: match &config.storage {
+                    Some(_) => "images".to_string(),
+                    None => output_dir.join("images").to_string_lossy().to_string(),
+                },
                 trash_path: result
                     .trash_path
                     .map(|p| p.to_string_lossy().to_string()),
                 trash_count: result.trash_count,
+                markdown_parts: result
+                    .markdown_parts
+                    .iter()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .collect(),
+                rag_jsonl_path: result
+                    .rag_jsonl_path
+                    .map(|p| p.to_string_lossy().to_string()),
+                pages_jsonl_path: result
+                    .pages_jsonl_path
+                    .map(|p| p.to_string_lossy().to_string()),
+                manifest_path: result
+                    .manifest_path
+                    .map(|p| p.to_string_lossy().to_string()),
+                input_tokens: result.input_tokens,
+                output_tokens: result.output_tokens,
+                partial: result.partial,
+                last_page: result.last_page,
             };
             queue.set_completed(&job_id, job_result).await;
         }
@@ -198,3 +381,192 @@ pub async fn run_job(
         crate::notifications::notify_job_finished(&job, &settings).await;
     }
 }
+
+/// Pages generated by a simulated job.
+const SIMULATED_PAGES: u32 = 5;
+
+/// Remove every output a run of job `job_id` may have written to
+/// `output_dir`: its images directory and all `{job_id}_*` files (markdown and
+/// its parts, metadata, trash, RAG and page JSON Lines, manifest).
+///
+/// Outputs are named after the job id (the stem of the stored `{id}.pdf`), so
+/// nothing of another job is touched.
+pub(crate) async fn remove_job_outputs(output_dir: &Path, job_id: &Uuid) {
+    let _ = tokio::fs::remove_dir_all(output_dir.join("images").join(job_id.to_string())).await;
+    let Ok(mut entries) = tokio::fs::read_dir(output_dir).await else {
+        return;
+    };
+    let prefix = format!("{job_id}_");
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with(&prefix) && path.is_file() {
+            let _ = tokio::fs::remove_file(&path).await;
+        }
+    }
+}
+
+/// A 1x1 white PNG used as the placeholder page image of simulated jobs.
+const PLACEHOLDER_PNG: &[u8] = &[
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x00, 0x00, 0x00, 0x00, 0x3a, 0x7e, 0x9b,
+    0x55, 0x00, 0x00, 0x00, 0x0a, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0xf8, 0x0f, 0x00, 0x01,
+    0x01, 0x01, 0x00, 0xb1, 0x38, 0xf6, 0x14, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae,
+    0x42, 0x60, 0x82,
+];
+
+/// Run a fake job for `serve --simulate`: synthetic progress over a few
+/// seconds, then canned markdown/metadata written to the usual output paths.
+///
+/// No pdfium, provider or API key is used. Outputs are marked as simulated.
+pub async fn run_simulated_job(
+    job_id: Uuid,
+    pdf_path: PathBuf,
+    output_dir: PathBuf,
+    queue: JobQueue,
+) {
+    queue.update_status(&job_id, JobStatus::Processing).await;
+
+    let doc_stem = pdf_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("output")
+        .to_string();
+
+    let reporter = WebSocketReporter::new(job_id, queue.clone());
+
+    match write_simulated_output(&doc_stem, &output_dir, &reporter).await {
+        Ok(job_result) => queue.set_completed(&job_id, job_result).await,
+        Err(e) => {
+            queue
+                .set_failed(&job_id, format!("Simulation failed: {e}"))
+                .await
+        }
+    }
+}
+
+async fn write_simulated_output(
+    doc_stem: &str,
+    output_dir: &std::path::Path,
+    reporter: &WebSocketReporter,
+) -> std::io::Result<JobResult> {
+    let images_root = output_dir.join("images");
+    let images_dir = images_root.join(doc_stem);
+    tokio::fs::create_dir_all(&images_dir).await?;
+
+    reporter.on_pdf_start(doc_stem, SIMULATED_PAGES);
+
+    let mut content = vec![
+        format!("# {doc_stem}\n"),
+        format!(
+            "> Provider: `simulated` | Model: `simulated` | Quality: `standard` | Pages: {SIMULATED_PAGES}\n"
+        ),
+        "> **SIMULATED RESULT** — generated by `serve --simulate`, no real processing.\n"
+            .to_string(),
+        format!("> Images: `images/{doc_stem}/`\n"),
+    ];
+    let mut metadata = Vec::new();
+
+    for page in 1..=SIMULATED_PAGES {
+        reporter.on_page_start(page, SIMULATED_PAGES);
+        tokio::time::sleep(std::time::Duration::from_millis(800)).await;
+
+        let image_id = jay_rag_core::metadata::image_id(doc_stem, page, "img1");
+        let image_file = format!("{image_id}.png");
+        tokio::fs::write(images_dir.join(&image_file), PLACEHOLDER_PNG).await?;
+        let image_ref = format!("{doc_stem}/{image_file}");
+        let description =
+            format!("[simulated] Placeholder description of the image on page {page}.");
+
+        content.push(format!(
+            "\n\n---\n## Page {page}\n\n[simulated] Sample text for page {page}.\n\n[IMAGE:{image_ref}]\n**[Figure 1]:** {description}\n"
+        ));
+        metadata.push(jay_rag_core::ImageMetadata {
+            image_id,
+            image_file: image_ref,
+            image_url: None,
+            page,
+            index: Some(1),
+            image_type: jay_rag_core::metadata::ImageType::ExtractedImage,
+            width: Some(1),
+            height: Some(1),
+            bbox: None,
+            description: description.clone(),
+            source_doc: doc_stem.to_string(),
+            provider: "simulated".to_string(),
+            model: "simulated".to_string(),
+            fallback: None,
+            input_tokens: None,
+            output_tokens: None,
+            latency_ms: None,
+            cost_usd: None,
+            deduped: false,
+            deduped_from: None,
+            low_confidence: false,
+            tables: Vec::new(),
+        });
+
+        reporter.on_image_processed(page, 1, &description);
+        reporter.on_page_complete(page, SIMULATED_PAGES);
+    }
+
+    let markdown_path = output_dir.join(format!("{doc_stem}_enriched.md"));
+    tokio::fs::write(&markdown_path, content.join("\n")).await?;
+    let metadata_path = output_dir.join(format!("{doc_stem}_images_metadata.json"));
+    tokio::fs::write(&metadata_path, serde_json::to_string_pretty(&metadata)?).await?;
+
+    reporter.on_pdf_complete(doc_stem, SIMULATED_PAGES);
+
+    Ok(JobResult {
+        markdown_path: markdown_path.to_string_lossy().to_string(),
+        metadata_path: metadata_path.to_string_lossy().to_string(),
+        image_count: SIMULATED_PAGES,
+        images_dir: images_root.to_string_lossy().to_string(),
+        trash_path: None,
+        trash_count: 0,
+        markdown_parts: Vec::new(),
+        rag_jsonl_path: None,
+        pages_jsonl_path: None,
+        manifest_path: None,
+        input_tokens: 0,
+        output_tokens: 0,
+        partial: false,
+        last_page: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_remove_job_outputs_removes_every_output_of_the_job() {
+        let dir = std::env::temp_dir().join(format!("jay-rag-outputs-{}", Uuid::new_v4()));
+        let (job, other) = (Uuid::new_v4(), Uuid::new_v4());
+        std::fs::create_dir_all(dir.join("images").join(job.to_string())).unwrap();
+        std::fs::create_dir_all(dir.join("images").join(other.to_string())).unwrap();
+        for suffix in [
+            "enriched.md",
+            "part1_enriched.md",
+            "images_metadata.json",
+            "trash.json",
+            "rag.jsonl",
+            "pages.jsonl",
+            "manifest.json",
+        ] {
+            std::fs::write(dir.join(format!("{job}_{suffix}")), "").unwrap();
+        }
+        std::fs::write(dir.join(format!("{other}_enriched.md")), "").unwrap();
+
+        remove_job_outputs(&dir, &job).await;
+
+        let left: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name != "images")
+            .collect();
+        assert_eq!(left, [format!("{other}_enriched.md")]);
+        assert!(!dir.join("images").join(job.to_string()).exists());
+        assert!(dir.join("images").join(other.to_string()).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
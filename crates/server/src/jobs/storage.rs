@@ -0,0 +1,215 @@
+use super::models::JobConfig;
+use jay_rag_storage::{
+    AzureBlobStorage, GcsStorage, LocalStorage, NfsStorage, S3Storage, StorageBackend,
+};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// Check that the storage fields required by the selected backend are set.
+pub fn validate_storage_config(config: &JobConfig) -> Result<(), String> {
+    if let Some(path) = &config.storage_path {
+        relative_storage_path(path)?;
+    }
+    match config.storage.as_str() {
+        "local" => Ok(()),
+        "s3" => match config.s3_bucket.as_deref().map(str::trim) {
+            Some(bucket) if !bucket.is_empty() => Ok(()),
+            _ => Err("Storage 's3' requires s3_bucket".to_string()),
+        },
+        "gcs" => match config.gcs_bucket.as_deref().map(str::trim) {
+            Some(bucket) if !bucket.is_empty() => Ok(()),
+            _ => Err("Storage 'gcs' requires gcs_bucket".to_string()),
+        },
+        "azure" => {
+            let set = |v: &Option<String>| v.as_deref().is_some_and(|s| !s.trim().is_empty());
+            if set(&config.azure_account) && set(&config.azure_container) {
+                Ok(())
+            } else {
+                Err("Storage 'azure' requires azure_account and azure_container".to_string())
+            }
+        }
+        "nfs" => match config.storage_path.as_deref().map(str::trim) {
+            Some(path) if !path.is_empty() => Ok(()),
+            _ => Err("Storage 'nfs' requires storage_path (the mount point)".to_string()),
+        },
+        other => Err(format!(
+            "Unknown storage backend: '{other}' (expected local, s3, gcs, azure or nfs)"
+        )),
+    }
+}
+
+/// `storage_path` as a path relative to the storage root. Absolute paths and
+/// `..` are rejected so a job cannot write outside the root.
+fn relative_storage_path(storage_path: &str) -> Result<&Path, String> {
+    let path = Path::new(storage_path.trim());
+    let inside_root = path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if inside_root {
+        Ok(path)
+    } else {
+        Err(format!(
+            "storage_path must be relative to the server storage root without '..': '{storage_path}'"
+        ))
+    }
+}
+
+/// Build the storage backend selected by the job config. Local and NFS
+/// `storage_path`s are resolved under `storage_root`.
+///
+/// Returns `None` for plain local storage without a `storage_path`: outputs
+/// then stay in the server output directory only.
+pub async fn create_storage(
+    config: &JobConfig,
+    storage_root: &Path,
+) -> Result<Option<Box<dyn StorageBackend>>, String> {
+    validate_storage_config(config)?;
+    let resolve = |path: &str| relative_storage_path(path).map(|p| storage_root.join(p));
+
+    match config.storage.as_str() {
+        "s3" => {
+            let bucket = config.s3_bucket.clone().unwrap_or_default();
+            let prefix = config.s3_prefix.clone().unwrap_or_default();
+            let storage = S3Storage::new(bucket, prefix, String::new())
+                .await
+                .map_err(|e| format!("Failed to initialize S3 storage: {e}"))?;
+            Ok(Some(Box::new(storage)))
+        }
+        "gcs" => {
+            let bucket = config.gcs_bucket.clone().unwrap_or_default();
+            let prefix = config.gcs_prefix.clone().unwrap_or_default();
+            let storage = GcsStorage::new(bucket, prefix, String::new())
+                .await
+                .map_err(|e| format!("Failed to initialize GCS storage: {e}"))?;
+            Ok(Some(Box::new(storage)))
+        }
+        "azure" => {
+            let storage = AzureBlobStorage::new(
+                config.azure_account.clone().unwrap_or_default(),
+                config.azure_container.clone().unwrap_or_default(),
+                config.azure_prefix.clone().unwrap_or_default(),
+                String::new(),
+            )
+            .await
+            .map_err(|e| format!("Failed to initialize Azure storage: {e}"))?;
+            Ok(Some(Box::new(storage)))
+        }
+        "nfs" => {
+            let mount = resolve(config.storage_path.as_deref().unwrap_or_default())?;
+            let storage = NfsStorage::new(mount, String::new()).map_err(|e| e.to_string())?;
+            Ok(Some(Box::new(storage)))
+        }
+        _ => {
+            let path = config.storage_path.as_deref().unwrap_or_default();
+            if path.trim().is_empty() {
+                return Ok(None);
+            }
+            let storage = LocalStorage::new(resolve(path)?, String::new());
+            Ok(Some(Box::new(storage)))
+        }
+    }
+}
+
+/// Where a job's outputs live: the storage backend selected by its config,
+/// or by default local files under the server output directory.
+pub struct JobOutputs {
+    storage: Arc<dyn StorageBackend>,
+    /// Local directory the keys resolve to (`None` for other backends).
+    dir: Option<PathBuf>,
+}
+
+impl JobOutputs {
+    pub async fn open(
+        config: &JobConfig,
+        output_dir: &Path,
+        storage_root: &Path,
+    ) -> Result<Self, String> {
+        Ok(match create_storage(config, storage_root).await? {
+            Some(storage) => Self {
+                storage: Arc::from(storage),
+                dir: None,
+            },
+            None => Self {
+                storage: Arc::new(LocalStorage::new(output_dir.to_path_buf(), String::new())),
+                dir: Some(output_dir.to_path_buf()),
+            },
+        })
+    }
+
+    /// Path recorded in a [`JobResult`](super::models::JobResult) for `key`:
+    /// the local file, or the key itself for other backends.
+    pub fn path(&self, key: &str) -> String {
+        match &self.dir {
+            Some(dir) => dir.join(key).to_string_lossy().to_string(),
+            None => key.to_string(),
+        }
+    }
+
+    /// Storage key of a recorded output path (see [`JobOutputs::path`]).
+    fn key(&self, path: &str) -> String {
+        let path = Path::new(path);
+        let key = match &self.dir {
+            Some(dir) => path.strip_prefix(dir).unwrap_or(path),
+            None => path,
+        };
+        key.to_string_lossy().replace('\\', "/")
+    }
+
+    /// Read the output recorded as `path`.
+    pub async fn read_to_string(&self, path: &str) -> Result<String, String> {
+        let bytes = self
+            .storage
+            .read_bytes(&self.key(path))
+            .await
+            .map_err(|e| e.to_string())?;
+        String::from_utf8(bytes).map_err(|e| e.to_string())
+    }
+
+    /// Write `content` to `key`, returning its [`JobOutputs::path`].
+    pub async fn write(&self, key: &str, content: &str) -> Result<String, String> {
+        self.storage
+            .write_text(key, content)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(self.path(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_storage_config() {
+        let mut config = JobConfig::default();
+        assert!(validate_storage_config(&config).is_ok());
+
+        config.storage = "s3".to_string();
+        assert!(validate_storage_config(&config).is_err());
+        config.s3_bucket = Some("docs".to_string());
+        assert!(validate_storage_config(&config).is_ok());
+
+        config.storage = "gcs".to_string();
+        assert!(validate_storage_config(&config).is_err());
+        config.gcs_bucket = Some("docs".to_string());
+        assert!(validate_storage_config(&config).is_ok());
+
+        config.storage = "azure".to_string();
+        config.azure_account = Some("docsaccount".to_string());
+        assert!(validate_storage_config(&config).is_err());
+        config.azure_container = Some("rag".to_string());
+        assert!(validate_storage_config(&config).is_ok());
+
+        config.storage = "nfs".to_string();
+        assert!(validate_storage_config(&config).is_err());
+        config.storage_path = Some("nfs/output".to_string());
+        assert!(validate_storage_config(&config).is_ok());
+        config.storage_path = Some("/mnt/nfs".to_string());
+        assert!(validate_storage_config(&config).is_err());
+        config.storage_path = Some("nfs/../../etc".to_string());
+        assert!(validate_storage_config(&config).is_err());
+
+        config.storage = "ftp".to_string();
+        assert!(validate_storage_config(&config).is_err());
+    }
+}
@@ -4,6 +4,7 @@ pub mod error;
 pub mod jobs;
 pub mod notifications;
 pub mod routes;
+pub mod sse;
 pub mod state;
 pub mod ws;
 
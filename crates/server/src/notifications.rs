@@ -1,4 +1,5 @@
 use crate::jobs::models::{Job, JobStatus, NotificationSettings};
+use jay_rag_core::http::{send_with_retry, HttpConfig};
 
 /// Send notifications after a job finishes, if enabled.
 pub async fn notify_job_finished(job: &Job, settings: &NotificationSettings) {
@@ -68,7 +69,8 @@ fn build_message(job: &Job) -> String {
 
 /// Send a push message via LINE Messaging API.
 async fn send_line_message(channel_token: &str, user_id: &str, message: &str) -> Result<(), String> {
-    let client = reqwest::Client::new();
+    let http = HttpConfig::from_env();
+    let client = http.client().map_err(|e| e.to_string())?;
     let body = serde_json::json!({
         "to": user_id,
         "messages": [
@@ -78,14 +80,15 @@ async fn send_line_message(channel_token: &str, user_id: &str, message: &str) ->
             }
         ]
     });
-    let resp = client
-        .post("https://api.line.me/v2/bot/message/push")
-        .header("Authorization", format!("Bearer {channel_token}"))
-        .header("Content-Type", "application/json")
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+    let resp = send_with_retry(&http, || {
+        client
+            .post("https://api.line.me/v2/bot/message/push")
+            .header("Authorization", format!("Bearer {channel_token}"))
+            .header("Content-Type", "application/json")
+            .json(&body)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
 
     if !resp.status().is_success() {
         let status = resp.status();
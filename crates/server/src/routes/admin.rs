@@ -0,0 +1,26 @@
+use axum::extract::State;
+use axum::Json;
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::state::AppState;
+
+#[derive(Serialize)]
+pub struct PauseResponse {
+    pub paused: bool,
+}
+
+/// Pause processing: jobs that have not started yet wait until resumed.
+/// Jobs already processing run to completion.
+pub async fn pause(State(state): State<Arc<AppState>>) -> Json<PauseResponse> {
+    state.paused.send_replace(true);
+    tracing::info!("Processing paused");
+    Json(PauseResponse { paused: true })
+}
+
+/// Resume processing: waiting jobs start right away.
+pub async fn resume(State(state): State<Arc<AppState>>) -> Json<PauseResponse> {
+    state.paused.send_replace(false);
+    tracing::info!("Processing resumed");
+    Json(PauseResponse { paused: false })
+}
@@ -0,0 +1,67 @@
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::Response;
+use std::sync::Arc;
+use tokio::io::{AsyncWriteExt, DuplexStream};
+use tokio_util::io::ReaderStream;
+
+use crate::jobs::models::{Job, JobStatus};
+use crate::jobs::queue::JobFilter;
+use crate::state::AppState;
+
+/// Stream the image metadata of every completed job as NDJSON.
+///
+/// GET /api/catalog
+///
+/// One line per image: the entry from `{doc}_images_metadata.json` plus
+/// `job_id` and `source_file`. Metadata files are read one job at a time, so
+/// the whole corpus is never held in memory. Jobs whose metadata file is gone
+/// are skipped.
+pub async fn get_catalog(State(state): State<Arc<AppState>>) -> Response {
+    let completed = JobFilter {
+        status: Some(JobStatus::Completed),
+        ..Default::default()
+    };
+    let jobs = state.job_queue.list_jobs(&completed).await;
+
+    let (writer, reader) = tokio::io::duplex(64 * 1024);
+    tokio::spawn(write_catalog(jobs, writer));
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .body(Body::from_stream(ReaderStream::new(reader)))
+        .unwrap()
+}
+
+/// Write catalog lines until the jobs run out or the client disconnects.
+async fn write_catalog(jobs: Vec<Job>, mut out: DuplexStream) {
+    for job in jobs {
+        let Some(result) = job.result else {
+            continue;
+        };
+        let entries: Vec<serde_json::Value> = match tokio::fs::read(&result.metadata_path).await {
+            Ok(bytes) => match serde_json::from_slice(&bytes) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    tracing::warn!("Catalog: skipping job {} (bad metadata: {e})", job.id);
+                    continue;
+                }
+            },
+            Err(_) => continue,
+        };
+
+        for mut entry in entries {
+            if let Some(fields) = entry.as_object_mut() {
+                fields.insert("job_id".to_string(), job.id.to_string().into());
+                fields.insert("source_file".to_string(), job.filename.clone().into());
+            }
+            let mut line = entry.to_string();
+            line.push('\n');
+            if out.write_all(line.as_bytes()).await.is_err() {
+                // Client went away
+                return;
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use axum::extract::{Path, State};
 use axum::Json;
+use jay_rag_core::trash::{pages_to_strip, TrashDetection};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -20,6 +21,21 @@ pub struct CleanResponse {
     pub pages_removed: Vec<u32>,
 }
 
+#[derive(Deserialize)]
+pub struct StripTrashRequest {
+    /// Trash types (`toc`, `boilerplate`, `blank`, `header_footer`) or reason
+    /// codes to strip; all detections when omitted.
+    #[serde(default)]
+    pub types: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+pub struct StripTrashResponse {
+    /// Path of `<doc>_cleaned.md` (`None` when no page matched).
+    pub cleaned_path: Option<String>,
+    pub pages_removed: Vec<u32>,
+}
+
 /// Remove specified pages from a job's markdown output.
 ///
 /// POST /api/results/{job_id}/clean
@@ -82,3 +98,58 @@ pub async fn clean_results(
         pages_removed: request.remove_pages,
     }))
 }
+
+/// Remove the pages flagged by trash detection, like `--strip-trash` in the CLI,
+/// and save the result as `<doc>_cleaned.md`.
+///
+/// POST /api/results/{job_id}/strip-trash
+pub async fn strip_trash(
+    Path(job_id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<StripTrashRequest>,
+) -> Result<Json<StripTrashResponse>, ApiError> {
+    let job = state
+        .job_queue
+        .get_job(&job_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Job {job_id} not found")))?;
+
+    if job.status != JobStatus::Completed {
+        return Err(ApiError::BadRequest(format!(
+            "Job {job_id} is not completed (status: {:?})",
+            job.status
+        )));
+    }
+
+    let result = job
+        .result
+        .ok_or_else(|| ApiError::Internal("Job completed but no results found".to_string()))?;
+
+    let detections: Vec<TrashDetection> = match &result.trash_path {
+        Some(trash_path) => {
+            let json = tokio::fs::read_to_string(trash_path)
+                .await
+                .map_err(|_| ApiError::NotFound("Trash file no longer available".to_string()))?;
+            serde_json::from_str(&json)
+                .map_err(|e| ApiError::Internal(format!("Invalid trash file: {e}")))?
+        }
+        None => Vec::new(),
+    };
+
+    let filter = request.types.map(|types| types.join(","));
+    let pages_removed = pages_to_strip(&detections, filter.as_deref());
+    if pages_removed.is_empty() {
+        return Ok(Json(StripTrashResponse {
+            cleaned_path: None,
+            pages_removed,
+        }));
+    }
+
+    let markdown_path = PathBuf::from(&result.markdown_path);
+    let (cleaned_path, _) = jay_rag_core::clean_markdown(&markdown_path, &pages_removed).await?;
+
+    Ok(Json(StripTrashResponse {
+        cleaned_path: Some(cleaned_path.to_string_lossy().to_string()),
+        pages_removed,
+    }))
+}
@@ -18,6 +18,8 @@ pub struct ProviderInfo {
     pub default_model: &'static str,
     pub models: Vec<&'static str>,
     pub cost_per_image_usd: f64,
+    pub recommended_concurrency: usize,
+    pub rate_limit_rpm: Option<u32>,
 }
 
 #[derive(Serialize)]
@@ -43,6 +45,8 @@ pub async fn get_config() -> Json<ConfigResponse> {
             default_model: p.default_model,
             models: p.models.to_vec(),
             cost_per_image_usd: p.cost_per_image_usd,
+            recommended_concurrency: p.recommended_concurrency,
+            rate_limit_rpm: p.rate_limit_rpm,
         })
         .collect();
 
@@ -57,8 +61,20 @@ pub async fn get_config() -> Json<ConfigResponse> {
                 code: "en",
                 name: "English",
             },
+            LanguageInfo {
+                code: "lo",
+                name: "Lao",
+            },
+            LanguageInfo {
+                code: "km",
+                name: "Khmer",
+            },
+            LanguageInfo {
+                code: "vi",
+                name: "Vietnamese",
+            },
         ],
-        storage_backends: vec!["local", "s3", "nfs"],
+        storage_backends: vec!["local", "s3", "gcs", "azure", "nfs"],
         quality_levels: vec![
             QualityInfo {
                 value: "standard",
@@ -9,6 +9,7 @@ use crate::error::ApiError;
 use crate::jobs::models::JobStatus;
 use crate::routes::export::convert_image_tags;
 use crate::state::AppState;
+use jay_rag_core::redact_secrets;
 
 #[derive(Deserialize)]
 pub struct DeployRequest {
@@ -26,12 +27,21 @@ pub enum ImageTarget {
         prefix: String,
         region: Option<String>,
     },
+    Gcs {
+        bucket: String,
+        prefix: String,
+    },
     Scp {
         host: String,
         port: Option<u16>,
         username: String,
         private_key_path: Option<String>,
         remote_path: String,
+        /// Known hosts file listing the server (default `~/.ssh/known_hosts`).
+        known_hosts_path: Option<String>,
+        /// Expected SHA256 host key fingerprint; checked instead of the known
+        /// hosts file when given.
+        host_key_fingerprint: Option<String>,
     },
 }
 
@@ -103,32 +113,44 @@ pub async fn deploy_handler(
                     detail,
                 });
             }
-            Err(e) => errors.push(format!("Image deploy failed: {e}")),
+            Err(e) => errors.push(redact_secrets(&format!("Image deploy failed: {e}"))),
         }
     }
 
-    // Convert markdown with image base URL
-    let md_content = tokio::fs::read_to_string(&result.markdown_path)
-        .await
-        .map_err(|e| ApiError::Internal(format!("Failed to read markdown: {e}")))?;
-    let converted_md = convert_image_tags(&md_content, &req.image_base_url);
-
-    // Deploy markdown if target specified
+    // Deploy markdown if target specified — split output is uploaded part by part
     if let Some(ref md_target) = req.markdown_target {
-        let md_path = std::path::Path::new(&result.markdown_path);
-        let doc_stem = md_path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("output");
-
-        match deploy::markdown::deploy_markdown(md_target, &converted_md, doc_stem).await {
-            Ok(detail) => {
-                markdown_result = Some(DeployStepResult {
-                    target_type: md_target_type(md_target),
-                    detail,
-                });
+        let md_paths: Vec<&String> = if result.markdown_parts.is_empty() {
+            vec![&result.markdown_path]
+        } else {
+            result.markdown_parts.iter().collect()
+        };
+
+        let mut details = Vec::new();
+        for md_path in md_paths {
+            // Convert markdown with image base URL
+            let md_content = tokio::fs::read_to_string(md_path)
+                .await
+                .map_err(|e| ApiError::Internal(format!("Failed to read markdown: {e}")))?;
+            let converted_md = convert_image_tags(&md_content, &req.image_base_url);
+
+            let doc_stem = std::path::Path::new(md_path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("output");
+
+            match deploy::markdown::deploy_markdown(md_target, &converted_md, doc_stem).await {
+                Ok(detail) => details.push(detail),
+                Err(e) => errors.push(redact_secrets(&format!(
+                    "Markdown deploy failed ({doc_stem}): {e}"
+                ))),
             }
-            Err(e) => errors.push(format!("Markdown deploy failed: {e}")),
+        }
+
+        if !details.is_empty() {
+            markdown_result = Some(DeployStepResult {
+                target_type: md_target_type(md_target),
+                detail: details.join("; "),
+            });
         }
     }
 
@@ -145,6 +167,7 @@ fn image_target_type(target: &ImageTarget) -> String {
     match target {
         ImageTarget::LocalFolder { .. } => "local_folder".to_string(),
         ImageTarget::S3 { .. } => "s3".to_string(),
+        ImageTarget::Gcs { .. } => "gcs".to_string(),
         ImageTarget::Scp { .. } => "scp".to_string(),
     }
 }
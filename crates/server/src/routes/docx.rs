@@ -0,0 +1,312 @@
+use axum::extract::{Path, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use docx_rs::{
+    Docx, Paragraph, Pic, Run, RunFonts, Style, StyleType, Table as DocxTable, TableCell, TableRow,
+};
+use jay_rag_core::table::{parse_markdown_tables, Table};
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::jobs::models::JobStatus;
+use crate::routes::export::image_file;
+use crate::state::AppState;
+
+const DOCX_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+
+/// Latin text font.
+const LATIN_FONT: &str = "Calibri";
+
+/// Word renders Thai as a complex script, with the run's `cs` font; Tahoma
+/// covers Thai and ships with both Windows and macOS.
+const THAI_FONT: &str = "Tahoma";
+
+/// Widest an embedded image is drawn: 6 inches, in EMU.
+const MAX_IMAGE_WIDTH_EMU: u32 = 6 * 914_400;
+
+/// One block of the enriched markdown, as laid out in the document.
+#[derive(Debug, PartialEq)]
+enum Block {
+    /// Heading level (1–6) and text.
+    Heading(usize, String),
+    Paragraph(String),
+    Table(Table),
+    /// Image path relative to the images dir, with its alt text (if any).
+    Image(String, Option<String>),
+}
+
+/// Export a job's enriched markdown as a Word document.
+///
+/// Headings, paragraphs and pipe tables become their Word equivalents;
+/// `[IMAGE:...]` tags and `![...](images/...)` links embed the image from
+/// the job's images dir.
+///
+/// GET /api/results/{job_id}/export.docx
+pub async fn export_docx(
+    Path(job_id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let job = state
+        .job_queue
+        .get_job(&job_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Job {job_id} not found")))?;
+
+    if job.status != JobStatus::Completed {
+        return Err(ApiError::BadRequest(format!(
+            "Job {job_id} is not completed (status: {:?})",
+            job.status
+        )));
+    }
+
+    let result = job
+        .result
+        .ok_or_else(|| ApiError::Internal("Job completed but no results found".to_string()))?;
+
+    let markdown = tokio::fs::read_to_string(&result.markdown_path)
+        .await
+        .map_err(|_| ApiError::NotFound("Markdown file no longer available".to_string()))?;
+    let images_dir = PathBuf::from(&result.images_dir);
+
+    let document =
+        tokio::task::spawn_blocking(move || build_docx(&parse_blocks(&markdown), &images_dir))
+            .await
+            .map_err(|e| ApiError::Internal(format!("Export task failed: {e}")))??;
+
+    let filename = format!("{}_results.docx", &job_id.to_string()[..8]);
+    Ok((
+        [
+            (header::CONTENT_TYPE, DOCX_CONTENT_TYPE.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        document,
+    ))
+}
+
+/// Split enriched markdown into blocks. Page separators (`---`) and blank
+/// lines are dropped; every other line is its own paragraph.
+fn parse_blocks(markdown: &str) -> Vec<Block> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        i += 1;
+
+        if line.starts_with('|') {
+            let start = i - 1;
+            while i < lines.len() && lines[i].trim_start().starts_with('|') {
+                i += 1;
+            }
+            let block = lines[start..i].join("\n");
+            match parse_markdown_tables(&block).pop() {
+                Some(table) => blocks.push(Block::Table(table)),
+                None => blocks.extend(lines[start..i].iter().map(|l| paragraph(l))),
+            }
+            continue;
+        }
+
+        if line.is_empty() || line == "---" {
+            continue;
+        }
+        if let Some(image) = image_block(line) {
+            blocks.push(image);
+            continue;
+        }
+        let level = line.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&level) && line[level..].starts_with(' ') {
+            blocks.push(Block::Heading(level, line[level..].trim().to_string()));
+            continue;
+        }
+        blocks.push(paragraph(line));
+    }
+    blocks
+}
+
+fn paragraph(line: &str) -> Block {
+    let line = line.trim();
+    let line = line.strip_prefix("> ").unwrap_or(line);
+    Block::Paragraph(line.to_string())
+}
+
+/// `[IMAGE:path]` or `![alt](images/path)` on a line of its own.
+fn image_block(line: &str) -> Option<Block> {
+    if let Some(path) = line
+        .strip_prefix("[IMAGE:")
+        .and_then(|r| r.strip_suffix(']'))
+    {
+        return Some(Block::Image(path.to_string(), None));
+    }
+    let (alt, target) = line
+        .strip_prefix("![")?
+        .strip_suffix(')')?
+        .rsplit_once("](")?;
+    let path = target.strip_prefix("images/")?;
+    Some(Block::Image(path.to_string(), Some(alt.to_string())))
+}
+
+fn build_docx(blocks: &[Block], images_dir: &std::path::Path) -> Result<Vec<u8>, ApiError> {
+    let mut docx = Docx::new().default_fonts(fonts());
+    for (level, size) in [(1, 36), (2, 30), (3, 26)] {
+        docx = docx.add_style(
+            Style::new(&format!("Heading{level}"), StyleType::Paragraph)
+                .name(&format!("Heading {level}"))
+                .size(size)
+                .bold(),
+        );
+    }
+
+    for block in blocks {
+        docx = match block {
+            Block::Heading(level, text) => docx.add_paragraph(
+                Paragraph::new()
+                    .style(&format!("Heading{}", (*level).min(3)))
+                    .add_run(Run::new().add_text(text)),
+            ),
+            Block::Paragraph(text) => docx.add_paragraph(inline_runs(Paragraph::new(), text)),
+            Block::Table(table) => docx.add_table(word_table(table)),
+            Block::Image(path, alt) => {
+                let run = match load_picture(images_dir, path) {
+                    Some(pic) => Run::new().add_image(pic),
+                    None => {
+                        tracing::warn!("DOCX export: cannot embed image {path}; writing its path");
+                        Run::new().add_text(format!("[{path}]"))
+                    }
+                };
+                let docx = docx.add_paragraph(Paragraph::new().add_run(run));
+                match alt {
+                    Some(alt) => docx.add_paragraph(inline_runs(Paragraph::new(), alt)),
+                    None => docx,
+                }
+            }
+        };
+    }
+
+    let mut out = Cursor::new(Vec::new());
+    docx.build()
+        .pack(&mut out)
+        .map_err(|e| ApiError::Internal(format!("Failed to write document: {e}")))?;
+    Ok(out.into_inner())
+}
+
+fn fonts() -> RunFonts {
+    RunFonts::new()
+        .ascii(LATIN_FONT)
+        .hi_ansi(LATIN_FONT)
+        .east_asia(THAI_FONT)
+        .cs(THAI_FONT)
+}
+
+/// Add `text` to `paragraph`, with `**bold**` spans as bold runs.
+fn inline_runs(mut paragraph: Paragraph, text: &str) -> Paragraph {
+    for (i, span) in text.split("**").enumerate() {
+        if span.is_empty() {
+            continue;
+        }
+        let run = Run::new().add_text(span);
+        paragraph = paragraph.add_run(if i % 2 == 1 { run.bold() } else { run });
+    }
+    paragraph
+}
+
+fn word_table(table: &Table) -> DocxTable {
+    let cell = |text: &str, bold: bool| {
+        let run = Run::new().add_text(text);
+        TableCell::new().add_paragraph(Paragraph::new().add_run(if bold {
+            run.bold()
+        } else {
+            run
+        }))
+    };
+    let header = TableRow::new(table.headers.iter().map(|h| cell(h, true)).collect());
+    let rows = table
+        .rows
+        .iter()
+        .map(|row| TableRow::new(row.iter().map(|c| cell(c, false)).collect()));
+    DocxTable::new(std::iter::once(header).chain(rows).collect())
+}
+
+/// Read an image from the images dir, scaled down to fit the page width.
+///
+/// Returns `None` for missing files and images that cannot be decoded.
+fn load_picture(images_dir: &std::path::Path, path: &str) -> Option<Pic> {
+    let bytes = std::fs::read(image_file(images_dir, path)?).ok()?;
+    let img = image::load_from_memory(&bytes).ok()?;
+    // docx-rs embeds PNG data only
+    let png = if image::guess_format(&bytes).ok() == Some(image::ImageFormat::Png) {
+        bytes
+    } else {
+        let mut png = Cursor::new(Vec::new());
+        img.write_to(&mut png, image::ImageFormat::Png).ok()?;
+        png.into_inner()
+    };
+    let pic = Pic::new_with_dimensions(png, img.width(), img.height());
+    let (width, height) = pic.size;
+    if width <= MAX_IMAGE_WIDTH_EMU {
+        return Some(pic);
+    }
+    let scaled_height =
+        (u64::from(height) * u64::from(MAX_IMAGE_WIDTH_EMU) / u64::from(width)) as u32;
+    Some(pic.size(MAX_IMAGE_WIDTH_EMU, scaled_height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_blocks() {
+        let markdown = "# คู่มือ\n> Mode: `hybrid`\n\n---\n## Page 1\nข้อความ **สำคัญ**\n\
+                        [IMAGE:doc/p1_img1.png]\n**[ภาพที่ 1]:** ปุ่ม\n\
+                        | รุ่น | ราคา |\n|---|---|\n| A-1 | 100 |\n\
+                        ![แผนผัง](images/doc/p1_img2.png)\n";
+        assert_eq!(
+            parse_blocks(markdown),
+            vec![
+                Block::Heading(1, "คู่มือ".to_string()),
+                Block::Paragraph("Mode: `hybrid`".to_string()),
+                Block::Heading(2, "Page 1".to_string()),
+                Block::Paragraph("ข้อความ **สำคัญ**".to_string()),
+                Block::Image("doc/p1_img1.png".to_string(), None),
+                Block::Paragraph("**[ภาพที่ 1]:** ปุ่ม".to_string()),
+                Block::Table(Table {
+                    headers: vec!["รุ่น".to_string(), "ราคา".to_string()],
+                    rows: vec![vec!["A-1".to_string(), "100".to_string()]],
+                }),
+                Block::Image("doc/p1_img2.png".to_string(), Some("แผนผัง".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_picture_skips_undecodable_images() {
+        let dir = std::env::temp_dir().join(format!("jay-rag-docx-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("broken.png"), b"\x89PNG\r\n\x1a\nnot really").unwrap();
+        let mut jpeg = Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(40, 20)
+            .write_to(&mut jpeg, image::ImageFormat::Jpeg)
+            .unwrap();
+        std::fs::write(dir.join("photo.jpg"), jpeg.into_inner()).unwrap();
+
+        assert!(load_picture(&dir, "broken.png").is_none());
+        assert!(load_picture(&dir, "missing.png").is_none());
+        let pic = load_picture(&dir, "photo.jpg").unwrap();
+        let format = image::guess_format(&pic.image).unwrap();
+        assert_eq!(format, image::ImageFormat::Png);
+        assert!(pic.size.0 > pic.size.1);
+        // Images outside the images dir are never read
+        let absolute = dir.join("photo.jpg").to_string_lossy().to_string();
+        assert!(load_picture(&dir.join("images"), &absolute).is_none());
+        assert!(load_picture(&dir.join("images"), "../photo.jpg").is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
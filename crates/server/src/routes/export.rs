@@ -1,27 +1,68 @@
-use axum::extract::{Path, Query, State};
-use axum::http::header;
+use axum::body::Body;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderValue};
 use axum::response::Response;
+use jay_rag_core::config::{parse_page_selection, PageSelection};
+use jay_rag_core::ImageMetadata;
 use serde::Deserialize;
-use std::io::{Cursor, Write};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
 use std::sync::Arc;
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
 use uuid::Uuid;
 use zip::write::SimpleFileOptions;
 use zip::ZipWriter;
 
 use crate::error::ApiError;
-use crate::jobs::models::JobStatus;
+use crate::jobs::models::{JobResult, JobStatus};
 use crate::state::AppState;
 
 #[derive(Deserialize)]
 pub struct ExportParams {
     pub image_base_url: Option<String>,
+    /// Comma-separated 1-indexed pages to include, e.g. `3,7,10` or `2-5`.
+    pub pages: Option<String>,
+    /// Compression for text entries (images are always stored uncompressed).
+    #[serde(default)]
+    pub compression: ExportCompression,
+}
+
+/// ZIP compression level for exported text files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportCompression {
+    /// No compression for any entry.
+    Stored,
+    /// Deflate at level 1.
+    Fast,
+    /// Deflate at the default level.
+    Default,
+    /// Deflate at level 9.
+    Best,
+}
+
+impl Default for ExportCompression {
+    fn default() -> Self {
+        Self::Default
+    }
 }
 
 /// Export all results for a completed job as a ZIP archive.
+///
+/// When `pages` is given, only those pages' markdown sections and the images
+/// they reference are included.
+///
+/// The archive is written to `output/exports/` once per job, parameters and
+/// source file versions, then served from disk with `Range` / `If-Range`
+/// support so interrupted downloads of large exports can resume.
 pub async fn export_zip(
     Path(job_id): Path<Uuid>,
     Query(params): Query<ExportParams>,
     State(state): State<Arc<AppState>>,
+    request: Request,
 ) -> Result<Response, ApiError> {
     let job = state
         .job_queue
@@ -40,11 +81,110 @@ pub async fn export_zip(
         .result
         .ok_or_else(|| ApiError::Internal("Job completed but no results found".to_string()))?;
 
-    // Build the ZIP in memory
-    let buf = Cursor::new(Vec::new());
-    let mut zip = ZipWriter::new(buf);
-    let options = SimpleFileOptions::default()
-        .compression_method(zip::CompressionMethod::Deflated);
+    let selected_pages = match params.pages.as_deref() {
+        Some(spec) if !spec.trim().is_empty() => {
+            Some(parse_page_selection(spec).map_err(ApiError::BadRequest)?)
+        }
+        _ => None,
+    };
+
+    let zip_path = export_cache_path(&state.output_dir, &job_id, &params, &result).await;
+    if !zip_path.is_file() {
+        let zip_path = zip_path.clone();
+        tokio::task::spawn_blocking(move || {
+            write_export_zip(&zip_path, &result, &params, selected_pages.as_ref())
+        })
+        .await
+        .map_err(|e| ApiError::Internal(format!("Export task failed: {e}")))??;
+    }
+
+    let short_id = &job_id.to_string()[..8];
+    let filename = format!("{short_id}_results.zip");
+
+    let Ok(response) = ServeFile::new(&zip_path).oneshot(request).await;
+    let mut response = response.map(Body::new);
+    response.headers_mut().insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_str(&format!("attachment; filename=\"{filename}\"")).unwrap(),
+    );
+    Ok(response)
+}
+
+/// Remove cached export archives of a job (called when the job is deleted).
+pub async fn remove_cached_exports(output_dir: &std::path::Path, job_id: &Uuid) {
+    let Ok(mut entries) = tokio::fs::read_dir(output_dir.join("exports")).await else {
+        return;
+    };
+    let prefix = format!("{job_id}_");
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if entry.file_name().to_string_lossy().starts_with(&prefix) {
+            let _ = tokio::fs::remove_file(entry.path()).await;
+        }
+    }
+}
+
+/// Cache location for an export: `exports/{job_id}_{key}.zip`.
+///
+/// The key hashes the export parameters and the modification times of the
+/// markdown, metadata and images directory, so edits or image deletions after
+/// the first download produce a fresh archive.
+async fn export_cache_path(
+    output_dir: &std::path::Path,
+    job_id: &Uuid,
+    params: &ExportParams,
+    result: &JobResult,
+) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(params.pages.as_deref().unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(params.image_base_url.as_deref().unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(format!("{:?}", params.compression));
+    for path in [
+        &result.markdown_path,
+        &result.metadata_path,
+        &result.images_dir,
+    ] {
+        let modified = tokio::fs::metadata(path)
+            .await
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        hasher.update(modified.to_le_bytes());
+    }
+    let key: String = hasher.finalize()[..8]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    output_dir
+        .join("exports")
+        .join(format!("{job_id}_{key}.zip"))
+}
+
+/// Build the export archive at `zip_path`.
+///
+/// Written to a temporary file and renamed, so a concurrent download never
+/// serves a partial archive. Images are streamed from disk into the ZIP.
+fn write_export_zip(
+    zip_path: &std::path::Path,
+    result: &JobResult,
+    params: &ExportParams,
+    selected_pages: Option<&PageSelection>,
+) -> Result<(), ApiError> {
+    let zip_err = |e: zip::result::ZipError| ApiError::Internal(format!("ZIP error: {e}"));
+    let write_err = |e: std::io::Error| ApiError::Internal(format!("ZIP write error: {e}"));
+
+    if let Some(dir) = zip_path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| ApiError::Internal(format!("Failed to create exports dir: {e}")))?;
+    }
+    let tmp_path = zip_path.with_extension(format!("{}.tmp", Uuid::new_v4()));
+    let file = std::fs::File::create(&tmp_path)
+        .map_err(|e| ApiError::Internal(format!("Failed to create export file: {e}")))?;
+    let mut zip = ZipWriter::new(BufWriter::new(file));
+    let compression = params.compression;
 
     // Derive document stem from the markdown filename
     let md_path = std::path::Path::new(&result.markdown_path);
@@ -53,9 +193,22 @@ pub async fn export_zip(
         .and_then(|s| s.to_str())
         .unwrap_or("output");
 
-    // Add markdown file (optionally converting image tags)
-    if let Ok(md_bytes) = tokio::fs::read(&result.markdown_path).await {
-        let md_content = String::from_utf8_lossy(&md_bytes);
+    // Add markdown file (optionally slicing pages and converting image tags)
+    let mut referenced_images: Option<BTreeSet<String>> = None;
+    if let Ok(md_bytes) = std::fs::read(&result.markdown_path) {
+        let mut md_content = String::from_utf8_lossy(&md_bytes);
+        if let Some(pages) = selected_pages {
+            let sliced = jay_rag_core::select_markdown_pages(&md_content, pages);
+            referenced_images = Some(
+                sliced
+                    .lines()
+                    .filter_map(extract_image_path)
+                    .filter(|p| !p.contains(".."))
+                    .map(|p| p.to_string())
+                    .collect(),
+            );
+            md_content = sliced.into();
+        }
         let final_md = match &params.image_base_url {
             Some(base_url) if !base_url.is_empty() => {
                 convert_image_tags(&md_content, base_url)
@@ -63,76 +216,118 @@ pub async fn export_zip(
             _ => md_content.into_owned(),
         };
         let name = format!("{doc_stem}.md");
-        zip.start_file(&name, options)
-            .map_err(|e| ApiError::Internal(format!("ZIP error: {e}")))?;
-        zip.write_all(final_md.as_bytes())
-            .map_err(|e| ApiError::Internal(format!("ZIP write error: {e}")))?;
+        zip.start_file(&name, file_options(&name, compression))
+            .map_err(zip_err)?;
+        zip.write_all(final_md.as_bytes()).map_err(write_err)?;
     }
 
-    // Add metadata JSON
-    if let Ok(meta_bytes) = tokio::fs::read(&result.metadata_path).await {
+    // Add metadata JSON (only the selected pages' images, matched by image id)
+    if let Ok(mut meta_bytes) = std::fs::read(&result.metadata_path) {
+        if let Some(referenced) = &referenced_images {
+            meta_bytes = filter_metadata(&meta_bytes, referenced)?;
+        }
         let meta_path = std::path::Path::new(&result.metadata_path);
         let meta_name = meta_path
             .file_name()
             .and_then(|s| s.to_str())
             .unwrap_or("metadata.json");
-        zip.start_file(meta_name, options)
-            .map_err(|e| ApiError::Internal(format!("ZIP error: {e}")))?;
-        zip.write_all(&meta_bytes)
-            .map_err(|e| ApiError::Internal(format!("ZIP write error: {e}")))?;
+        zip.start_file(meta_name, file_options(meta_name, compression))
+            .map_err(zip_err)?;
+        zip.write_all(&meta_bytes).map_err(write_err)?;
     }
 
-    // Add all images from the images directory
+    // Add images: only the referenced ones for a page selection, otherwise all
     let images_dir = std::path::Path::new(&result.images_dir);
-    if images_dir.is_dir() {
-        let mut entries = tokio::fs::read_dir(images_dir)
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to read images dir: {e}")))?;
-
-        while let Some(entry) = entries
-            .next_entry()
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to read dir entry: {e}")))?
-        {
-            let path = entry.path();
-            if path.is_file() {
-                if let Ok(img_bytes) = tokio::fs::read(&path).await {
-                    let file_name = path
-                        .file_name()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("image.png");
-                    let zip_name = format!("images/{file_name}");
-                    zip.start_file(&zip_name, options)
-                        .map_err(|e| ApiError::Internal(format!("ZIP error: {e}")))?;
-                    zip.write_all(&img_bytes)
-                        .map_err(|e| ApiError::Internal(format!("ZIP write error: {e}")))?;
-                }
-            }
-        }
+    let image_paths: Vec<PathBuf> = match &referenced_images {
+        Some(referenced) => referenced.iter().map(|r| images_dir.join(r)).collect(),
+        None => std::fs::read_dir(images_dir)
+            .map(|entries| entries.filter_map(|e| e.ok().map(|e| e.path())).collect())
+            .unwrap_or_default(),
+    };
+    for path in image_paths.iter().filter(|p| p.is_file()) {
+        let Ok(mut img_file) = std::fs::File::open(path) else {
+            continue;
+        };
+        let file_name = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("image.png");
+        let zip_name = format!("images/{file_name}");
+        zip.start_file(&zip_name, file_options(&zip_name, compression))
+            .map_err(zip_err)?;
+        std::io::copy(&mut img_file, &mut zip).map_err(write_err)?;
     }
 
-    let cursor = zip
-        .finish()
-        .map_err(|e| ApiError::Internal(format!("ZIP finalize error: {e}")))?;
-    let zip_bytes = cursor.into_inner();
+    zip.finish()
+        .map_err(|e| ApiError::Internal(format!("ZIP finalize error: {e}")))?
+        .flush()
+        .map_err(write_err)?;
+    std::fs::rename(&tmp_path, zip_path)
+        .map_err(|e| ApiError::Internal(format!("Failed to save export file: {e}")))?;
+    Ok(())
+}
+
+/// Keep only the metadata entries for referenced images.
+///
+/// Entries are correlated with `[IMAGE:...]` tags by `image_id`, which is
+/// unique per page even when it mixes full-page, table and extracted images.
+fn filter_metadata(meta_bytes: &[u8], referenced: &BTreeSet<String>) -> Result<Vec<u8>, ApiError> {
+    let ids: BTreeSet<String> = referenced
+        .iter()
+        .map(|r| ImageMetadata::id_for_file(r))
+        .collect();
+    let metadata: Vec<ImageMetadata> = serde_json::from_slice(meta_bytes)
+        .map_err(|e| ApiError::Internal(format!("Invalid metadata JSON: {e}")))?;
+    let selected: Vec<ImageMetadata> = metadata
+        .into_iter()
+        .filter(|m| ids.contains(&m.resolved_id()))
+        .collect();
+    serde_json::to_vec_pretty(&selected)
+        .map_err(|e| ApiError::Internal(format!("Failed to serialize metadata: {e}")))
+}
 
-    let short_id = &job_id.to_string()[..8];
-    let filename = format!("{short_id}_results.zip");
+/// Pick the compression method and level for a ZIP entry.
+///
+/// Images are already compressed, so they are always stored; text files
+/// (markdown, JSON) use the requested level.
+fn compression_for(
+    file_name: &str,
+    compression: ExportCompression,
+) -> (zip::CompressionMethod, Option<i64>) {
+    let lower = file_name.to_lowercase();
+    let is_image = [".png", ".jpg", ".jpeg", ".webp", ".gif"]
+        .iter()
+        .any(|ext| lower.ends_with(ext));
 
-    Ok(Response::builder()
-        .header(header::CONTENT_TYPE, "application/zip")
-        .header(
-            header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{filename}\""),
-        )
-        .body(axum::body::Body::from(zip_bytes))
-        .unwrap())
+    if is_image {
+        return (zip::CompressionMethod::Stored, None);
+    }
+    match compression {
+        ExportCompression::Stored => (zip::CompressionMethod::Stored, None),
+        ExportCompression::Fast => (zip::CompressionMethod::Deflated, Some(1)),
+        ExportCompression::Default => (zip::CompressionMethod::Deflated, None),
+        ExportCompression::Best => (zip::CompressionMethod::Deflated, Some(9)),
+    }
+}
+
+/// Build ZIP entry options for a file according to [`compression_for`].
+fn file_options(file_name: &str, compression: ExportCompression) -> SimpleFileOptions {
+    let (method, level) = compression_for(file_name, compression);
+    SimpleFileOptions::default()
+        .compression_method(method)
+        .compression_level(level)
 }
 
 /// Convert `[IMAGE:path]` tags to HTML `<img>` tags, grouping consecutive
 /// images into a flex container with responsive widths.
 pub(crate) fn convert_image_tags(markdown: &str, base_url: &str) -> String {
     let base = base_url.trim_end_matches('/');
+    convert_image_tags_with(markdown, |p| format!("{base}/{p}"))
+}
+
+/// [`convert_image_tags`] with each image's `src` built by `src` from its path
+/// (attribute-escaped here).
+pub(crate) fn convert_image_tags_with(markdown: &str, src: impl Fn(&str) -> String) -> String {
     let mut output = String::with_capacity(markdown.len());
     let lines: Vec<&str> = markdown.lines().collect();
     let mut i = 0;
@@ -164,7 +359,8 @@ pub(crate) fn convert_image_tags(markdown: &str, base_url: &str) -> String {
             }
             for p in &paths {
                 output.push_str(&format!(
-                    "<img src=\"{base}/{p}\" style=\"{img_style};border-radius:8px;margin:8px 0;\">\n"
+                    "<img src=\"{}\" style=\"{img_style};border-radius:8px;margin:8px 0;\">\n",
+                    escape_attr(&src(p))
                 ));
             }
             if count > 1 {
@@ -187,9 +383,32 @@ pub(crate) fn convert_image_tags(markdown: &str, base_url: &str) -> String {
     output
 }
 
+/// Escape `value` for use inside a double-quoted HTML attribute.
+pub(crate) fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// File of an `[IMAGE:path]` reference under `images_dir`. `None` unless
+/// every component of `path` is a plain name, so a reference edited into the
+/// markdown cannot reach other files on the server.
+pub(crate) fn image_file(images_dir: &std::path::Path, path: &str) -> Option<PathBuf> {
+    let rel = std::path::Path::new(path);
+    let plain = rel.components().next().is_some()
+        && rel
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)));
+    let file = images_dir.join(rel);
+    (plain && file.starts_with(images_dir)).then_some(file)
+}
+
 /// Extract the path from an `[IMAGE:path]` tag, returning `None` if the line
 /// doesn't match the pattern.
-fn extract_image_path(line: &str) -> Option<&str> {
+pub(crate) fn extract_image_path(line: &str) -> Option<&str> {
     let trimmed = line.trim();
     let rest = trimmed.strip_prefix("[IMAGE:")?;
     let path = rest.strip_suffix(']')?;
@@ -244,10 +463,72 @@ mod tests {
         assert!(result.contains(r#"src="http://host/path/img.png""#));
     }
 
+    #[test]
+    fn test_parse_page_selection() {
+        assert_eq!(parse_page_selection("3,7,10").unwrap().to_string(), "3,7,10");
+        assert_eq!(parse_page_selection("2-4, 3, 9").unwrap().to_string(), "2-4,9");
+        assert!(parse_page_selection("0").is_err());
+        assert!(parse_page_selection("5-2").is_err());
+        assert!(parse_page_selection("a,b").is_err());
+    }
+
+    #[test]
+    fn test_compression_per_file_type() {
+        use zip::CompressionMethod;
+
+        assert_eq!(
+            compression_for("images/page_001.png", ExportCompression::Best),
+            (CompressionMethod::Stored, None)
+        );
+        assert_eq!(
+            compression_for("doc.md", ExportCompression::Default),
+            (CompressionMethod::Deflated, None)
+        );
+        assert_eq!(
+            compression_for("doc_images_metadata.json", ExportCompression::Fast),
+            (CompressionMethod::Deflated, Some(1))
+        );
+        assert_eq!(
+            compression_for("doc.md", ExportCompression::Best),
+            (CompressionMethod::Deflated, Some(9))
+        );
+        assert_eq!(
+            compression_for("doc.md", ExportCompression::Stored),
+            (CompressionMethod::Stored, None)
+        );
+    }
+
+    #[test]
+    fn test_filter_metadata_by_image_id() {
+        let entry = |file: &str, kind: &str| {
+            format!(
+                r#"{{"image_file":"doc/{file}","page":3,"type":"{kind}","description":"","source_doc":"doc","provider":"p","model":"m"}}"#
+            )
+        };
+        let json = format!(
+            "[{},{},{}]",
+            entry("doc_page_003_full.png", "full_page"),
+            entry("doc_page_003_table.png", "table_region"),
+            entry("doc_page_003_img1.png", "extracted_image"),
+        );
+        let referenced: BTreeSet<String> = ["doc/doc_page_003_table.png".to_string()].into();
+        let filtered = filter_metadata(json.as_bytes(), &referenced).unwrap();
+        let filtered: Vec<ImageMetadata> = serde_json::from_slice(&filtered).unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].resolved_id(), "doc_page_003_table");
+    }
+
     #[test]
     fn test_empty_image_tag_ignored() {
         let md = "[IMAGE:]";
         let result = convert_image_tags(md, "http://host");
         assert_eq!(result, "[IMAGE:]");
     }
+
+    #[test]
+    fn test_image_src_attribute_escaped() {
+        let result = convert_image_tags("[IMAGE:a.png]", "http://host/\"><script>");
+        assert!(!result.contains("<script>"));
+        assert!(result.contains("src=\"http://host/&quot;&gt;&lt;script&gt;/a.png\""));
+    }
 }
@@ -1,15 +1,47 @@
+use axum::extract::State;
+use axum::http::StatusCode;
 use axum::Json;
 use serde::Serialize;
+use std::sync::Arc;
+
+use crate::state::AppState;
 
 #[derive(Serialize)]
 pub struct HealthResponse {
     pub status: &'static str,
     pub version: &'static str,
+    /// Whether pdfium loaded at startup (jobs cannot run without it).
+    pub pdfium: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pdfium_error: Option<String>,
+    /// Server runs with `--simulate`: job results are canned, not real.
+    pub simulate: bool,
+    /// Processing is paused: new jobs stay pending until resumed.
+    pub paused: bool,
 }
 
-pub async fn health_check() -> Json<HealthResponse> {
-    Json(HealthResponse {
-        status: "ok",
-        version: env!("CARGO_PKG_VERSION"),
-    })
+/// Readiness check: 503 when pdfium could not be loaded (except with
+/// `--simulate`), so probes catch a missing `libpdfium` before uploads arrive.
+///
+/// The pdfium bind is attempted once at startup and cached in [`AppState`].
+pub async fn health_check(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, Json<HealthResponse>) {
+    let ready = state.pdfium_ok || state.simulate;
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(HealthResponse {
+            status: if ready { "ok" } else { "unavailable" },
+            version: env!("CARGO_PKG_VERSION"),
+            pdfium: state.pdfium_ok,
+            pdfium_error: state.pdfium_error.clone(),
+            simulate: state.simulate,
+            paused: *state.paused.borrow(),
+        }),
+    )
 }
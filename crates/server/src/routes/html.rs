@@ -0,0 +1,226 @@
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use base64::Engine;
+use pulldown_cmark::{html, Event, Options, Parser};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::jobs::models::JobStatus;
+use crate::routes::export::image_file;
+use crate::state::AppState;
+
+/// Page styles: Thai-capable fonts with the taller line height Thai vowel
+/// and tone marks need, and images and tables that fit narrow screens.
+const HTML_STYLE: &str = "\
+body{max-width:860px;margin:0 auto;padding:16px;\
+font-family:\"Sarabun\",\"Noto Sans Thai\",\"Leelawadee UI\",Tahoma,sans-serif;\
+font-size:17px;line-height:1.8;color:#222;overflow-wrap:break-word}\
+img{max-width:100%;height:auto}\
+table{display:block;overflow-x:auto;border-collapse:collapse;margin:12px 0}\
+th,td{border:1px solid #ccc;padding:6px 10px;text-align:left}\
+th{background:#f4f4f4}\
+hr{border:0;border-top:1px solid #ddd;margin:32px 0}";
+
+/// No scripts, frames or remote fetches besides images: the page renders
+/// LLM output and is served inline from the API origin.
+const CONTENT_SECURITY_POLICY: &str =
+    "default-src 'none'; img-src http: https: data:; style-src 'unsafe-inline'";
+
+#[derive(Deserialize)]
+pub struct HtmlExportParams {
+    /// Link images under this URL instead of embedding them.
+    pub image_base_url: Option<String>,
+}
+
+/// Export a job's enriched markdown as a standalone HTML page.
+///
+/// `[IMAGE:...]` tags become images: linked under `image_base_url` (http or
+/// https only) when given, otherwise embedded as data URIs so the file can be
+/// shared on its own. Raw HTML in the markdown is dropped.
+///
+/// GET /api/results/{job_id}/export.html?image_base_url=
+pub async fn export_html(
+    Path(job_id): Path<Uuid>,
+    Query(params): Query<HtmlExportParams>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let job = state
+        .job_queue
+        .get_job(&job_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Job {job_id} not found")))?;
+
+    if job.status != JobStatus::Completed {
+        return Err(ApiError::BadRequest(format!(
+            "Job {job_id} is not completed (status: {:?})",
+            job.status
+        )));
+    }
+
+    let result = job
+        .result
+        .ok_or_else(|| ApiError::Internal("Job completed but no results found".to_string()))?;
+
+    let markdown = tokio::fs::read_to_string(&result.markdown_path)
+        .await
+        .map_err(|_| ApiError::NotFound("Markdown file no longer available".to_string()))?;
+
+    let title = job
+        .filename
+        .strip_suffix(".pdf")
+        .unwrap_or(&job.filename)
+        .to_string();
+    let page = match params.image_base_url.as_deref() {
+        Some(base_url) => {
+            let base = image_base_url(base_url)?;
+            render_html(&title, &markdown, |p| format!("{base}/{p}"))
+        }
+        None => {
+            let images_dir = std::path::PathBuf::from(&result.images_dir);
+            tokio::task::spawn_blocking(move || {
+                render_html(&title, &markdown, |p| data_uri(&images_dir, p))
+            })
+            .await
+            .map_err(|e| ApiError::Internal(format!("Export task failed: {e}")))?
+        }
+    };
+
+    let filename = format!("{}_results.html", &job_id.to_string()[..8]);
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{filename}\""),
+            ),
+            (
+                header::CONTENT_SECURITY_POLICY,
+                CONTENT_SECURITY_POLICY.to_string(),
+            ),
+        ],
+        page,
+    ))
+}
+
+/// `image_base_url` without its trailing slash; only http(s) URLs are accepted.
+fn image_base_url(base_url: &str) -> Result<&str, ApiError> {
+    let base = base_url.trim().trim_end_matches('/');
+    let scheme = base.split_once("://").map(|(s, _)| s.to_ascii_lowercase());
+    match scheme.as_deref() {
+        Some("http" | "https") if base.len() > "https://".len() => Ok(base),
+        _ => Err(ApiError::BadRequest(
+            "image_base_url must be an http(s) URL".to_string(),
+        )),
+    }
+}
+
+/// Render markdown (with tables) into a complete HTML document.
+///
+/// `[IMAGE:path]` lines become markdown images with `src(path)` as target,
+/// so the renderer escapes them; raw HTML (including any the LLM wrote) is
+/// dropped.
+fn render_html(title: &str, markdown: &str, src: impl Fn(&str) -> String) -> String {
+    let markdown: String = markdown
+        .lines()
+        .map(|line| match super::export::extract_image_path(line) {
+            Some(path) => format!("![](<{}>)\n", src(path).replace(['<', '>', '\n'], "")),
+            None => format!("{line}\n"),
+        })
+        .collect();
+    let events = Parser::new_ext(&markdown, Options::ENABLE_TABLES)
+        .filter(|event| !matches!(event, Event::Html(_) | Event::InlineHtml(_)));
+    let mut body = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut body, events);
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"th\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n{body}</body>\n</html>\n",
+        escape_html(title)
+    )
+}
+
+/// Inline an image from the images dir as a `data:` URI, or keep its path
+/// under `/images` (served by this server) if it cannot be read.
+fn data_uri(images_dir: &std::path::Path, path: &str) -> String {
+    let bytes = image_file(images_dir, path).and_then(|file| std::fs::read(file).ok());
+    let Some(bytes) = bytes else {
+        return format!("/images/{path}");
+    };
+    let mime = match path.rsplit('.').next().map(str::to_lowercase).as_deref() {
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        Some("gif") => "image/gif",
+        _ => "image/png",
+    };
+    let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+    format!("data:{mime};base64,{encoded}")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_html() {
+        let page = render_html(
+            "คู่มือ <v2>",
+            "## Page 1\nข้อความ\n\n| รุ่น | ราคา |\n|---|---|\n| A-1 | 100 |\n",
+            |p| p.to_string(),
+        );
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<title>คู่มือ &lt;v2&gt;</title>"));
+        assert!(page.contains("<h2>Page 1</h2>"));
+        assert!(page.contains("<td>A-1</td>"));
+        assert!(page.ends_with("</html>\n"));
+    }
+
+    #[test]
+    fn test_render_html_escapes_injected_markup() {
+        assert!(image_base_url("\"><script>alert(1)</script>").is_err());
+        assert!(image_base_url("javascript:alert(1)//").is_err());
+        assert_eq!(
+            image_base_url(" https://cdn.example.com/rag/ ").unwrap(),
+            "https://cdn.example.com/rag"
+        );
+
+        let base = image_base_url("https://cdn.example.com/\"><script>alert(1)</script>").unwrap();
+        let page = render_html(
+            "doc",
+            "[IMAGE:doc/p1_img1.png]\n**[ภาพที่ 1]:** <script>alert(2)</script>\n\n<div onclick=\"x()\">\n",
+            |p| format!("{base}/{p}"),
+        );
+        assert!(!page.contains("<script>"), "{page}");
+        assert!(!page.contains("onclick"), "{page}");
+        assert!(page.contains("<img src=\"https://cdn.example.com/"));
+        assert!(page.contains("doc/p1_img1.png"));
+    }
+
+    #[test]
+    fn test_data_uri_stays_in_images_dir() {
+        let dir = std::env::temp_dir().join(format!("jay-rag-html-{}", Uuid::new_v4()));
+        let images_dir = dir.join("images");
+        std::fs::create_dir_all(images_dir.join("doc")).unwrap();
+        std::fs::write(images_dir.join("doc/p1.png"), b"png").unwrap();
+        std::fs::write(dir.join("secret.png"), b"secret").unwrap();
+
+        assert!(data_uri(&images_dir, "doc/p1.png").starts_with("data:image/png;base64,"));
+        let secret = dir.join("secret.png").to_string_lossy().to_string();
+        for path in [secret.as_str(), "../secret.png", "doc/../../secret.png"] {
+            assert!(
+                data_uri(&images_dir, path).starts_with("/images/"),
+                "{path}"
+            );
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
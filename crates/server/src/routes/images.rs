@@ -66,6 +66,7 @@ pub async fn delete_images(
         }
 
         let image_path = images_dir.join(image_file);
+        let _ = tokio::fs::remove_file(format!("{}.json", image_path.display())).await;
         match tokio::fs::remove_file(&image_path).await {
             Ok(()) => deleted.push(image_file.clone()),
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
@@ -1,16 +1,38 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::Json;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::error::ApiError;
-use crate::jobs::models::{Job, JobProgress, JobStatus};
+use crate::jobs::models::{Job, JobConfig, JobProgress, JobResult, JobStatus};
+use crate::jobs::queue::JobFilter;
+use crate::jobs::retention;
+use crate::jobs::storage::JobOutputs;
 use crate::state::AppState;
+use jay_rag_core::config::{
+    CaptionPlacement, FigureNumbering, Language, LineEnding, PageFallbackStrategy, Quality,
+    WideTableStrategy,
+};
+use jay_rag_core::noise::NoiseFilter;
+use jay_rag_core::provider::{self, ModelPolicy};
+use jay_rag_core::trash::TrashPreset;
 
 #[derive(Serialize)]
 pub struct JobListResponse {
     pub jobs: Vec<Job>,
+    /// Jobs matching the filters, across all pages.
+    pub total: u64,
+}
+
+#[derive(Deserialize)]
+pub struct JobListParams {
+    pub status: Option<JobStatus>,
+    /// Case-insensitive substring of the filename.
+    pub q: Option<String>,
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub offset: u32,
 }
 
 #[derive(Serialize)]
@@ -18,12 +40,33 @@ pub struct DeleteResponse {
     pub message: String,
 }
 
-/// List all jobs.
+#[derive(Deserialize)]
+pub struct CleanupParams {
+    /// Retention period in days (default: the server's `--retention-days`).
+    pub days: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct CleanupResponse {
+    pub deleted: usize,
+    pub message: String,
+}
+
+/// List jobs, newest first, optionally filtered by `status` and filename
+/// (`q`) and paginated with `limit` / `offset`.
 pub async fn list_jobs(
+    Query(params): Query<JobListParams>,
     State(state): State<Arc<AppState>>,
 ) -> Json<JobListResponse> {
-    let jobs = state.job_queue.list_jobs().await;
-    Json(JobListResponse { jobs })
+    let filter = JobFilter {
+        status: params.status,
+        filename: params.q.filter(|q| !q.trim().is_empty()),
+        limit: params.limit,
+        offset: params.offset,
+    };
+    let jobs = state.job_queue.list_jobs(&filter).await;
+    let total = state.job_queue.count_jobs(&filter).await;
+    Json(JobListResponse { jobs, total })
 }
 
 /// Get a single job by ID.
@@ -39,6 +82,126 @@ pub async fn get_job(
         .ok_or_else(|| ApiError::NotFound(format!("Job {id} not found")))
 }
 
+/// Child jobs of a split upload, in page order (empty for other jobs).
+///
+/// GET /api/jobs/{id}/children
+pub async fn list_children(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Job>>, ApiError> {
+    if state.job_queue.get_job(&id).await.is_none() {
+        return Err(ApiError::NotFound(format!("Job {id} not found")));
+    }
+    Ok(Json(state.job_queue.list_children(&id).await))
+}
+
+/// Combine the outputs of a split upload's parts, in page order, into
+/// `{id}_merged.md` and `{id}_merged_images_metadata.json` in the job's
+/// storage, and record them as the job's result. Every part must be completed.
+///
+/// POST /api/jobs/{id}/merge
+pub async fn merge_job(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Job>, ApiError> {
+    let job = state
+        .job_queue
+        .get_job(&id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Job {id} not found")))?;
+
+    let children = state.job_queue.list_children(&id).await;
+    if children.is_empty() {
+        return Err(ApiError::BadRequest(format!(
+            "Job {id} was not split into parts"
+        )));
+    }
+    if let Some(child) = children.iter().find(|c| c.status != JobStatus::Completed) {
+        return Err(ApiError::BadRequest(format!(
+            "Part {} ({}) is {:?}; every part must be completed before merging",
+            child.id, child.filename, child.status
+        )));
+    }
+
+    let outputs = JobOutputs::open(&job.config, &state.output_dir, &state.storage_root)
+        .await
+        .map_err(ApiError::BadRequest)?;
+    let mut markdown = Vec::with_capacity(children.len());
+    let mut metadata: Vec<serde_json::Value> = Vec::new();
+    let (mut image_count, mut input_tokens, mut output_tokens) = (0, 0, 0);
+    for child in &children {
+        let result = child.result.as_ref().ok_or_else(|| {
+            ApiError::Internal(format!("Part {} completed but no results found", child.id))
+        })?;
+        let unavailable =
+            |_| ApiError::NotFound(format!("Output of part {} no longer available", child.id));
+        markdown.push(
+            outputs
+                .read_to_string(&result.markdown_path)
+                .await
+                .map_err(unavailable)?,
+        );
+        let json = outputs
+            .read_to_string(&result.metadata_path)
+            .await
+            .map_err(unavailable)?;
+        let entries: Vec<serde_json::Value> = serde_json::from_str(&json)
+            .map_err(|e| ApiError::Internal(format!("Invalid metadata file: {e}")))?;
+        metadata.extend(entries);
+        image_count += result.image_count;
+        input_tokens += result.input_tokens;
+        output_tokens += result.output_tokens;
+    }
+
+    // The upload's filename only titles the document: files are named by job id
+    let title = job.filename.strip_suffix(".pdf").unwrap_or(&job.filename);
+    let metadata_json = serde_json::to_string_pretty(&metadata)
+        .map_err(|e| ApiError::Internal(format!("Failed to serialize metadata: {e}")))?;
+    let merged = jay_rag_core::merge_markdown_parts(title, &markdown);
+    let line_ending: LineEnding = job.config.line_ending.parse().unwrap_or_default();
+    let markdown_path = outputs
+        .write(&format!("{id}_merged.md"), &line_ending.apply(&merged))
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to write merged markdown: {e}")))?;
+    let metadata_path = outputs
+        .write(&format!("{id}_merged_images_metadata.json"), &metadata_json)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to write merged metadata: {e}")))?;
+
+    let result = JobResult {
+        markdown_path: markdown_path.clone(),
+        metadata_path,
+        image_count,
+        images_dir: state
+            .output_dir
+            .join("images")
+            .to_string_lossy()
+            .to_string(),
+        trash_path: None,
+        trash_count: 0,
+        markdown_parts: Vec::new(),
+        rag_jsonl_path: None,
+        pages_jsonl_path: None,
+        manifest_path: None,
+        input_tokens,
+        output_tokens,
+        partial: false,
+        last_page: None,
+    };
+    state.job_queue.set_result(&id, &result).await;
+    tracing::info!(
+        "Job {id}: merged {} part(s) into {markdown_path}",
+        children.len()
+    );
+
+    state
+        .job_queue
+        .get_job(&id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Job {id} not found")))
+}
+
 /// Delete/cancel a job and clean up associated files.
 pub async fn delete_job(
     Path(id): Path<Uuid>,
@@ -51,23 +214,7 @@ pub async fn delete_job(
         .await
         .ok_or_else(|| ApiError::NotFound(format!("Job {id} not found")))?;
 
-    // Clean up uploaded PDF
-    let pdf_path = state.upload_dir.join(format!("{id}.pdf"));
-    let _ = tokio::fs::remove_file(&pdf_path).await;
-
-    // Clean up output files if the job produced results
-    if let Some(result) = &job.result {
-        let _ = tokio::fs::remove_file(&result.markdown_path).await;
-        let _ = tokio::fs::remove_file(&result.metadata_path).await;
-
-        // Delete images directory: derive doc stem from filename
-        let doc_stem = job.filename.strip_suffix(".pdf").unwrap_or(&job.filename);
-        let images_dir = state.output_dir.join("images").join(doc_stem);
-        let _ = tokio::fs::remove_dir_all(&images_dir).await;
-    }
-
-    // Delete the DB row
-    if state.job_queue.delete_job(&id).await {
+    if retention::remove_job(&state, &job).await {
         Ok(Json(DeleteResponse {
             message: format!("Job {id} deleted"),
         }))
@@ -76,64 +223,239 @@ pub async fn delete_job(
     }
 }
 
-/// Cancel a pending or processing job.
-pub async fn cancel_job(
+/// Delete jobs older than the retention period, with their files, without
+/// waiting for the hourly retention task. Jobs still processing are kept.
+pub async fn cleanup_jobs(
+    Query(params): Query<CleanupParams>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<CleanupResponse>, ApiError> {
+    let retention_days = params.days.or(state.retention_days).ok_or_else(|| {
+        ApiError::BadRequest(
+            "No retention period: pass `days` or start the server with --retention-days"
+                .to_string(),
+        )
+    })?;
+    let deleted = retention::cleanup_expired(&state, retention_days).await;
+    Ok(Json(CleanupResponse {
+        deleted,
+        message: format!("Deleted {deleted} job(s) older than {retention_days} day(s)"),
+    }))
+}
+
+/// Replace the config of a job that has not started processing yet.
+pub async fn update_job_config(
     Path(id): Path<Uuid>,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<DeleteResponse>, ApiError> {
+    Json(config): Json<JobConfig>,
+) -> Result<Json<Job>, ApiError> {
     let job = state
         .job_queue
         .get_job(&id)
         .await
         .ok_or_else(|| ApiError::NotFound(format!("Job {id} not found")))?;
 
-    if job.status != JobStatus::Pending && job.status != JobStatus::Processing {
+    if job.status != JobStatus::Pending {
         return Err(ApiError::BadRequest(format!(
-            "Job {id} is {:?} and cannot be cancelled",
+            "Job {id} is {:?}; only pending jobs can be reconfigured",
             job.status
         )));
     }
 
-    // Abort the spawned task if it exists
-    if let Some(handle) = state.task_handles.lock().await.remove(&id) {
-        handle.abort();
+    validate_job_config(&config).map_err(ApiError::BadRequest)?;
+
+    if !state.job_queue.update_pending_config(&id, &config).await {
+        return Err(ApiError::BadRequest(format!(
+            "Job {id} started before the config could be updated"
+        )));
     }
 
-    // Update DB status
-    state.job_queue.set_cancelled(&id).await;
+    tracing::info!("Job {id} config updated: {} / {:?}", config.provider, config.model);
 
-    // Notify WebSocket clients
     state
         .job_queue
-        .update_progress(
-            &id,
-            JobProgress {
-                current_page: 0,
-                total_pages: 0,
-                images_processed: 0,
-                phase: "cancelled".to_string(),
-                message: "Job cancelled by user".to_string(),
-            },
-        )
-        .await;
+        .get_job(&id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Job {id} not found")))
+}
 
-    // Clean up partial output files
+/// Check that a job config names known providers and parseable options.
+fn validate_job_config(config: &JobConfig) -> Result<(), String> {
+    if provider::find_provider(&config.provider).is_none() {
+        return Err(format!("Unknown provider '{}'", config.provider));
+    }
+    let model = config
+        .model
+        .clone()
+        .unwrap_or_else(|| provider::default_model(&config.provider).to_string());
+    let policy = ModelPolicy::from_env().map_err(|e| e.to_string())?;
+    policy
+        .check(&config.provider, &model)
+        .map_err(|e| e.to_string())?;
+
+    if let Some(premium) = config
+        .premium_provider
+        .as_deref()
+        .filter(|p| provider::find_provider(p).is_none())
+    {
+        return Err(format!("Unknown premium provider '{premium}'"));
+    }
+
+    config.language.parse::<Language>()?;
+    config.quality.parse::<Quality>()?;
+    config.figure_numbering.parse::<FigureNumbering>()?;
+    config.wide_table_strategy.parse::<WideTableStrategy>()?;
+    config.line_ending.parse::<LineEnding>()?;
+    config.trash_preset.parse::<TrashPreset>()?;
+    config.page_fallback_strategy.parse::<PageFallbackStrategy>()?;
+    config.caption_placement.parse::<CaptionPlacement>()?;
+    crate::jobs::storage::validate_storage_config(config)?;
+    NoiseFilter::new(&config.chunk_noise_patterns).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Re-run a failed or cancelled job from its stored PDF, with its original config.
+///
+/// Markdown is only written once every page has been processed, so a failed
+/// run leaves nothing to resume from: the configured page range runs again.
+/// For a split upload, its failed and cancelled parts are retried.
+pub async fn retry_job(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Job>, ApiError> {
+    let job = state
+        .job_queue
+        .get_job(&id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Job {id} not found")))?;
+
+    if job.status != JobStatus::Failed && job.status != JobStatus::Cancelled {
+        return Err(ApiError::BadRequest(format!(
+            "Job {id} is {:?}; only failed or cancelled jobs can be retried",
+            job.status
+        )));
+    }
+
+    let children = state.job_queue.list_children(&id).await;
+    if children.is_empty() {
+        retry_single_job(&state, job).await?;
+    } else {
+        for child in children
+            .into_iter()
+            .filter(|c| matches!(c.status, JobStatus::Failed | JobStatus::Cancelled))
+        {
+            retry_single_job(&state, child).await?;
+        }
+    }
+
+    state
+        .job_queue
+        .get_job(&id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Job {id} not found")))
+}
+
+async fn retry_single_job(state: &AppState, job: Job) -> Result<(), ApiError> {
+    let id = job.id;
     let pdf_path = state.upload_dir.join(format!("{id}.pdf"));
-    let _ = tokio::fs::remove_file(&pdf_path).await;
+    if !pdf_path.exists() {
+        return Err(ApiError::BadRequest(format!(
+            "Uploaded PDF for job {id} no longer exists; upload it again"
+        )));
+    }
+
+    if !state.job_queue.reset_for_retry(&id).await {
+        return Err(ApiError::BadRequest(format!(
+            "Job {id} changed state before it could be retried"
+        )));
+    }
 
-    let doc_stem = job.filename.strip_suffix(".pdf").unwrap_or(&job.filename);
-    let images_dir = state.output_dir.join("images").join(doc_stem);
-    let _ = tokio::fs::remove_dir_all(&images_dir).await;
+    super::upload::spawn_job(state, id, pdf_path, job.config).await;
+    tracing::info!("Job {id} queued for retry");
+    Ok(())
+}
 
-    let md_path = state.output_dir.join(format!("{doc_stem}.md"));
-    let _ = tokio::fs::remove_file(&md_path).await;
+/// Cancel a pending or processing job; for a split upload, each of its
+/// parts still pending or processing.
+pub async fn cancel_job(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<DeleteResponse>, ApiError> {
+    let job = state
+        .job_queue
+        .get_job(&id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Job {id} not found")))?;
 
-    let meta_path = state.output_dir.join(format!("{doc_stem}_metadata.json"));
-    let _ = tokio::fs::remove_file(&meta_path).await;
+    if job.status != JobStatus::Pending && job.status != JobStatus::Processing {
+        return Err(ApiError::BadRequest(format!(
+            "Job {id} is {:?} and cannot be cancelled",
+            job.status
+        )));
+    }
 
-    tracing::info!("Job {id} cancelled by user");
+    let children = state.job_queue.list_children(&id).await;
+    if children.is_empty() {
+        let message = cancel_single_job(&state, job).await;
+        return Ok(Json(DeleteResponse { message }));
+    }
 
+    let running: Vec<Job> = children
+        .into_iter()
+        .filter(|c| matches!(c.status, JobStatus::Pending | JobStatus::Processing))
+        .collect();
+    let count = running.len();
+    for child in running {
+        cancel_single_job(&state, child).await;
+    }
     Ok(Json(DeleteResponse {
-        message: format!("Job {id} cancelled"),
+        message: format!("Job {id}: cancelling {count} part(s)"),
     }))
 }
+
+async fn cancel_single_job(state: &AppState, job: Job) -> String {
+    let id = job.id;
+
+    // A running job stops between pages and completes with the pages done so far
+    let cancel = if job.status == JobStatus::Processing {
+        state.cancel_tokens.lock().await.remove(&id)
+    } else {
+        None
+    };
+    if let Some(cancel) = cancel {
+        cancel.cancel();
+        let progress = job.progress.clone();
+        state
+            .job_queue
+            .update_progress(
+                &id,
+                JobProgress {
+                    current_page: progress.as_ref().map_or(0, |p| p.current_page),
+                    total_pages: progress.as_ref().map_or(0, |p| p.total_pages),
+                    images_processed: progress.as_ref().map_or(0, |p| p.images_processed),
+                    phase: "cancelling".to_string(),
+                    message: "Cancelling — keeping the pages finished so far".to_string(),
+                    partial: None,
+                },
+            )
+            .await;
+        tracing::info!("Job {id} cancelling; finished pages will be kept");
+        return format!("Job {id} cancelling; finished pages will be kept");
+    }
+
+    // Abort the spawned task if it exists
+    if let Some(handle) = state.task_handles.lock().await.remove(&id) {
+        handle.abort();
+    }
+
+    // Update DB status and notify WebSocket clients
+    state.job_queue.set_cancelled(&id).await;
+
+    // Clean up partial output files; the uploaded PDF stays for a retry and
+    // is removed when the job is deleted or expires
+    crate::jobs::runner::remove_job_outputs(&state.output_dir, &id).await;
+
+    tracing::info!("Job {id} cancelled by user");
+    format!("Job {id} cancelled")
+}
@@ -1,4 +1,6 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
 use axum::Json;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -9,6 +11,12 @@ use crate::error::ApiError;
 use crate::jobs::models::JobStatus;
 use crate::state::AppState;
 
+#[derive(Deserialize)]
+pub struct RawMarkdownParams {
+    /// Rewrite `[IMAGE:...]` tags as `<img>` tags under this URL.
+    pub base_url: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct SaveMarkdownRequest {
     pub markdown: String,
@@ -20,6 +28,51 @@ pub struct SaveMarkdownResponse {
     pub bytes_written: usize,
 }
 
+/// Return the job's markdown as-is, without the JSON wrapping of `get_results`.
+///
+/// GET /api/results/{job_id}/markdown?base_url=
+pub async fn get_markdown(
+    Path(job_id): Path<Uuid>,
+    Query(params): Query<RawMarkdownParams>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let job = state
+        .job_queue
+        .get_job(&job_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Job {job_id} not found")))?;
+
+    if job.status != JobStatus::Completed {
+        return Err(ApiError::BadRequest(format!(
+            "Job {job_id} is not completed (status: {:?})",
+            job.status
+        )));
+    }
+
+    let result = job
+        .result
+        .ok_or_else(|| ApiError::Internal("Job completed but no results found".to_string()))?;
+
+    let markdown = tokio::fs::read_to_string(&result.markdown_path)
+        .await
+        .map_err(|_| ApiError::NotFound("Markdown file no longer available".to_string()))?;
+
+    let markdown = match params.base_url.as_deref().map(str::trim) {
+        Some("") => {
+            return Err(ApiError::BadRequest(
+                "base_url must not be empty".to_string(),
+            ));
+        }
+        Some(base_url) => super::export::convert_image_tags(&markdown, base_url),
+        None => markdown,
+    };
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+        markdown,
+    ))
+}
+
 /// Save edited markdown back to the job's result file.
 ///
 /// POST /api/results/{job_id}/markdown
@@ -1,12 +1,18 @@
+pub mod admin;
+pub mod catalog;
 pub mod clean;
 pub mod config;
 pub mod deploy;
+pub mod docx;
 pub mod export;
 pub mod health;
+pub mod html;
 pub mod images;
 pub mod jobs;
 pub mod markdown;
 pub mod pdf;
+pub mod providers;
 pub mod results;
 pub mod settings;
+pub mod tables;
 pub mod upload;
@@ -0,0 +1,70 @@
+use axum::Json;
+use jay_rag_core::provider::{self, ModelPolicy};
+use jay_rag_core::{CoreError, CoreResult};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::error::ApiError;
+
+/// Longest a provider check may take before it is reported as unreachable.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+pub struct ProviderCheckRequest {
+    pub provider: String,
+    /// Model to check (default: the provider's default model).
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ProviderCheckResponse {
+    pub ok: bool,
+    pub message: String,
+}
+
+/// Check a provider's credentials and connectivity before submitting a job.
+///
+/// A failed check (missing API key, unreachable server, Ollama model not
+/// pulled) is reported as `ok: false` with the provider's message.
+///
+/// POST /api/providers/check
+pub async fn check_provider(
+    Json(request): Json<ProviderCheckRequest>,
+) -> Result<Json<ProviderCheckResponse>, ApiError> {
+    if provider::find_provider(&request.provider).is_none() {
+        return Err(ApiError::BadRequest(format!(
+            "Unknown provider '{}'",
+            request.provider
+        )));
+    }
+    let model = request
+        .model
+        .unwrap_or_else(|| provider::default_model(&request.provider).to_string());
+
+    let result = run_check(&request.provider, &model).await;
+    Ok(Json(match result {
+        Ok(()) => ProviderCheckResponse {
+            ok: true,
+            message: format!("{} / {model} is available", request.provider),
+        },
+        Err(e) => ProviderCheckResponse {
+            ok: false,
+            message: jay_rag_core::redact_secrets(&e.to_string()),
+        },
+    }))
+}
+
+/// Apply the model policy, build the provider and run its check.
+async fn run_check(provider_name: &str, model: &str) -> CoreResult<()> {
+    ModelPolicy::from_env()?.check(provider_name, model)?;
+    let vision = provider::create_provider(provider_name, model)?;
+    tokio::time::timeout(CHECK_TIMEOUT, vision.check())
+        .await
+        .map_err(|_| {
+            CoreError::Provider(format!(
+                "No answer from {provider_name} within {}s",
+                CHECK_TIMEOUT.as_secs()
+            ))
+        })?
+}
@@ -18,6 +18,13 @@ pub struct ResultsResponse {
     pub trash_count: u32,
 }
 
+#[derive(Serialize)]
+pub struct TrashResponse {
+    pub job_id: Uuid,
+    pub trash: Vec<serde_json::Value>,
+    pub trash_count: u32,
+}
+
 /// Get results for a completed job.
 pub async fn get_results(
     Path(job_id): Path<Uuid>,
@@ -69,3 +76,48 @@ pub async fn get_results(
         trash_count: result.trash_count,
     }))
 }
+
+/// Trash detections of a completed job, for offering pages to strip.
+///
+/// Empty when detection was off or found nothing; pages removed with
+/// `POST /api/results/{job_id}/clean` are no longer listed.
+///
+/// GET /api/results/{job_id}/trash
+pub async fn get_trash(
+    Path(job_id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<TrashResponse>, ApiError> {
+    let job = state
+        .job_queue
+        .get_job(&job_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Job {job_id} not found")))?;
+
+    if job.status != JobStatus::Completed {
+        return Err(ApiError::BadRequest(format!(
+            "Job {job_id} is not completed (status: {:?})",
+            job.status
+        )));
+    }
+
+    let result = job
+        .result
+        .ok_or_else(|| ApiError::Internal("Job completed but no results found".to_string()))?;
+
+    let trash: Vec<serde_json::Value> = match &result.trash_path {
+        Some(trash_path) => {
+            let json = tokio::fs::read_to_string(trash_path)
+                .await
+                .map_err(|_| ApiError::NotFound("Trash file no longer available".to_string()))?;
+            serde_json::from_str(&json)
+                .map_err(|e| ApiError::Internal(format!("Invalid trash file: {e}")))?
+        }
+        None => Vec::new(),
+    };
+
+    Ok(Json(TrashResponse {
+        job_id,
+        trash_count: trash.len() as u32,
+        trash,
+    }))
+}
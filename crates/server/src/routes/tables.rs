@@ -0,0 +1,179 @@
+use axum::extract::{Path, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use jay_rag_core::table::Table;
+use jay_rag_core::ImageMetadata;
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::jobs::models::JobStatus;
+use crate::state::AppState;
+
+const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// Export every table parsed from a job's table regions as CSV.
+///
+/// Tables follow each other in page order, separated by a blank line; each
+/// starts with its header row, and every row is prefixed with its page.
+/// A UTF-8 BOM lets Excel open Thai text correctly.
+///
+/// GET /api/results/{job_id}/tables.csv
+pub async fn export_tables_csv(
+    Path(job_id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let tables = job_tables(&state, &job_id).await?;
+    let filename = format!("{}_tables.csv", &job_id.to_string()[..8]);
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        tables_to_csv(&tables),
+    ))
+}
+
+/// Export every table parsed from a job's table regions as an Excel workbook,
+/// laid out like [`export_tables_csv`] on a single "Tables" sheet.
+///
+/// GET /api/results/{job_id}/tables.xlsx
+pub async fn export_tables_xlsx(
+    Path(job_id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let tables = job_tables(&state, &job_id).await?;
+    let workbook = tables_to_xlsx(&tables)
+        .map_err(|e| ApiError::Internal(format!("Failed to write workbook: {e}")))?;
+    let filename = format!("{}_tables.xlsx", &job_id.to_string()[..8]);
+    Ok((
+        [
+            (header::CONTENT_TYPE, XLSX_CONTENT_TYPE.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        workbook,
+    ))
+}
+
+/// `(1-indexed page, table)` pairs of a completed job, in page order.
+async fn job_tables(state: &AppState, job_id: &Uuid) -> Result<Vec<(u32, Table)>, ApiError> {
+    let job = state
+        .job_queue
+        .get_job(job_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Job {job_id} not found")))?;
+
+    if job.status != JobStatus::Completed {
+        return Err(ApiError::BadRequest(format!(
+            "Job {job_id} is not completed (status: {:?})",
+            job.status
+        )));
+    }
+
+    let result = job
+        .result
+        .ok_or_else(|| ApiError::Internal("Job completed but no results found".to_string()))?;
+
+    let json = tokio::fs::read_to_string(&result.metadata_path)
+        .await
+        .map_err(|_| ApiError::NotFound("Metadata file no longer available".to_string()))?;
+    let metadata: Vec<ImageMetadata> = serde_json::from_str(&json)
+        .map_err(|e| ApiError::Internal(format!("Invalid metadata file: {e}")))?;
+
+    let mut tables: Vec<(u32, Table)> = metadata
+        .into_iter()
+        .flat_map(|meta| {
+            let page = meta.page;
+            meta.tables.into_iter().map(move |table| (page, table))
+        })
+        .collect();
+    tables.sort_by_key(|(page, _)| *page);
+    Ok(tables)
+}
+
+fn tables_to_csv(tables: &[(u32, Table)]) -> String {
+    let mut out = String::from("\u{feff}");
+    for (i, (page, table)) in tables.iter().enumerate() {
+        if i > 0 {
+            out.push_str("\r\n");
+        }
+        let header = std::iter::once("page").chain(table.headers.iter().map(String::as_str));
+        push_csv_row(&mut out, header);
+        for row in &table.rows {
+            let page = page.to_string();
+            let cells = std::iter::once(page.as_str()).chain(row.iter().map(String::as_str));
+            push_csv_row(&mut out, cells);
+        }
+    }
+    out
+}
+
+/// Append one CSV record, quoting fields that need it (RFC 4180).
+fn push_csv_row<'a>(out: &mut String, fields: impl Iterator<Item = &'a str>) {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+fn tables_to_xlsx(tables: &[(u32, Table)]) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("Tables")?;
+    let bold = Format::new().set_bold();
+
+    let mut row = 0;
+    for (i, (page, table)) in tables.iter().enumerate() {
+        if i > 0 {
+            row += 1;
+        }
+        sheet.write_string_with_format(row, 0, "page", &bold)?;
+        for (col, header) in table.headers.iter().enumerate() {
+            sheet.write_string_with_format(row, col as u16 + 1, header, &bold)?;
+        }
+        row += 1;
+        for cells in &table.rows {
+            sheet.write_number(row, 0, *page)?;
+            for (col, cell) in cells.iter().enumerate() {
+                sheet.write_string(row, col as u16 + 1, cell)?;
+            }
+            row += 1;
+        }
+    }
+    workbook.save_to_buffer()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables_to_csv() {
+        let table = Table {
+            headers: vec!["รุ่น".to_string(), "หมายเหตุ".to_string()],
+            rows: vec![vec!["A-1".to_string(), "ขนาด 2\", สีดำ".to_string()]],
+        };
+        assert_eq!(
+            tables_to_csv(&[(3, table.clone()), (5, table)]),
+            "\u{feff}page,รุ่น,หมายเหตุ\r\n3,A-1,\"ขนาด 2\"\", สีดำ\"\r\n\
+             \r\npage,รุ่น,หมายเหตุ\r\n5,A-1,\"ขนาด 2\"\", สีดำ\"\r\n"
+        );
+        assert_eq!(tables_to_csv(&[]), "\u{feff}");
+    }
+}
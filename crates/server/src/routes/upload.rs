@@ -1,26 +1,43 @@
 use axum::extract::{Multipart, State};
 use axum::Json;
+use jay_rag_core::pdf::PdfEngine;
 use serde::Serialize;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 use uuid::Uuid;
 
 use crate::error::ApiError;
 use crate::jobs::models::{Job, JobConfig};
 use crate::jobs::runner;
 use crate::state::AppState;
-use jay_rag_core::provider;
 
 #[derive(Serialize)]
 pub struct UploadResponse {
+    /// Job of the first uploaded file.
     pub job_id: Uuid,
+    /// One job per uploaded file, in upload order.
+    pub job_ids: Vec<Uuid>,
     pub message: String,
 }
 
+/// Create a job for each uploaded PDF.
+///
+/// The multipart body holds one or more `file` fields and an optional
+/// `config` (a [`JobConfig`] as JSON) shared by all of them. The server's
+/// body limit applies to the whole request, not to each file.
+///
+/// With `split_every`, a longer PDF gets a parent job that runs nothing and
+/// one child job per page range (see `GET /api/jobs/{id}/children`). When
+/// any file cannot be saved or read, the request fails and no job is created.
+///
+/// POST /api/upload
 pub async fn upload_pdf(
     State(state): State<Arc<AppState>>,
     mut multipart: Multipart,
 ) -> Result<Json<UploadResponse>, ApiError> {
-    let mut pdf_data: Option<(String, Vec<u8>)> = None;
+    let mut pdfs: Vec<(String, Vec<u8>)> = Vec::new();
     let mut config_json: Option<String> = None;
 
     while let Ok(Some(field)) = multipart.next_field().await {
@@ -35,7 +52,7 @@ pub async fn upload_pdf(
                     .bytes()
                     .await
                     .map_err(|e| ApiError::BadRequest(format!("Failed to read file: {e}")))?;
-                pdf_data = Some((filename, data.to_vec()));
+                pdfs.push((filename, data.to_vec()));
             }
             "config" => {
                 let text = field
@@ -48,88 +65,201 @@ pub async fn upload_pdf(
         }
     }
 
-    let (filename, data) = pdf_data.ok_or_else(|| ApiError::BadRequest("No PDF file provided".to_string()))?;
+    if pdfs.is_empty() {
+        return Err(ApiError::BadRequest("No PDF file provided".to_string()));
+    }
 
     let config: JobConfig = match config_json {
         Some(json) => serde_json::from_str(&json)
             .map_err(|e| ApiError::BadRequest(format!("Invalid config JSON: {e}")))?,
-        None => JobConfig {
-            provider: "ollama".to_string(),
-            model: None,
-            language: "th".to_string(),
-            start_page: None,
-            end_page: None,
-            table_extraction: false,
-            text_only: false,
-            storage: "local".to_string(),
-            s3_bucket: None,
-            s3_prefix: None,
-            storage_path: None,
-            quality: "standard".to_string(),
-            dpi: None,
-            notify: true,
-            enhance: false,
-        },
+        None => JobConfig::default(),
     };
 
-    // Resolve model
-    let model = config
-        .model
-        .clone()
-        .unwrap_or_else(|| provider::default_model(&config.provider).to_string());
+    tokio::fs::create_dir_all(&state.upload_dir).await?;
+    let message = match pdfs.as_slice() {
+        [(filename, _)] => format!("Job created for '{filename}'"),
+        _ => format!("{} jobs created", pdfs.len()),
+    };
 
-    // Save uploaded PDF to temp directory
-    let job = Job::new(filename.clone(), config.clone());
-    let job_id = job.id;
+    // Save and check every file before creating any job, so a bad file
+    // leaves no job running and no PDF behind
+    let mut saved = Vec::with_capacity(pdfs.len());
+    for (filename, data) in pdfs {
+        let job = Job::new(filename, config.clone());
+        let pdf_path = state.upload_dir.join(format!("{}.pdf", job.id));
+        match save_upload(&state, &job, &pdf_path, &data).await {
+            Ok(ranges) => saved.push((job, pdf_path, ranges)),
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&pdf_path).await;
+                for (_, path, _) in &saved {
+                    let _ = tokio::fs::remove_file(path).await;
+                }
+                return Err(e);
+            }
+        }
+    }
 
-    tokio::fs::create_dir_all(&state.upload_dir).await?;
-    let pdf_path = state.upload_dir.join(format!("{job_id}.pdf"));
-    tokio::fs::write(&pdf_path, &data).await?;
+    let mut job_ids = Vec::with_capacity(saved.len());
+    for (job, pdf_path, ranges) in saved {
+        // Add job to queue
+        let job_id = job.id;
+        if ranges.is_empty() {
+            state.job_queue.add_job(job).await;
+            spawn_job(&state, job_id, pdf_path, config.clone()).await;
+        } else {
+            state.job_queue.add_job(job.clone()).await;
+            spawn_children(&state, &job, &pdf_path, &ranges).await?;
+        }
+        job_ids.push(job_id);
+    }
 
-    // Add job to queue
-    state.job_queue.add_job(job).await;
+    Ok(Json(UploadResponse {
+        job_id: job_ids[0],
+        job_ids,
+        message,
+    }))
+}
+
+/// Write an uploaded PDF to `pdf_path` and return its split ranges (see
+/// [`split_ranges`]).
+async fn save_upload(
+    state: &AppState,
+    job: &Job,
+    pdf_path: &Path,
+    data: &[u8],
+) -> Result<Vec<(u32, u32)>, ApiError> {
+    tokio::fs::write(pdf_path, data).await?;
+    split_ranges(state, job, pdf_path).await
+}
 
-    // Spawn background processing task
+/// Page ranges of the child jobs for a PDF longer than `split_every` pages
+/// (within its `start_page` / `end_page`); empty when it is not split.
+async fn split_ranges(
+    state: &AppState,
+    job: &Job,
+    pdf_path: &Path,
+) -> Result<Vec<(u32, u32)>, ApiError> {
+    let Some(every) = job.config.split_every.filter(|&n| n > 0) else {
+        return Ok(Vec::new());
+    };
+    // Simulated jobs never open the PDF
+    if state.simulate || jay_rag_core::is_image_input(Path::new(&job.filename)) {
+        return Ok(Vec::new());
+    }
+    let total = PdfEngine::document_page_count(pdf_path)
+        .await
+        .map_err(|e| ApiError::BadRequest(format!("Failed to read '{}': {e}", job.filename)))?;
+    let start = job.config.start_page.unwrap_or(1).max(1);
+    let end = job.config.end_page.unwrap_or(total).min(total);
+    Ok(page_ranges(start, end, every))
+}
+
+/// `start..=end` cut into ranges of `every` pages; empty when it fits in one.
+fn page_ranges(start: u32, end: u32, every: usize) -> Vec<(u32, u32)> {
+    let every = u32::try_from(every).unwrap_or(u32::MAX);
+    if end < start || end - start < every {
+        return Vec::new();
+    }
+    (start..=end)
+        .step_by(every as usize)
+        .map(|first| (first, first.saturating_add(every - 1).min(end)))
+        .collect()
+}
+
+/// Create and start a child job of `parent` for each page range. Each child
+/// gets its own hard link to (or copy of) the uploaded PDF, so retrying or
+/// deleting one works as for any other job.
+async fn spawn_children(
+    state: &AppState,
+    parent: &Job,
+    pdf_path: &Path,
+    ranges: &[(u32, u32)],
+) -> Result<(), ApiError> {
+    for &(start_page, end_page) in ranges {
+        let child = Job::new_child(parent, start_page, end_page);
+        let child_id = child.id;
+        let child_pdf = state.upload_dir.join(format!("{child_id}.pdf"));
+        if tokio::fs::hard_link(pdf_path, &child_pdf).await.is_err() {
+            tokio::fs::copy(pdf_path, &child_pdf).await?;
+        }
+        let config = child.config.clone();
+        state.job_queue.add_job(child).await;
+        spawn_job(state, child_id, child_pdf, config).await;
+    }
+    tracing::info!(
+        "Job {} split into {} part(s) of up to {} pages",
+        parent.id,
+        ranges.len(),
+        parent.config.split_every.unwrap_or_default()
+    );
+    Ok(())
+}
+
+/// Spawn the background processing task for a pending job.
+///
+/// Used for new uploads and for retries of failed jobs.
+pub(crate) async fn spawn_job(
+    state: &AppState,
+    job_id: Uuid,
+    pdf_path: PathBuf,
+    config: JobConfig,
+) {
     let output_dir = state.output_dir.clone();
+    let storage_root = state.storage_root.clone();
     let queue = state.job_queue.clone();
-    let provider_name = config.provider.clone();
-    let language = config.language.clone();
-    let start_page = config.start_page;
-    let end_page = config.end_page;
-    let table_extraction = config.table_extraction;
-    let text_only = config.text_only;
-    let quality = config.quality.clone();
-    let dpi = config.dpi;
-    let enhance = config.enhance;
     let task_handles = state.task_handles.clone();
+    let cancel_tokens = state.cancel_tokens.clone();
+    let cancel = CancellationToken::new();
+    cancel_tokens.lock().await.insert(job_id, cancel.clone());
 
+    let simulate = state.simulate;
+    let mut paused = state.paused.subscribe();
+    let span = tracing::info_span!("job", job_id = %job_id, provider = %config.provider);
     let handle = tokio::spawn(async move {
-        runner::run_job(
-            job_id,
-            pdf_path,
-            output_dir,
-            queue,
-            provider_name,
-            model,
-            language,
-            start_page,
-            end_page,
-            table_extraction,
-            text_only,
-            quality,
-            dpi,
-            enhance,
-        )
-        .await;
+        // Stay pending while processing is paused via /api/admin/pause
+        if *paused.borrow() {
+            tracing::info!("Processing paused — job {job_id} waiting to start");
+        }
+        let _ = paused.wait_for(|is_paused| !is_paused).await;
+
+        if simulate {
+            runner::run_simulated_job(job_id, pdf_path, output_dir, queue)
+                .instrument(span)
+                .await;
+        } else {
+            runner::run_job(
+                job_id,
+                pdf_path,
+                output_dir,
+                storage_root,
+                queue,
+                config,
+                cancel,
+            )
+            .instrument(span)
+            .await;
+        }
 
         // Self-cleanup: remove our handle entry on normal completion
         task_handles.lock().await.remove(&job_id);
+        cancel_tokens.lock().await.remove(&job_id);
     });
 
     state.task_handles.lock().await.insert(job_id, handle);
+}
 
-    Ok(Json(UploadResponse {
-        job_id,
-        message: format!("Job created for '{filename}'"),
-    }))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_ranges() {
+        assert_eq!(
+            page_ranges(1, 1100, 500),
+            vec![(1, 500), (501, 1000), (1001, 1100)]
+        );
+        assert_eq!(page_ranges(11, 20, 5), vec![(11, 15), (16, 20)]);
+        assert!(page_ranges(1, 500, 500).is_empty());
+        assert!(page_ranges(5, 1, 2).is_empty());
+    }
 }
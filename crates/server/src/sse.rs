@@ -0,0 +1,72 @@
+use axum::extract::{Path, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::state::AppState;
+
+/// Server-Sent Events alternative to [`crate::ws::ws_handler`], for clients
+/// behind proxies that break WebSockets.
+///
+/// GET /api/jobs/{id}/events
+///
+/// Sends the current job state and the job's recent updates first, then each
+/// progress update, and ends after the `complete`, `error` or `cancelled` update.
+pub async fn sse_handler(
+    Path(job_id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let rx = state
+        .job_queue
+        .subscribe_progress(&job_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Job {job_id} not found")))?;
+
+    // Send current job state first
+    let current = state
+        .job_queue
+        .get_job(&job_id)
+        .await
+        .map(|job| json_event(&job));
+
+    // Replay recent updates for clients reconnecting mid-job
+    let mut recent = state.job_queue.recent_progress(&job_id).await;
+    let finished = recent.iter().position(|event| event.progress.is_final());
+    if let Some(last) = finished {
+        recent.truncate(last + 1);
+    }
+    let replayed = recent.last().map(|event| event.seq);
+    let replay = recent
+        .iter()
+        .map(|event| json_event(&event.progress))
+        .collect::<Vec<_>>();
+
+    // Stream progress updates, skipping those already replayed
+    let updates = stream::unfold(finished.is_none().then_some(rx), move |rx| async move {
+        let mut rx = rx?;
+        loop {
+            match rx.recv().await {
+                Ok(event) if replayed.is_some_and(|seq| event.seq <= seq) => continue,
+                Ok(event) => {
+                    let progress = event.progress;
+                    let sse_event = json_event(&progress);
+                    return Some((sse_event, (!progress.is_final()).then_some(rx)));
+                }
+                Err(RecvError::Closed) => return None,
+                Err(RecvError::Lagged(_)) => continue,
+            }
+        }
+    });
+
+    let events = stream::iter(current.into_iter().chain(replay)).chain(updates);
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+fn json_event(value: &impl Serialize) -> Result<Event, Infallible> {
+    Ok(Event::default().data(serde_json::to_string(value).unwrap_or_default()))
+}
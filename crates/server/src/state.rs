@@ -1,8 +1,10 @@
 use crate::jobs::queue::JobQueue;
+use jay_rag_core::pdf::PdfEngine;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 /// Shared application state.
@@ -14,20 +16,77 @@ pub struct AppState {
     pub upload_dir: PathBuf,
     /// Default output directory for processed files.
     pub output_dir: PathBuf,
+    /// `serve --storage-root`: directory that job `storage_path`s are
+    /// resolved under (default: `output_dir`).
+    pub storage_root: PathBuf,
     /// Handles for in-flight processing tasks, keyed by job ID.
     pub task_handles: Arc<tokio::sync::Mutex<HashMap<Uuid, JoinHandle<()>>>>,
+    /// Cancellation tokens for in-flight jobs: processing stops between pages
+    /// once triggered (e.g. by `DELETE /api/jobs/{id}`).
+    pub cancel_tokens: Arc<tokio::sync::Mutex<HashMap<Uuid, CancellationToken>>>,
+    /// Whether the pdfium library loaded at startup.
+    pub pdfium_ok: bool,
+    /// Load error when pdfium is unavailable.
+    pub pdfium_error: Option<String>,
+    /// `serve --simulate`: jobs produce canned output without processing.
+    pub simulate: bool,
+    /// `serve --retention-days`: jobs older than this are deleted with their
+    /// files (see [`crate::jobs::retention`]); `None` keeps everything.
+    pub retention_days: Option<u32>,
+    /// Processing pause flag: while `true`, new jobs wait before starting;
+    /// in-flight jobs continue.
+    pub paused: tokio::sync::watch::Sender<bool>,
 }
 
 impl AppState {
-    pub fn new(upload_dir: PathBuf, output_dir: PathBuf) -> Arc<Self> {
+    pub fn new(
+        upload_dir: PathBuf,
+        output_dir: PathBuf,
+        storage_root: PathBuf,
+        retention_days: Option<u32>,
+    ) -> Arc<Self> {
+        Self::build(upload_dir, output_dir, storage_root, retention_days, false)
+    }
+
+    /// State for `serve --simulate` (frontend development without pdfium or API keys).
+    pub fn new_simulated(
+        upload_dir: PathBuf,
+        output_dir: PathBuf,
+        storage_root: PathBuf,
+        retention_days: Option<u32>,
+    ) -> Arc<Self> {
+        Self::build(upload_dir, output_dir, storage_root, retention_days, true)
+    }
+
+    fn build(
+        upload_dir: PathBuf,
+        output_dir: PathBuf,
+        storage_root: PathBuf,
+        retention_days: Option<u32>,
+        simulate: bool,
+    ) -> Arc<Self> {
         let db_path = output_dir.join("jay-rag.db");
         let job_queue = JobQueue::new(&db_path).expect("Failed to initialize job database");
 
+        let pdfium_error = PdfEngine::check_available().err().map(|e| e.to_string());
+        match (&pdfium_error, simulate) {
+            (_, true) => tracing::warn!("Simulation mode — jobs produce canned results"),
+            (Some(e), false) => tracing::error!("pdfium is not available — jobs will fail: {e}"),
+            (None, false) => {}
+        }
+
         Arc::new(Self {
             job_queue,
             upload_dir,
             output_dir,
+            storage_root,
             task_handles: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            cancel_tokens: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            pdfium_ok: pdfium_error.is_none(),
+            pdfium_error,
+            simulate,
+            retention_days,
+            paused: tokio::sync::watch::Sender::new(false),
         })
     }
 }
@@ -36,15 +36,30 @@ async fn handle_socket(mut socket: WebSocket, job_id: Uuid, state: Arc<AppState>
         }
     }
 
-    // Stream progress updates
+    // Replay recent updates for clients reconnecting mid-job
+    let mut replayed = None;
+    for event in state.job_queue.recent_progress(&job_id).await {
+        let msg = serde_json::to_string(&event.progress).unwrap_or_default();
+        if socket.send(Message::Text(msg.into())).await.is_err() {
+            return;
+        }
+        if event.progress.is_final() {
+            return;
+        }
+        replayed = Some(event.seq);
+    }
+
+    // Stream progress updates, skipping those already replayed
     loop {
         match rx.recv().await {
-            Ok(progress) => {
+            Ok(event) if replayed.is_some_and(|seq| event.seq <= seq) => continue,
+            Ok(event) => {
+                let progress = event.progress;
                 let msg = serde_json::to_string(&progress).unwrap_or_default();
                 if socket.send(Message::Text(msg.into())).await.is_err() {
                     break;
                 }
-                if progress.phase == "complete" || progress.phase == "error" || progress.phase == "cancelled" {
+                if progress.is_final() {
                     break;
                 }
             }
@@ -0,0 +1,114 @@
+use crate::error::StorageError;
+use crate::s3::content_type;
+use crate::traits::StorageBackend;
+use azure_storage::StorageCredentials;
+use azure_storage_blobs::prelude::{BlobClient, ClientBuilder, ContainerClient};
+
+/// Azure Blob Storage backend.
+pub struct AzureBlobStorage {
+    container: ContainerClient,
+    account: String,
+    container_name: String,
+    prefix: String,
+    public_base_url: String,
+}
+
+impl AzureBlobStorage {
+    /// Create a new Azure Blob Storage backend.
+    ///
+    /// Authenticates with the account key in `AZURE_STORAGE_KEY` when set,
+    /// otherwise with the default Azure credential chain (environment,
+    /// managed identity, Azure CLI). `public_base_url` is the base URL for
+    /// public access (e.g., a CDN endpoint); when empty, the blob URL is used.
+    pub async fn new(
+        account: String,
+        container: String,
+        prefix: String,
+        public_base_url: String,
+    ) -> Result<Self, StorageError> {
+        let credentials = match std::env::var("AZURE_STORAGE_KEY") {
+            Ok(key) if !key.trim().is_empty() => {
+                StorageCredentials::access_key(account.clone(), key.trim().to_string())
+            }
+            _ => {
+                let credential = azure_identity::create_credential().map_err(|e| {
+                    StorageError::Azure(format!("Failed to load Azure credentials: {e}"))
+                })?;
+                StorageCredentials::token_credential(credential)
+            }
+        };
+        let client = ClientBuilder::new(account.clone(), credentials).container_client(&container);
+        Ok(Self {
+            container: client,
+            account,
+            container_name: container,
+            prefix,
+            public_base_url,
+        })
+    }
+
+    fn blob_name(&self, path: &str) -> String {
+        if self.prefix.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}/{}", self.prefix.trim_end_matches('/'), path)
+        }
+    }
+
+    fn blob(&self, path: &str) -> BlobClient {
+        self.container.blob_client(self.blob_name(path))
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for AzureBlobStorage {
+    async fn write_bytes(&self, path: &str, data: &[u8]) -> Result<(), StorageError> {
+        let name = self.blob_name(path);
+        self.blob(path)
+            .put_block_blob(data.to_vec())
+            .content_type(content_type(path))
+            .await
+            .map_err(|e| StorageError::Azure(format!("Failed to upload {name}: {e}")))?;
+        Ok(())
+    }
+
+    async fn write_text(&self, path: &str, text: &str) -> Result<(), StorageError> {
+        self.write_bytes(path, text.as_bytes()).await
+    }
+
+    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        let name = self.blob_name(path);
+        self.blob(path)
+            .get_content()
+            .await
+            .map_err(|e| StorageError::Azure(format!("Failed to read {name}: {e}")))
+    }
+
+    async fn exists(&self, path: &str) -> Result<bool, StorageError> {
+        match self.blob(path).get_properties().await {
+            Ok(_) => Ok(true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    async fn create_dir(&self, _path: &str) -> Result<(), StorageError> {
+        // Blob storage doesn't have directories — no-op
+        Ok(())
+    }
+
+    fn public_url(&self, path: &str) -> String {
+        let name = self.blob_name(path);
+        if self.public_base_url.is_empty() {
+            format!(
+                "https://{}.blob.core.windows.net/{}/{name}",
+                self.account, self.container_name
+            )
+        } else {
+            format!("{}/{name}", self.public_base_url.trim_end_matches('/'))
+        }
+    }
+
+    fn backend_name(&self) -> &str {
+        "azure"
+    }
+}
@@ -8,6 +8,12 @@ pub enum StorageError {
     #[error("S3 error: {0}")]
     S3(String),
 
+    #[error("GCS error: {0}")]
+    Gcs(String),
+
+    #[error("Azure error: {0}")]
+    Azure(String),
+
     #[error("Storage config error: {0}")]
     Config(String),
 }
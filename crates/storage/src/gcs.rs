@@ -0,0 +1,112 @@
+use crate::error::StorageError;
+use crate::s3::content_type;
+use crate::traits::StorageBackend;
+use google_cloud_storage::client::{Client, ClientConfig};
+use google_cloud_storage::http::objects::download::Range;
+use google_cloud_storage::http::objects::get::GetObjectRequest;
+use google_cloud_storage::http::objects::upload::{Media, UploadObjectRequest, UploadType};
+
+/// Google Cloud Storage backend.
+pub struct GcsStorage {
+    client: Client,
+    bucket: String,
+    prefix: String,
+    public_base_url: String,
+}
+
+impl GcsStorage {
+    /// Create a new GCS storage backend.
+    ///
+    /// Credentials come from Application Default Credentials
+    /// (`GOOGLE_APPLICATION_CREDENTIALS`, gcloud login or the metadata server).
+    /// `public_base_url` is the base URL for public access; when empty,
+    /// `https://storage.googleapis.com/{bucket}` is used.
+    pub async fn new(
+        bucket: String,
+        prefix: String,
+        public_base_url: String,
+    ) -> Result<Self, StorageError> {
+        let config = ClientConfig::default()
+            .with_auth()
+            .await
+            .map_err(|e| StorageError::Gcs(format!("Failed to load GCS credentials: {e}")))?;
+        Ok(Self {
+            client: Client::new(config),
+            bucket,
+            prefix,
+            public_base_url,
+        })
+    }
+
+    fn object_name(&self, path: &str) -> String {
+        if self.prefix.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}/{}", self.prefix.trim_end_matches('/'), path)
+        }
+    }
+
+    fn get_request(&self, object: String) -> GetObjectRequest {
+        GetObjectRequest {
+            bucket: self.bucket.clone(),
+            object,
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for GcsStorage {
+    async fn write_bytes(&self, path: &str, data: &[u8]) -> Result<(), StorageError> {
+        let name = self.object_name(path);
+        let mut media = Media::new(name.clone());
+        media.content_type = content_type(path).into();
+        let request = UploadObjectRequest {
+            bucket: self.bucket.clone(),
+            ..Default::default()
+        };
+        self.client
+            .upload_object(&request, data.to_vec(), &UploadType::Simple(media))
+            .await
+            .map_err(|e| StorageError::Gcs(format!("Failed to upload {name}: {e}")))?;
+        Ok(())
+    }
+
+    async fn write_text(&self, path: &str, text: &str) -> Result<(), StorageError> {
+        self.write_bytes(path, text.as_bytes()).await
+    }
+
+    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        let name = self.object_name(path);
+        self.client
+            .download_object(&self.get_request(name.clone()), &Range::default())
+            .await
+            .map_err(|e| StorageError::Gcs(format!("Failed to read {name}: {e}")))
+    }
+
+    async fn exists(&self, path: &str) -> Result<bool, StorageError> {
+        let name = self.object_name(path);
+        match self.client.get_object(&self.get_request(name)).await {
+            Ok(_) => Ok(true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    async fn create_dir(&self, _path: &str) -> Result<(), StorageError> {
+        // GCS doesn't have directories — no-op
+        Ok(())
+    }
+
+    fn public_url(&self, path: &str) -> String {
+        let name = self.object_name(path);
+        if self.public_base_url.is_empty() {
+            format!("https://storage.googleapis.com/{}/{name}", self.bucket)
+        } else {
+            format!("{}/{name}", self.public_base_url.trim_end_matches('/'))
+        }
+    }
+
+    fn backend_name(&self) -> &str {
+        "gcs"
+    }
+}
@@ -1,10 +1,14 @@
+pub mod azure;
 pub mod error;
+pub mod gcs;
 pub mod local;
 pub mod nfs;
 pub mod s3;
 pub mod traits;
 
+pub use azure::AzureBlobStorage;
 pub use error::StorageError;
+pub use gcs::GcsStorage;
 pub use local::LocalStorage;
 pub use nfs::NfsStorage;
 pub use s3::S3Storage;
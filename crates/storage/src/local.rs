@@ -27,17 +27,17 @@ impl StorageBackend for LocalStorage {
         if let Some(parent) = full.parent() {
             tokio::fs::create_dir_all(parent).await?;
         }
-        tokio::fs::write(&full, data).await?;
+        // Write to a temporary file and rename, so readers never see a
+        // partially written file (as with object stores)
+        let file_name = full.file_name().unwrap_or_default().to_string_lossy();
+        let tmp = full.with_file_name(format!(".{file_name}.tmp"));
+        tokio::fs::write(&tmp, data).await?;
+        tokio::fs::rename(&tmp, &full).await?;
         Ok(())
     }
 
     async fn write_text(&self, path: &str, text: &str) -> Result<(), StorageError> {
-        let full = self.full_path(path);
-        if let Some(parent) = full.parent() {
-            tokio::fs::create_dir_all(parent).await?;
-        }
-        tokio::fs::write(&full, text.as_bytes()).await?;
-        Ok(())
+        self.write_bytes(path, text.as_bytes()).await
     }
 
     async fn read_bytes(&self, path: &str) -> Result<Vec<u8>, StorageError> {
@@ -36,19 +36,22 @@ impl S3Storage {
             format!("{}/{}", self.prefix.trim_end_matches('/'), path)
         }
     }
+}
 
-    fn content_type(path: &str) -> &'static str {
-        if path.ends_with(".png") {
-            "image/png"
-        } else if path.ends_with(".jpg") || path.ends_with(".jpeg") {
-            "image/jpeg"
-        } else if path.ends_with(".md") {
-            "text/markdown; charset=utf-8"
-        } else if path.ends_with(".json") {
-            "application/json; charset=utf-8"
-        } else {
-            "application/octet-stream"
-        }
+/// Content type for an uploaded file, by extension (shared with GCS).
+pub(crate) fn content_type(path: &str) -> &'static str {
+    if path.ends_with(".png") {
+        "image/png"
+    } else if path.ends_with(".jpg") || path.ends_with(".jpeg") {
+        "image/jpeg"
+    } else if path.ends_with(".webp") {
+        "image/webp"
+    } else if path.ends_with(".md") {
+        "text/markdown; charset=utf-8"
+    } else if path.ends_with(".json") {
+        "application/json; charset=utf-8"
+    } else {
+        "application/octet-stream"
     }
 }
 
@@ -60,7 +63,7 @@ impl StorageBackend for S3Storage {
             .put_object()
             .bucket(&self.bucket)
             .key(&key)
-            .content_type(Self::content_type(path))
+            .content_type(content_type(path))
             .body(data.to_vec().into())
             .send()
             .await
@@ -24,3 +24,11 @@ pub trait StorageBackend: Send + Sync {
     /// Storage backend name.
     fn backend_name(&self) -> &str;
 }
+
+impl std::fmt::Debug for dyn StorageBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StorageBackend")
+            .field("backend", &self.backend_name())
+            .finish()
+    }
+}
@@ -143,6 +143,8 @@ export default function JobDetailPage({
           </h2>
           <p className="text-emerald-700 text-sm mb-4">
             {job.result?.image_count} images processed.
+            {job.result?.partial &&
+              ` Cancelled partway — output covers pages up to ${job.result.last_page}.`}
           </p>
           <Link
             href={`/results/${id}`}
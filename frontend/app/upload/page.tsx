@@ -24,6 +24,11 @@ export default function UploadPage() {
     end_page: "",
     s3_bucket: "",
     s3_prefix: "",
+    gcs_bucket: "",
+    gcs_prefix: "",
+    azure_account: "",
+    azure_container: "",
+    azure_prefix: "",
     storage_path: "",
     notify: true,
     enhance: false,
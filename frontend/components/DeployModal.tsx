@@ -41,11 +41,15 @@ export default function DeployModal({
   const [imgS3Bucket, setImgS3Bucket] = useState("");
   const [imgS3Prefix, setImgS3Prefix] = useState("");
   const [imgS3Region, setImgS3Region] = useState("");
+  const [imgGcsBucket, setImgGcsBucket] = useState("");
+  const [imgGcsPrefix, setImgGcsPrefix] = useState("");
   const [imgScpHost, setImgScpHost] = useState("");
   const [imgScpPort, setImgScpPort] = useState("");
   const [imgScpUser, setImgScpUser] = useState("");
   const [imgScpKeyPath, setImgScpKeyPath] = useState("");
   const [imgScpRemotePath, setImgScpRemotePath] = useState("");
+  const [imgScpKnownHosts, setImgScpKnownHosts] = useState("");
+  const [imgScpFingerprint, setImgScpFingerprint] = useState("");
 
   // Markdown target
   const [mdTargetType, setMdTargetType] = useState<MarkdownTargetType | "">("");
@@ -71,6 +75,8 @@ export default function DeployModal({
           prefix: imgS3Prefix,
           ...(imgS3Region ? { region: imgS3Region } : {}),
         };
+      case "gcs":
+        return { type: "gcs", bucket: imgGcsBucket, prefix: imgGcsPrefix };
       case "scp":
         return {
           type: "scp",
@@ -79,6 +85,8 @@ export default function DeployModal({
           username: imgScpUser,
           ...(imgScpKeyPath ? { private_key_path: imgScpKeyPath } : {}),
           remote_path: imgScpRemotePath,
+          ...(imgScpKnownHosts ? { known_hosts_path: imgScpKnownHosts } : {}),
+          ...(imgScpFingerprint ? { host_key_fingerprint: imgScpFingerprint } : {}),
         };
     }
   }
@@ -112,6 +120,8 @@ export default function DeployModal({
     if (imageTargetType === "local_folder" && !imgLocalPath.trim()) return false;
     if (imageTargetType === "s3" && (!imgS3Bucket.trim() || !imgS3Prefix.trim()))
       return false;
+    if (imageTargetType === "gcs" && (!imgGcsBucket.trim() || !imgGcsPrefix.trim()))
+      return false;
     if (
       imageTargetType === "scp" &&
       (!imgScpHost.trim() || !imgScpUser.trim() || !imgScpRemotePath.trim())
@@ -232,6 +242,7 @@ export default function DeployModal({
                   <option value="">Skip image deployment</option>
                   <option value="local_folder">Local Folder</option>
                   <option value="s3">AWS S3</option>
+                  <option value="gcs">Google Cloud Storage</option>
                   <option value="scp">SCP / SFTP</option>
                 </select>
               </div>
@@ -284,6 +295,31 @@ export default function DeployModal({
                 </div>
               )}
 
+              {imageTargetType === "gcs" && (
+                <div className="space-y-2">
+                  <div>
+                    <label className={labelClass}>Bucket *</label>
+                    <input
+                      type="text"
+                      value={imgGcsBucket}
+                      onChange={(e) => setImgGcsBucket(e.target.value)}
+                      placeholder="my-rag-images"
+                      className={inputClass}
+                    />
+                  </div>
+                  <div>
+                    <label className={labelClass}>Prefix *</label>
+                    <input
+                      type="text"
+                      value={imgGcsPrefix}
+                      onChange={(e) => setImgGcsPrefix(e.target.value)}
+                      placeholder="images/manual"
+                      className={inputClass}
+                    />
+                  </div>
+                </div>
+              )}
+
               {imageTargetType === "scp" && (
                 <div className="space-y-2">
                   <div className="grid grid-cols-3 gap-2">
@@ -338,6 +374,28 @@ export default function DeployModal({
                       className={inputClass}
                     />
                   </div>
+                  <div className="grid grid-cols-2 gap-2">
+                    <div>
+                      <label className={labelClass}>Known Hosts File</label>
+                      <input
+                        type="text"
+                        value={imgScpKnownHosts}
+                        onChange={(e) => setImgScpKnownHosts(e.target.value)}
+                        placeholder="~/.ssh/known_hosts"
+                        className={inputClass}
+                      />
+                    </div>
+                    <div>
+                      <label className={labelClass}>Host Key Fingerprint</label>
+                      <input
+                        type="text"
+                        value={imgScpFingerprint}
+                        onChange={(e) => setImgScpFingerprint(e.target.value)}
+                        placeholder="SHA256:... (overrides known hosts)"
+                        className={inputClass}
+                      />
+                    </div>
+                  </div>
                 </div>
               )}
             </fieldset>
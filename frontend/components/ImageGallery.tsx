@@ -4,6 +4,7 @@ import { useState } from "react";
 import { useDeleteImages } from "@/hooks/useJobs";
 
 interface ImageMeta {
+  image_id?: string;
   image_file: string;
   page: number;
   description: string;
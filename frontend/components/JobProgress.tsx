@@ -30,7 +30,7 @@ export default function JobProgress({ jobId }: JobProgressProps) {
   return (
     <div className="p-4 bg-slate-50 rounded-lg border border-slate-200 space-y-3">
       <div className="flex justify-between text-sm">
-        <span className="font-medium text-slate-900 capitalize">{progress.phase}</span>
+        <span className="font-medium text-slate-900 capitalize">{progress.phase.replace("_", " ")}</span>
         <span className="text-slate-600 tabular-nums">
           {progress.current_page}/{progress.total_pages} pages
         </span>
@@ -48,6 +48,17 @@ export default function JobProgress({ jobId }: JobProgressProps) {
         <span>{progress.message}</span>
         <span className="tabular-nums">{progress.images_processed} images processed</span>
       </div>
+
+      {progress.partial && (
+        <div className="space-y-1">
+          <div className="text-xs font-medium text-slate-600">
+            Page {progress.partial.page} (live)
+          </div>
+          <div className="max-h-48 overflow-y-auto p-2 bg-white rounded border border-slate-200 text-xs text-slate-700 whitespace-pre-wrap">
+            {progress.partial.text}
+          </div>
+        </div>
+      )}
     </div>
   );
 }
@@ -16,6 +16,11 @@ interface PipelineConfigProps {
     end_page: string;
     s3_bucket: string;
     s3_prefix: string;
+    gcs_bucket: string;
+    gcs_prefix: string;
+    azure_account: string;
+    azure_container: string;
+    azure_prefix: string;
     storage_path: string;
     notify: boolean;
     enhance: boolean;
@@ -179,6 +184,14 @@ export default function PipelineConfig({
               </option>
             ))}
           </select>
+          {currentProvider && (
+            <p className="text-xs text-slate-500">
+              Recommended: {currentProvider.recommended_concurrency} concurrent page(s)
+              {currentProvider.rate_limit_rpm
+                ? `, ~${currentProvider.rate_limit_rpm} req/min`
+                : ""}
+            </p>
+          )}
         </div>
 
         {/* Model */}
@@ -290,10 +303,82 @@ export default function PipelineConfig({
         </div>
       )}
 
+      {/* GCS options */}
+      {config.storage === "gcs" && (
+        <div className="grid grid-cols-2 gap-4 p-4 bg-slate-50 rounded-lg border border-slate-200">
+          <div>
+            <label className={labelClasses}>GCS Bucket</label>
+            <input
+              type="text"
+              className={inputClasses}
+              value={config.gcs_bucket}
+              onChange={(e) =>
+                onChange({ ...config, gcs_bucket: e.target.value })
+              }
+              placeholder="my-bucket"
+            />
+          </div>
+          <div>
+            <label className={labelClasses}>GCS Prefix</label>
+            <input
+              type="text"
+              className={inputClasses}
+              value={config.gcs_prefix}
+              onChange={(e) =>
+                onChange({ ...config, gcs_prefix: e.target.value })
+              }
+              placeholder="rag-output/"
+            />
+          </div>
+        </div>
+      )}
+
+      {/* Azure options */}
+      {config.storage === "azure" && (
+        <div className="grid grid-cols-3 gap-4 p-4 bg-slate-50 rounded-lg border border-slate-200">
+          <div>
+            <label className={labelClasses}>Storage Account</label>
+            <input
+              type="text"
+              className={inputClasses}
+              value={config.azure_account}
+              onChange={(e) =>
+                onChange({ ...config, azure_account: e.target.value })
+              }
+              placeholder="mystorageaccount"
+            />
+          </div>
+          <div>
+            <label className={labelClasses}>Container</label>
+            <input
+              type="text"
+              className={inputClasses}
+              value={config.azure_container}
+              onChange={(e) =>
+                onChange({ ...config, azure_container: e.target.value })
+              }
+              placeholder="rag-assets"
+            />
+          </div>
+          <div>
+            <label className={labelClasses}>Prefix</label>
+            <input
+              type="text"
+              className={inputClasses}
+              value={config.azure_prefix}
+              onChange={(e) =>
+                onChange({ ...config, azure_prefix: e.target.value })
+              }
+              placeholder="rag-output/"
+            />
+          </div>
+        </div>
+      )}
+
       {/* NFS path */}
       {config.storage === "nfs" && (
         <div className="p-4 bg-slate-50 rounded-lg border border-slate-200">
-          <label className={labelClasses}>Mount Path</label>
+          <label className={labelClasses}>Mount Path (under the server storage root)</label>
           <input
             type="text"
             className={inputClasses}
@@ -301,7 +386,7 @@ export default function PipelineConfig({
             onChange={(e) =>
               onChange({ ...config, storage_path: e.target.value })
             }
-            placeholder="/mnt/nfs/output"
+            placeholder="nfs/output"
           />
         </div>
       )}
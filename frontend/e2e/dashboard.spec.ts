@@ -75,7 +75,7 @@ test.describe("Config API", () => {
     expect(body.providers.map((p: { name: string }) => p.name)).toContain("ollama");
     expect(body.providers.map((p: { name: string }) => p.name)).toContain("openai");
 
-    expect(body.languages.length).toBe(2);
+    expect(body.languages.length).toBe(5);
     expect(body.storage_backends).toContain("local");
   });
 });
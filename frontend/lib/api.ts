@@ -7,10 +7,16 @@ import type {
   DeployRequest,
   DeployResponse,
   Job,
+  JobConfig,
   NotificationSettings,
+  ProviderCheckRequest,
+  ProviderCheckResponse,
   ResultsResponse,
   SaveMarkdownResponse,
+  StripTrashRequest,
+  StripTrashResponse,
   TestNotificationResponse,
+  TrashResponse,
   UploadResponse,
 } from "./types";
 
@@ -25,7 +31,14 @@ async function fetchJson<T>(url: string, init?: RequestInit): Promise<T> {
   return res.json();
 }
 
-export async function getHealth(): Promise<{ status: string; version: string }> {
+export async function getHealth(): Promise<{
+  status: string;
+  version: string;
+  pdfium: boolean;
+  pdfium_error?: string;
+  simulate?: boolean;
+  paused?: boolean;
+}> {
   return fetchJson("/api/health");
 }
 
@@ -33,6 +46,16 @@ export async function getConfig(): Promise<ConfigResponse> {
   return fetchJson("/api/config");
 }
 
+export async function checkProvider(
+  request: ProviderCheckRequest
+): Promise<ProviderCheckResponse> {
+  return fetchJson("/api/providers/check", {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify(request),
+  });
+}
+
 export async function uploadPdf(
   file: File,
   config: Record<string, unknown>
@@ -83,26 +106,74 @@ export function uploadPdfWithProgress(
   });
 }
 
-export async function listJobs(): Promise<{ jobs: Job[] }> {
-  return fetchJson("/api/jobs");
+export interface JobListParams {
+  status?: Job["status"];
+  q?: string;
+  limit?: number;
+  offset?: number;
+}
+
+export async function listJobs(
+  params: JobListParams = {}
+): Promise<{ jobs: Job[]; total: number }> {
+  const query = new URLSearchParams();
+  for (const [key, value] of Object.entries(params)) {
+    if (value !== undefined && value !== "") query.set(key, String(value));
+  }
+  const qs = query.toString();
+  return fetchJson(qs ? `/api/jobs?${qs}` : "/api/jobs");
 }
 
 export async function getJob(id: string): Promise<Job> {
   return fetchJson(`/api/jobs/${id}`);
 }
 
+export async function getJobChildren(id: string): Promise<Job[]> {
+  return fetchJson(`/api/jobs/${id}/children`);
+}
+
+export async function mergeJob(id: string): Promise<Job> {
+  return fetchJson(`/api/jobs/${id}/merge`, { method: "POST" });
+}
+
 export async function deleteJob(id: string): Promise<{ message: string }> {
   return fetchJson(`/api/jobs/${id}`, { method: "DELETE" });
 }
 
+export async function cleanupJobs(
+  days?: number
+): Promise<{ deleted: number; message: string }> {
+  const qs = days === undefined ? "" : `?days=${days}`;
+  return fetchJson(`/api/jobs/cleanup${qs}`, { method: "POST" });
+}
+
 export async function cancelJob(id: string): Promise<{ message: string }> {
   return fetchJson(`/api/jobs/${id}/cancel`, { method: "POST" });
 }
 
+export async function retryJob(id: string): Promise<Job> {
+  return fetchJson(`/api/jobs/${id}/retry`, { method: "POST" });
+}
+
+export async function updateJobConfig(
+  id: string,
+  config: JobConfig
+): Promise<Job> {
+  return fetchJson(`/api/jobs/${id}/config`, {
+    method: "PATCH",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify(config),
+  });
+}
+
 export async function getResults(jobId: string): Promise<ResultsResponse> {
   return fetchJson(`/api/results/${jobId}`);
 }
 
+export async function getTrash(jobId: string): Promise<TrashResponse> {
+  return fetchJson(`/api/results/${jobId}/trash`);
+}
+
 export function getExportZipUrl(jobId: string, imageBaseUrl?: string): string {
   const base = `${API_BASE}/api/results/${jobId}/export`;
   if (imageBaseUrl) {
@@ -133,10 +204,37 @@ export async function cleanResults(
   });
 }
 
+export async function stripTrash(
+  jobId: string,
+  request: StripTrashRequest
+): Promise<StripTrashResponse> {
+  return fetchJson(`/api/results/${jobId}/strip-trash`, {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify(request),
+  });
+}
+
 export function getPdfUrl(jobId: string): string {
   return `${API_BASE}/api/pdf/${jobId}`;
 }
 
+export function getDocxUrl(jobId: string): string {
+  return `${API_BASE}/api/results/${jobId}/export.docx`;
+}
+
+export function getHtmlExportUrl(jobId: string, imageBaseUrl?: string): string {
+  const base = `${API_BASE}/api/results/${jobId}/export.html`;
+  if (imageBaseUrl) {
+    return `${base}?image_base_url=${encodeURIComponent(imageBaseUrl)}`;
+  }
+  return base;
+}
+
+export function getTablesUrl(jobId: string, format: "csv" | "xlsx"): string {
+  return `${API_BASE}/api/results/${jobId}/tables.${format}`;
+}
+
 export async function saveMarkdown(
   jobId: string,
   markdown: string
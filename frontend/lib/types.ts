@@ -6,16 +6,60 @@ export interface JobConfig {
   language: string;
   start_page?: number;
   end_page?: number;
+  skip_pages?: string;
+  include_layers?: string[];
+  exclude_layers?: string[];
   table_extraction: boolean;
   text_only?: boolean;
   storage: string;
   s3_bucket?: string;
   s3_prefix?: string;
+  gcs_bucket?: string;
+  gcs_prefix?: string;
+  azure_account?: string;
+  azure_container?: string;
+  azure_prefix?: string;
   storage_path?: string;
   quality?: string;
   dpi?: number;
+  max_render_pixels?: number;
+  image_encoding?: string;
+  saved_image_format?: string;
   notify?: boolean;
   enhance?: boolean;
+  auto_rotate?: boolean;
+  figure_numbering?: string;
+  emit_image_sidecars?: boolean;
+  image_context?: boolean;
+  stream_text?: boolean;
+  custom_full_page?: string;
+  custom_single_image?: string;
+  custom_table?: string;
+  strip_repeated_lines?: boolean;
+  max_markdown_bytes?: number;
+  premium_provider?: string;
+  premium_model?: string;
+  extract_form_fields?: boolean;
+  emit_toc?: boolean;
+  chunk_noise_patterns?: string[];
+  pages_per_request?: number;
+  escalate_empty_text?: boolean;
+  auto_high_quality_on_scanned?: boolean;
+  dedup_images?: boolean;
+  dedup_pages?: boolean;
+  max_concurrent_requests?: number;
+  requests_per_minute?: number;
+  page_timeout_secs?: number;
+  rag_jsonl?: boolean;
+  wide_table_strategy?: "keep" | "transpose" | "split" | "records";
+  wide_table_max_columns?: number;
+  table_multispace_ratio?: number;
+  table_min_consistent_run?: number;
+  line_ending?: "lf" | "crlf";
+  trash_preset?: "conservative" | "balanced" | "aggressive";
+  page_fallback_strategy?: "none" | "quadrants" | "high_quality" | "auto";
+  caption_placement?: "after" | "before" | "as_alt_text";
+  split_every?: number;
 }
 
 export interface JobProgress {
@@ -24,6 +68,7 @@ export interface JobProgress {
   images_processed: number;
   phase: string;
   message: string;
+  partial?: { page: number; text: string };
 }
 
 export interface JobResult {
@@ -31,6 +76,14 @@ export interface JobResult {
   metadata_path: string;
   image_count: number;
   images_dir: string;
+  markdown_parts?: string[];
+  rag_jsonl_path?: string;
+  pages_jsonl_path?: string;
+  manifest_path?: string;
+  input_tokens?: number;
+  output_tokens?: number;
+  partial?: boolean;
+  last_page?: number;
 }
 
 export interface Job {
@@ -46,6 +99,7 @@ export interface Job {
   started_at?: string;
   completed_at?: string;
   duration_seconds?: number;
+  parent_id?: string;
 }
 
 export interface ProviderInfo {
@@ -54,6 +108,8 @@ export interface ProviderInfo {
   default_model: string;
   models: string[];
   cost_per_image_usd: number;
+  recommended_concurrency: number;
+  rate_limit_rpm: number | null;
 }
 
 export interface LanguageInfo {
@@ -75,8 +131,19 @@ export interface ConfigResponse {
   dpi_presets: number[];
 }
 
+export interface ProviderCheckRequest {
+  provider: string;
+  model?: string;
+}
+
+export interface ProviderCheckResponse {
+  ok: boolean;
+  message: string;
+}
+
 export interface UploadResponse {
   job_id: string;
+  job_ids: string[];
   message: string;
 }
 
@@ -89,16 +156,41 @@ export interface ResultsResponse {
   trash_count: number;
 }
 
+export interface StripTrashRequest {
+  types?: string[];
+}
+
+export interface StripTrashResponse {
+  cleaned_path?: string;
+  pages_removed: number[];
+}
+
+export interface TrashResponse {
+  job_id: string;
+  trash: TrashDetection[];
+  trash_count: number;
+}
+
 export type TrashTypeName =
   | "table_of_contents"
   | "boilerplate"
   | "blank_page"
   | "header_footer";
 
+export type TrashReasonCode =
+  | "toc_heading"
+  | "dot_leaders"
+  | "boilerplate_keywords"
+  | "short_page"
+  | "blank_marker"
+  | "repeated_text"
+  | "other";
+
 export interface TrashDetection {
   page: number;
   trash_type: TrashTypeName;
   confidence: number;
+  code?: TrashReasonCode;
   reason: string;
   preview: string;
 }
@@ -114,13 +206,23 @@ export interface CleanResponse {
 
 // Deploy types
 
-export type ImageTargetType = "local_folder" | "s3" | "scp";
+export type ImageTargetType = "local_folder" | "s3" | "gcs" | "scp";
 export type MarkdownTargetType = "local_folder" | "flowise" | "anythingllm";
 
 export type ImageTarget =
   | { type: "local_folder"; path: string }
   | { type: "s3"; bucket: string; prefix: string; region?: string }
-  | { type: "scp"; host: string; port?: number; username: string; private_key_path?: string; remote_path: string };
+  | { type: "gcs"; bucket: string; prefix: string }
+  | {
+      type: "scp";
+      host: string;
+      port?: number;
+      username: string;
+      private_key_path?: string;
+      remote_path: string;
+      known_hosts_path?: string;
+      host_key_fingerprint?: string;
+    };
 
 export type MarkdownTarget =
   | { type: "local_folder"; path: string }